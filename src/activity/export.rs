@@ -0,0 +1,121 @@
+use super::ActivityEntry;
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, NaiveTime, TimeZone};
+use std::error::Error;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "csv" => Ok(Self::Csv),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("expected `csv` or `json`, got {:?}", s)),
+        }
+    }
+}
+
+/// Entries starting in `[from, to)`; an open end is unbounded
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TimeRange {
+    pub from: Option<DateTime<Local>>,
+    pub to: Option<DateTime<Local>>,
+}
+
+impl TimeRange {
+    pub fn contains(&self, time: DateTime<Local>) -> bool {
+        self.from.is_none_or(|from| time >= from) && self.to.is_none_or(|to| time < to)
+    }
+}
+
+/// Parse `YYYY-MM-DD` (midnight) or `YYYY-MM-DD HH:MM` / `YYYY-MM-DDTHH:MM`
+/// as local time. An `--to` date alone is meant inclusively, so `end_of_day`
+/// moves it to the following midnight.
+pub fn parse_time(text: &str, end_of_day: bool) -> Result<DateTime<Local>, String> {
+    let naive = match NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        Ok(date) => {
+            let date = if end_of_day {
+                date.succ_opt().unwrap_or(date)
+            } else {
+                date
+            };
+            date.and_time(NaiveTime::MIN)
+        }
+        Err(_) => NaiveDateTime::parse_from_str(&text.replace('T', " "), "%Y-%m-%d %H:%M")
+            .map_err(|_| format!("expected YYYY-MM-DD or YYYY-MM-DD HH:MM, got {:?}", text))?,
+    };
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("{} does not exist in the local time zone", text))
+}
+
+pub fn render(entries: &[ActivityEntry], format: ExportFormat) -> Result<String, Box<dyn Error>> {
+    match format {
+        ExportFormat::Json => Ok(serde_json::to_string_pretty(entries)? + "\n"),
+        ExportFormat::Csv => Ok(to_csv(entries)),
+    }
+}
+
+fn to_csv(entries: &[ActivityEntry]) -> String {
+    let mut csv = String::from("start,duration_secs,class,title,workspace\n");
+    for entry in entries {
+        csv.push_str(&format!(
+            "{},{},{},{},{}\n",
+            entry.start.to_rfc3339(),
+            entry.duration_secs,
+            csv_field(&entry.class),
+            csv_field(&entry.title),
+            csv_field(&entry.workspace)
+        ));
+    }
+    csv
+}
+
+/// Quote a field when it holds a separator, quote or line break (RFC 4180)
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_quotes_fields() {
+        let entry = ActivityEntry {
+            start: parse_time("2026-03-02 09:00", false).unwrap(),
+            duration_secs: 90,
+            class: "firefox".to_string(),
+            title: "Docs, \"draft\"".to_string(),
+            workspace: "2".to_string(),
+        };
+
+        let csv = to_csv(&[entry]);
+        let row = csv.lines().nth(1).unwrap();
+        assert!(row.ends_with(",90,firefox,\"Docs, \"\"draft\"\"\",2"));
+    }
+
+    #[test]
+    fn test_range_to_date_is_inclusive() {
+        let range = TimeRange {
+            from: Some(parse_time("2026-03-02", false).unwrap()),
+            to: Some(parse_time("2026-03-03", true).unwrap()),
+        };
+
+        assert!(range.contains(parse_time("2026-03-02 00:00", false).unwrap()));
+        assert!(range.contains(parse_time("2026-03-03T23:59", false).unwrap()));
+        assert!(!range.contains(parse_time("2026-03-04", false).unwrap()));
+        assert!(parse_time("yesterday", false).is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Opt-in time tracking: which window had focus, on which workspace, and
+//! for how long, built from `activewindow` events.
+
+pub mod export;
+
+use crate::ipc::events::HyprEvent;
+use chrono::{DateTime, Local, NaiveDate};
+use export::TimeRange;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// One stretch of focus on a window
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    pub start: DateTime<Local>,
+    pub duration_secs: u64,
+    pub class: String,
+    pub title: String,
+    pub workspace: String,
+}
+
+/// Day files of entries, named `YYYY-MM-DD.jsonl` after the day each entry started
+pub struct ActivityLog {
+    dir: PathBuf,
+}
+
+impl ActivityLog {
+    pub fn new(dir: impl AsRef<Path>) -> Self {
+        Self {
+            dir: dir.as_ref().to_path_buf(),
+        }
+    }
+
+    fn day_path(&self, day: NaiveDate) -> PathBuf {
+        self.dir.join(format!("{}.jsonl", day.format("%Y-%m-%d")))
+    }
+
+    pub fn append(&self, entry: &ActivityEntry) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.dir)?;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(self.day_path(entry.start.date_naive()))?;
+        writeln!(file, "{}", serde_json::to_string(entry)?)?;
+        Ok(())
+    }
+
+    /// Entries that started on `day`; unreadable lines are skipped
+    pub fn day(&self, day: NaiveDate) -> Result<Vec<ActivityEntry>, Box<dyn Error>> {
+        let path = self.day_path(day);
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        Ok(fs::read_to_string(path)?
+            .lines()
+            .filter_map(|line| serde_json::from_str(line).ok())
+            .collect())
+    }
+
+    /// Entries that started within `range`, oldest first
+    pub fn range(&self, range: &TimeRange) -> Result<Vec<ActivityEntry>, Box<dyn Error>> {
+        if !self.dir.exists() {
+            return Ok(vec![]);
+        }
+        let mut days: Vec<NaiveDate> = fs::read_dir(&self.dir)?
+            .filter_map(|entry| {
+                let name = entry.ok()?.file_name();
+                let stem = name.to_str()?.strip_suffix(".jsonl")?;
+                NaiveDate::parse_from_str(stem, "%Y-%m-%d").ok()
+            })
+            .filter(|day| {
+                range.from.is_none_or(|from| *day >= from.date_naive())
+                    && range.to.is_none_or(|to| *day <= to.date_naive())
+            })
+            .collect();
+        days.sort_unstable();
+
+        let mut entries = vec![];
+        for day in days {
+            entries.extend(
+                self.day(day)?
+                    .into_iter()
+                    .filter(|e| range.contains(e.start)),
+            );
+        }
+        Ok(entries)
+    }
+}
+
+/// Turns focus events into finished [`ActivityEntry`]s
+#[derive(Default)]
+pub struct ActivityTracker {
+    workspace: String,
+    // Focused window (class, title, workspace) and since when
+    focused: Option<(String, String, String, DateTime<Local>)>,
+}
+
+impl ActivityTracker {
+    /// A tracker that assumes `workspace` is active until told otherwise
+    pub fn starting_on(workspace: &str) -> Self {
+        Self {
+            workspace: workspace.to_string(),
+            focused: None,
+        }
+    }
+
+    /// Whether events named `name` matter to the tracker
+    pub fn tracks(name: &str) -> bool {
+        matches!(name, "activewindow" | "workspace")
+    }
+
+    /// Feed an event seen at `now`; returns the stretch of focus it ended
+    pub fn observe(&mut self, event: &HyprEvent, now: DateTime<Local>) -> Option<ActivityEntry> {
+        match event {
+            HyprEvent::WorkspaceChanged { name, .. } => {
+                self.workspace = name.clone();
+                None
+            }
+            HyprEvent::ActiveWindow { class, title } => {
+                let ended = self.finish(now);
+                // An empty class means nothing has focus, e.g. an empty workspace
+                if !class.is_empty() {
+                    self.focused =
+                        Some((class.clone(), title.clone(), self.workspace.clone(), now));
+                }
+                ended
+            }
+            _ => None,
+        }
+    }
+
+    /// End the current stretch of focus, e.g. on shutdown
+    pub fn finish(&mut self, now: DateTime<Local>) -> Option<ActivityEntry> {
+        let (class, title, workspace, start) = self.focused.take()?;
+        Some(ActivityEntry {
+            start,
+            duration_secs: (now - start).num_seconds().max(0) as u64,
+            class,
+            title,
+            workspace,
+        })
+    }
+}
+
+/// Total focus time per class, longest first
+pub fn summarize(entries: &[ActivityEntry]) -> Vec<(String, u64)> {
+    let mut totals: HashMap<&str, u64> = HashMap::new();
+    for entry in entries {
+        *totals.entry(&entry.class).or_default() += entry.duration_secs;
+    }
+    let mut totals: Vec<(String, u64)> = totals
+        .into_iter()
+        .map(|(class, secs)| (class.to_string(), secs))
+        .collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
+}
+
+/// `1h 05m`, `12m 30s` or `45s`
+pub fn format_duration(secs: u64) -> String {
+    match (secs / 3600, secs % 3600 / 60, secs % 60) {
+        (0, 0, s) => format!("{}s", s),
+        (0, m, s) => format!("{}m {:02}s", m, s),
+        (h, m, _) => format!("{}h {:02}m", h, m),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    fn focus(class: &str, title: &str) -> HyprEvent {
+        HyprEvent::ActiveWindow {
+            class: class.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_tracker_records_focus_stretches() {
+        let t0 = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let mut tracker = ActivityTracker::default();
+
+        let workspace = HyprEvent::WorkspaceChanged {
+            id: Some(2),
+            name: "2".to_string(),
+        };
+        assert_eq!(tracker.observe(&workspace, t0), None);
+        assert_eq!(tracker.observe(&focus("code", "main.rs"), t0), None);
+
+        let entry = tracker
+            .observe(&focus("firefox", "Docs"), t0 + Duration::seconds(90))
+            .unwrap();
+        assert_eq!(entry.class, "code");
+        assert_eq!(entry.workspace, "2");
+        assert_eq!(entry.duration_secs, 90);
+
+        // Focus on nothing ends the stretch without starting another
+        assert!(tracker
+            .observe(&focus("", ""), t0 + Duration::seconds(100))
+            .is_some());
+        assert_eq!(tracker.finish(t0 + Duration::seconds(200)), None);
+    }
+
+    #[test]
+    fn test_summarize_and_format() {
+        let start = Local.with_ymd_and_hms(2026, 3, 2, 9, 0, 0).unwrap();
+        let entry = |class: &str, duration_secs| ActivityEntry {
+            start,
+            duration_secs,
+            class: class.to_string(),
+            title: String::new(),
+            workspace: "1".to_string(),
+        };
+        let entries = vec![entry("kitty", 60), entry("code", 3000), entry("kitty", 30)];
+
+        assert_eq!(
+            summarize(&entries),
+            vec![("code".to_string(), 3000), ("kitty".to_string(), 90)]
+        );
+        assert_eq!(format_duration(45), "45s");
+        assert_eq!(format_duration(750), "12m 30s");
+        assert_eq!(format_duration(3900), "1h 05m");
+    }
+
+    #[test]
+    fn test_log_round_trip() {
+        let dir = std::env::temp_dir().join(format!("hyprdrover-activity-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let log = ActivityLog::new(&dir);
+        let start = Local.with_ymd_and_hms(2026, 3, 2, 23, 59, 0).unwrap();
+        let entry = ActivityEntry {
+            start,
+            duration_secs: 120,
+            class: "mpv".to_string(),
+            title: "film".to_string(),
+            workspace: "3".to_string(),
+        };
+
+        log.append(&entry).unwrap();
+
+        assert_eq!(log.day(start.date_naive()).unwrap(), vec![entry]);
+        assert!(log
+            .day(start.date_naive().succ_opt().unwrap())
+            .unwrap()
+            .is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
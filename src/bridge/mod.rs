@@ -0,0 +1,335 @@
+//! Relays Hyprland's command and event sockets over TCP, for containers and
+//! VMs that can't share the host's Unix sockets.
+//!
+//! Every connection starts with one line, `AUTH <token> <channel>`, where the
+//! channel is `command` or `events`. The server answers `ok` or
+//! `error <message>`. On the command channel the client then sends one raw
+//! request (e.g. `j/clients`) and shuts down its write half; the server
+//! replies with Hyprland's answer and closes. On the event channel the server
+//! streams socket2 lines until either side disconnects.
+//!
+//! The token only authenticates; the connection itself is not encrypted, and
+//! the command channel runs any request, `dispatch exec` included. Callers
+//! should only serve and connect on loopback addresses (see [`is_loopback`]),
+//! with an SSH tunnel or TLS terminator carrying the traffic between hosts.
+
+use crate::ipc::HyprCommandClient;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream, ToSocketAddrs};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Environment variable holding the shared bridge token
+pub const TOKEN_VAR: &str = "HYPRDROVER_BRIDGE_TOKEN";
+
+/// Hyprland reads a request with a single read of at most this many bytes
+const MAX_REQUEST: usize = 8192;
+
+/// Longest accepted `AUTH` line
+const MAX_AUTH_LINE: u64 = 512;
+
+/// How long a client may take to send its auth line and request
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Channel {
+    Command,
+    Events,
+}
+
+impl Channel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Command => "command",
+            Self::Events => "events",
+        }
+    }
+}
+
+/// Parse an `AUTH <token> <channel>` line, checking the token
+fn parse_auth(line: &str, token: &str) -> Result<Channel, &'static str> {
+    let mut parts = line.trim_end().splitn(3, ' ');
+    if parts.next() != Some("AUTH") {
+        return Err("expected AUTH");
+    }
+    let given = parts.next().unwrap_or_default();
+    if !constant_time_eq(given.as_bytes(), token.as_bytes()) {
+        return Err("unauthorized");
+    }
+    match parts.next() {
+        Some("command") => Ok(Channel::Command),
+        Some("events") => Ok(Channel::Events),
+        _ => Err("unknown channel"),
+    }
+}
+
+/// Compare without leaking how many leading bytes matched
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// Whether every address `addr` resolves to is a loopback one
+pub fn is_loopback(addr: &str) -> io::Result<bool> {
+    let mut addrs = addr.to_socket_addrs()?.peekable();
+    Ok(addrs.peek().is_some() && addrs.all(|a| a.ip().is_loopback()))
+}
+
+/// The token stored in the first line of `path`
+pub fn read_token_file(path: &Path) -> io::Result<String> {
+    let content = fs::read_to_string(path)?;
+    Ok(content
+        .lines()
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_string())
+}
+
+// --- Server ---
+
+/// Serves the local Hyprland sockets to authenticated TCP clients
+pub struct BridgeServer {
+    token: String,
+    command_socket: PathBuf,
+    event_socket: PathBuf,
+}
+
+impl BridgeServer {
+    pub fn new(token: &str, command_socket: PathBuf, event_socket: PathBuf) -> Self {
+        Self {
+            token: token.to_string(),
+            command_socket,
+            event_socket,
+        }
+    }
+
+    /// Accept connections forever, one thread per client. Failed accepts
+    /// (e.g. running out of file descriptors) are logged and skipped.
+    pub fn serve(self, listener: TcpListener) -> io::Result<()> {
+        let server = std::sync::Arc::new(self);
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Bridge accept failed: {}", e);
+                    thread::sleep(Duration::from_millis(100));
+                    continue;
+                }
+            };
+            let server = server.clone();
+            thread::spawn(move || {
+                let peer = stream.peer_addr().ok();
+                if let Err(e) = server.handle(stream) {
+                    eprintln!("Bridge client {:?}: {}", peer, e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    fn handle(&self, stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+
+        let mut line = String::new();
+        (&mut reader).take(MAX_AUTH_LINE).read_line(&mut line)?;
+        if !line.ends_with('\n') {
+            return writeln!(writer, "error expected AUTH");
+        }
+        let channel = match parse_auth(&line, &self.token) {
+            Ok(channel) => channel,
+            Err(e) => return writeln!(writer, "error {}", e),
+        };
+
+        match channel {
+            Channel::Command => {
+                let mut request = String::new();
+                (&mut reader)
+                    .take(MAX_REQUEST as u64 + 1)
+                    .read_to_string(&mut request)?;
+                if request.len() > MAX_REQUEST {
+                    return writeln!(writer, "error request too long");
+                }
+                match HyprCommandClient::with_socket(&self.command_socket).send_raw(&request) {
+                    Ok(reply) => {
+                        writeln!(writer, "ok")?;
+                        writer.write_all(reply.as_bytes())
+                    }
+                    Err(e) => writeln!(writer, "error {}", e),
+                }
+            }
+            Channel::Events => {
+                let mut events = match UnixStream::connect(&self.event_socket) {
+                    Ok(events) => events,
+                    Err(e) => return writeln!(writer, "error {}", e),
+                };
+                writeln!(writer, "ok")?;
+                io::copy(&mut events, &mut writer).map(|_| ())
+            }
+        }
+    }
+}
+
+// --- Client ---
+
+/// Open an authenticated connection, returning the stream positioned after
+/// the server's `ok`
+fn open(
+    addr: impl ToSocketAddrs,
+    token: &str,
+    channel: Channel,
+    request: Option<&str>,
+) -> io::Result<BufReader<TcpStream>> {
+    let mut stream = TcpStream::connect(addr)?;
+    writeln!(stream, "AUTH {} {}", token, channel.as_str())?;
+    if let Some(request) = request {
+        stream.write_all(request.as_bytes())?;
+        stream.shutdown(Shutdown::Write)?;
+    }
+
+    let mut reader = BufReader::new(stream);
+    let mut status = String::new();
+    reader.read_line(&mut status)?;
+    match status.trim_end() {
+        "ok" => Ok(reader),
+        other => Err(io::Error::other(format!(
+            "bridge refused the connection: {}",
+            other.strip_prefix("error ").unwrap_or(other)
+        ))),
+    }
+}
+
+/// Send one raw command request (e.g. `j/clients`) through the bridge
+pub fn request(addr: impl ToSocketAddrs, token: &str, request: &str) -> io::Result<String> {
+    let mut reader = open(addr, token, Channel::Command, Some(request))?;
+    let mut reply = String::new();
+    reader.read_to_string(&mut reply)?;
+    Ok(reply)
+}
+
+/// Expose the bridge as local `.socket.sock` and `.socket2.sock` files in
+/// `dir`, for use with `--socket`/`--event-socket`. Blocks forever.
+pub fn connect(addr: &str, token: &str, dir: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dir)?;
+    let command_path = dir.join(".socket.sock");
+    let event_path = dir.join(".socket2.sock");
+    for path in [&command_path, &event_path] {
+        let _ = fs::remove_file(path);
+    }
+    let commands = UnixListener::bind(&command_path)?;
+    let events = UnixListener::bind(&event_path)?;
+
+    let (event_addr, event_token) = (addr.to_string(), token.to_string());
+    thread::spawn(move || {
+        for local in events.incoming().flatten() {
+            let (addr, token) = (event_addr.clone(), event_token.clone());
+            thread::spawn(move || {
+                if let Err(e) = relay_events(&addr, &token, local) {
+                    eprintln!("Bridge events: {}", e);
+                }
+            });
+        }
+    });
+
+    for local in commands.incoming() {
+        let local = local?;
+        let (addr, token) = (addr.to_string(), token.to_string());
+        thread::spawn(move || {
+            if let Err(e) = relay_command(&addr, &token, local) {
+                eprintln!("Bridge command: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn relay_command(addr: &str, token: &str, mut local: UnixStream) -> io::Result<()> {
+    // Like Hyprland, take the request from a single read
+    let mut buf = vec![0; MAX_REQUEST];
+    let n = local.read(&mut buf)?;
+    let reply = request(addr, token, &String::from_utf8_lossy(&buf[..n]))?;
+    local.write_all(reply.as_bytes())
+}
+
+fn relay_events(addr: &str, token: &str, mut local: UnixStream) -> io::Result<()> {
+    let mut remote = open(addr, token, Channel::Events, None)?;
+    io::copy(&mut remote, &mut local).map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_only_loopback_counts_as_local() {
+        assert!(is_loopback("127.0.0.1:7878").unwrap());
+        assert!(is_loopback("[::1]:7878").unwrap());
+        assert!(!is_loopback("0.0.0.0:7878").unwrap());
+        assert!(!is_loopback("192.168.1.5:7878").unwrap());
+    }
+
+    #[test]
+    fn test_parse_auth() {
+        assert_eq!(
+            parse_auth("AUTH s3cret command\n", "s3cret"),
+            Ok(Channel::Command)
+        );
+        assert_eq!(
+            parse_auth("AUTH s3cret events", "s3cret"),
+            Ok(Channel::Events)
+        );
+        assert_eq!(
+            parse_auth("AUTH wrong command", "s3cret"),
+            Err("unauthorized")
+        );
+        assert_eq!(
+            parse_auth("AUTH s3cret shell", "s3cret"),
+            Err("unknown channel")
+        );
+        assert_eq!(parse_auth("HELLO", "s3cret"), Err("expected AUTH"));
+    }
+
+    #[test]
+    fn test_command_round_trip_through_bridge() {
+        let socket = env::temp_dir().join(format!("hyprdrover-bridge-{}.sock", std::process::id()));
+        let _ = fs::remove_file(&socket);
+        let hyprland = UnixListener::bind(&socket).unwrap();
+        thread::spawn(move || {
+            let (mut stream, _) = hyprland.accept().unwrap();
+            let mut buf = [0; 64];
+            let n = stream.read(&mut buf).unwrap();
+            assert_eq!(&buf[..n], b"j/monitors");
+            stream.write_all(b"[]").unwrap();
+        });
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = BridgeServer::new("s3cret", socket.clone(), PathBuf::from("/nonexistent"));
+        thread::spawn(move || server.serve(listener));
+
+        assert!(request(addr, "wrong", "j/monitors").is_err());
+        assert_eq!(request(addr, "s3cret", "j/monitors").unwrap(), "[]");
+        let _ = fs::remove_file(&socket);
+    }
+
+    #[test]
+    fn test_oversized_request_is_refused() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = BridgeServer::new(
+            "s3cret",
+            PathBuf::from("/nonexistent"),
+            PathBuf::from("/nonexistent"),
+        );
+        thread::spawn(move || server.serve(listener));
+
+        let err = request(addr, "s3cret", &"x".repeat(MAX_REQUEST + 1)).unwrap_err();
+        assert!(err.to_string().contains("too long"), "{}", err);
+    }
+}
@@ -0,0 +1,123 @@
+use super::Config;
+use std::error::Error;
+use toml::{Table, Value};
+
+/// Prefix of environment variables that override config settings
+pub const ENV_PREFIX: &str = "HYPRDROVER_";
+
+/// A single `key = value` override from the environment or the command line
+pub type Override = (String, String);
+
+/// Settings named by `HYPRDROVER_<KEY>` variables, e.g. `HYPRDROVER_SESSION_DIR`.
+/// A double underscore separates a section from its setting, so
+/// `HYPRDROVER_DAEMON__AUTOSAVE_SECS` sets `daemon.autosave_secs`.
+///
+/// Variables that don't name a config setting (like `HYPRDROVER_CONFIG`) are
+/// left alone.
+pub fn env_overrides(vars: impl IntoIterator<Item = (String, String)>) -> Vec<Override> {
+    let known = setting_names();
+    let mut overrides: Vec<Override> = vars
+        .into_iter()
+        .filter_map(|(name, value)| {
+            let key = name
+                .strip_prefix(ENV_PREFIX)?
+                .to_lowercase()
+                .replace("__", ".");
+            known.contains(&key).then_some((key, value))
+        })
+        .collect();
+    // Environment order is arbitrary; keep the result stable
+    overrides.sort();
+    overrides
+}
+
+/// Parse a `KEY=VALUE` command-line override; `SECTION.KEY` names a setting
+/// inside a section
+pub fn parse_override(arg: &str) -> Result<Override, String> {
+    let (key, value) = arg
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got {:?}", arg))?;
+    Ok((key.trim().replace('-', "_"), value.to_string()))
+}
+
+/// Apply `overrides` on top of the settings parsed from the config file.
+/// Dotted keys merge into the section's table, keeping its other settings.
+pub fn apply_overrides(table: &mut Table, overrides: &[Override]) -> Result<(), Box<dyn Error>> {
+    let known = setting_names();
+    let defaults = Table::try_from(Config::default())?;
+    for (key, raw) in overrides {
+        if !known.contains(key) {
+            return Err(format!("unknown setting `{}`", key).into());
+        }
+        let (sections, name) = match key.rsplit_once('.') {
+            Some((sections, name)) => (sections.split('.').collect(), name),
+            None => (vec![], key.as_str()),
+        };
+
+        let mut target = &mut *table;
+        let mut default_section = Some(&defaults);
+        for section in sections {
+            default_section = default_section
+                .and_then(|t| t.get(section))
+                .and_then(Value::as_table);
+            target = target
+                .entry(section)
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| format!("`{}` is not a section", section))?;
+        }
+        let default = default_section
+            .and_then(|t| t.get(name))
+            .cloned()
+            .unwrap_or(Value::String(String::new()));
+        target.insert(name.to_string(), parse_value(raw, &default));
+    }
+    Ok(())
+}
+
+/// Names of every setting, with `section.key` for those inside a section
+fn setting_names() -> Vec<String> {
+    let mut names = vec![];
+    if let Ok(table) = Table::try_from(Config::default()) {
+        collect_names(&table, "", &mut names);
+    }
+    // `None` settings are left out of the serialized defaults
+    names.push("script".to_string());
+    names.push("signing_key".to_string());
+    names
+}
+
+fn collect_names(table: &Table, prefix: &str, names: &mut Vec<String>) {
+    for (key, value) in table {
+        let name = format!("{}{}", prefix, key);
+        if let Value::Table(section) = value {
+            collect_names(section, &format!("{}.", name), names);
+        }
+        names.push(name);
+    }
+}
+
+/// Interpret `raw` according to the type of the setting's default: strings
+/// are taken verbatim, lists accept TOML (`["a", "b"]`, `[{ ... }]`) or a
+/// comma-separated shorthand (`HYPRDROVER_IGNORED_CLASSES=rofi,waybar`), and
+/// anything else is parsed as a TOML value.
+fn parse_value(raw: &str, default: &Value) -> Value {
+    let parsed = format!("value = {}", raw)
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut t| t.remove("value"));
+
+    match (default, parsed) {
+        (Value::String(_), _) => Value::String(raw.to_string()),
+        (Value::Array(_), Some(value @ Value::Array(_))) => value,
+        (Value::Array(_), _) => Value::Array(
+            raw.split(',')
+                .map(str::trim)
+                .filter(|s| !s.is_empty())
+                .map(|s| Value::String(s.to_string()))
+                .collect(),
+        ),
+        (_, Some(value)) => value,
+        (_, None) => Value::String(raw.to_string()),
+    }
+}
@@ -1,5 +1,10 @@
+pub mod layers;
 pub mod model;
 #[cfg(test)]
 mod tests;
 
-pub use model::Config;
+pub use model::{
+    ActivityConfig, CompanionConfig, Config, CrashAction, CrashConfig, DaemonConfig,
+    DependencyConfig, FreezeConfig, HookConfig, LaunchWave, LogConfig, MemoryAction,
+    NotificationConfig, PacingConfig, PauseConfig, TimeoutConfig, TransientConfig,
+};
@@ -1,23 +1,336 @@
+use super::layers::{self, Override};
+use crate::ipc::queue::EventBufferConfig;
+use crate::paths;
+use crate::restore::dependencies::{self, Requirement};
+use crate::restore::rules::{RestorePolicy, Rule};
+use crate::runtime::crash::SessionMarker;
+use crate::runtime::RuntimeContext;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::env;
-use std::path::PathBuf;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Table;
+
+/// Runs a shell command whenever a matching compositor event arrives
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HookConfig {
+    /// Event name as sent by Hyprland, e.g. `openwindow` or `monitoradded`
+    pub event: String,
+    /// Event fields that must match (case-insensitive), e.g. `{ class = "zoom" }`
+    #[serde(default)]
+    pub when: BTreeMap<String, String>,
+    /// Shell command, run with `sh -c`
+    pub command: String,
+}
+
+/// A background program (bar, wallpaper daemon, applet) that restore starts when it is absent
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct CompanionConfig {
+    pub name: String,
+    /// Shell command that starts it
+    pub command: String,
+    /// Layer-shell namespace that shows it is running, e.g. `waybar`
+    #[serde(default)]
+    pub layer: Option<String>,
+    /// Process name that shows it is running; defaults to `name`
+    #[serde(default)]
+    pub process: Option<String>,
+}
+
+/// A group of apps restored together, after the groups before it.
+///
+/// Heavy apps starting at the same moment tend to misplace their windows, so
+/// e.g. terminals can come first, browsers a little later and Electron apps last.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct LaunchWave {
+    /// Window classes in this wave, compared case-insensitively
+    pub classes: Vec<String>,
+    /// Pause before the wave starts, in milliseconds
+    #[serde(default)]
+    pub delay_ms: u64,
+}
+
+/// Makes launching one app wait for others, e.g. a mail client for the password manager
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct DependencyConfig {
+    /// Window class of the dependent app, compared case-insensitively
+    pub class: String,
+    /// `class:NAME` waits for a window of that class (restoring it first if it
+    /// is part of the session); `hook:COMMAND` waits until the shell command succeeds
+    pub after: Vec<String>,
+}
+
+/// What a restore does when launching an app would leave too little memory
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MemoryAction {
+    /// Wait for memory to free up, like the other pacing limits
+    #[default]
+    Wait,
+    /// Ask on the terminal whether to launch, wait or skip the app
+    Prompt,
+}
+
+/// Thresholds that hold back launches during a restore while the machine is busy.
+/// All limits are off unless set.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct PacingConfig {
+    /// Wait while the 1-minute load average is above this
+    pub max_load: Option<f64>,
+    /// Wait while less than this much memory (MiB) is available
+    pub min_available_mb: Option<u64>,
+    /// Memory (MiB) an app is expected to take, by window class
+    /// (case-insensitive); it must be available on top of `min_available_mb`
+    pub memory_mb: BTreeMap<String, u64>,
+    pub on_memory_pressure: MemoryAction,
+    /// Launch anyway after waiting this many seconds
+    pub max_wait_secs: u64,
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            max_load: None,
+            min_available_mb: None,
+            memory_mb: BTreeMap::new(),
+            on_memory_pressure: MemoryAction::default(),
+            max_wait_secs: 30,
+        }
+    }
+}
+
+/// Where long-running commands log when systemd isn't collecting their output
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct LogConfig {
+    /// Start a new file once the current one reaches this size (KiB)
+    pub max_size_kb: u64,
+    /// Rotated files kept besides the current one
+    pub keep: usize,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        Self {
+            max_size_kb: 1024,
+            keep: 3,
+        }
+    }
+}
+
+/// How long a restore waits before giving up on something
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TimeoutConfig {
+    /// Seconds to wait for a launched app's window
+    pub launch_secs: u64,
+    /// Seconds to wait for an app's dependencies before launching it anyway
+    pub dependency_secs: u64,
+}
+
+impl Default for TimeoutConfig {
+    fn default() -> Self {
+        Self {
+            launch_secs: 10,
+            dependency_secs: 30,
+        }
+    }
+}
+
+/// Which desktop notifications hyprdrover shows
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct NotificationConfig {
+    /// Progress of a restore that launches apps, updated in place
+    pub progress: bool,
+    /// Confirmation (or error) after `quicksave` and `quickrestore`
+    pub quick: bool,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            progress: true,
+            quick: true,
+        }
+    }
+}
+
+/// Opt-in record of which window had focus and for how long
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ActivityConfig {
+    pub enabled: bool,
+}
+
+/// `hyprdrover daemon` settings
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct DaemonConfig {
+    /// Seconds between autosaves of a changed session
+    pub autosave_secs: u64,
+    /// Session the daemon autosaves to, with the usual backups
+    pub session: String,
+    /// Closed windows or workspaces within two seconds that save right away
+    pub burst: usize,
+    /// Also run hooks, activity tracking, freezing and crash recovery, on
+    /// the daemon's connection to the event socket
+    pub hooks: bool,
+}
+
+impl Default for DaemonConfig {
+    fn default() -> Self {
+        Self {
+            autosave_secs: 60,
+            session: "autosave".to_string(),
+            burst: 3,
+            hooks: false,
+        }
+    }
+}
+
+/// Which windows are treated as too short-lived to save
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct TransientConfig {
+    /// Skip zero-size windows, file choosers, splash screens and popups at capture
+    pub enabled: bool,
+    /// Further window classes to skip, compared case-insensitively
+    pub classes: Vec<String>,
+    /// Further title substrings to skip, compared case-insensitively
+    pub titles: Vec<String>,
+}
+
+impl Default for TransientConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            classes: vec![],
+            titles: vec![],
+        }
+    }
+}
+
+/// Apps stopped while all their windows are parked on hidden special workspaces
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FreezeConfig {
+    /// Window classes that may be frozen, compared case-insensitively; empty disables
+    pub classes: Vec<String>,
+}
+
+/// When tracking pauses on its own
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct PauseConfig {
+    /// Window classes (e.g. games) that pause tracking while fullscreen;
+    /// a trailing `*` matches any suffix
+    pub fullscreen_classes: Vec<String>,
+}
+
+/// What the listener does after finding that the last session did not end
+/// through `hyprdrover exit`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CrashAction {
+    #[default]
+    Off,
+    /// Show a notification suggesting `hyprdrover quickrestore`
+    Offer,
+    /// Restore the most recent session right away
+    Restore,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct CrashConfig {
+    pub action: CrashAction,
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
 pub struct Config {
     pub session_dir: String,
+    /// Where restore reports and other runtime history are kept
+    pub state_dir: String,
+    /// Base of the per-seat, per-instance daemon lock and control socket;
+    /// empty for the per-user default
+    pub runtime_dir: String,
     pub ignored_classes: Vec<String>,
+    /// Layer surfaces (by namespace) not worth a warning when missing after
+    /// a restore. Separate from `ignored_classes`, which lists bars like
+    /// waybar whose windows shouldn't be saved but whose absence should
+    /// still be reported.
+    pub ignored_layers: Vec<String>,
+    pub hooks: Vec<HookConfig>,
+    /// Optional Rhai script customizing window matching and launch commands
+    pub script: Option<String>,
+    /// File holding a secret used to sign snapshots; signed snapshots whose
+    /// signature no longer matches are refused
+    pub signing_key: Option<String>,
+    /// Rotated copies (`work.json.1`, `work.json.2`, ...) kept when a named
+    /// session is overwritten; 0 disables them
+    pub backups: usize,
+    /// Launch commands by window class, on top of the built-in aliases
+    pub aliases: BTreeMap<String, String>,
+    /// Arguments that make a running single-instance app open another
+    /// window, by class, on top of the built-in list; empty if it can't
+    pub single_instance: BTreeMap<String, String>,
+    /// Shared libraries providing launch resolvers, asked before the built-in logic
+    pub plugins: Vec<String>,
+    /// Recent compositor log lines attached to failed windows in restore
+    /// reports; 0 disables
+    pub compositor_log_lines: usize,
+    /// Turn Hyprland's animations off while restoring, and back on afterwards
+    pub disable_animations: bool,
+    /// Output that takes over the workspaces of a saved monitor that isn't
+    /// connected, by its connector name or description; `*` covers the rest
+    pub monitor_map: BTreeMap<String, String>,
+    /// Window matching strategy: `default` (scored) or `class` (first window of the app)
+    pub matcher: String,
+    /// Ordered restore rules; `ignored_classes` is applied after these
+    pub rules: Vec<Rule>,
+    /// Restore policy per class (case-insensitive), applied after `rules`
+    pub policies: BTreeMap<String, RestorePolicy>,
+    /// Started before windows are restored if they aren't already running
+    pub companions: Vec<CompanionConfig>,
+    /// Pause launches while system load or memory pressure is high
+    pub pacing: PacingConfig,
+    /// Ordered launch waves; windows in no wave are restored first
+    pub waves: Vec<LaunchWave>,
+    /// What must be up before an app is launched
+    pub dependencies: Vec<DependencyConfig>,
+    pub timeouts: TimeoutConfig,
+    pub notifications: NotificationConfig,
+    /// Log files of long-running commands
+    pub log: LogConfig,
+    /// How many events may wait for a slow consumer, and what happens beyond that
+    pub event_buffer: EventBufferConfig,
+    pub activity: ActivityConfig,
+    pub daemon: DaemonConfig,
+    /// Recovery after a compositor crash or power loss
+    pub crash: CrashConfig,
+    /// SIGSTOP parked scratchpad apps, SIGCONT them when shown
+    pub freeze: FreezeConfig,
+    /// Automatic pauses of hooks and activity tracking
+    pub pause: PauseConfig,
+    /// Dialogs and other windows left out of snapshots
+    pub transient: TransientConfig,
 }
 
 impl Default for Config {
     fn default() -> Self {
-        let home = env::var("HOME").unwrap_or_else(|_| ".".to_string());
-        let session_dir = PathBuf::from(home)
-            .join(".config")
-            .join("hyprdrover")
-            .join("sessions");
+        let home = paths::home();
+        let session_dir = home.join(".config").join(paths::APP_DIR).join("sessions");
+        let state_dir = home.join(".local").join("state").join(paths::APP_DIR);
 
         Self {
             session_dir: session_dir.to_string_lossy().into_owned(),
+            state_dir: state_dir.to_string_lossy().into_owned(),
+            runtime_dir: String::new(),
             // Don't snapshot these background/overlay apps
             ignored_classes: vec![
                 "rofi".to_string(),
@@ -26,6 +339,186 @@ impl Default for Config {
                 "hyprland-share-picker".to_string(),
                 "polkit-gnome-authentication-agent-1".to_string(),
             ],
+            ignored_layers: vec![],
+            hooks: vec![],
+            script: None,
+            signing_key: None,
+            backups: 3,
+            aliases: BTreeMap::new(),
+            single_instance: BTreeMap::new(),
+            plugins: vec![],
+            compositor_log_lines: 0,
+            disable_animations: false,
+            monitor_map: BTreeMap::new(),
+            matcher: "default".to_string(),
+            rules: vec![],
+            policies: BTreeMap::new(),
+            companions: vec![],
+            pacing: PacingConfig::default(),
+            waves: vec![],
+            dependencies: vec![],
+            timeouts: TimeoutConfig::default(),
+            notifications: NotificationConfig::default(),
+            log: LogConfig::default(),
+            event_buffer: EventBufferConfig::default(),
+            activity: ActivityConfig::default(),
+            daemon: DaemonConfig::default(),
+            crash: CrashConfig::default(),
+            freeze: FreezeConfig::default(),
+            pause: PauseConfig::default(),
+            transient: TransientConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the optional config file, `$HYPRDROVER_CONFIG` if set
+    pub fn path() -> PathBuf {
+        paths::config_file()
+    }
+
+    /// The defaults, with directories following the environment: the XDG
+    /// base directories, and a session directory per seat
+    pub fn from_environment() -> Self {
+        let session_dir = RuntimeContext::detect().namespace_session_dir(&paths::session_dir());
+        Self {
+            session_dir: session_dir.to_string_lossy().into_owned(),
+            state_dir: paths::state_dir().to_string_lossy().into_owned(),
+            ..Self::default()
+        }
+    }
+
+    /// Load the config file (if any) overridden by `HYPRDROVER_*` variables.
+    /// Settings missing from both keep their default values.
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        Self::load_layered(&Self::path(), &[])
+    }
+
+    /// Layer the settings: defaults (see [`from_environment`](Self::from_environment)),
+    /// then the file at `path`, then `HYPRDROVER_*` environment variables,
+    /// then command-line `flags`
+    pub fn load_layered(path: &Path, flags: &[Override]) -> Result<Self, Box<dyn Error>> {
+        let mut table = if path.exists() {
+            fs::read_to_string(path)?
+                .parse::<Table>()
+                .map_err(|e| format!("{}: {}", path.display(), e))?
+        } else {
+            Table::new()
+        };
+        let defaults = Self::from_environment();
+        for (key, dir) in [
+            ("session_dir", defaults.session_dir),
+            ("state_dir", defaults.state_dir),
+        ] {
+            table.entry(key).or_insert(dir.into());
+        }
+
+        layers::apply_overrides(&mut table, &layers::env_overrides(env::vars()))
+            .map_err(|e| format!("environment: {}", e))?;
+        layers::apply_overrides(&mut table, flags)?;
+
+        Ok(table
+            .try_into()
+            .map_err(|e| format!("{}: {}", path.display(), e))?)
+    }
+
+    /// Contents of the signing key file, if one is configured
+    pub fn signing_key(&self) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        match &self.signing_key {
+            Some(path) => fs::read(path)
+                .map(Some)
+                .map_err(|e| format!("could not read signing key {}: {}", path, e).into()),
+            None => Ok(None),
+        }
+    }
+
+    /// File long-running commands log to, rotated as `daemon.log.1` and so on
+    pub fn log_path(&self) -> PathBuf {
+        Path::new(&self.state_dir).join("logs").join("daemon.log")
+    }
+
+    /// Directory of the activity log, one JSON-lines file per day
+    pub fn activity_dir(&self) -> PathBuf {
+        Path::new(&self.state_dir).join("activity")
+    }
+
+    /// Marker telling whether the last session on `seat` ended cleanly
+    pub fn session_marker(&self, seat: &str) -> SessionMarker {
+        SessionMarker::new(&self.state_dir, seat)
+    }
+
+    /// Check the settings for values that would break saving or restoring
+    pub fn validate(&self) -> Result<(), String> {
+        if self.session_dir.trim().is_empty() {
+            return Err("session_dir is empty".to_string());
+        }
+        if self.ignored_classes.iter().any(|c| c.trim().is_empty()) {
+            return Err("ignored_classes contains an empty class name".to_string());
+        }
+        if let Some(hook) = self
+            .hooks
+            .iter()
+            .find(|h| h.event.trim().is_empty() || h.command.trim().is_empty())
+        {
+            return Err(format!(
+                "hook needs both an event and a command: {:?}",
+                hook
+            ));
+        }
+        if crate::restore::matcher::by_name(&self.matcher).is_none() {
+            return Err(format!(
+                "unknown matcher `{}` (expected `default` or `class`)",
+                self.matcher
+            ));
+        }
+        if let Some(rule) = self
+            .rules
+            .iter()
+            .find(|r| r.class.is_none() && r.title.is_none() && r.workspace.is_none())
+        {
+            return Err(format!(
+                "rule needs at least one of class, title or workspace: {:?}",
+                rule
+            ));
+        }
+        if let Some(companion) = self
+            .companions
+            .iter()
+            .find(|c| c.name.trim().is_empty() || c.command.trim().is_empty())
+        {
+            return Err(format!(
+                "companion needs both a name and a command: {:?}",
+                companion
+            ));
+        }
+        if self.timeouts.launch_secs == 0 {
+            return Err("timeouts.launch_secs must be at least 1".to_string());
+        }
+        if let Some(wave) = self.waves.iter().find(|w| w.classes.is_empty()) {
+            return Err(format!("launch wave lists no classes: {:?}", wave));
+        }
+        for dependency in &self.dependencies {
+            for spec in &dependency.after {
+                Requirement::parse(spec)
+                    .map_err(|e| format!("dependency of {}: {}", dependency.class, e))?;
+            }
+        }
+        if let Some(cycle) = dependencies::find_cycle(&self.dependencies) {
+            return Err(format!(
+                "launch dependencies form a cycle: {}",
+                cycle.join(" -> ")
+            ));
+        }
+        if let Some(script) = &self.script {
+            if !Path::new(script).exists() {
+                return Err(format!("script {} does not exist", script));
+            }
+        }
+        if let Some(key) = &self.signing_key {
+            if !Path::new(key).exists() {
+                return Err(format!("signing key {} does not exist", key));
+            }
         }
+        Ok(())
     }
 }
@@ -1,8 +1,10 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use crate::config::Config;
+    use crate::config::{layers, Config};
     use std::env;
     use std::path::PathBuf;
+    use toml::Table;
 
     #[test]
     fn test_config_defaults() {
@@ -20,4 +22,164 @@ mod tests {
         assert!(config.ignored_classes.contains(&"rofi".to_string()));
         assert!(config.ignored_classes.contains(&"waybar".to_string()));
     }
+
+    #[test]
+    fn test_config_validate() {
+        assert!(Config::default().validate().is_ok());
+
+        let config = Config {
+            ignored_classes: vec!["".to_string()],
+            ..Config::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_config_validate_dependencies() {
+        let parse = |toml: &str| toml::from_str::<Config>(toml).unwrap();
+
+        let config = parse(
+            r#"
+            [[dependencies]]
+            class = "thunderbird"
+            after = ["class:keepassxc", "hook:true"]
+            "#,
+        );
+        assert!(config.validate().is_ok());
+
+        let bad_spec = parse(
+            r#"
+            [[dependencies]]
+            class = "thunderbird"
+            after = ["keepassxc"]
+            "#,
+        );
+        assert!(bad_spec.validate().is_err());
+
+        let cycle = parse(
+            r#"
+            [[dependencies]]
+            class = "a"
+            after = ["class:b"]
+
+            [[dependencies]]
+            class = "b"
+            after = ["class:A"]
+            "#,
+        );
+        assert!(cycle.validate().unwrap_err().contains("cycle"));
+    }
+
+    #[test]
+    fn test_config_partial_toml_keeps_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+            [[hooks]]
+            event = "openwindow"
+            when = { class = "zoom" }
+            command = "echo zoom"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.hooks.len(), 1);
+        assert_eq!(config.hooks[0].when["class"], "zoom");
+        assert!(config.ignored_classes.contains(&"rofi".to_string()));
+    }
+
+    #[test]
+    fn test_env_overrides_only_pick_known_settings() {
+        let vars = vec![
+            ("HYPRDROVER_SESSION_DIR".to_string(), "/tmp/s".to_string()),
+            ("HYPRDROVER_CONFIG".to_string(), "/tmp/c.toml".to_string()),
+            ("HYPRDROVER_SCRIPT".to_string(), "/tmp/r.rhai".to_string()),
+            ("HOME".to_string(), "/home/me".to_string()),
+        ];
+
+        assert_eq!(
+            layers::env_overrides(vars),
+            vec![
+                ("script".to_string(), "/tmp/r.rhai".to_string()),
+                ("session_dir".to_string(), "/tmp/s".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_overrides_replace_file_settings() {
+        let mut table: Table = r#"
+            session_dir = "/from/file"
+            matcher = "class"
+            "#
+        .parse()
+        .unwrap();
+
+        let overrides = vec![
+            layers::parse_override("session-dir=/from/flag").unwrap(),
+            layers::parse_override("ignored_classes=rofi, waybar").unwrap(),
+            layers::parse_override(r#"rules=[{ class = "zoom", action = "ignore" }]"#).unwrap(),
+        ];
+        layers::apply_overrides(&mut table, &overrides).unwrap();
+        let config: Config = table.try_into().unwrap();
+
+        assert_eq!(config.session_dir, "/from/flag");
+        assert_eq!(config.matcher, "class");
+        assert_eq!(config.ignored_classes, vec!["rofi", "waybar"]);
+        assert_eq!(config.rules.len(), 1);
+
+        let mut table = Table::new();
+        let typo = vec![("sesion_dir".to_string(), "/x".to_string())];
+        assert!(layers::apply_overrides(&mut table, &typo).is_err());
+        assert!(layers::parse_override("no-equals-sign").is_err());
+    }
+
+    #[test]
+    fn test_nested_overrides_merge_into_sections() {
+        let mut table: Table = r#"
+            [daemon]
+            session = "from-file"
+            "#
+        .parse()
+        .unwrap();
+
+        let vars = vec![("HYPRDROVER_DAEMON__BURST".to_string(), "5".to_string())];
+        let mut overrides = layers::env_overrides(vars);
+        assert_eq!(
+            overrides,
+            vec![("daemon.burst".to_string(), "5".to_string())]
+        );
+        overrides.push(layers::parse_override("daemon.autosave-secs=30").unwrap());
+        layers::apply_overrides(&mut table, &overrides).unwrap();
+        let config: Config = table.try_into().unwrap();
+
+        assert_eq!(config.daemon.session, "from-file");
+        assert_eq!(config.daemon.autosave_secs, 30);
+        assert_eq!(config.daemon.burst, 5);
+
+        let mut table = Table::new();
+        let typo = vec![("daemon.autosave".to_string(), "30".to_string())];
+        assert!(layers::apply_overrides(&mut table, &typo).is_err());
+    }
+
+    #[test]
+    fn test_timeouts_and_notifications() {
+        let config: Config = toml::from_str(
+            r#"
+            [timeouts]
+            launch_secs = 25
+
+            [notifications]
+            progress = false
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.timeouts.launch_secs, 25);
+        assert_eq!(config.timeouts.dependency_secs, 30);
+        assert!(!config.notifications.progress);
+        assert!(config.notifications.quick);
+
+        let mut config = Config::default();
+        config.timeouts.launch_secs = 0;
+        assert!(config.validate().is_err());
+    }
 }
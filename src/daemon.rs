@@ -0,0 +1,325 @@
+//! `hyprdrover daemon`: keeps the session in memory as it changes and
+//! writes it out regularly, so a compositor crash loses at most a few
+//! seconds of layout.
+//!
+//! The model is refreshed once events settle rather than on every event,
+//! and saved every `autosave_secs` when it changed. A burst of closed windows
+//! or workspaces (a logout, or a compositor about to go down) saves the
+//! model as it stood before the burst right away. Quicksaves go through the
+//! control socket and write the model without capturing again.
+
+use crate::config::Config;
+use crate::ipc::bus::EventBus;
+use crate::ipc::events::{Backoff, IpcEventListener};
+use crate::ipc::SessionSnapshot;
+use crate::runtime::pause::Pauser;
+use crate::runtime::{DaemonState, RuntimeContext};
+use crate::state::SessionManager;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fs;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Quiet time after the last change before the model is refreshed, so a
+/// burst of events costs one capture
+const SETTLE: Duration = Duration::from_millis(500);
+/// Span within which closes count as one burst
+const BURST_WINDOW: Duration = Duration::from_secs(2);
+/// How often to look again while paused, when `hyprdrover resume` may end
+/// the pause without an event
+const PAUSED_POLL: Duration = Duration::from_secs(1);
+/// How long to wait for a restarted compositor before stopping, which also
+/// covers logging in again after a crash
+pub const RECONNECT_GRACE: Duration = Duration::from_secs(120);
+
+/// The last captured session and whether it changed since it was written
+#[derive(Default)]
+struct Model {
+    snapshot: Option<SessionSnapshot>,
+    unsaved: bool,
+}
+
+type SharedModel = Arc<Mutex<Model>>;
+
+/// Events after which the saved layout may differ
+fn changes_layout(name: &str) -> bool {
+    matches!(
+        name,
+        "openwindow"
+            | "closewindow"
+            | "movewindow"
+            | "windowtitle"
+            | "changefloatingmode"
+            | "fullscreen"
+            | "pin"
+            | "createworkspace"
+            | "destroyworkspace"
+            | "moveworkspace"
+            | "renameworkspace"
+            | "monitoradded"
+            | "monitorremoved"
+    )
+}
+
+/// Counts closes in a sliding window; `threshold` of them make a burst
+struct BurstDetector {
+    threshold: usize,
+    window: Duration,
+    seen: VecDeque<Instant>,
+}
+
+impl BurstDetector {
+    fn new(threshold: usize, window: Duration) -> Self {
+        Self {
+            threshold: threshold.max(1),
+            window,
+            seen: VecDeque::new(),
+        }
+    }
+
+    /// Record a close at `now`; true when it completes a burst
+    fn observe(&mut self, now: Instant) -> bool {
+        while self
+            .seen
+            .front()
+            .is_some_and(|&t| now.duration_since(t) > self.window)
+        {
+            self.seen.pop_front();
+        }
+        self.seen.push_back(now);
+        if self.seen.len() >= self.threshold {
+            self.seen.clear();
+            return true;
+        }
+        false
+    }
+}
+
+/// The daemon lock, holding our pid; removed with the control socket on drop
+struct Lock {
+    path: PathBuf,
+    socket: PathBuf,
+}
+
+impl Lock {
+    fn acquire(context: &RuntimeContext) -> Result<Self, Box<dyn Error>> {
+        if let DaemonState::Running(pid) = context.daemon_state() {
+            if pid != std::process::id() {
+                return Err(format!("a daemon is already running (pid {})", pid).into());
+            }
+        }
+        fs::create_dir_all(context.runtime_dir())?;
+        fs::write(context.lock_path(), format!("{}\n", std::process::id()))?;
+        Ok(Self {
+            path: context.lock_path(),
+            socket: context.control_socket_path(),
+        })
+    }
+}
+
+impl Drop for Lock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.socket);
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+/// Run until the compositor is gone for good, following it across
+/// restarts. Every event read is published on `bus`, so other subsystems
+/// subscribed to it share the daemon's connection.
+pub fn run(config: &Config, bus: &EventBus) -> Result<(), Box<dyn Error>> {
+    let context = RuntimeContext::for_config(config);
+    let mut _lock = Lock::acquire(&context)?;
+    let manager = SessionManager::new(config.clone());
+    let model = SharedModel::default();
+    serve_control(&context, config, model.clone())?;
+
+    // `activewindow` is for the auto-pause check
+    let events = bus.subscribe("model", &config.event_buffer, |event| {
+        changes_layout(event.name()) || matches!(event.name(), "activewindow" | "reconnected")
+    });
+    bus.feed(
+        IpcEventListener::new()?,
+        Backoff {
+            give_up: Some(RECONNECT_GRACE),
+            ..Backoff::default()
+        },
+    );
+    let mut pauser = Pauser::new(&context, &config.pause.fullscreen_classes);
+    let mut bursts = BurstDetector::new(config.daemon.burst, BURST_WINDOW);
+    let autosave_every = Duration::from_secs(config.daemon.autosave_secs.max(1));
+    let session = config.daemon.session.as_str();
+    println!(
+        "Daemon started; autosaving to `{}` every {} s",
+        session,
+        autosave_every.as_secs()
+    );
+
+    refresh(&manager, &model);
+    let mut last_save = Instant::now();
+    let mut changed_at = None;
+    loop {
+        let next_save = autosave_every.saturating_sub(last_save.elapsed());
+        let wait = if pauser.is_paused() {
+            // The timers may have run out, which would make this a busy loop
+            PAUSED_POLL
+        } else {
+            changed_at.map_or(next_save, |at: Instant| {
+                SETTLE.saturating_sub(at.elapsed()).min(next_save)
+            })
+        };
+        if let Some(event) = events.recv_timeout(wait) {
+            let name = event.name();
+            if name == "reconnected" {
+                // The runtime directory belongs to the new instance now
+                let context = RuntimeContext::for_config(config);
+                match Lock::acquire(&context) {
+                    Ok(lock) => _lock = lock,
+                    Err(e) => {
+                        println!("Compositor restarted, but {}; stopping", e);
+                        return Ok(());
+                    }
+                }
+                serve_control(&context, config, model.clone())?;
+                pauser = Pauser::new(&context, &config.pause.fullscreen_classes);
+                println!("Compositor restarted; following the new instance");
+                changed_at = Some(Instant::now());
+                continue;
+            }
+            if pauser.tracks(name) {
+                if let Ok(clients) = crate::ipc::get_clients() {
+                    pauser.update(&clients);
+                }
+            }
+            let closing = matches!(name, "closewindow" | "destroyworkspace");
+            if closing && bursts.observe(Instant::now()) && !pauser.is_paused() {
+                println!("Windows are closing quickly; saving the session as it was");
+                save(&manager, &model, session);
+                last_save = Instant::now();
+            }
+            if changes_layout(name) {
+                changed_at = Some(Instant::now());
+            }
+        } else if events.is_closed() {
+            break;
+        }
+
+        if pauser.is_paused() {
+            continue;
+        }
+        if changed_at.is_some_and(|at| at.elapsed() >= SETTLE) {
+            refresh(&manager, &model);
+            changed_at = None;
+        }
+        if last_save.elapsed() >= autosave_every {
+            save(&manager, &model, session);
+            last_save = Instant::now();
+        }
+    }
+
+    println!("No compositor came back; saving and stopping");
+    if !pauser.is_paused() {
+        save(&manager, &model, session);
+    }
+    Ok(())
+}
+
+/// Capture the session into the model
+fn refresh(manager: &SessionManager, model: &SharedModel) {
+    match manager.capture(false) {
+        Ok(snapshot) => {
+            let mut model = model.lock().unwrap();
+            model.snapshot = Some(snapshot);
+            model.unsaved = true;
+        }
+        Err(e) => eprintln!("Warning: could not capture the session: {}", e),
+    }
+}
+
+/// Write the model to `session` if it changed since the last write
+fn save(manager: &SessionManager, model: &SharedModel, session: &str) {
+    let mut model = model.lock().unwrap();
+    let Some(snapshot) = model.snapshot.as_ref().filter(|_| model.unsaved) else {
+        return;
+    };
+    match manager.save(snapshot, Some(session)) {
+        Ok(path) => {
+            println!("Autosaved to {}", path.display());
+            model.unsaved = false;
+        }
+        Err(e) => eprintln!("Warning: autosave failed: {}", e),
+    }
+}
+
+/// Answer `save` (a new timestamped session from the model) and `status`
+/// on the control socket, in the protocol of [`crate::runtime::control`]
+fn serve_control(
+    context: &RuntimeContext,
+    config: &Config,
+    model: SharedModel,
+) -> Result<(), Box<dyn Error>> {
+    let path = context.control_socket_path();
+    // Left behind by a daemon that didn't stop cleanly; the lock is ours now
+    let _ = fs::remove_file(&path);
+    let listener = UnixListener::bind(&path)?;
+    let manager = SessionManager::new(config.clone());
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            if let Err(e) = answer(stream, &manager, &model) {
+                eprintln!("Warning: control request failed: {}", e);
+            }
+        }
+    });
+    Ok(())
+}
+
+fn answer(
+    mut stream: UnixStream,
+    manager: &SessionManager,
+    model: &SharedModel,
+) -> Result<(), Box<dyn Error>> {
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let reply = match line.trim() {
+        "save" => {
+            let model = model.lock().unwrap();
+            let saved = match &model.snapshot {
+                Some(snapshot) => manager.save(snapshot, None),
+                None => manager.snapshot(None, None),
+            };
+            match saved {
+                Ok(path) => format!("ok {}", path.display()),
+                Err(e) => format!("error {}", e),
+            }
+        }
+        "status" => "ok running".to_string(),
+        other => format!("error unknown command {:?}", other),
+    };
+    writeln!(stream, "{}", reply)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst_needs_closes_close_together() {
+        let mut bursts = BurstDetector::new(3, Duration::from_secs(2));
+        let t0 = Instant::now();
+        let at = |ms| t0 + Duration::from_millis(ms);
+
+        assert!(!bursts.observe(at(0)));
+        assert!(!bursts.observe(at(500)));
+        // The first close has aged out by now
+        assert!(!bursts.observe(at(2600)));
+        assert!(!bursts.observe(at(2700)));
+        assert!(bursts.observe(at(2800)));
+        assert!(!bursts.observe(at(2900)));
+    }
+}
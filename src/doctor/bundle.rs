@@ -0,0 +1,191 @@
+use crate::activity::export::TimeRange;
+use crate::activity::ActivityLog;
+use crate::config::Config;
+use crate::ipc;
+use crate::logging;
+use crate::paths;
+use crate::restore::report::RestoreReport;
+use crate::state::anonymize::hash_text;
+use crate::state::{anonymize_snapshot, format, SessionManager};
+use chrono::{Local, TimeDelta};
+use std::env;
+use std::error::Error;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// How much of the end of each log goes into a bundle
+const LOG_TAIL_BYTES: u64 = 256 * 1024;
+
+/// Collect diagnostics into a gzipped tarball suitable for attaching to a bug report.
+///
+/// The bundle contains the doctor report, the configuration with the home
+/// directory redacted, the last restore report, the latest snapshot and the
+/// last day of focus history (both anonymized on request), and the end of
+/// the daemon's and the compositor's logs. The logs mention window titles,
+/// so anonymized bundles leave them out.
+pub fn create_bundle(
+    config: &Config,
+    output: Option<&Path>,
+    anonymize: bool,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let timestamp = Local::now().format("%Y-%m-%d_%H-%M-%S");
+    let bundle_name = format!("hyprdrover-debug_{}", timestamp);
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.gz", bundle_name)));
+
+    let staging_root = paths::staging_dir("bundle", Path::new(&config.state_dir))?;
+    let staging = staging_root.join(&bundle_name);
+    fs::create_dir(&staging)?;
+
+    let result = write_contents(config, &staging, anonymize).and_then(|_| {
+        let status = Command::new("tar")
+            .arg("-czf")
+            .arg(&output)
+            .arg("-C")
+            .arg(&staging_root)
+            .arg(&bundle_name)
+            .status()?;
+        if !status.success() {
+            return Err(format!("tar exited with {}", status).into());
+        }
+        Ok(())
+    });
+
+    let _ = fs::remove_dir_all(&staging_root);
+    result.map(|_| output)
+}
+
+fn write_contents(config: &Config, dir: &Path, anonymize: bool) -> Result<(), Box<dyn Error>> {
+    let checks = super::run_checks(config);
+    fs::write(dir.join("doctor.txt"), super::format_report(&checks))?;
+
+    let config_json = serde_json::to_string_pretty(config)?;
+    fs::write(dir.join("config.json"), redact_home(&config_json))?;
+
+    let manager = SessionManager::new(config.clone());
+    // Sessions are listed by modification time, newest first
+    if let Some(latest) = manager.list_sessions()?.first() {
+        let content = fs::read_to_string(latest)?;
+        let content = if anonymize {
+            let mut snapshot = format::parse(content.as_bytes())?;
+            anonymize_snapshot(&mut snapshot);
+            serde_json::to_string_pretty(&snapshot)?
+        } else {
+            content
+        };
+        fs::write(dir.join("latest-session.json"), content)?;
+    }
+
+    if let Some(report) = RestoreReport::load_last(&manager.reports_dir())? {
+        fs::write(
+            dir.join("last-restore-report.json"),
+            serde_json::to_string_pretty(&report)?,
+        )?;
+    }
+
+    if !anonymize {
+        write_logs(config, dir)?;
+    }
+    write_activity(config, dir, anonymize)?;
+
+    fs::write(
+        dir.join("version.txt"),
+        format!("hyprdrover {}\n", env!("CARGO_PKG_VERSION")),
+    )?;
+
+    Ok(())
+}
+
+/// The end of the daemon log and its latest rotation, or of the journal
+/// when the daemon runs under systemd, plus Hyprland's recent log
+fn write_logs(config: &Config, dir: &Path) -> Result<(), Box<dyn Error>> {
+    let path = config.log_path();
+    let mut log = String::new();
+    for path in [logging::rotated_path(&path, 1), path.clone()] {
+        if let Ok(tail) = tail(&path, LOG_TAIL_BYTES) {
+            log.push_str(&tail);
+        }
+    }
+    if log.is_empty() {
+        log = Command::new("journalctl")
+            .args(["--user", "--unit", "hyprdrover.service", "--lines", "2000"])
+            .arg("--no-pager")
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .map(|output| String::from_utf8_lossy(&output.stdout).into_owned())
+            .unwrap_or_default();
+    }
+    if !log.is_empty() {
+        fs::write(dir.join("daemon.log"), redact_home(&log))?;
+    }
+
+    if let Ok(lines) = ipc::get_rolling_log() {
+        if !lines.is_empty() {
+            fs::write(dir.join("hyprland.log"), redact_home(&lines.join("\n")))?;
+        }
+    }
+    Ok(())
+}
+
+/// The last `max` bytes of a file, from the first whole line on
+fn tail(path: &Path, max: u64) -> io::Result<String> {
+    let mut file = File::open(path)?;
+    let len = file.metadata()?.len();
+    file.seek(SeekFrom::Start(len.saturating_sub(max)))?;
+    let mut bytes = vec![];
+    file.read_to_end(&mut bytes)?;
+    let text = String::from_utf8_lossy(&bytes).into_owned();
+    if len <= max {
+        return Ok(text);
+    }
+    Ok(text
+        .split_once('\n')
+        .map_or(String::new(), |(_, rest)| rest.to_string()))
+}
+
+/// The focus history (activity log) of the last day, one JSON entry per line
+fn write_activity(config: &Config, dir: &Path, anonymize: bool) -> Result<(), Box<dyn Error>> {
+    let range = TimeRange {
+        from: Some(Local::now() - TimeDelta::days(1)),
+        to: None,
+    };
+    let mut entries = ActivityLog::new(config.activity_dir()).range(&range)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut lines = String::new();
+    for entry in &mut entries {
+        if anonymize {
+            entry.title = hash_text(&entry.title);
+        }
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    fs::write(dir.join("activity.jsonl"), lines)?;
+    Ok(())
+}
+
+fn redact_home(text: &str) -> String {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tail_starts_at_a_whole_line() {
+        let path = env::temp_dir().join(format!("hyprdrover-tail-{}.log", std::process::id()));
+        fs::write(&path, "first line\nsecond\nthird\n").unwrap();
+        assert_eq!(tail(&path, 1024).unwrap(), "first line\nsecond\nthird\n");
+        assert_eq!(tail(&path, 10).unwrap(), "third\n");
+        let _ = fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,184 @@
+pub mod bundle;
+
+use crate::config::Config;
+use crate::ipc::{self, socket};
+use crate::runtime::{DaemonState, RuntimeContext};
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+
+/// Outcome of a single diagnostic check
+pub struct Check {
+    pub name: &'static str,
+    pub ok: bool,
+    pub detail: String,
+}
+
+impl Check {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok: true,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            ok: false,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// Run every check in order. Checks never abort early so a single report
+/// shows everything that is wrong.
+pub fn run_checks(config: &Config) -> Vec<Check> {
+    let mut checks = vec![check_runtime_dir()];
+
+    let overrides = socket::socket_overrides();
+    match ipc::instance_signature() {
+        Ok(signature) => {
+            let source = if env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+                "from environment"
+            } else {
+                "discovered"
+            };
+            checks.push(Check::pass(
+                "Hyprland instance",
+                format!("{} ({})", signature, source),
+            ));
+        }
+        // Both sockets given explicitly: no instance needs to be found
+        Err(_) if overrides.command.is_some() && overrides.event.is_some() => {
+            checks.push(Check::pass("Hyprland instance", "sockets overridden"))
+        }
+        Err(e) => checks.push(Check::fail("Hyprland instance", e.to_string())),
+    }
+    if let Ok(path) = socket::command_socket_path() {
+        checks.push(check_socket("Command socket", &path));
+    }
+    if let Ok(path) = socket::event_socket_path() {
+        checks.push(check_socket("Event socket", &path));
+    }
+
+    checks.push(check_version());
+    checks.push(check_compositor_config());
+    checks.push(check_daemon(config));
+    checks.push(check_config(config));
+    checks.push(check_session_dir(Path::new(&config.session_dir)));
+    checks
+}
+
+/// Render the checks with ✔/✘ markers followed by a summary line
+pub fn format_report(checks: &[Check]) -> String {
+    let mut report = String::new();
+    for check in checks {
+        let mark = if check.ok { "✔" } else { "✘" };
+        report.push_str(&format!("{} {:<18} {}\n", mark, check.name, check.detail));
+    }
+
+    let failed = checks.iter().filter(|c| !c.ok).count();
+    if failed == 0 {
+        report.push_str("\nAll checks passed.\n");
+    } else {
+        report.push_str(&format!("\n{} check(s) failed.\n", failed));
+    }
+    report
+}
+
+fn check_runtime_dir() -> Check {
+    match env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if Path::new(&dir).is_dir() => Check::pass("XDG_RUNTIME_DIR", dir),
+        Ok(dir) => Check::fail("XDG_RUNTIME_DIR", format!("{} does not exist", dir)),
+        Err(_) => Check::fail("XDG_RUNTIME_DIR", "not set"),
+    }
+}
+
+fn check_socket(name: &'static str, path: &Path) -> Check {
+    if !path.exists() {
+        return Check::fail(name, format!("{} does not exist", path.display()));
+    }
+
+    match UnixStream::connect(path) {
+        Ok(_) => Check::pass(name, path.display().to_string()),
+        Err(e) if e.kind() == ErrorKind::PermissionDenied => Check::fail(
+            name,
+            format!(
+                "{}: permission denied (owned by another user?)",
+                path.display()
+            ),
+        ),
+        Err(e) => Check::fail(name, format!("{}: {}", path.display(), e)),
+    }
+}
+
+fn check_version() -> Check {
+    let caps = ipc::capabilities();
+    match caps.version {
+        Some(version) if caps.v2_events => Check::pass("Hyprland version", version.to_string()),
+        Some(version) => Check::fail(
+            "Hyprland version",
+            format!("{} is old; using legacy event formats", version),
+        ),
+        None => Check::fail("Hyprland version", "could not query `hyprctl version`"),
+    }
+}
+
+fn check_compositor_config() -> Check {
+    match ipc::get_config_errors() {
+        Ok(errors) if errors.is_empty() => Check::pass("Hyprland config", "no errors"),
+        Ok(errors) => Check::fail(
+            "Hyprland config",
+            format!("{} error(s), first: {}", errors.len(), errors[0]),
+        ),
+        Err(e) => Check::fail("Hyprland config", format!("`configerrors` failed: {}", e)),
+    }
+}
+
+fn check_daemon(config: &Config) -> Check {
+    match RuntimeContext::for_config(config).daemon_state() {
+        DaemonState::Running(pid) => Check::pass("Daemon", format!("running (pid {})", pid)),
+        DaemonState::NotRunning => Check::pass("Daemon", "not running"),
+        DaemonState::Stale(pid) => Check::fail(
+            "Daemon",
+            format!("stale lock left by pid {} (daemon crashed?)", pid),
+        ),
+    }
+}
+
+fn check_config(config: &Config) -> Check {
+    let path = Config::path();
+    if path.exists() {
+        if let Err(e) = Config::load() {
+            return Check::fail("Configuration", e.to_string());
+        }
+    }
+
+    match config.validate() {
+        Ok(()) if path.exists() => Check::pass("Configuration", path.display().to_string()),
+        Ok(()) => Check::pass("Configuration", "no config file, using defaults"),
+        Err(e) => Check::fail("Configuration", e),
+    }
+}
+
+fn check_session_dir(dir: &Path) -> Check {
+    if let Err(e) = fs::create_dir_all(dir) {
+        return Check::fail("Session directory", format!("{}: {}", dir.display(), e));
+    }
+
+    let probe = dir.join(".hyprdrover-doctor");
+    match fs::write(&probe, b"") {
+        Ok(()) => {
+            let _ = fs::remove_file(&probe);
+            Check::pass("Session directory", dir.display().to_string())
+        }
+        Err(e) => Check::fail(
+            "Session directory",
+            format!("{} is not writable: {}", dir.display(), e),
+        ),
+    }
+}
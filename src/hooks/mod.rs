@@ -0,0 +1,145 @@
+#[cfg(test)]
+mod tests;
+
+use crate::config::HookConfig;
+use crate::ipc::events::HyprEvent;
+use crate::ipc::tracker::TrackedWindow;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// Runs configured shell commands in response to compositor events.
+///
+/// Each command gets the event described in its environment:
+/// `HYPRDROVER_EVENT` holds the event name and every event field is exported
+/// as `HYPRDROVER_<FIELD>` (e.g. `HYPRDROVER_CLASS`, `HYPRDROVER_ADDRESS`).
+pub struct HookRunner {
+    hooks: Vec<HookConfig>,
+}
+
+impl HookRunner {
+    pub fn new(hooks: Vec<HookConfig>) -> Self {
+        Self { hooks }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    /// Whether any hook listens for events called `name`; lets the listener
+    /// skip the rest without building owned events
+    pub fn wants(&self, name: &str) -> bool {
+        self.hooks
+            .iter()
+            .any(|hook| hook.event.eq_ignore_ascii_case(name))
+    }
+
+    /// Hooks whose event name and field filters match `event`
+    pub fn matching<'a>(&'a self, event: &'a HyprEvent) -> impl Iterator<Item = &'a HookConfig> {
+        let fields = event.fields();
+        self.hooks
+            .iter()
+            .filter(move |hook| hook_matches(hook, event.name(), &fields))
+    }
+
+    /// Spawn every matching hook without waiting for it to finish
+    pub fn handle(&self, event: &HyprEvent) {
+        self.handle_window(event, None);
+    }
+
+    /// Like [`handle`](Self::handle), with the class and title of the
+    /// window the event concerns filled in where the event lacks them
+    pub fn handle_window(&self, event: &HyprEvent, window: Option<&TrackedWindow>) {
+        let fields = event_fields(event, window);
+        for hook in self
+            .hooks
+            .iter()
+            .filter(|hook| hook_matches(hook, event.name(), &fields))
+        {
+            if let Err(e) = spawn_hook(hook, event.name(), &fields) {
+                eprintln!("Hook `{}` failed to start: {}", hook.command, e);
+            }
+        }
+    }
+}
+
+/// Build a one-off hook from `watch` arguments:
+/// `--on EVENT --exec COMMAND`, plus `--FIELD VALUE` for each field filter
+/// (e.g. `--class Spotify`)
+pub fn hook_from_args(args: &[String]) -> Result<HookConfig, String> {
+    let mut event = None;
+    let mut command = None;
+    let mut when = BTreeMap::new();
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let Some(flag) = arg.strip_prefix("--") else {
+            return Err(format!("unexpected argument {:?}", arg));
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| format!("{} needs a value", arg))?
+            .clone();
+        match flag {
+            "on" => event = Some(value),
+            "exec" => command = Some(value),
+            field => {
+                when.insert(field.to_string(), value);
+            }
+        }
+    }
+
+    Ok(HookConfig {
+        event: event.ok_or("missing --on EVENT")?,
+        when,
+        command: command.ok_or("missing --exec COMMAND")?,
+    })
+}
+
+/// The event's fields, plus `class` and `title` from `window` if the event
+/// doesn't carry them (or carries them empty)
+fn event_fields(event: &HyprEvent, window: Option<&TrackedWindow>) -> Vec<(&'static str, String)> {
+    let mut fields = event.fields();
+    if let Some(window) = window {
+        for (name, value) in [("class", &window.class), ("title", &window.title)] {
+            match fields.iter_mut().find(|(field, _)| *field == name) {
+                Some((_, existing)) if existing.is_empty() => *existing = value.clone(),
+                Some(_) => {}
+                None => fields.push((name, value.clone())),
+            }
+        }
+        if let Some(id) = &window.id {
+            fields.push(("window_id", id.clone()));
+        }
+    }
+    fields
+}
+
+fn hook_matches(hook: &HookConfig, event_name: &str, fields: &[(&'static str, String)]) -> bool {
+    if !hook.event.eq_ignore_ascii_case(event_name) {
+        return false;
+    }
+
+    hook.when.iter().all(|(key, expected)| {
+        fields.iter().any(|(name, value)| {
+            name.eq_ignore_ascii_case(key) && value.eq_ignore_ascii_case(expected)
+        })
+    })
+}
+
+fn spawn_hook(
+    hook: &HookConfig,
+    event_name: &str,
+    fields: &[(&'static str, String)],
+) -> std::io::Result<()> {
+    let mut command = Command::new("sh");
+    command.arg("-c").arg(&hook.command);
+    command.env("HYPRDROVER_EVENT", event_name);
+    for (name, value) in fields {
+        command.env(format!("HYPRDROVER_{}", name.to_uppercase()), value);
+    }
+
+    let mut child = command.spawn()?;
+    // Reap in the background so long-running hooks never stall the event loop
+    std::thread::spawn(move || child.wait());
+    Ok(())
+}
@@ -0,0 +1,93 @@
+use crate::config::HookConfig;
+use crate::hooks::{event_fields, hook_from_args, hook_matches, HookRunner};
+use crate::ipc::events::HyprEvent;
+use crate::ipc::tracker::TrackedWindow;
+use std::collections::BTreeMap;
+
+fn hook(event: &str, when: &[(&str, &str)]) -> HookConfig {
+    HookConfig {
+        event: event.to_string(),
+        when: when
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect::<BTreeMap<_, _>>(),
+        command: "true".to_string(),
+    }
+}
+
+fn opened(class: &str) -> HyprEvent {
+    HyprEvent::WindowOpened {
+        address: "5f3a".to_string(),
+        workspace: "1".to_string(),
+        class: class.to_string(),
+        title: "".to_string(),
+    }
+}
+
+#[test]
+fn test_hook_matches_event_and_fields() {
+    let runner = HookRunner::new(vec![hook("openwindow", &[("class", "zoom")])]);
+
+    assert_eq!(runner.matching(&opened("Zoom")).count(), 1);
+    assert_eq!(runner.matching(&opened("kitty")).count(), 0);
+}
+
+#[test]
+fn test_hook_ignores_other_events() {
+    let runner = HookRunner::new(vec![hook("monitoradded", &[("monitor", "DP-3")])]);
+
+    let added = HyprEvent::MonitorAdded {
+        id: Some(2),
+        name: "DP-3".to_string(),
+        description: None,
+    };
+    assert_eq!(runner.matching(&added).count(), 1);
+    assert_eq!(runner.matching(&opened("DP-3")).count(), 0);
+    assert!(runner.wants("MonitorAdded"));
+    assert!(!runner.wants("openwindow"));
+}
+
+#[test]
+fn test_hook_from_watch_args() {
+    let args: Vec<String> = [
+        "--on",
+        "openwindow",
+        "--class",
+        "Spotify",
+        "--exec",
+        "hyprctl dispatch movetoworkspacesilent 9",
+    ]
+    .iter()
+    .map(|s| s.to_string())
+    .collect();
+
+    let hook = hook_from_args(&args).unwrap();
+    assert_eq!(hook.event, "openwindow");
+    assert_eq!(hook.when["class"], "Spotify");
+    assert_eq!(hook.command, "hyprctl dispatch movetoworkspacesilent 9");
+
+    let runner = HookRunner::new(vec![hook]);
+    assert_eq!(runner.matching(&opened("spotify")).count(), 1);
+
+    assert!(hook_from_args(&args[..4]).is_err());
+    assert!(hook_from_args(&["--on".to_string()]).is_err());
+}
+
+#[test]
+fn test_hook_matches_on_tracked_window_fields() {
+    let runner = HookRunner::new(vec![hook("closewindow", &[("class", "zoom")])]);
+    let closed = HyprEvent::WindowClosed {
+        address: "5f3a".to_string(),
+    };
+    let window = TrackedWindow {
+        class: "zoom".to_string(),
+        title: "Meeting".to_string(),
+        id: Some("1a2b3c".to_string()),
+    };
+
+    assert_eq!(runner.matching(&closed).count(), 0);
+    let fields = event_fields(&closed, Some(&window));
+    assert!(fields.contains(&("title", "Meeting".to_string())));
+    assert!(fields.contains(&("window_id", "1a2b3c".to_string())));
+    assert!(hook_matches(&runner.hooks[0], closed.name(), &fields));
+}
@@ -0,0 +1,234 @@
+//! Async counterparts of [`HyprCommandClient`](super::HyprCommandClient) and
+//! [`IpcEventListener`](super::events::IpcEventListener) on tokio, for GUIs
+//! and daemons that can't spare a thread per socket (`tokio` feature).
+//! Errors are `Send + Sync`, so results can cross `tokio::spawn`.
+
+use super::events::{parse_event_ref, EventProfile, HyprEvent};
+use super::models::{HyprBind, HyprClient, HyprDevices, HyprMonitor, HyprWorkspace};
+use super::socket::{command_socket_path, event_socket_path};
+use serde::de::DeserializeOwned;
+use std::error::Error;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::UnixStream;
+
+/// Talks to Hyprland's command socket without blocking the runtime
+pub struct AsyncHyprCommandClient {
+    socket_path: PathBuf,
+}
+
+impl AsyncHyprCommandClient {
+    /// Client for the command socket in effect (see [`command_socket_path`])
+    pub fn new() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = command_socket_path().map_err(|e| e.to_string())?;
+        Ok(Self::with_socket(path))
+    }
+
+    pub fn with_socket(socket_path: impl AsRef<Path>) -> Self {
+        Self {
+            socket_path: socket_path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Send `args` and return the reply, which is JSON when `json` is set
+    pub async fn request(&self, args: &[&str], json: bool) -> io::Result<String> {
+        let flags = if json { "j" } else { "" };
+        self.send_raw(&format!("{}/{}", flags, args.join(" ")))
+            .await
+    }
+
+    /// Send `args` and parse the JSON reply into `T`
+    pub async fn request_json<T: DeserializeOwned>(
+        &self,
+        args: &[&str],
+    ) -> Result<T, Box<dyn Error + Send + Sync>> {
+        Ok(serde_json::from_str(&self.request(args, true).await?)?)
+    }
+
+    pub async fn clients(&self) -> Result<Vec<HyprClient>, Box<dyn Error + Send + Sync>> {
+        self.request_json(&["clients"]).await
+    }
+
+    pub async fn workspaces(&self) -> Result<Vec<HyprWorkspace>, Box<dyn Error + Send + Sync>> {
+        self.request_json(&["workspaces"]).await
+    }
+
+    pub async fn monitors(&self) -> Result<Vec<HyprMonitor>, Box<dyn Error + Send + Sync>> {
+        self.request_json(&["monitors"]).await
+    }
+
+    pub async fn binds(&self) -> Result<Vec<HyprBind>, Box<dyn Error + Send + Sync>> {
+        self.request_json(&["binds"]).await
+    }
+
+    pub async fn devices(&self) -> Result<HyprDevices, Box<dyn Error + Send + Sync>> {
+        self.request_json(&["devices"]).await
+    }
+
+    /// Send an already formatted request such as `j/clients`
+    pub async fn send_raw(&self, request: &str) -> io::Result<String> {
+        let mut stream = UnixStream::connect(&self.socket_path).await?;
+        stream.write_all(request.as_bytes()).await?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply).await?;
+        Ok(reply)
+    }
+}
+
+/// Connects to Hyprland's event socket (`.socket2.sock`) without blocking
+pub struct AsyncIpcEventListener {
+    socket_path: PathBuf,
+    profile: EventProfile,
+}
+
+impl AsyncIpcEventListener {
+    /// Listen on the event socket of the resolved Hyprland instance, or the
+    /// one given by `--event-socket`/`HYPRDROVER_EVENT_SOCKET`
+    pub fn new() -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let path = event_socket_path().map_err(|e| e.to_string())?;
+        Ok(Self::connect_to(path))
+    }
+
+    /// Listen on an explicit socket path
+    pub fn connect_to(socket_path: impl AsRef<Path>) -> Self {
+        Self {
+            socket_path: socket_path.as_ref().to_path_buf(),
+            profile: EventProfile::detect(),
+        }
+    }
+
+    /// Override the event format profile instead of relying on version probing
+    pub fn with_profile(mut self, profile: EventProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Open the socket; events are then read with [`AsyncEventStream::next`]
+    pub async fn connect(&self) -> io::Result<AsyncEventStream> {
+        let stream = UnixStream::connect(&self.socket_path).await?;
+        Ok(AsyncEventStream {
+            reader: BufReader::new(stream),
+            line: String::new(),
+            profile: self.profile,
+        })
+    }
+
+    /// Invoke `handler` for every event until the socket closes
+    pub async fn listen<F: FnMut(HyprEvent)>(&self, mut handler: F) -> io::Result<()> {
+        let mut events = self.connect().await?;
+        while let Some(event) = events.next().await? {
+            handler(event);
+        }
+        Ok(())
+    }
+}
+
+/// An open event socket
+pub struct AsyncEventStream {
+    reader: BufReader<UnixStream>,
+    line: String,
+    profile: EventProfile,
+}
+
+impl AsyncEventStream {
+    /// The next event, or `None` once the socket closes. Lines that aren't
+    /// events of this profile are skipped.
+    pub async fn next(&mut self) -> io::Result<Option<HyprEvent>> {
+        loop {
+            self.line.clear();
+            if self.reader.read_line(&mut self.line).await? == 0 {
+                return Ok(None);
+            }
+            let trimmed = self.line.trim_end_matches('\n');
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(event) = parse_event_ref(trimmed, self.profile) {
+                return Ok(Some(event.into_owned()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use tokio::net::UnixListener;
+
+    fn socket(name: &str) -> PathBuf {
+        let path = env::temp_dir().join(format!(
+            "hyprdrover-async-{}-{}.sock",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_file(&path);
+        path
+    }
+
+    #[tokio::test]
+    async fn test_request_against_mock_socket() {
+        let path = socket("cmd");
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 64];
+            let n = stream.read(&mut buf).await.unwrap();
+            stream.write_all(b"[]").await.unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        // Runs on another task, so the error type must be Send
+        let client = AsyncHyprCommandClient::with_socket(&path);
+        let clients = tokio::spawn(async move { client.clients().await })
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(clients.is_empty());
+        assert_eq!(server.await.unwrap(), "j/clients");
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_event_stream_ends_with_socket() {
+        let path = socket("events");
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            stream
+                .write_all(b"workspace>>3\nworkspacev2>>3,3\nclosewindow>>5f3a\n")
+                .await
+                .unwrap();
+        });
+
+        let mut events = AsyncIpcEventListener::connect_to(&path)
+            .with_profile(EventProfile::CURRENT)
+            .connect()
+            .await
+            .unwrap();
+        assert_eq!(
+            events.next().await.unwrap(),
+            Some(HyprEvent::WorkspaceChanged {
+                id: Some(3),
+                name: "3".to_string(),
+            })
+        );
+        assert_eq!(
+            events.next().await.unwrap(),
+            Some(HyprEvent::WindowClosed {
+                address: "5f3a".to_string(),
+            })
+        );
+        assert_eq!(events.next().await.unwrap(), None);
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,193 @@
+use super::dispatch::{Address, Dispatch, WorkspaceRef};
+use super::hypr_commands::batch;
+use super::models::HyprClient;
+use super::version::capabilities;
+use std::error::Error;
+
+/// Window commands queued up and sent together over one connection.
+///
+/// The setters mirror [`set_floating`](super::set_floating),
+/// [`move_window_pixel`](super::move_window_pixel) and friends. Those that
+/// can only toggle go by `live`, the window as queried before queuing.
+#[derive(Debug, Default)]
+pub struct CommandBatch {
+    commands: Vec<String>,
+    /// Dispatchers refused while queuing, by their place among the results
+    rejected: Vec<(usize, String)>,
+}
+
+impl CommandBatch {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len() + self.rejected.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queue a dispatcher with its arguments, e.g. `workspace 3`
+    pub fn dispatch(&mut self, command: &str) -> &mut Self {
+        self.commands.push(format!("dispatch {}", command));
+        self
+    }
+
+    /// Queue a dispatcher built from its parts. One with arguments that
+    /// can't be sent is not queued, and fails in [`send`](Self::send).
+    pub fn dispatch_typed(&mut self, dispatch: Dispatch) -> &mut Self {
+        match dispatch.to_command_string() {
+            Ok(command) => self.dispatch(&command),
+            Err(e) => {
+                self.rejected.push((self.len(), e.to_string()));
+                self
+            }
+        }
+    }
+
+    /// Queue a silent move of the window to `workspace`
+    pub fn move_to_workspace(&mut self, address: &str, workspace: WorkspaceRef) -> &mut Self {
+        self.dispatch_typed(Dispatch::MoveToWorkspaceSilent {
+            workspace,
+            window: address.into(),
+        })
+    }
+
+    pub fn set_floating(&mut self, live: &HyprClient, floating: bool) -> &mut Self {
+        let window = Address::from(live.address.as_str());
+        if capabilities().set_floating {
+            return self.dispatch_typed(if floating {
+                Dispatch::SetFloating(window)
+            } else {
+                Dispatch::SetTiled(window)
+            });
+        }
+        if live.floating != floating {
+            self.dispatch_typed(Dispatch::ToggleFloating(window));
+        }
+        self
+    }
+
+    pub fn set_pinned(&mut self, live: &HyprClient, pinned: bool) -> &mut Self {
+        if live.pinned != pinned {
+            self.dispatch_typed(Dispatch::Pin(live.address.as_str().into()));
+        }
+        self
+    }
+
+    pub fn move_window_pixel(&mut self, address: &str, x: i32, y: i32) -> &mut Self {
+        self.dispatch_typed(Dispatch::MoveWindowPixel {
+            window: address.into(),
+            x,
+            y,
+        })
+    }
+
+    pub fn resize_window_pixel(&mut self, address: &str, width: i32, height: i32) -> &mut Self {
+        self.dispatch_typed(Dispatch::ResizeWindowPixel {
+            window: address.into(),
+            width,
+            height,
+        })
+    }
+
+    /// Queue a `setprop`, like [`set_window_prop`](super::set_window_prop)
+    pub fn set_prop(&mut self, address: &str, prop: &str, value: &str) -> &mut Self {
+        self.commands
+            .push(format!("setprop address:{} {} {}", address, prop, value));
+        self
+    }
+
+    /// Send the queued commands, returning for each, in order, whether
+    /// Hyprland accepted it (`Err` holds its reply). Fails only when the
+    /// request itself fails.
+    ///
+    /// Commands whose reply can't be told apart (older compositors run the
+    /// replies together) are sent again one at a time, unless sending them
+    /// twice would undo them.
+    pub fn send(self) -> Result<Vec<Result<(), String>>, Box<dyn Error>> {
+        let mut results = vec![];
+        if !self.commands.is_empty() {
+            self.send_commands(&mut results)?;
+        }
+        for (index, error) in self.rejected {
+            results.insert(index, Err(error));
+        }
+        Ok(results)
+    }
+
+    fn send_commands(&self, results: &mut Vec<Result<(), String>>) -> Result<(), Box<dyn Error>> {
+        let replies = batch(&self.commands)?;
+        for (command, reply) in self.commands.iter().zip(replies) {
+            let reply = match reply {
+                Some(reply) => reply,
+                None if repeatable(command) => batch(std::slice::from_ref(command))?
+                    .pop()
+                    .flatten()
+                    .unwrap_or_default(),
+                None => "no reply of its own from the compositor".to_string(),
+            };
+            results.push(if reply.eq_ignore_ascii_case("ok") {
+                Ok(())
+            } else {
+                Err(format!("{}: {}", command, reply))
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Whether sending `command` again leaves things as sending it once does.
+/// Toggles (`togglefloating`, `pin`) don't.
+fn repeatable(command: &str) -> bool {
+    const REPEATABLE: &[&str] = &[
+        "dispatch movetoworkspacesilent ",
+        "dispatch movewindowpixel ",
+        "dispatch resizewindowpixel ",
+        "dispatch setfloating ",
+        "dispatch settiled ",
+        "dispatch focuswindow ",
+        "dispatch workspace ",
+        "setprop ",
+    ];
+    REPEATABLE.iter().any(|prefix| command.starts_with(prefix))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_toggles_only_when_state_differs() {
+        let live = HyprClient {
+            pinned: true,
+            ..HyprClient::fixture("0x1", "kitty").with_floating(true)
+        };
+
+        let mut batch = CommandBatch::new();
+        batch
+            .move_to_workspace("0x1", WorkspaceRef::Id(3))
+            .set_pinned(&live, true)
+            .move_window_pixel("0x1", 10, 20)
+            .set_prop("0x1", "alpha", "0.9");
+        assert_eq!(
+            batch.commands,
+            [
+                "dispatch movetoworkspacesilent 3,address:0x1",
+                "dispatch movewindowpixel exact 10 20,address:0x1",
+                "setprop address:0x1 alpha 0.9",
+            ]
+        );
+        batch.set_pinned(&live, false);
+        assert_eq!(batch.commands[3], "dispatch pin address:0x1");
+        assert!(repeatable(&batch.commands[0]));
+        assert!(!repeatable(&batch.commands[3]));
+
+        batch.dispatch_typed(Dispatch::FocusWindow(Address::from("0x1;dispatch exit")));
+        assert_eq!(batch.commands.len(), 4);
+        assert_eq!(batch.len(), 5);
+        assert_eq!(batch.rejected[0].0, 4);
+    }
+}
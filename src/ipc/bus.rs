@@ -0,0 +1,167 @@
+//! Fan-out of one event stream to several consumers.
+//!
+//! Each subscriber gets its own bounded queue (see [`queue`](super::queue)),
+//! so one that falls behind loses or coalesces its own events under its
+//! [`OverflowPolicy`](super::queue::OverflowPolicy) without holding up the
+//! others. Only a subscriber with the `block` policy stalls the bus, and
+//! with it everyone else.
+
+use super::events::{Backoff, HyprEvent, IpcEventListener};
+use super::queue::{self, EventBufferConfig, EventReceiver, EventSender, QueueStats};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+type Filter = Box<dyn Fn(&HyprEvent) -> bool + Send>;
+
+struct Subscriber {
+    name: String,
+    filter: Filter,
+    sender: EventSender,
+}
+
+/// Hands every published event to each subscriber whose filter accepts it.
+/// Clones share the same subscribers.
+#[derive(Clone, Default)]
+pub struct EventBus {
+    subscribers: Arc<Mutex<Vec<Subscriber>>>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Receive the events `filter` accepts from now on, through a buffer
+    /// bounded by `settings`. `name` identifies the subscriber in
+    /// [`stats`](Self::stats).
+    pub fn subscribe(
+        &self,
+        name: &str,
+        settings: &EventBufferConfig,
+        filter: impl Fn(&HyprEvent) -> bool + Send + 'static,
+    ) -> EventReceiver {
+        let (sender, receiver) = queue::bounded(settings);
+        self.subscribers.lock().unwrap().push(Subscriber {
+            name: name.to_string(),
+            filter: Box::new(filter),
+            sender,
+        });
+        receiver
+    }
+
+    /// Deliver `event`; subscribers whose receiver is gone are dropped.
+    /// Returns how many subscribers took it.
+    pub fn publish(&self, event: &HyprEvent) -> usize {
+        let mut delivered = 0;
+        self.subscribers.lock().unwrap().retain(|s| {
+            if !(s.filter)(event) {
+                return true;
+            }
+            let open = s.sender.send(event.clone());
+            delivered += usize::from(open);
+            open
+        });
+        delivered
+    }
+
+    /// Events each subscriber lost or had coalesced so far, by name
+    pub fn stats(&self) -> Vec<(String, QueueStats)> {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|s| (s.name.clone(), s.sender.stats()))
+            .collect()
+    }
+
+    /// Let every subscriber know no more events are coming
+    pub fn close(&self) {
+        self.subscribers.lock().unwrap().clear();
+    }
+
+    /// Whether [`close`](Self::close) was called or every subscriber is gone
+    pub fn is_closed(&self) -> bool {
+        self.subscribers.lock().unwrap().is_empty()
+    }
+
+    /// Publish what `listener` reads on a background thread. One connection
+    /// serves every subscriber. When the compositor restarts, the listener
+    /// reconnects with `backoff` and publishes a
+    /// [`HyprEvent::Reconnected`]; the bus closes once it gives up, or
+    /// stops listening once the bus is closed.
+    pub fn feed(&self, listener: IpcEventListener, backoff: Backoff) -> JoinHandle<()> {
+        let bus = self.clone();
+        thread::spawn(move || {
+            listener.listen_with_reconnect(backoff, |event| {
+                bus.publish(&event);
+                !bus.is_closed()
+            });
+            bus.close();
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::queue::OverflowPolicy;
+
+    fn closed(address: &str) -> HyprEvent {
+        HyprEvent::WindowClosed {
+            address: address.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_subscribers_get_filtered_copies() {
+        let bus = EventBus::new();
+        let settings = EventBufferConfig::default();
+        let all = bus.subscribe("all", &settings, |_| true);
+        let windows = bus.subscribe("windows", &settings, |e| e.name() == "closewindow");
+
+        assert_eq!(bus.publish(&closed("a")), 2);
+        assert_eq!(bus.publish(&HyprEvent::ConfigReloaded), 1);
+        bus.close();
+
+        assert_eq!(
+            std::iter::from_fn(|| all.recv()).collect::<Vec<_>>(),
+            vec![closed("a"), HyprEvent::ConfigReloaded]
+        );
+        assert_eq!(windows.recv(), Some(closed("a")));
+        assert_eq!(windows.recv(), None);
+    }
+
+    #[test]
+    fn test_slow_subscriber_only_drops_its_own_events() {
+        let bus = EventBus::new();
+        let small = EventBufferConfig {
+            capacity: 1,
+            overflow: OverflowPolicy::DropOldest,
+        };
+        let slow = bus.subscribe("slow", &small, |_| true);
+        let fast = bus.subscribe("fast", &EventBufferConfig::default(), |_| true);
+        for address in ["a", "b", "c"] {
+            bus.publish(&closed(address));
+        }
+
+        assert_eq!(slow.drain(), vec![closed("c")]);
+        assert_eq!(fast.drain().len(), 3);
+        let stats = bus.stats();
+        assert_eq!(
+            stats[0],
+            (
+                "slow".to_string(),
+                QueueStats {
+                    dropped: 2,
+                    coalesced: 0
+                }
+            )
+        );
+        assert_eq!(stats[1].1, QueueStats::default());
+
+        // A subscriber that went away is forgotten
+        drop(slow);
+        assert_eq!(bus.publish(&closed("d")), 1);
+        assert_eq!(bus.stats().len(), 1);
+    }
+}
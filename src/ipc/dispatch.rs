@@ -0,0 +1,303 @@
+//! Typed dispatchers, so callers build dispatch commands from parts
+//! instead of formatting them by hand.
+
+use std::error::Error;
+use std::fmt;
+
+/// A window's address, e.g. `0x55d4c0a1b2c0`. Formats as the
+/// `address:0x...` window selector dispatchers take.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Address(String);
+
+impl Address {
+    pub fn new(address: impl Into<String>) -> Self {
+        Self(address.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Addresses are hex, with or without `0x`
+    fn check(&self) -> Result<(), String> {
+        let hex = self.0.trim_start_matches("0x");
+        if !hex.is_empty() && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            Ok(())
+        } else {
+            Err(format!("not a window address: {:?}", self.0))
+        }
+    }
+}
+
+impl From<&str> for Address {
+    fn from(address: &str) -> Self {
+        Self::new(address)
+    }
+}
+
+impl From<String> for Address {
+    fn from(address: String) -> Self {
+        Self(address)
+    }
+}
+
+impl fmt::Display for Address {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "address:{}", self.0)
+    }
+}
+
+/// A workspace as dispatchers refer to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WorkspaceRef {
+    Id(i32),
+    Name(String),
+    /// A special workspace (scratchpad), by name; `None` is the unnamed one
+    Special(Option<String>),
+}
+
+impl WorkspaceRef {
+    /// Names end at `,` (the next argument) and `;` (the next batched command)
+    fn check(&self) -> Result<(), String> {
+        match self {
+            Self::Name(name) | Self::Special(Some(name)) => check_arg("workspace name", name),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Reject `,` and `;`, which would end `value` early and let the rest be
+/// taken as further arguments or another command
+fn check_arg(what: &str, value: &str) -> Result<(), String> {
+    match value.chars().find(|c| matches!(c, ',' | ';')) {
+        Some(c) => Err(format!("{} can't contain `{}`: {:?}", what, c, value)),
+        None => Ok(()),
+    }
+}
+
+impl fmt::Display for WorkspaceRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Id(id) => write!(f, "{}", id),
+            Self::Name(name) => write!(f, "name:{}", name),
+            Self::Special(None) => write!(f, "special"),
+            Self::Special(Some(name)) => write!(f, "special:{}", name),
+        }
+    }
+}
+
+/// A Hyprland dispatcher with its arguments
+#[derive(Debug, Clone, PartialEq)]
+pub enum Dispatch {
+    /// Switch to a workspace
+    Workspace(WorkspaceRef),
+    /// Move a window to a workspace without following it
+    MoveToWorkspaceSilent {
+        workspace: WorkspaceRef,
+        window: Address,
+    },
+    MoveWorkspaceToMonitor {
+        workspace: WorkspaceRef,
+        monitor: String,
+    },
+    /// Show or hide a special workspace, by name without `special:`
+    ToggleSpecialWorkspace(Option<String>),
+    FocusWindow(Address),
+    FocusMonitor(String),
+    SetFloating(Address),
+    SetTiled(Address),
+    ToggleFloating(Address),
+    /// Pin or unpin a floating window, whichever it isn't
+    Pin(Address),
+    MoveWindowPixel {
+        window: Address,
+        x: i32,
+        y: i32,
+    },
+    ResizeWindowPixel {
+        window: Address,
+        width: i32,
+        height: i32,
+    },
+    /// Set the focused window's fullscreen state as Hyprland and the client
+    /// see it (0 none, 1 maximized, 2 fullscreen)
+    FullscreenState {
+        internal: i32,
+        client: i32,
+    },
+    /// Toggle the focused window's fullscreen; `maximize` toggles
+    /// maximizing instead
+    Fullscreen {
+        maximize: bool,
+    },
+    FakeFullscreen,
+    /// A message to the current layout, e.g. `preselect r`
+    LayoutMsg(String),
+    /// Warp the pointer to a position in the global layout
+    MoveCursor {
+        x: i32,
+        y: i32,
+    },
+    /// Run a shell command, with window rules (e.g. `workspace 3 silent`)
+    /// for the window it opens
+    Exec {
+        rules: Vec<String>,
+        cmd: String,
+    },
+    Exit,
+}
+
+impl Dispatch {
+    /// The command `hyprctl dispatch` takes, e.g.
+    /// `movetoworkspacesilent 3,address:0x1`. Fails when an argument would
+    /// spill into the next one: an address that isn't hex, a workspace name
+    /// with `,` or `;`, or a window rule with `;` or `]`. Commands may
+    /// contain both, as `exec` takes the rest of the line; they are only
+    /// refused in batches.
+    pub fn to_command_string(&self) -> Result<String, Box<dyn Error>> {
+        self.check()?;
+        Ok(self.format())
+    }
+
+    fn check(&self) -> Result<(), String> {
+        match self {
+            Self::Workspace(workspace) => workspace.check(),
+            Self::MoveToWorkspaceSilent { workspace, window } => {
+                workspace.check()?;
+                window.check()
+            }
+            Self::MoveWorkspaceToMonitor { workspace, .. } => workspace.check(),
+            Self::ToggleSpecialWorkspace(Some(name)) => check_arg("workspace name", name),
+            Self::FocusWindow(window)
+            | Self::SetFloating(window)
+            | Self::SetTiled(window)
+            | Self::ToggleFloating(window)
+            | Self::Pin(window)
+            | Self::MoveWindowPixel { window, .. }
+            | Self::ResizeWindowPixel { window, .. } => window.check(),
+            Self::Exec { rules, .. } => match rules.iter().find(|r| r.contains([';', ']'])) {
+                Some(rule) => Err(format!("window rule can't contain `;` or `]`: {:?}", rule)),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn format(&self) -> String {
+        match self {
+            Self::Workspace(workspace) => format!("workspace {}", workspace),
+            Self::MoveToWorkspaceSilent { workspace, window } => {
+                format!("movetoworkspacesilent {},{}", workspace, window)
+            }
+            Self::MoveWorkspaceToMonitor { workspace, monitor } => {
+                format!("moveworkspacetomonitor {} {}", workspace, monitor)
+            }
+            Self::ToggleSpecialWorkspace(None) => "togglespecialworkspace".to_string(),
+            Self::ToggleSpecialWorkspace(Some(name)) => {
+                format!("togglespecialworkspace {}", name)
+            }
+            Self::FocusWindow(window) => format!("focuswindow {}", window),
+            Self::FocusMonitor(monitor) => format!("focusmonitor {}", monitor),
+            Self::SetFloating(window) => format!("setfloating {}", window),
+            Self::SetTiled(window) => format!("settiled {}", window),
+            Self::ToggleFloating(window) => format!("togglefloating {}", window),
+            Self::Pin(window) => format!("pin {}", window),
+            Self::MoveWindowPixel { window, x, y } => {
+                format!("movewindowpixel exact {} {},{}", x, y, window)
+            }
+            Self::ResizeWindowPixel {
+                window,
+                width,
+                height,
+            } => format!("resizewindowpixel exact {} {},{}", width, height, window),
+            Self::FullscreenState { internal, client } => {
+                format!("fullscreenstate {} {}", internal, client)
+            }
+            Self::Fullscreen { maximize } => format!("fullscreen {}", u8::from(*maximize)),
+            Self::FakeFullscreen => "fakefullscreen".to_string(),
+            Self::LayoutMsg(message) => format!("layoutmsg {}", message),
+            Self::MoveCursor { x, y } => format!("movecursor {} {}", x, y),
+            Self::Exec { rules, cmd } if rules.is_empty() => format!("exec {}", cmd),
+            Self::Exec { rules, cmd } => format!("exec [{}] {}", rules.join("; "), cmd),
+            Self::Exit => "exit".to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_strings() {
+        let window = Address::from("0x1");
+        assert_eq!(
+            Dispatch::MoveToWorkspaceSilent {
+                workspace: WorkspaceRef::Name("mail".to_string()),
+                window: window.clone(),
+            }
+            .to_command_string()
+            .unwrap(),
+            "movetoworkspacesilent name:mail,address:0x1"
+        );
+        assert_eq!(
+            Dispatch::ResizeWindowPixel {
+                window,
+                width: 800,
+                height: 600,
+            }
+            .to_command_string()
+            .unwrap(),
+            "resizewindowpixel exact 800 600,address:0x1"
+        );
+        assert_eq!(
+            Dispatch::Workspace(WorkspaceRef::Special(None))
+                .to_command_string()
+                .unwrap(),
+            "workspace special"
+        );
+        assert_eq!(
+            Dispatch::Exec {
+                rules: vec!["workspace 3 silent".to_string(), "float".to_string()],
+                cmd: "kitty --title 'a b'".to_string(),
+            }
+            .to_command_string()
+            .unwrap(),
+            "exec [workspace 3 silent; float] kitty --title 'a b'"
+        );
+        assert_eq!(
+            Dispatch::Fullscreen { maximize: true }
+                .to_command_string()
+                .unwrap(),
+            "fullscreen 1"
+        );
+    }
+
+    #[test]
+    fn test_arguments_cant_spill_over() {
+        let window = Address::from("0x1");
+        let invalid = [
+            Dispatch::MoveToWorkspaceSilent {
+                workspace: WorkspaceRef::Name("mail,address:0x2".to_string()),
+                window: window.clone(),
+            },
+            Dispatch::Workspace(WorkspaceRef::Special(Some("a;dispatch exit".to_string()))),
+            Dispatch::FocusWindow(Address::from("0x1;dispatch exit")),
+            Dispatch::Exec {
+                rules: vec!["float] sh -c x; [tile".to_string()],
+                cmd: "kitty".to_string(),
+            },
+        ];
+        for dispatch in invalid {
+            assert!(dispatch.to_command_string().is_err(), "{:?}", dispatch);
+        }
+        // `exec` takes the rest of the line, separators and all
+        assert!(Dispatch::Exec {
+            rules: vec![],
+            cmd: "export A=1; kitty --title a,b".to_string(),
+        }
+        .to_command_string()
+        .is_ok());
+        assert!(Dispatch::FocusWindow(window).to_command_string().is_ok());
+    }
+}
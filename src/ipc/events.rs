@@ -0,0 +1,1276 @@
+use super::instance::{follow_instance, instance_socket_dir, rediscover_signature};
+use super::queue::{self, EventBufferConfig, EventReceiver};
+use super::socket::{event_socket_path, socket_overrides};
+use super::version::Capabilities;
+use std::error::Error;
+use std::io::{self, BufRead, BufReader};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+// --- Event Model (parsed from socket2 lines: `NAME>>DATA`) ---
+
+/// A compositor event, normalized across Hyprland versions.
+///
+/// Fields that only newer compositors report (workspace ids, monitor
+/// descriptions) are optional so consumers handle a single shape.
+#[derive(Debug, Clone, PartialEq)]
+pub enum HyprEvent {
+    WorkspaceChanged {
+        id: Option<i32>,
+        name: String,
+    },
+    WorkspaceCreated {
+        id: Option<i32>,
+        name: String,
+    },
+    WorkspaceDestroyed {
+        id: Option<i32>,
+        name: String,
+    },
+    WorkspaceMoved {
+        id: Option<i32>,
+        name: String,
+        monitor: String,
+    },
+    WorkspaceRenamed {
+        id: i32,
+        name: String,
+    },
+    ActiveSpecial {
+        workspace: String,
+        monitor: String,
+    },
+    FocusedMonitor {
+        monitor: String,
+        workspace: String,
+    },
+    ActiveWindow {
+        class: String,
+        title: String,
+    },
+    ActiveWindowAddress {
+        address: String,
+    },
+    WindowOpened {
+        address: String,
+        workspace: String,
+        class: String,
+        title: String,
+    },
+    WindowClosed {
+        address: String,
+    },
+    WindowMoved {
+        address: String,
+        workspace_id: Option<i32>,
+        workspace: String,
+    },
+    WindowTitleChanged {
+        address: String,
+        title: Option<String>,
+    },
+    FloatingChanged {
+        address: String,
+        floating: bool,
+    },
+    Fullscreen(bool),
+    Pinned {
+        address: String,
+        pinned: bool,
+    },
+    Urgent {
+        address: String,
+    },
+    MonitorAdded {
+        id: Option<i32>,
+        name: String,
+        description: Option<String>,
+    },
+    MonitorRemoved {
+        name: String,
+    },
+    ActiveLayout {
+        keyboard: String,
+        layout: String,
+    },
+    Submap(String),
+    ConfigReloaded,
+    /// Not from the compositor: the listener connected again after losing
+    /// the socket (see [`IpcEventListener::listen_with_reconnect`])
+    Reconnected,
+    Unknown {
+        name: String,
+        data: String,
+    },
+}
+
+impl HyprEvent {
+    /// The socket2 event name this variant corresponds to (v1 spelling)
+    pub fn name(&self) -> &str {
+        match self {
+            Self::WorkspaceChanged { .. } => "workspace",
+            Self::WorkspaceCreated { .. } => "createworkspace",
+            Self::WorkspaceDestroyed { .. } => "destroyworkspace",
+            Self::WorkspaceMoved { .. } => "moveworkspace",
+            Self::WorkspaceRenamed { .. } => "renameworkspace",
+            Self::ActiveSpecial { .. } => "activespecial",
+            Self::FocusedMonitor { .. } => "focusedmon",
+            Self::ActiveWindow { .. } => "activewindow",
+            Self::ActiveWindowAddress { .. } => "activewindowv2",
+            Self::WindowOpened { .. } => "openwindow",
+            Self::WindowClosed { .. } => "closewindow",
+            Self::WindowMoved { .. } => "movewindow",
+            Self::WindowTitleChanged { .. } => "windowtitle",
+            Self::FloatingChanged { .. } => "changefloatingmode",
+            Self::Fullscreen(_) => "fullscreen",
+            Self::Pinned { .. } => "pin",
+            Self::Urgent { .. } => "urgent",
+            Self::MonitorAdded { .. } => "monitoradded",
+            Self::MonitorRemoved { .. } => "monitorremoved",
+            Self::ActiveLayout { .. } => "activelayout",
+            Self::Submap(_) => "submap",
+            Self::ConfigReloaded => "configreloaded",
+            Self::Reconnected => "reconnected",
+            Self::Unknown { name, .. } => name,
+        }
+    }
+
+    /// Named fields of the event, for matching and for exporting to hook commands
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let opt_id = |id: &Option<i32>| id.map(|i| i.to_string()).unwrap_or_default();
+        let flag = |b: bool| if b { "1" } else { "0" }.to_string();
+
+        match self {
+            Self::WorkspaceChanged { id, name }
+            | Self::WorkspaceCreated { id, name }
+            | Self::WorkspaceDestroyed { id, name } => {
+                vec![("id", opt_id(id)), ("workspace", name.clone())]
+            }
+            Self::WorkspaceMoved { id, name, monitor } => vec![
+                ("id", opt_id(id)),
+                ("workspace", name.clone()),
+                ("monitor", monitor.clone()),
+            ],
+            Self::WorkspaceRenamed { id, name } => {
+                vec![("id", id.to_string()), ("workspace", name.clone())]
+            }
+            Self::ActiveSpecial { workspace, monitor }
+            | Self::FocusedMonitor { monitor, workspace } => vec![
+                ("workspace", workspace.clone()),
+                ("monitor", monitor.clone()),
+            ],
+            Self::ActiveWindow { class, title } => {
+                vec![("class", class.clone()), ("title", title.clone())]
+            }
+            Self::ActiveWindowAddress { address }
+            | Self::WindowClosed { address }
+            | Self::Urgent { address } => vec![("address", address.clone())],
+            Self::WindowOpened {
+                address,
+                workspace,
+                class,
+                title,
+            } => vec![
+                ("address", address.clone()),
+                ("workspace", workspace.clone()),
+                ("class", class.clone()),
+                ("title", title.clone()),
+            ],
+            Self::WindowMoved {
+                address,
+                workspace_id,
+                workspace,
+            } => vec![
+                ("address", address.clone()),
+                ("id", opt_id(workspace_id)),
+                ("workspace", workspace.clone()),
+            ],
+            Self::WindowTitleChanged { address, title } => vec![
+                ("address", address.clone()),
+                ("title", title.clone().unwrap_or_default()),
+            ],
+            Self::FloatingChanged { address, floating } => {
+                vec![("address", address.clone()), ("floating", flag(*floating))]
+            }
+            Self::Fullscreen(state) => vec![("fullscreen", flag(*state))],
+            Self::Pinned { address, pinned } => {
+                vec![("address", address.clone()), ("pinned", flag(*pinned))]
+            }
+            Self::MonitorAdded {
+                id,
+                name,
+                description,
+            } => vec![
+                ("id", opt_id(id)),
+                ("monitor", name.clone()),
+                ("description", description.clone().unwrap_or_default()),
+            ],
+            Self::MonitorRemoved { name } => vec![("monitor", name.clone())],
+            Self::ActiveLayout { keyboard, layout } => {
+                vec![("keyboard", keyboard.clone()), ("layout", layout.clone())]
+            }
+            Self::Submap(name) => vec![("submap", name.clone())],
+            Self::ConfigReloaded | Self::Reconnected => vec![],
+            Self::Unknown { data, .. } => vec![("data", data.clone())],
+        }
+    }
+}
+
+/// [`HyprEvent`] borrowing its text from the socket line, so events can be
+/// inspected (and mostly discarded) without allocating.
+///
+/// Convert with [`HyprEventRef::into_owned`] to keep one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HyprEventRef<'a> {
+    WorkspaceChanged {
+        id: Option<i32>,
+        name: &'a str,
+    },
+    WorkspaceCreated {
+        id: Option<i32>,
+        name: &'a str,
+    },
+    WorkspaceDestroyed {
+        id: Option<i32>,
+        name: &'a str,
+    },
+    WorkspaceMoved {
+        id: Option<i32>,
+        name: &'a str,
+        monitor: &'a str,
+    },
+    WorkspaceRenamed {
+        id: i32,
+        name: &'a str,
+    },
+    ActiveSpecial {
+        workspace: &'a str,
+        monitor: &'a str,
+    },
+    FocusedMonitor {
+        monitor: &'a str,
+        workspace: &'a str,
+    },
+    ActiveWindow {
+        class: &'a str,
+        title: &'a str,
+    },
+    ActiveWindowAddress {
+        address: &'a str,
+    },
+    WindowOpened {
+        address: &'a str,
+        workspace: &'a str,
+        class: &'a str,
+        title: &'a str,
+    },
+    WindowClosed {
+        address: &'a str,
+    },
+    WindowMoved {
+        address: &'a str,
+        workspace_id: Option<i32>,
+        workspace: &'a str,
+    },
+    WindowTitleChanged {
+        address: &'a str,
+        title: Option<&'a str>,
+    },
+    FloatingChanged {
+        address: &'a str,
+        floating: bool,
+    },
+    Fullscreen(bool),
+    Pinned {
+        address: &'a str,
+        pinned: bool,
+    },
+    Urgent {
+        address: &'a str,
+    },
+    MonitorAdded {
+        id: Option<i32>,
+        name: &'a str,
+        description: Option<&'a str>,
+    },
+    MonitorRemoved {
+        name: &'a str,
+    },
+    ActiveLayout {
+        keyboard: &'a str,
+        layout: &'a str,
+    },
+    Submap(&'a str),
+    ConfigReloaded,
+    Unknown {
+        name: &'a str,
+        data: &'a str,
+    },
+}
+
+impl<'a> HyprEventRef<'a> {
+    /// The socket2 event name this variant corresponds to (v1 spelling)
+    pub fn name(&self) -> &'a str {
+        match self {
+            Self::WorkspaceChanged { .. } => "workspace",
+            Self::WorkspaceCreated { .. } => "createworkspace",
+            Self::WorkspaceDestroyed { .. } => "destroyworkspace",
+            Self::WorkspaceMoved { .. } => "moveworkspace",
+            Self::WorkspaceRenamed { .. } => "renameworkspace",
+            Self::ActiveSpecial { .. } => "activespecial",
+            Self::FocusedMonitor { .. } => "focusedmon",
+            Self::ActiveWindow { .. } => "activewindow",
+            Self::ActiveWindowAddress { .. } => "activewindowv2",
+            Self::WindowOpened { .. } => "openwindow",
+            Self::WindowClosed { .. } => "closewindow",
+            Self::WindowMoved { .. } => "movewindow",
+            Self::WindowTitleChanged { .. } => "windowtitle",
+            Self::FloatingChanged { .. } => "changefloatingmode",
+            Self::Fullscreen(_) => "fullscreen",
+            Self::Pinned { .. } => "pin",
+            Self::Urgent { .. } => "urgent",
+            Self::MonitorAdded { .. } => "monitoradded",
+            Self::MonitorRemoved { .. } => "monitorremoved",
+            Self::ActiveLayout { .. } => "activelayout",
+            Self::Submap(_) => "submap",
+            Self::ConfigReloaded => "configreloaded",
+            Self::Unknown { name, .. } => name,
+        }
+    }
+
+    pub fn into_owned(self) -> HyprEvent {
+        let own = |s: &str| s.to_string();
+        match self {
+            Self::WorkspaceChanged { id, name } => HyprEvent::WorkspaceChanged {
+                id,
+                name: own(name),
+            },
+            Self::WorkspaceCreated { id, name } => HyprEvent::WorkspaceCreated {
+                id,
+                name: own(name),
+            },
+            Self::WorkspaceDestroyed { id, name } => HyprEvent::WorkspaceDestroyed {
+                id,
+                name: own(name),
+            },
+            Self::WorkspaceMoved { id, name, monitor } => HyprEvent::WorkspaceMoved {
+                id,
+                name: own(name),
+                monitor: own(monitor),
+            },
+            Self::WorkspaceRenamed { id, name } => HyprEvent::WorkspaceRenamed {
+                id,
+                name: own(name),
+            },
+            Self::ActiveSpecial { workspace, monitor } => HyprEvent::ActiveSpecial {
+                workspace: own(workspace),
+                monitor: own(monitor),
+            },
+            Self::FocusedMonitor { monitor, workspace } => HyprEvent::FocusedMonitor {
+                monitor: own(monitor),
+                workspace: own(workspace),
+            },
+            Self::ActiveWindow { class, title } => HyprEvent::ActiveWindow {
+                class: own(class),
+                title: own(title),
+            },
+            Self::ActiveWindowAddress { address } => HyprEvent::ActiveWindowAddress {
+                address: own(address),
+            },
+            Self::WindowOpened {
+                address,
+                workspace,
+                class,
+                title,
+            } => HyprEvent::WindowOpened {
+                address: own(address),
+                workspace: own(workspace),
+                class: own(class),
+                title: own(title),
+            },
+            Self::WindowClosed { address } => HyprEvent::WindowClosed {
+                address: own(address),
+            },
+            Self::WindowMoved {
+                address,
+                workspace_id,
+                workspace,
+            } => HyprEvent::WindowMoved {
+                address: own(address),
+                workspace_id,
+                workspace: own(workspace),
+            },
+            Self::WindowTitleChanged { address, title } => HyprEvent::WindowTitleChanged {
+                address: own(address),
+                title: title.map(own),
+            },
+            Self::FloatingChanged { address, floating } => HyprEvent::FloatingChanged {
+                address: own(address),
+                floating,
+            },
+            Self::Fullscreen(state) => HyprEvent::Fullscreen(state),
+            Self::Pinned { address, pinned } => HyprEvent::Pinned {
+                address: own(address),
+                pinned,
+            },
+            Self::Urgent { address } => HyprEvent::Urgent {
+                address: own(address),
+            },
+            Self::MonitorAdded {
+                id,
+                name,
+                description,
+            } => HyprEvent::MonitorAdded {
+                id,
+                name: own(name),
+                description: description.map(own),
+            },
+            Self::MonitorRemoved { name } => HyprEvent::MonitorRemoved { name: own(name) },
+            Self::ActiveLayout { keyboard, layout } => HyprEvent::ActiveLayout {
+                keyboard: own(keyboard),
+                layout: own(layout),
+            },
+            Self::Submap(name) => HyprEvent::Submap(own(name)),
+            Self::ConfigReloaded => HyprEvent::ConfigReloaded,
+            Self::Unknown { name, data } => HyprEvent::Unknown {
+                name: own(name),
+                data: own(data),
+            },
+        }
+    }
+}
+
+/// Coarse grouping of events, used to filter what `hyprdrover events` shows
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventCategory {
+    Window,
+    Workspace,
+    Monitor,
+    Other,
+}
+
+impl EventCategory {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "window" => Some(Self::Window),
+            "workspace" => Some(Self::Workspace),
+            "monitor" => Some(Self::Monitor),
+            "other" => Some(Self::Other),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Window => "window",
+            Self::Workspace => "workspace",
+            Self::Monitor => "monitor",
+            Self::Other => "other",
+        }
+    }
+
+    /// ANSI color used when printing to a terminal
+    fn color(&self) -> &'static str {
+        match self {
+            Self::Window => "\x1b[32m",
+            Self::Workspace => "\x1b[34m",
+            Self::Monitor => "\x1b[35m",
+            Self::Other => "\x1b[90m",
+        }
+    }
+}
+
+impl HyprEvent {
+    pub fn category(&self) -> EventCategory {
+        match self {
+            Self::WorkspaceChanged { .. }
+            | Self::WorkspaceCreated { .. }
+            | Self::WorkspaceDestroyed { .. }
+            | Self::WorkspaceMoved { .. }
+            | Self::WorkspaceRenamed { .. }
+            | Self::ActiveSpecial { .. } => EventCategory::Workspace,
+            Self::ActiveWindow { .. }
+            | Self::ActiveWindowAddress { .. }
+            | Self::WindowOpened { .. }
+            | Self::WindowClosed { .. }
+            | Self::WindowMoved { .. }
+            | Self::WindowTitleChanged { .. }
+            | Self::FloatingChanged { .. }
+            | Self::Fullscreen(_)
+            | Self::Pinned { .. }
+            | Self::Urgent { .. } => EventCategory::Window,
+            Self::FocusedMonitor { .. }
+            | Self::MonitorAdded { .. }
+            | Self::MonitorRemoved { .. } => EventCategory::Monitor,
+            Self::ActiveLayout { .. }
+            | Self::Submap(_)
+            | Self::ConfigReloaded
+            | Self::Reconnected
+            | Self::Unknown { .. } => EventCategory::Other,
+        }
+    }
+
+    /// One line for `hyprdrover events`: time, category, name and fields,
+    /// colored by category when `color` is set
+    pub fn pretty(&self, time: &str, color: bool) -> String {
+        let fields: Vec<String> = self
+            .fields()
+            .into_iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| {
+                if value.contains(' ') {
+                    format!("{}={:?}", key, value)
+                } else {
+                    format!("{}={}", key, value)
+                }
+            })
+            .collect();
+        let category = self.category();
+
+        if color {
+            format!(
+                "\x1b[2m{}\x1b[0m {}{:<9}\x1b[0m \x1b[1m{:<18}\x1b[0m {}",
+                time,
+                category.color(),
+                category.label(),
+                self.name(),
+                fields.join(" ")
+            )
+        } else {
+            format!(
+                "{} {:<9} {:<18} {}",
+                time,
+                category.label(),
+                self.name(),
+                fields.join(" ")
+            )
+        }
+        .trim_end()
+        .to_string()
+    }
+}
+
+/// Which event formats the connected compositor speaks.
+///
+/// Compositors that have a v2 form of an event emit it twice (v1 and v2,
+/// which carries more fields); only the v2 copy is kept. Events without a v2
+/// form on the running version arrive as v1 and parse into the same variants.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventProfile {
+    /// `workspacev2`, `createworkspacev2` and `destroyworkspacev2`
+    pub workspace_v2: bool,
+    /// `movewindowv2` and `monitoraddedv2`
+    pub move_monitor_v2: bool,
+    pub window_title_v2: bool,
+}
+
+impl EventProfile {
+    /// A compositor predating every v2 event
+    pub const LEGACY: Self = Self {
+        workspace_v2: false,
+        move_monitor_v2: false,
+        window_title_v2: false,
+    };
+    /// A compositor sending every v2 event
+    pub const CURRENT: Self = Self {
+        workspace_v2: true,
+        move_monitor_v2: true,
+        window_title_v2: true,
+    };
+
+    pub fn for_capabilities(caps: &Capabilities) -> Self {
+        Self {
+            workspace_v2: caps.v2_events,
+            move_monitor_v2: caps.move_monitor_v2_events,
+            window_title_v2: caps.window_title_v2,
+        }
+    }
+
+    /// Profile for the running compositor, from the probed version
+    pub fn detect() -> Self {
+        Self::for_capabilities(&super::version::capabilities())
+    }
+
+    /// v1 events that the profile expects to be duplicated by a v2 event
+    fn is_superseded(&self, name: &str) -> bool {
+        match name {
+            "workspace" | "createworkspace" | "destroyworkspace" => self.workspace_v2,
+            "movewindow" | "monitoradded" => self.move_monitor_v2,
+            "windowtitle" => self.window_title_v2,
+            _ => false,
+        }
+    }
+}
+
+fn parse_bool(s: &str) -> bool {
+    s.trim() == "1"
+}
+
+fn parse_id(s: &str) -> Option<i32> {
+    s.trim().parse().ok()
+}
+
+/// Parse one socket2 line.
+///
+/// Returns `None` for events the profile deliberately skips (v1 duplicates of
+/// v2 events). Anything unrecognized comes back as `HyprEvent::Unknown`.
+pub fn parse_event(line: &str, profile: EventProfile) -> Option<HyprEvent> {
+    parse_event_ref(line, profile).map(HyprEventRef::into_owned)
+}
+
+/// Split `data` into at most `N` comma-separated fields; the last one keeps
+/// any further commas
+fn split_fields<const N: usize>(data: &str) -> [Option<&str>; N] {
+    let mut fields = [None; N];
+    for (slot, part) in fields.iter_mut().zip(data.splitn(N, ',')) {
+        *slot = Some(part);
+    }
+    fields
+}
+
+/// Parse one socket2 line without copying it; see [`parse_event`]
+pub fn parse_event_ref(line: &str, profile: EventProfile) -> Option<HyprEventRef<'_>> {
+    let (name, data) = line.split_once(">>").unwrap_or((line, ""));
+
+    if profile.is_superseded(name) {
+        return None;
+    }
+
+    // Titles and workspace names may contain commas, so always cap the split
+    // at the number of fields the event defines.
+    let event = match name {
+        "workspace" => HyprEventRef::WorkspaceChanged {
+            id: None,
+            name: data,
+        },
+        "workspacev2" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::WorkspaceChanged {
+                id: parse_id(f[0].unwrap_or_default()),
+                name: f[1].unwrap_or_default(),
+            }
+        }
+        "createworkspace" => HyprEventRef::WorkspaceCreated {
+            id: None,
+            name: data,
+        },
+        "createworkspacev2" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::WorkspaceCreated {
+                id: parse_id(f[0].unwrap_or_default()),
+                name: f[1].unwrap_or_default(),
+            }
+        }
+        "destroyworkspace" => HyprEventRef::WorkspaceDestroyed {
+            id: None,
+            name: data,
+        },
+        "destroyworkspacev2" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::WorkspaceDestroyed {
+                id: parse_id(f[0].unwrap_or_default()),
+                name: f[1].unwrap_or_default(),
+            }
+        }
+        "moveworkspace" => {
+            // The workspace name comes first and may contain commas; the
+            // monitor name never does.
+            let (name, monitor) = data.rsplit_once(',').unwrap_or((data, ""));
+            HyprEventRef::WorkspaceMoved {
+                id: None,
+                name,
+                monitor,
+            }
+        }
+        "moveworkspacev2" => {
+            let (rest, monitor) = data.rsplit_once(',').unwrap_or((data, ""));
+            let (id, name) = rest.split_once(',').unwrap_or((rest, ""));
+            HyprEventRef::WorkspaceMoved {
+                id: parse_id(id),
+                name,
+                monitor,
+            }
+        }
+        "renameworkspace" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::WorkspaceRenamed {
+                id: parse_id(f[0].unwrap_or_default()).unwrap_or_default(),
+                name: f[1].unwrap_or_default(),
+            }
+        }
+        "activespecial" => {
+            let (workspace, monitor) = data.rsplit_once(',').unwrap_or((data, ""));
+            HyprEventRef::ActiveSpecial { workspace, monitor }
+        }
+        "focusedmon" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::FocusedMonitor {
+                monitor: f[0].unwrap_or_default(),
+                workspace: f[1].unwrap_or_default(),
+            }
+        }
+        "activewindow" => {
+            // Very old builds sent only the class
+            let f = split_fields::<2>(data);
+            HyprEventRef::ActiveWindow {
+                class: f[0].unwrap_or_default(),
+                title: f[1].unwrap_or_default(),
+            }
+        }
+        "activewindowv2" => HyprEventRef::ActiveWindowAddress { address: data },
+        "openwindow" => {
+            let f = split_fields::<4>(data);
+            HyprEventRef::WindowOpened {
+                address: f[0].unwrap_or_default(),
+                workspace: f[1].unwrap_or_default(),
+                class: f[2].unwrap_or_default(),
+                title: f[3].unwrap_or_default(),
+            }
+        }
+        "closewindow" => HyprEventRef::WindowClosed { address: data },
+        "movewindow" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::WindowMoved {
+                address: f[0].unwrap_or_default(),
+                workspace_id: None,
+                workspace: f[1].unwrap_or_default(),
+            }
+        }
+        "movewindowv2" => {
+            let f = split_fields::<3>(data);
+            HyprEventRef::WindowMoved {
+                address: f[0].unwrap_or_default(),
+                workspace_id: parse_id(f[1].unwrap_or_default()),
+                workspace: f[2].unwrap_or_default(),
+            }
+        }
+        "windowtitle" => HyprEventRef::WindowTitleChanged {
+            address: data,
+            title: None,
+        },
+        "windowtitlev2" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::WindowTitleChanged {
+                address: f[0].unwrap_or_default(),
+                title: Some(f[1].unwrap_or_default()),
+            }
+        }
+        "changefloatingmode" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::FloatingChanged {
+                address: f[0].unwrap_or_default(),
+                floating: parse_bool(f[1].unwrap_or_default()),
+            }
+        }
+        "fullscreen" => HyprEventRef::Fullscreen(parse_bool(data)),
+        "pin" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::Pinned {
+                address: f[0].unwrap_or_default(),
+                pinned: parse_bool(f[1].unwrap_or_default()),
+            }
+        }
+        "urgent" => HyprEventRef::Urgent { address: data },
+        "monitoradded" => HyprEventRef::MonitorAdded {
+            id: None,
+            name: data,
+            description: None,
+        },
+        "monitoraddedv2" => {
+            let f = split_fields::<3>(data);
+            HyprEventRef::MonitorAdded {
+                id: parse_id(f[0].unwrap_or_default()),
+                name: f[1].unwrap_or_default(),
+                description: f[2],
+            }
+        }
+        "monitorremoved" => HyprEventRef::MonitorRemoved { name: data },
+        "activelayout" => {
+            let f = split_fields::<2>(data);
+            HyprEventRef::ActiveLayout {
+                keyboard: f[0].unwrap_or_default(),
+                layout: f[1].unwrap_or_default(),
+            }
+        }
+        "submap" => HyprEventRef::Submap(data),
+        "configreloaded" => HyprEventRef::ConfigReloaded,
+        _ => HyprEventRef::Unknown { name, data },
+    };
+
+    Some(event)
+}
+
+// --- Listener ---
+
+/// Delays between attempts to reconnect: doubling from `initial` up to `max`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    /// Stop once the socket has been gone this long; `None` retries forever
+    pub give_up: Option<Duration>,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_millis(250),
+            max: Duration::from_secs(30),
+            give_up: None,
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before retry number `attempt`, counting from 0
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.initial
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max)
+    }
+}
+
+/// Reads events from Hyprland's event socket (`.socket2.sock`)
+pub struct IpcEventListener {
+    socket_path: PathBuf,
+    profile: EventProfile,
+    // The socket belongs to whichever instance runs, so a restarted
+    // compositor's socket is looked up again on reconnect
+    rediscover: bool,
+}
+
+impl IpcEventListener {
+    /// Listen on the event socket of the resolved Hyprland instance, or the
+    /// one given by `--event-socket`/`HYPRDROVER_EVENT_SOCKET`
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Ok(Self {
+            rediscover: socket_overrides().event.is_none(),
+            ..Self::connect_to(event_socket_path()?)
+        })
+    }
+
+    /// Listen on an explicit socket path
+    pub fn connect_to(socket_path: impl AsRef<Path>) -> Self {
+        Self {
+            socket_path: socket_path.as_ref().to_path_buf(),
+            profile: EventProfile::detect(),
+            rediscover: false,
+        }
+    }
+
+    /// Override the event format profile instead of relying on version probing
+    pub fn with_profile(mut self, profile: EventProfile) -> Self {
+        self.profile = profile;
+        self
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Block and invoke `handler` for every event until the socket closes
+    pub fn listen<F: FnMut(HyprEvent)>(&self, mut handler: F) -> io::Result<()> {
+        self.listen_ref(|event| handler(event.into_owned()))
+    }
+
+    /// Listen on a background thread, handing events over through a buffer
+    /// bounded by `settings`
+    pub fn spawn(self, settings: &EventBufferConfig) -> EventReceiver {
+        let (sender, receiver) = queue::bounded(settings);
+        // The thread ends with the first event after the receiver is dropped
+        thread::spawn(move || {
+            let _ = self.read_events(|event| sender.send(event.into_owned()));
+        });
+        receiver
+    }
+
+    /// Like [`listen`](Self::listen), but outlives the compositor: when the
+    /// socket closes or fails, connect again with `backoff` between failed
+    /// attempts and hand `handler` a [`HyprEvent::Reconnected`]. Unless the
+    /// path was given explicitly, a restarted instance's socket is looked up
+    /// and commands follow it to that instance (see [`follow_instance`]).
+    /// Runs until `handler` returns `false` or `backoff.give_up` passes
+    /// without a compositor.
+    pub fn listen_with_reconnect<F: FnMut(HyprEvent) -> bool>(
+        &self,
+        backoff: Backoff,
+        mut handler: F,
+    ) {
+        let mut socket_path = self.socket_path.clone();
+        let mut signature: Option<String> = None;
+        let mut connected = false;
+        let mut failures = 0;
+        let mut lost_at: Option<Instant> = None;
+        loop {
+            match UnixStream::connect(&socket_path) {
+                Ok(stream) => {
+                    failures = 0;
+                    // Before the handler runs, so its queries reach the new instance
+                    if let Some(signature) = signature.take() {
+                        follow_instance(&signature);
+                    }
+                    if connected && !handler(HyprEvent::Reconnected) {
+                        return;
+                    }
+                    connected = true;
+                    let read = self.read_stream(stream, |event| handler(event.into_owned()));
+                    if matches!(read, Ok(true)) {
+                        return;
+                    }
+                    lost_at = Some(Instant::now());
+                    // Give a compositor that is going down time to remove its socket
+                    thread::sleep(backoff.initial);
+                }
+                Err(_) => {
+                    let lost_for = lost_at.get_or_insert_with(Instant::now).elapsed();
+                    if backoff.give_up.is_some_and(|limit| lost_for >= limit) {
+                        return;
+                    }
+                    thread::sleep(backoff.delay(failures));
+                    failures = failures.saturating_add(1);
+                }
+            }
+            if self.rediscover {
+                if let Ok(found) = rediscover_signature() {
+                    socket_path = instance_socket_dir(&found).join(".socket2.sock");
+                    signature = Some(found);
+                }
+            }
+        }
+    }
+
+    /// Events as an iterator, for callers running their own loop. It ends
+    /// when the socket closes; failing to connect or read yields one error
+    /// and then ends.
+    pub fn events(&self) -> Events {
+        let (reader, error) = match UnixStream::connect(&self.socket_path) {
+            Ok(stream) => (Some(BufReader::new(stream)), None),
+            Err(e) => (None, Some(e)),
+        };
+        Events {
+            reader,
+            error,
+            line: String::new(),
+            profile: self.profile,
+        }
+    }
+
+    /// Listen on a background thread and send every event to the returned
+    /// receiver, e.g. to select over it with other channels. The thread ends
+    /// when the socket closes or the receiver is dropped.
+    pub fn spawn_channel(self) -> (JoinHandle<io::Result<()>>, Receiver<HyprEvent>) {
+        let (sender, receiver) = mpsc::channel();
+        let handle = thread::spawn(move || {
+            self.read_events(|event| sender.send(event.into_owned()).is_ok())
+        });
+        (handle, receiver)
+    }
+
+    /// Like [`listen`](Self::listen), but hands out events borrowing a single
+    /// reused line buffer, so busy streams (e.g. dragging windows) cost no
+    /// allocation per event
+    pub fn listen_ref<F: FnMut(HyprEventRef<'_>)>(&self, mut handler: F) -> io::Result<()> {
+        self.read_events(|event| {
+            handler(event);
+            true
+        })
+    }
+
+    /// Hand events to `handler` until the socket closes or it returns `false`
+    fn read_events<F: FnMut(HyprEventRef<'_>) -> bool>(&self, handler: F) -> io::Result<()> {
+        let stream = UnixStream::connect(&self.socket_path)?;
+        self.read_stream(stream, handler).map(|_| ())
+    }
+
+    /// Read `stream` until it closes (`false`) or `handler` stops (`true`)
+    fn read_stream<F: FnMut(HyprEventRef<'_>) -> bool>(
+        &self,
+        stream: UnixStream,
+        mut handler: F,
+    ) -> io::Result<bool> {
+        let mut reader = BufReader::new(stream);
+        let mut line = String::new();
+
+        loop {
+            line.clear();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(false);
+            }
+            let trimmed = line.trim_end_matches('\n');
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(event) = parse_event_ref(trimmed, self.profile) {
+                if !handler(event) {
+                    return Ok(true);
+                }
+            }
+        }
+    }
+}
+
+/// Iterator over an event socket, from [`IpcEventListener::events`]
+pub struct Events {
+    reader: Option<BufReader<UnixStream>>,
+    error: Option<io::Error>,
+    line: String,
+    profile: EventProfile,
+}
+
+impl Iterator for Events {
+    type Item = io::Result<HyprEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        let reader = self.reader.as_mut()?;
+        loop {
+            self.line.clear();
+            match reader.read_line(&mut self.line) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(e) => {
+                    self.reader = None;
+                    return Some(Err(e));
+                }
+            }
+            let trimmed = self.line.trim_end_matches('\n');
+            if trimmed.is_empty() {
+                continue;
+            }
+            if let Some(event) = parse_event_ref(trimmed, self.profile) {
+                return Some(Ok(event.into_owned()));
+            }
+        }
+        self.reader = None;
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_openwindow_title_with_commas() {
+        let event = parse_event(
+            "openwindow>>5f3a,2,kitty,vim: a, b, c",
+            EventProfile::CURRENT,
+        );
+        assert_eq!(
+            event,
+            Some(HyprEvent::WindowOpened {
+                address: "5f3a".to_string(),
+                workspace: "2".to_string(),
+                class: "kitty".to_string(),
+                title: "vim: a, b, c".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_current_profile_skips_v1_duplicates() {
+        assert_eq!(parse_event("workspace>>3", EventProfile::CURRENT), None);
+        assert_eq!(
+            parse_event("workspacev2>>3,3", EventProfile::CURRENT),
+            Some(HyprEvent::WorkspaceChanged {
+                id: Some(3),
+                name: "3".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_v1_and_v2_pair_yields_one_event() {
+        let pairs = [
+            ("movewindow>>5f3a,2", "movewindowv2>>5f3a,2,2"),
+            ("windowtitle>>5f3a", "windowtitlev2>>5f3a,vim"),
+            ("monitoradded>>DP-1", "monitoraddedv2>>1,DP-1,Dell"),
+        ];
+        for (v1, v2) in pairs {
+            let events: Vec<_> = [v1, v2]
+                .into_iter()
+                .filter_map(|line| parse_event(line, EventProfile::CURRENT))
+                .collect();
+            assert_eq!(events.len(), 1, "{} + {}", v1, v2);
+            assert_eq!(events[0], parse_event(v2, EventProfile::CURRENT).unwrap());
+        }
+
+        // Before windowtitlev2, the v1 title event is all there is
+        let no_title_v2 = EventProfile {
+            window_title_v2: false,
+            ..EventProfile::CURRENT
+        };
+        assert!(parse_event("windowtitle>>5f3a", no_title_v2).is_some());
+    }
+
+    #[test]
+    fn test_legacy_profile_uses_v1_events() {
+        assert_eq!(
+            parse_event("destroyworkspace>>special:term", EventProfile::LEGACY),
+            Some(HyprEvent::WorkspaceDestroyed {
+                id: None,
+                name: "special:term".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_event("activewindow>>firefox", EventProfile::LEGACY),
+            Some(HyprEvent::ActiveWindow {
+                class: "firefox".to_string(),
+                title: String::new(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_category_and_pretty_line() {
+        let event =
+            parse_event("openwindow>>5f3a,2,kitty,vim notes", EventProfile::CURRENT).unwrap();
+        assert_eq!(event.category(), EventCategory::Window);
+        assert_eq!(
+            event.pretty("12:00:01", false),
+            "12:00:01 window    openwindow         address=5f3a workspace=2 class=kitty title=\"vim notes\""
+        );
+        assert_eq!(
+            parse_event("focusedmon>>DP-1,3", EventProfile::CURRENT)
+                .unwrap()
+                .category(),
+            EventCategory::Monitor
+        );
+        assert_eq!(
+            EventCategory::parse("workspace"),
+            Some(EventCategory::Workspace)
+        );
+        assert_eq!(EventCategory::parse("windows"), None);
+    }
+
+    #[test]
+    fn test_borrowed_parse() {
+        assert_eq!(
+            parse_event_ref("windowtitlev2>>5f3a,new, title", EventProfile::CURRENT),
+            Some(HyprEventRef::WindowTitleChanged {
+                address: "5f3a",
+                title: Some("new, title"),
+            })
+        );
+        assert_eq!(
+            parse_event_ref("monitoraddedv2>>1,DP-1", EventProfile::CURRENT),
+            Some(HyprEventRef::MonitorAdded {
+                id: Some(1),
+                name: "DP-1",
+                description: None,
+            })
+        );
+
+        let event = parse_event_ref("openwindow>>5f3a,2,kitty,vim", EventProfile::CURRENT).unwrap();
+        assert_eq!(event.name(), "openwindow");
+        assert_eq!(
+            event.into_owned(),
+            HyprEvent::WindowOpened {
+                address: "5f3a".to_string(),
+                workspace: "2".to_string(),
+                class: "kitty".to_string(),
+                title: "vim".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_unknown_event() {
+        assert_eq!(
+            parse_event("somethingnew>>a,b", EventProfile::CURRENT),
+            Some(HyprEvent::Unknown {
+                name: "somethingnew".to_string(),
+                data: "a,b".to_string(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_events_iterator_and_channel() {
+        use std::io::Write;
+        use std::os::unix::net::UnixListener;
+
+        let path =
+            std::env::temp_dir().join(format!("hyprdrover-events-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            for _ in 0..2 {
+                let (mut stream, _) = listener.accept().unwrap();
+                stream
+                    .write_all(b"closewindow>>5f3a\n\nactivewindow>>kitty,vim\n")
+                    .unwrap();
+            }
+        });
+        let listener = IpcEventListener::connect_to(&path).with_profile(EventProfile::CURRENT);
+
+        let names: Vec<String> = listener
+            .events()
+            .map(|event| event.unwrap().name().to_string())
+            .collect();
+        assert_eq!(names, ["closewindow", "activewindow"]);
+
+        let (handle, receiver) = listener.spawn_channel();
+        assert_eq!(receiver.iter().count(), 2);
+        assert!(handle.join().unwrap().is_ok());
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        let mut missing = IpcEventListener::connect_to(&path).events();
+        assert!(missing.next().unwrap().is_err());
+        assert!(missing.next().is_none());
+    }
+
+    #[test]
+    fn test_listen_with_reconnect() {
+        use std::io::Write;
+        use std::os::unix::net::UnixListener;
+
+        let path =
+            std::env::temp_dir().join(format!("hyprdrover-reconnect-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        let server = thread::spawn(move || {
+            for line in [&b"closewindow>>1\n"[..], b"closewindow>>2\n"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                stream.write_all(line).unwrap();
+            }
+        });
+        let backoff = Backoff {
+            initial: Duration::from_millis(1),
+            max: Duration::from_millis(4),
+            give_up: None,
+        };
+
+        let mut names = vec![];
+        IpcEventListener::connect_to(&path)
+            .with_profile(EventProfile::CURRENT)
+            .listen_with_reconnect(backoff, |event| {
+                names.push(event.name().to_string());
+                names.len() < 3
+            });
+        assert_eq!(names, ["closewindow", "reconnected", "closewindow"]);
+        server.join().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(backoff.delay(1), Duration::from_millis(2));
+        assert_eq!(backoff.delay(40), Duration::from_millis(4));
+
+        // Nobody comes back
+        let give_up = Backoff {
+            give_up: Some(Duration::from_millis(20)),
+            ..backoff
+        };
+        let mut called = false;
+        IpcEventListener::connect_to(&path).listen_with_reconnect(give_up, |_| {
+            called = true;
+            true
+        });
+        assert!(!called);
+    }
+}
@@ -1,84 +1,43 @@
-use serde::{Deserialize, Serialize};
+use super::dispatch::{Dispatch, WorkspaceRef};
+use super::input::InputSettings;
+use super::instance::instance_signature;
+use super::models::*;
+use super::socket::{is_remote, socket_overrides, HyprCommandClient};
+use super::version::capabilities;
+use std::collections::BTreeMap;
 use std::error::Error;
 use std::process::Command;
+use std::thread::{self, Scope, ScopedJoinHandle};
 
-// --- Data Models (matching hyprctl -j output) ---
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct HyprWorkspaceRef {
-    pub id: i32,
-    pub name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct HyprActiveWorkspace {
-    pub id: i32,
-    pub name: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct HyprClient {
-    pub address: String,
-    pub at: [i32; 2],
-    pub size: [i32; 2],
-    pub workspace: HyprWorkspaceRef,
-    pub class: String,
-    pub title: String,
-    pub initial_class: String,
-    pub initial_title: String,
-    pub floating: bool,
-    pub pinned: bool,
-    pub monitor: i64,
-    pub fullscreen: i32, // 0: none, 1: maximized, 2: fullscreen
-    pub xwayland: bool,
-    pub pid: i32,
-    #[serde(default)]
-    pub exec_path: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct HyprWorkspace {
-    pub id: i32,
-    pub name: String,
-    pub monitor: String,
-    pub windows: i32,
-    pub hasfullscreen: bool,
-    pub lastwindow: String,
-    pub lastwindowtitle: String,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(rename_all = "camelCase")]
-pub struct HyprMonitor {
-    pub id: i64,
-    pub name: String,
-    pub width: i32,
-    pub height: i32,
-    pub refresh_rate: f32,
-    pub x: i32,
-    pub y: i32,
-    pub active_workspace: HyprWorkspaceRef,
-}
-
-// --- Helper Struct for the full snapshot ---
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SessionSnapshot {
-    pub clients: Vec<HyprClient>,
-    pub workspaces: Vec<HyprWorkspace>,
-    pub monitors: Vec<HyprMonitor>,
+// --- Implementation ---
+
+/// Build a hyprctl invocation bound to the resolved Hyprland instance.
+///
+/// hyprctl only reads `HYPRLAND_INSTANCE_SIGNATURE` itself, so a discovered
+/// signature has to be passed explicitly.
+pub fn hyprctl() -> Result<Command, Box<dyn Error>> {
+    let signature = instance_signature()?;
+    let mut command = Command::new("hyprctl");
+    // The variable names the instance hyprdrover started under, which may
+    // have been replaced since (see `follow_instance`)
+    if std::env::var("HYPRLAND_INSTANCE_SIGNATURE").ok().as_deref() != Some(signature.as_str()) {
+        command.arg("--instance").arg(signature);
+    }
+    Ok(command)
 }
 
-// --- Implementation ---
+/// Send a command to the compositor and return its reply: over the
+/// overridden command socket when one is set, through `hyprctl` otherwise
+fn send(args: &[&str], json: bool) -> Result<String, Box<dyn Error>> {
+    if socket_overrides().command.is_some() {
+        return Ok(HyprCommandClient::new()?.request(args, json)?);
+    }
 
-/// Execute a hyprctl command and return the output as a string
-fn run_hyprctl(args: &[&str]) -> Result<String, Box<dyn Error>> {
-    let output = Command::new("hyprctl")
-        .arg("-j") // Request JSON output
-        .args(args)
-        .output()?;
+    let mut command = hyprctl()?;
+    if json {
+        command.arg("-j");
+    }
+    let output = command.args(args).output()?;
 
     if !output.status.success() {
         return Err(format!(
@@ -91,27 +50,227 @@ fn run_hyprctl(args: &[&str]) -> Result<String, Box<dyn Error>> {
     Ok(String::from_utf8(output.stdout)?)
 }
 
+/// Send several commands (e.g. `dispatch focuswindow address:0x1`) in one
+/// request, like `hyprctl --batch`, returning each command's reply in order.
+/// A reply is `None` when the compositor ran it together with others and it
+/// can't be told which command it belongs to.
+pub fn batch(commands: &[String]) -> Result<Vec<Option<String>>, Box<dyn Error>> {
+    Ok(split_batch_reply(&send_batch(commands)?, commands.len()))
+}
+
+/// Send several queries (e.g. `getprop address:0x1 alpha`) in one request
+/// instead of one each, returning their replies in order. Fails when the
+/// compositor runs the replies together, as they can't be told apart.
+pub fn send_many(commands: &[String]) -> Result<Vec<String>, Box<dyn Error>> {
+    split_replies(&send_batch(commands)?, commands.len()).ok_or_else(inseparable_replies)
+}
+
+/// The unsplit reply to `commands` sent as one batch
+fn send_batch(commands: &[String]) -> Result<String, Box<dyn Error>> {
+    let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+    if socket_overrides().command.is_some() {
+        return HyprCommandClient::new()?.send_batch(&commands);
+    }
+    if let Some(command) = commands.iter().find(|c| c.contains(';')) {
+        return Err(unbatchable(command));
+    }
+
+    let output = hyprctl()?
+        .arg("--batch")
+        .arg(commands.join(" ; "))
+        .output()?;
+    if !output.status.success() {
+        return Err(format!(
+            "hyprctl failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )
+        .into());
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// `;` separates batched commands, so one containing it can't be batched
+pub(super) fn unbatchable(command: &str) -> Box<dyn Error> {
+    format!("can't batch a command containing `;`: {}", command).into()
+}
+
+pub(super) fn inseparable_replies() -> Box<dyn Error> {
+    "the compositor doesn't separate batched replies".into()
+}
+
+/// The replies to `count` batched commands, if they can be told apart.
+/// Hyprland separates them with two blank lines, or one in older versions;
+/// the oldest run them together.
+pub(super) fn split_replies(reply: &str, count: usize) -> Option<Vec<String>> {
+    ["\n\n\n", "\n\n"].into_iter().find_map(|separator| {
+        let parts: Vec<String> = reply
+            .split(separator)
+            .map(str::trim)
+            .filter(|part| !part.is_empty())
+            .map(String::from)
+            .collect();
+        (parts.len() == count).then_some(parts)
+    })
+}
+
+/// The replies to `count` batched commands. When they run together, only
+/// the leading `ok`s can be attributed; the commands after them get `None`,
+/// since any of them may have produced the error text that follows.
+pub(super) fn split_batch_reply(reply: &str, count: usize) -> Vec<Option<String>> {
+    if let Some(parts) = split_replies(reply, count) {
+        return parts.into_iter().map(Some).collect();
+    }
+
+    let mut rest = reply.trim();
+    let mut replies = vec![];
+    while let Some(after) = rest.strip_prefix("ok").filter(|_| replies.len() < count) {
+        replies.push(Some("ok".to_string()));
+        rest = after.trim_start();
+    }
+    replies.resize(count, None);
+    replies
+}
+
+/// Whether every batched command was answered with `ok`
+fn batch_succeeded(replies: &[Option<String>]) -> bool {
+    replies.iter().all(|reply| {
+        reply
+            .as_deref()
+            .is_some_and(|r| r.eq_ignore_ascii_case("ok"))
+    })
+}
+
+/// Send a command whose only successful reply is `ok`, e.g. a dispatch
+fn send_expect_ok(args: &[&str]) -> Result<(), String> {
+    match send(args, false) {
+        Ok(reply) if reply.trim().eq_ignore_ascii_case("ok") => Ok(()),
+        Ok(reply) => Err(reply.trim().to_string()),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Execute a command and return its JSON output as a string
+pub(super) fn run_hyprctl(args: &[&str]) -> Result<String, Box<dyn Error>> {
+    send(args, true)
+}
+
 /// Get all open windows (clients)
-fn get_clients() -> Result<Vec<HyprClient>, Box<dyn Error>> {
+pub fn get_clients() -> Result<Vec<HyprClient>, Box<dyn Error>> {
     let json = run_hyprctl(&["clients"])?;
     let clients: Vec<HyprClient> = serde_json::from_str(&json)?;
     Ok(clients)
 }
 
+/// The open window at `address` (with or without the `0x` prefix), if any
+pub fn find_client(address: &str) -> Result<Option<HyprClient>, Box<dyn Error>> {
+    let address = address.trim_start_matches("0x");
+    Ok(get_clients()?
+        .into_iter()
+        .find(|c| c.address.trim_start_matches("0x") == address))
+}
+
 /// Get all active workspaces
-fn get_workspaces() -> Result<Vec<HyprWorkspace>, Box<dyn Error>> {
+pub fn get_workspaces() -> Result<Vec<HyprWorkspace>, Box<dyn Error>> {
     let json = run_hyprctl(&["workspaces"])?;
     let workspaces: Vec<HyprWorkspace> = serde_json::from_str(&json)?;
     Ok(workspaces)
 }
 
 /// Get all connected monitors
-fn get_monitors() -> Result<Vec<HyprMonitor>, Box<dyn Error>> {
+pub fn get_monitors() -> Result<Vec<HyprMonitor>, Box<dyn Error>> {
     let json = run_hyprctl(&["monitors"])?;
     let monitors: Vec<HyprMonitor> = serde_json::from_str(&json)?;
     Ok(monitors)
 }
 
+/// Get the `workspace = ...` rules currently in effect
+pub fn get_workspace_rules() -> Result<Vec<HyprWorkspaceRule>, Box<dyn Error>> {
+    let json = run_hyprctl(&["workspacerules"])?;
+    let rules: Vec<HyprWorkspaceRule> = serde_json::from_str(&json)?;
+    Ok(rules)
+}
+
+/// Get every layer-shell surface on every monitor
+pub fn get_layers() -> Result<Vec<HyprLayer>, Box<dyn Error>> {
+    let json = run_hyprctl(&["layers"])?;
+    let raw: BTreeMap<String, RawMonitorLayers> = serde_json::from_str(&json)?;
+    Ok(flatten_layers(raw))
+}
+
+/// Get every keybinding currently defined
+pub fn get_binds() -> Result<Vec<HyprBind>, Box<dyn Error>> {
+    let json = run_hyprctl(&["binds"])?;
+    let binds: Vec<HyprBind> = serde_json::from_str(&json)?;
+    Ok(binds)
+}
+
+/// Get the loaded compositor plugins
+pub fn get_plugins() -> Result<Vec<HyprPlugin>, Box<dyn Error>> {
+    let reply = run_hyprctl(&["plugin", "list"])?;
+    // Some versions answer "no plugins loaded" even when asked for JSON
+    if !reply.trim_start().starts_with('[') {
+        return Ok(vec![]);
+    }
+    let plugins: Vec<HyprPlugin> = serde_json::from_str(&reply)?;
+    Ok(plugins)
+}
+
+/// Errors Hyprland found in its config file, one per line
+pub fn get_config_errors() -> Result<Vec<String>, Box<dyn Error>> {
+    let json = run_hyprctl(&["configerrors"])?;
+    Ok(parse_config_errors(&json)?)
+}
+
+fn parse_config_errors(json: &str) -> Result<Vec<String>, serde_json::Error> {
+    let errors: Vec<String> = serde_json::from_str(json)?;
+    // A clean config is reported as a single empty string
+    Ok(errors
+        .iter()
+        .flat_map(|e| e.lines())
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// The most recent lines of the compositor's own log
+pub fn get_rolling_log() -> Result<Vec<String>, Box<dyn Error>> {
+    let log = send(&["rollinglog"], false)?;
+    Ok(log.lines().map(String::from).collect())
+}
+
+/// Set a config keyword at runtime, like `hyprctl keyword NAME VALUE`
+pub fn keyword(name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    // Bad keywords are reported in the reply with a zero exit status
+    send_expect_ok(&["keyword", name, value])
+        .map_err(|e| format!("keyword {} failed: {}", name, e).into())
+}
+
+/// Set several keywords with a single request
+pub fn keyword_batch(keywords: &[(String, String)]) -> Result<(), Box<dyn Error>> {
+    // `;` separates batched commands, so such values are sent on their own
+    let (single, batched): (Vec<_>, Vec<_>) = keywords.iter().partition(|(_, v)| v.contains(';'));
+    for (name, value) in single {
+        keyword(name, value)?;
+    }
+    if batched.is_empty() {
+        return Ok(());
+    }
+
+    let commands: Vec<String> = batched
+        .iter()
+        .map(|(name, value)| format!("keyword {} {}", name, value))
+        .collect();
+    let replies = batch(&commands)?;
+    if !batch_succeeded(&replies) {
+        let replies: Vec<&str> = replies
+            .iter()
+            .map(|r| r.as_deref().unwrap_or("?"))
+            .collect();
+        return Err(format!("keyword batch failed: {}", replies.join("; ")).into());
+    }
+    Ok(())
+}
+
 /// Get the active workspace for the currently focused monitor
 pub fn get_active_workspace() -> Result<HyprActiveWorkspace, Box<dyn Error>> {
     let json = run_hyprctl(&["activeworkspace"])?;
@@ -119,73 +278,330 @@ pub fn get_active_workspace() -> Result<HyprActiveWorkspace, Box<dyn Error>> {
     Ok(workspace)
 }
 
+/// Get the pointer position
+pub fn get_cursor_pos() -> Result<CursorPosition, Box<dyn Error>> {
+    let json = run_hyprctl(&["cursorpos"])?;
+    let position: CursorPosition = serde_json::from_str(&json)?;
+    Ok(position)
+}
+
 /// Capture the entire current state of Hyprland
 pub fn capture_state() -> Result<SessionSnapshot, Box<dyn Error>> {
-    let mut clients = get_clients()?;
+    // Each query is a request of its own, so they all run side by side
+    thread::scope(|scope| {
+        let workspaces = spawn_query(scope, get_workspaces);
+        let monitors = spawn_query(scope, get_monitors);
+        let workspace_rules = spawn_query(scope, get_workspace_rules);
+        let layers = spawn_query(scope, get_layers);
+        let plugins = spawn_query(scope, get_plugins);
+        let cursor = spawn_query(scope, get_cursor_pos);
+        let input = scope.spawn(InputSettings::capture);
+
+        let mut clients = get_clients()?;
+        capture_props(&mut clients);
+
+        // Enrich clients with executable path from /proc/<pid>/exe. A remote
+        // compositor's pids mean nothing here, so leave the paths unset.
+        for client in clients.iter_mut().filter(|_| !is_remote()) {
+            if let Ok(path) = std::fs::read_link(format!("/proc/{}/exe", client.pid)) {
+                client.exec_path = Some(path.to_string_lossy().into_owned());
+            }
+            if client.swallowing.is_some() {
+                client.command_line = read_command_line(client.pid);
+            }
+            client.cwd = read_cwd(client.pid);
+        }
 
-    // Enrich clients with executable path from /proc/<pid>/exe
-    for client in &mut clients {
-        if let Ok(path) = std::fs::read_link(format!("/proc/{}/exe", client.pid)) {
-            client.exec_path = Some(path.to_string_lossy().into_owned());
+        let monitors = join_query(monitors)?;
+        for client in clients.iter_mut().filter(|c| c.floating) {
+            client.relative = monitors
+                .iter()
+                .find(|m| m.id == client.monitor)
+                .and_then(|m| RelativeGeometry::of(client, m));
         }
-    }
 
-    Ok(SessionSnapshot {
-        clients,
-        workspaces: get_workspaces()?,
-        monitors: get_monitors()?,
+        Ok(SessionSnapshot {
+            version: SNAPSHOT_VERSION,
+            clients,
+            workspaces: join_query(workspaces)?,
+            monitors,
+            // Best effort: not every Hyprland version answers `workspacerules`
+            workspace_rules: join_query(workspace_rules).unwrap_or_default(),
+            layers: join_query(layers).unwrap_or_default(),
+            input: input.join().ok(),
+            plugins: join_query(plugins).unwrap_or_default(),
+            cursor: join_query(cursor).ok(),
+            note: None,
+        })
     })
 }
 
+/// Run `query` on a thread of `scope`. Boxed errors aren't `Send`, so they
+/// cross over as text.
+fn spawn_query<'scope, T: Send + 'scope>(
+    scope: &'scope Scope<'scope, '_>,
+    query: fn() -> Result<T, Box<dyn Error>>,
+) -> ScopedJoinHandle<'scope, Result<T, String>> {
+    scope.spawn(move || query().map_err(|e| e.to_string()))
+}
+
+fn join_query<T>(handle: ScopedJoinHandle<'_, Result<T, String>>) -> Result<T, Box<dyn Error>> {
+    match handle.join() {
+        Ok(result) => result.map_err(Into::into),
+        Err(_) => Err("a capture query panicked".into()),
+    }
+}
+
+/// Windows whose properties are asked for in one batched request, which
+/// keeps requests short; the batches go out in parallel
+const PROP_BATCH_WINDOWS: usize = 16;
+
+fn capture_props(clients: &mut [HyprClient]) {
+    thread::scope(|scope| {
+        for chunk in clients.chunks_mut(PROP_BATCH_WINDOWS) {
+            scope.spawn(move || {
+                if batch_window_props(chunk).is_err() {
+                    for client in chunk {
+                        client.props = get_window_props(&client.address);
+                    }
+                }
+            });
+        }
+    });
+}
+
+/// Window properties kept in snapshots, with the value they have unless
+/// changed with `setprop`
+const TRACKED_PROPS: [(&str, f32); 4] = [
+    ("alpha", 1.0),
+    ("alphainactive", 1.0),
+    ("dimaround", 0.0),
+    ("nofocus", 0.0),
+];
+
+/// Fill in the props of all `clients` from a single batch of `getprop`s
+fn batch_window_props(clients: &mut [HyprClient]) -> Result<(), Box<dyn Error>> {
+    let commands: Vec<String> = clients
+        .iter()
+        .flat_map(|client| {
+            TRACKED_PROPS
+                .iter()
+                .map(move |(prop, _)| format!("getprop address:{} {}", client.address, prop))
+        })
+        .collect();
+    let replies = send_many(&commands)?;
+    for (client, replies) in clients.iter_mut().zip(replies.chunks(TRACKED_PROPS.len())) {
+        client.props = changed_props(replies.iter().map(String::as_str));
+    }
+    Ok(())
+}
+
+/// The tracked properties of a window that differ from their defaults,
+/// asked for one at a time
+fn get_window_props(address: &str) -> BTreeMap<String, String> {
+    let target = format!("address:{}", address);
+    let replies: Vec<String> = TRACKED_PROPS
+        .iter()
+        .map(|(prop, _)| send(&["getprop", &target, prop], false).unwrap_or_default())
+        .collect();
+    changed_props(replies.iter().map(String::as_str))
+}
+
+/// The tracked properties whose `getprop` replies, in `TRACKED_PROPS`
+/// order, differ from their defaults. Versions without `getprop` answer
+/// with an error, which yields nothing.
+fn changed_props<'a>(replies: impl Iterator<Item = &'a str>) -> BTreeMap<String, String> {
+    TRACKED_PROPS
+        .iter()
+        .zip(replies)
+        .filter_map(|(&(prop, default), reply)| {
+            changed_prop(reply, default).map(|value| (prop.to_string(), value))
+        })
+        .collect()
+}
+
+/// A `getprop` reply, if it is a value other than `default`
+fn changed_prop(reply: &str, default: f32) -> Option<String> {
+    let value = reply.trim();
+    let number = match value {
+        "true" => 1.0,
+        "false" => 0.0,
+        _ => value.parse::<f32>().ok()?,
+    };
+    ((number - default).abs() > 0.001).then(|| value.to_string())
+}
+
+/// Arguments of a process from `/proc/<pid>/cmdline`
+fn read_command_line(pid: i32) -> Option<Vec<String>> {
+    let raw = std::fs::read(format!("/proc/{}/cmdline", pid)).ok()?;
+    let args: Vec<String> = raw
+        .split(|&b| b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    (!args.is_empty()).then_some(args)
+}
+
+/// Working directory of `pid`'s first child, which for a terminal is its
+/// shell, else of `pid` itself
+fn read_cwd(pid: i32) -> Option<String> {
+    let children = std::fs::read_to_string(format!("/proc/{0}/task/{0}/children", pid));
+    let child = children
+        .ok()
+        .and_then(|c| c.split_whitespace().next().map(String::from));
+    let process = child.unwrap_or_else(|| pid.to_string());
+    let path = std::fs::read_link(format!("/proc/{}/cwd", process)).ok()?;
+    Some(path.to_string_lossy().into_owned())
+}
+
 // --- Dispatch Commands (Actions) ---
 
 /// Execute a raw hyprctl dispatch command
 pub fn dispatch(command: &str) -> Result<(), Box<dyn Error>> {
-    let args: Vec<&str> = command.split_whitespace().collect();
-    let output = Command::new("hyprctl")
-        .arg("dispatch")
-        .args(&args)
-        .output()?;
+    let mut args = vec!["dispatch"];
+    args.extend(command.split_whitespace());
+    send_expect_ok(&args).map_err(|e| format!("Dispatch failed: {}", e).into())
+}
 
-    if !output.status.success() {
-        return Err(format!(
-            "Dispatch failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        )
-        .into());
-    }
-    Ok(())
+/// Execute a dispatcher built from its parts
+pub fn dispatch_typed(dispatch: Dispatch) -> Result<(), Box<dyn Error>> {
+    send_expect_ok(&["dispatch", &dispatch.to_command_string()?])
+        .map_err(|e| format!("Dispatch failed: {}", e).into())
 }
 
 /// Move a specific window to a workspace (silently, without switching focus to that workspace)
-pub fn move_window_to_workspace(address: &str, workspace_id: i32) -> Result<(), Box<dyn Error>> {
-    // Syntax: movetoworkspacesilent ID,address:ADDRESS
-    let cmd = format!("movetoworkspacesilent {},address:{}", workspace_id, address);
-    dispatch(&cmd)
+pub fn move_window_to_workspace(
+    address: &str,
+    workspace: WorkspaceRef,
+) -> Result<(), Box<dyn Error>> {
+    dispatch_typed(Dispatch::MoveToWorkspaceSilent {
+        workspace,
+        window: address.into(),
+    })
 }
 
 /// Focus a specific window
-#[allow(dead_code)]
 pub fn focus_window(address: &str) -> Result<(), Box<dyn Error>> {
-    let cmd = format!("focuswindow address:{}", address);
-    dispatch(&cmd)
+    dispatch_typed(Dispatch::FocusWindow(address.into()))
+}
+
+/// Make a window floating or tiled, whichever it is now
+pub fn set_floating(address: &str, floating: bool) -> Result<(), Box<dyn Error>> {
+    if capabilities().set_floating {
+        return dispatch_typed(if floating {
+            Dispatch::SetFloating(address.into())
+        } else {
+            Dispatch::SetTiled(address.into())
+        });
+    }
+
+    // Older compositors can only toggle, so go by the live state
+    match find_client(address)? {
+        Some(client) if client.floating != floating => {
+            dispatch_typed(Dispatch::ToggleFloating(address.into()))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Pin or unpin a floating window. `pin` only toggles, so this goes by the
+/// live state.
+pub fn set_pinned(address: &str, pinned: bool) -> Result<(), Box<dyn Error>> {
+    match find_client(address)? {
+        Some(client) if client.pinned != pinned => dispatch_typed(Dispatch::Pin(address.into())),
+        _ => Ok(()),
+    }
+}
+
+/// Run a shell command through Hyprland, like `hyprctl dispatch exec`.
+/// The command is passed whole, so it may contain spaces and window rules.
+pub fn dispatch_exec(command: &str) -> Result<(), Box<dyn Error>> {
+    send_expect_ok(&["dispatch", "exec", command])
+        .map_err(|e| format!("Failed to launch {}: {}", command, e).into())
 }
 
 /// Move a window to a specific pixel coordinate
 pub fn move_window_pixel(address: &str, x: i32, y: i32) -> Result<(), Box<dyn Error>> {
-    // Syntax: movewindowpixel exact X Y,address:ADDRESS
-    let cmd = format!("movewindowpixel exact {} {},address:{}", x, y, address);
-    dispatch(&cmd)
+    dispatch_typed(Dispatch::MoveWindowPixel {
+        window: address.into(),
+        x,
+        y,
+    })
 }
 
 /// Resize a window to specific dimensions
 pub fn resize_window_pixel(address: &str, width: i32, height: i32) -> Result<(), Box<dyn Error>> {
-    // Syntax: resizewindowpixel exact W H,address:ADDRESS
-    let cmd = format!(
-        "resizewindowpixel exact {} {},address:{}",
-        width, height, address
-    );
-    dispatch(&cmd)
+    dispatch_typed(Dispatch::ResizeWindowPixel {
+        window: address.into(),
+        width,
+        height,
+    })
+}
+
+/// Warp the pointer to a position in the global layout
+pub fn move_cursor(x: i32, y: i32) -> Result<(), Box<dyn Error>> {
+    dispatch_typed(Dispatch::MoveCursor { x, y })
+}
+
+/// Change the cursor theme and size, like `hyprctl setcursor THEME SIZE`
+pub fn set_cursor(theme: &str, size: u32) -> Result<(), Box<dyn Error>> {
+    send_expect_ok(&["setcursor", theme, &size.to_string()])
+        .map_err(|e| format!("setcursor failed: {}", e).into())
+}
+
+/// Set a window property at runtime, like `hyprctl setprop`
+pub fn set_window_prop(address: &str, prop: &str, value: &str) -> Result<(), Box<dyn Error>> {
+    send_expect_ok(&["setprop", &format!("address:{}", address), prop, value])
+        .map_err(|e| format!("setprop {} failed: {}", prop, e).into())
+}
+
+/// Backends `hyprctl output create` can add an output to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputBackend {
+    Headless,
+    Wayland,
+    Auto,
+}
+
+impl OutputBackend {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Headless => "headless",
+            Self::Wayland => "wayland",
+            Self::Auto => "auto",
+        }
+    }
+}
+
+/// Add a virtual output, named `name` or by Hyprland (`HEADLESS-1`, ...)
+pub fn create_output(backend: OutputBackend, name: Option<&str>) -> Result<(), Box<dyn Error>> {
+    let mut args = vec!["output", "create", backend.as_str()];
+    args.extend(name);
+    send_expect_ok(&args).map_err(|e| format!("Failed to create output: {}", e).into())
+}
+
+/// Remove an output added with [`create_output`]
+pub fn remove_output(name: &str) -> Result<(), Box<dyn Error>> {
+    send_expect_ok(&["output", "remove", name])
+        .map_err(|e| format!("Failed to remove output {}: {}", name, e).into())
+}
+
+/// Icons accepted by `hyprctl notify`
+#[derive(Debug, Clone, Copy)]
+pub enum NotifyIcon {
+    Warning = 0,
+    Info = 1,
+    Error = 3,
+    Ok = 5,
+}
+
+/// Show a compositor-native notification for `timeout_ms` milliseconds
+pub fn notify(icon: NotifyIcon, timeout_ms: u32, message: &str) -> Result<(), Box<dyn Error>> {
+    // Color 0 keeps the icon's default color
+    let icon = (icon as i32).to_string();
+    let timeout = timeout_ms.to_string();
+    send_expect_ok(&["notify", &icon, &timeout, "0", message])
+        .map_err(|e| format!("notify failed: {}", e).into())
 }
 
 #[cfg(test)]
@@ -193,69 +609,49 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_deserialize_client() {
-        let json = r#"{
-            "address": "0x1234",
-            "at": [10, 10],
-            "size": [800, 600],
-            "workspace": { "id": 1, "name": "1" },
-            "class": "kitty",
-            "title": "terminal",
-            "initialClass": "kitty",
-            "initialTitle": "terminal",
-            "floating": false,
-            "pinned": false,
-            "monitor": 0,
-            "fullscreen": 0,
-            "xwayland": false,
-            "pid": 1234
-        }"#;
-
-        let client: HyprClient = serde_json::from_str(json).expect("Failed to deserialize client");
-
-        assert_eq!(client.address, "0x1234");
-        assert_eq!(client.class, "kitty");
-        assert_eq!(client.workspace.id, 1);
-        assert_eq!(client.size, [800, 600]);
+    fn test_batch_reply_needs_one_ok_per_command() {
+        let succeeded = |reply: &str, count| batch_succeeded(&split_batch_reply(reply, count));
+        assert!(succeeded("ok\n\nok\n\n", 2));
+        assert!(succeeded("okok", 2));
+        assert!(!succeeded("ok\n\ninvalid field", 2));
+        assert!(!succeeded("ok", 2));
     }
 
     #[test]
-    fn test_deserialize_workspace() {
-        let json = r#"{
-            "id": 1,
-            "name": "1",
-            "monitor": "eDP-1",
-            "windows": 5,
-            "hasfullscreen": false,
-            "lastwindow": "0x1234",
-            "lastwindowtitle": "terminal"
-        }"#;
-
-        let ws: HyprWorkspace =
-            serde_json::from_str(json).expect("Failed to deserialize workspace");
-
-        assert_eq!(ws.id, 1);
-        assert_eq!(ws.monitor, "eDP-1");
-        assert_eq!(ws.windows, 5);
+    fn test_split_batch_reply() {
+        let some = |reply: &str| Some(reply.to_string());
+        assert_eq!(
+            split_batch_reply("ok\n\nInvalid dispatcher\n\nok\n\n", 3),
+            [some("ok"), some("Invalid dispatcher"), some("ok")]
+        );
+        assert_eq!(
+            split_replies("0.8\n\n\n1.0\n\n\ntrue", 3).unwrap(),
+            ["0.8", "1.0", "true"]
+        );
+        assert_eq!(split_replies("0.81.0", 2), None);
+        // Older compositors don't separate the replies: either of the last
+        // two commands may have failed
+        assert_eq!(
+            split_batch_reply("okokInvalid dispatcher", 4),
+            [some("ok"), some("ok"), None, None]
+        );
+    }
+
+    #[test]
+    fn test_parse_config_errors() {
+        assert!(parse_config_errors(r#"[""]"#).unwrap().is_empty());
+
+        let json = r#"["Config error in file hyprland.conf at line 12: invalid dispatcher\nConfig error in file hyprland.conf at line 40: unknown keyword"]"#;
+        let errors = parse_config_errors(json).unwrap();
+        assert_eq!(errors.len(), 2);
+        assert!(errors[1].ends_with("unknown keyword"));
     }
 
     #[test]
-    fn test_deserialize_monitor() {
-        let json = r#"{
-            "id": 0,
-            "name": "eDP-1",
-            "width": 1920,
-            "height": 1080,
-            "refreshRate": 60.0,
-            "x": 0,
-            "y": 0,
-            "activeWorkspace": { "id": 1, "name": "1" }
-        }"#;
-
-        let mon: HyprMonitor = serde_json::from_str(json).expect("Failed to deserialize monitor");
-
-        assert_eq!(mon.id, 0);
-        assert_eq!(mon.width, 1920);
-        assert_eq!(mon.active_workspace.id, 1);
+    fn test_changed_prop() {
+        assert_eq!(changed_prop("0.850000\n", 1.0).as_deref(), Some("0.850000"));
+        assert_eq!(changed_prop("1.000000", 1.0), None);
+        assert_eq!(changed_prop("true", 0.0).as_deref(), Some("true"));
+        assert_eq!(changed_prop("prop not found", 1.0), None);
     }
 }
@@ -0,0 +1,135 @@
+use super::hypr_commands::{keyword, run_hyprctl};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::error::Error;
+
+/// Input settings that commonly get lost when docking or replugging devices.
+///
+/// Each field is `None` when it couldn't be read, and is then never re-applied.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct InputSettings {
+    pub repeat_rate: Option<i64>,
+    pub repeat_delay: Option<i64>,
+    pub natural_scroll: Option<bool>,
+    /// Comma-separated xkb layouts, e.g. `us,de`
+    pub kb_layout: Option<String>,
+    pub kb_variant: Option<String>,
+    pub kb_options: Option<String>,
+}
+
+impl InputSettings {
+    /// Read the current values with `hyprctl -j getoption`
+    pub fn capture() -> Self {
+        Self {
+            repeat_rate: get_option("input:repeat_rate").and_then(|v| v.as_i64()),
+            repeat_delay: get_option("input:repeat_delay").and_then(|v| v.as_i64()),
+            natural_scroll: get_option("input:touchpad:natural_scroll")
+                .and_then(|v| v.as_i64())
+                .map(|v| v != 0),
+            kb_layout: get_string_option("input:kb_layout"),
+            kb_variant: get_string_option("input:kb_variant"),
+            kb_options: get_string_option("input:kb_options"),
+        }
+    }
+
+    /// `(keyword, value)` pairs for every setting in `self` that differs from `current`
+    pub fn differences(&self, current: &InputSettings) -> Vec<(&'static str, String)> {
+        let mut changes = vec![];
+        if let Some(rate) = self
+            .repeat_rate
+            .filter(|_| self.repeat_rate != current.repeat_rate)
+        {
+            changes.push(("input:repeat_rate", rate.to_string()));
+        }
+        if let Some(delay) = self
+            .repeat_delay
+            .filter(|_| self.repeat_delay != current.repeat_delay)
+        {
+            changes.push(("input:repeat_delay", delay.to_string()));
+        }
+        if let Some(natural) = self
+            .natural_scroll
+            .filter(|_| self.natural_scroll != current.natural_scroll)
+        {
+            changes.push(("input:touchpad:natural_scroll", natural.to_string()));
+        }
+        for (name, saved, live) in [
+            ("input:kb_layout", &self.kb_layout, &current.kb_layout),
+            ("input:kb_variant", &self.kb_variant, &current.kb_variant),
+            ("input:kb_options", &self.kb_options, &current.kb_options),
+        ] {
+            if let Some(value) = saved.as_ref().filter(|_| saved != live) {
+                changes.push((name, value.clone()));
+            }
+        }
+        changes
+    }
+
+    /// Apply every setting that differs from the live configuration
+    pub fn apply(&self) -> Result<usize, Box<dyn Error>> {
+        let changes = self.differences(&Self::capture());
+        for (name, value) in &changes {
+            keyword(name, value)?;
+        }
+        Ok(changes.len())
+    }
+}
+
+/// The value of a config option: its `int`, `float` or `str` field
+pub(super) fn get_option(name: &str) -> Option<Value> {
+    let json = run_hyprctl(&["getoption", name]).ok()?;
+    option_value(&serde_json::from_str(&json).ok()?)
+}
+
+fn get_string_option(name: &str) -> Option<String> {
+    get_option(name).and_then(|v| v.as_str().map(String::from))
+}
+
+fn option_value(option: &Value) -> Option<Value> {
+    ["int", "float", "str"]
+        .iter()
+        .find_map(|key| option.get(key).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_option_value() {
+        let option = serde_json::json!({ "option": "input:repeat_rate", "int": 25, "set": true });
+        assert_eq!(option_value(&option), Some(Value::from(25)));
+
+        let option =
+            serde_json::json!({ "option": "input:kb_layout", "str": "us,de", "set": true });
+        assert_eq!(option_value(&option), Some(Value::from("us,de")));
+    }
+
+    #[test]
+    fn test_differences_only_lists_known_changed_settings() {
+        let saved = InputSettings {
+            repeat_rate: Some(40),
+            repeat_delay: Some(250),
+            natural_scroll: Some(true),
+            kb_layout: Some("us,de".to_string()),
+            ..Default::default()
+        };
+        let current = InputSettings {
+            repeat_rate: Some(25),
+            repeat_delay: Some(250),
+            natural_scroll: Some(false),
+            kb_layout: Some("us".to_string()),
+            kb_variant: Some("intl".to_string()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            saved.differences(&current),
+            vec![
+                ("input:repeat_rate", "40".to_string()),
+                ("input:touchpad:natural_scroll", "true".to_string()),
+                ("input:kb_layout", "us,de".to_string()),
+            ]
+        );
+    }
+}
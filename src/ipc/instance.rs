@@ -0,0 +1,143 @@
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::os::unix::fs::MetadataExt;
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::{OnceLock, RwLock};
+
+const SIGNATURE_VAR: &str = "HYPRLAND_INSTANCE_SIGNATURE";
+
+/// Resolve the Hyprland instance signature to talk to.
+///
+/// Uses `HYPRLAND_INSTANCE_SIGNATURE` when set. Otherwise (cron jobs, ssh
+/// sessions) the runtime directory is scanned for instances with a live
+/// command socket, and the result is cached for the rest of the process.
+/// Once [`follow_instance`] names another instance, that one is used.
+pub fn instance_signature() -> Result<String, Box<dyn Error>> {
+    if let Some(sig) = FOLLOWED.read().unwrap().clone() {
+        return Ok(sig);
+    }
+    if let Ok(sig) = env::var(SIGNATURE_VAR) {
+        if !sig.is_empty() {
+            return Ok(sig);
+        }
+    }
+
+    static DISCOVERED: OnceLock<Result<String, String>> = OnceLock::new();
+    DISCOVERED
+        .get_or_init(discover_signature)
+        .clone()
+        .map_err(|e| e.into())
+}
+
+/// Instance that replaced the one resolved at startup
+static FOLLOWED: RwLock<Option<String>> = RwLock::new(None);
+
+/// Talk to instance `signature` from now on, as when the compositor was
+/// restarted and the event listener reconnected to the new one
+pub fn follow_instance(signature: &str) {
+    *FOLLOWED.write().unwrap() = Some(signature.to_string());
+}
+
+/// Directory that holds one subdirectory per running Hyprland instance
+pub fn hypr_runtime_dir() -> PathBuf {
+    match env::var("XDG_RUNTIME_DIR") {
+        Ok(dir) if !dir.is_empty() => PathBuf::from(dir).join("hypr"),
+        _ => {
+            let uid = fs::metadata("/proc/self").map(|m| m.uid()).unwrap_or(0);
+            PathBuf::from(format!("/run/user/{}/hypr", uid))
+        }
+    }
+}
+
+/// Resolve the signature again, bypassing the cache: the one from
+/// `HYPRLAND_INSTANCE_SIGNATURE` while that instance is alive, else the only
+/// running instance. Used after the compositor has restarted.
+pub fn rediscover_signature() -> Result<String, Box<dyn Error>> {
+    if let Ok(sig) = env::var(SIGNATURE_VAR) {
+        let socket = instance_socket_dir(&sig).join(".socket.sock");
+        if !sig.is_empty() && UnixStream::connect(socket).is_ok() {
+            return Ok(sig);
+        }
+    }
+    Ok(discover_signature()?)
+}
+
+/// Hyprland releases before v0.40 kept their sockets under `/tmp/hypr`
+const LEGACY_SOCKET_ROOT: &str = "/tmp/hypr";
+
+/// Directory holding the sockets of the given instance, honoring the
+/// pre-v0.40 location still used by older distro packages.
+pub fn instance_socket_dir(signature: &str) -> PathBuf {
+    let current = hypr_runtime_dir().join(signature);
+    let legacy = Path::new(LEGACY_SOCKET_ROOT).join(signature);
+    if !current.exists() && legacy.exists() {
+        legacy
+    } else {
+        current
+    }
+}
+
+fn discover_signature() -> Result<String, String> {
+    let dir = hypr_runtime_dir();
+    let mut live = live_instances(&dir);
+    live.extend(live_instances(Path::new(LEGACY_SOCKET_ROOT)));
+
+    match live.len() {
+        1 => Ok(live.into_iter().next().unwrap()),
+        0 => Err(format!(
+            "{} is not set and no running Hyprland instance was found in {}",
+            SIGNATURE_VAR,
+            dir.display()
+        )),
+        _ => Err(format!(
+            "{} is not set and multiple Hyprland instances are running ({}); \
+             export {} to pick one",
+            SIGNATURE_VAR,
+            live.join(", "),
+            SIGNATURE_VAR
+        )),
+    }
+}
+
+/// List instance signatures under `dir` whose command socket accepts connections.
+/// Leftover directories from crashed compositors are skipped.
+pub fn live_instances(dir: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+
+    let mut live: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| UnixStream::connect(entry.path().join(".socket.sock")).is_ok())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect();
+    live.sort();
+    live
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    #[test]
+    fn test_live_instances_skips_dead_directories() {
+        let dir = env::temp_dir().join(format!("hyprdrover-instances-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("dead_instance")).unwrap();
+        fs::create_dir_all(dir.join("live_instance")).unwrap();
+        let _listener = UnixListener::bind(dir.join("live_instance/.socket.sock")).unwrap();
+
+        assert_eq!(live_instances(&dir), vec!["live_instance".to_string()]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_live_instances_missing_dir() {
+        assert!(live_instances(Path::new("/nonexistent/hypr")).is_empty());
+    }
+}
@@ -1,7 +1,39 @@
+#[cfg(feature = "tokio")]
+pub mod async_ipc;
+pub mod batch;
+pub mod bus;
+pub mod dispatch;
+pub mod events;
 pub mod hypr_commands;
+pub mod input;
+pub mod instance;
+pub mod models;
+pub mod queue;
+pub mod socket;
+pub mod tracker;
+pub mod transaction;
+pub mod version;
 
 // Re-export the actual functions and structs we created
+#[cfg(feature = "tokio")]
+pub use async_ipc::{AsyncEventStream, AsyncHyprCommandClient, AsyncIpcEventListener};
+pub use batch::CommandBatch;
+pub use dispatch::{Address, Dispatch, WorkspaceRef};
 pub use hypr_commands::{
-    capture_state, dispatch, get_active_workspace, move_window_pixel, move_window_to_workspace,
-    resize_window_pixel, HyprClient, SessionSnapshot,
+    batch, capture_state, create_output, dispatch, dispatch_exec, dispatch_typed, find_client,
+    focus_window, get_active_workspace, get_binds, get_clients, get_config_errors, get_cursor_pos,
+    get_layers, get_monitors, get_plugins, get_rolling_log, get_workspace_rules, get_workspaces,
+    hyprctl, keyword, keyword_batch, move_cursor, move_window_pixel, move_window_to_workspace,
+    notify, remove_output, resize_window_pixel, send_many, set_cursor, set_floating, set_pinned,
+    set_window_prop, NotifyIcon, OutputBackend,
 };
+pub use input::InputSettings;
+pub use instance::instance_signature;
+pub use models::{
+    CursorPosition, FullscreenMode, HyprBind, HyprClient, HyprDevice, HyprDevices, HyprKeyboard,
+    HyprLayer, HyprMonitor, HyprPlugin, HyprWorkspace, HyprWorkspaceRule, RelativeGeometry,
+    SessionSnapshot,
+};
+pub use socket::{set_socket_overrides, HyprCommandClient, SocketOverrides};
+pub use transaction::KeywordTransaction;
+pub use version::capabilities;
@@ -0,0 +1,892 @@
+//! Data models matching `hyprctl -j` output
+
+use super::dispatch::WorkspaceRef;
+use super::input::InputSettings;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyprWorkspaceRef {
+    pub id: i32,
+    pub name: String,
+}
+
+impl HyprWorkspaceRef {
+    /// How dispatchers should name this workspace. Special workspaces have
+    /// negative ids, which dispatchers would read as relative to the current
+    /// workspace, so they go by name.
+    pub fn target(&self) -> WorkspaceRef {
+        if self.id >= 0 {
+            return WorkspaceRef::Id(self.id);
+        }
+        match self.name.strip_prefix("special:") {
+            Some(name) if !name.is_empty() => WorkspaceRef::Special(Some(name.to_string())),
+            _ => WorkspaceRef::Special(None),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HyprActiveWorkspace {
+    pub id: i32,
+    pub name: String,
+    /// Name of the monitor showing it
+    #[serde(default)]
+    pub monitor: String,
+}
+
+/// Pointer position in the global layout, as reported by `cursorpos`
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct CursorPosition {
+    pub x: i32,
+    pub y: i32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprClient {
+    pub address: String,
+    pub at: [i32; 2],
+    pub size: [i32; 2],
+    pub workspace: HyprWorkspaceRef,
+    pub class: String,
+    pub title: String,
+    pub initial_class: String,
+    pub initial_title: String,
+    pub floating: bool,
+    pub pinned: bool,
+    pub monitor: i64,
+    pub fullscreen: i32, // 0: none, 1: maximized, 2: fullscreen
+    /// Fullscreen state the app itself was told about, which differs from
+    /// `fullscreen` for fake fullscreen
+    #[serde(default)]
+    pub fullscreen_client: i32,
+    pub xwayland: bool,
+    pub pid: i32,
+    /// Place in the focus order, 0 for the focused window; absent in older
+    /// snapshots
+    #[serde(
+        default,
+        rename = "focusHistoryID",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub focus_history_id: Option<i32>,
+    #[serde(default)]
+    pub exec_path: Option<String>,
+    /// Address of the terminal this window swallowed (hid) when it was
+    /// started from it
+    #[serde(
+        default,
+        deserialize_with = "non_null_address",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub swallowing: Option<String>,
+    /// Arguments of the process, recorded for windows that swallowed a
+    /// terminal so the pair can be relaunched together
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command_line: Option<Vec<String>>,
+    /// Working directory of the window's shell, or of the app itself when it
+    /// started no child, for `{cwd}` in launch commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<String>,
+    /// Opacity and dim properties changed at runtime with `setprop`, by name
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub props: BTreeMap<String, String>,
+    /// Launch command set with `hyprdrover edit`, used before rules and aliases
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub launch_command: Option<String>,
+    /// Floating geometry relative to the window's monitor, recorded alongside
+    /// the pixels so it can be scaled to another resolution
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub relative: Option<RelativeGeometry>,
+    /// Stable ID that follows the window across restores, unlike its address
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+}
+
+/// Position and size as fractions of a monitor's logical area, from its
+/// top-left corner
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct RelativeGeometry {
+    pub at: [f64; 2],
+    pub size: [f64; 2],
+}
+
+impl RelativeGeometry {
+    /// Where `client` sits on `monitor`
+    pub fn of(client: &HyprClient, monitor: &HyprMonitor) -> Option<Self> {
+        let [width, height] = monitor.logical_size();
+        if width <= 0.0 || height <= 0.0 {
+            return None;
+        }
+        Some(Self {
+            at: [
+                (client.at[0] - monitor.x) as f64 / width,
+                (client.at[1] - monitor.y) as f64 / height,
+            ],
+            size: [
+                client.size[0] as f64 / width,
+                client.size[1] as f64 / height,
+            ],
+        })
+    }
+
+    /// Position and size in the global layout when placed on `monitor`
+    pub fn on(&self, monitor: &HyprMonitor) -> ([i32; 2], [i32; 2]) {
+        let [width, height] = monitor.logical_size();
+        let at = [
+            monitor.x + (self.at[0] * width).round() as i32,
+            monitor.y + (self.at[1] * height).round() as i32,
+        ];
+        let size = [
+            ((self.size[0] * width).round() as i32).max(1),
+            ((self.size[1] * height).round() as i32).max(1),
+        ];
+        (at, size)
+    }
+}
+
+/// How a window fills its space, telling apart the compositor's and the
+/// app's view of fullscreen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FullscreenMode {
+    None,
+    Maximized,
+    Fullscreen,
+    /// The app draws fullscreen (e.g. a browser video) inside its tile
+    FakeFullscreen,
+    /// Fake fullscreen in a maximized window
+    MaximizedFakeFullscreen,
+}
+
+impl FullscreenMode {
+    /// `(internal, client)` arguments of the `fullscreenstate` dispatcher
+    pub fn state(&self) -> (i32, i32) {
+        match self {
+            Self::None => (0, 0),
+            Self::Maximized => (1, 1),
+            Self::Fullscreen => (2, 2),
+            Self::FakeFullscreen => (0, 2),
+            Self::MaximizedFakeFullscreen => (1, 2),
+        }
+    }
+
+    pub fn is_maximized(&self) -> bool {
+        matches!(self, Self::Maximized | Self::MaximizedFakeFullscreen)
+    }
+
+    pub fn is_fake(&self) -> bool {
+        matches!(self, Self::FakeFullscreen | Self::MaximizedFakeFullscreen)
+    }
+}
+
+impl HyprClient {
+    /// Whether the window was kept from taking focus (`nofocus`)
+    pub fn no_focus(&self) -> bool {
+        self.props
+            .get("nofocus")
+            .is_some_and(|v| v == "1" || v == "true")
+    }
+
+    pub fn fullscreen_mode(&self) -> FullscreenMode {
+        let maximized = self.fullscreen & 1 != 0;
+        if self.fullscreen & 2 != 0 {
+            FullscreenMode::Fullscreen
+        } else if self.fullscreen_client & 2 != 0 && maximized {
+            FullscreenMode::MaximizedFakeFullscreen
+        } else if self.fullscreen_client & 2 != 0 {
+            FullscreenMode::FakeFullscreen
+        } else if maximized {
+            FullscreenMode::Maximized
+        } else {
+            FullscreenMode::None
+        }
+    }
+}
+
+/// Windows for tests: a tiled, untitled 100x100 window on workspace 1,
+/// adjusted with the `with_*` methods or struct update syntax
+#[cfg(test)]
+impl HyprClient {
+    pub(crate) fn fixture(address: &str, class: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            at: [0, 0],
+            size: [100, 100],
+            workspace: HyprWorkspaceRef {
+                id: 1,
+                name: "1".to_string(),
+            },
+            class: class.to_string(),
+            title: String::new(),
+            initial_class: class.to_string(),
+            initial_title: String::new(),
+            floating: false,
+            pinned: false,
+            monitor: 0,
+            fullscreen: 0,
+            fullscreen_client: 0,
+            xwayland: false,
+            pid: 1,
+            focus_history_id: None,
+            exec_path: None,
+            swallowing: None,
+            command_line: None,
+            cwd: None,
+            props: BTreeMap::new(),
+            launch_command: None,
+            relative: None,
+            window_id: None,
+        }
+    }
+
+    /// Set the title, and the initial title with it
+    pub(crate) fn with_title(mut self, title: &str) -> Self {
+        self.title = title.to_string();
+        self.initial_title = title.to_string();
+        self
+    }
+
+    pub(crate) fn with_workspace(mut self, id: i32) -> Self {
+        self.workspace = HyprWorkspaceRef {
+            id,
+            name: id.to_string(),
+        };
+        self
+    }
+
+    pub(crate) fn with_floating(mut self, floating: bool) -> Self {
+        self.floating = floating;
+        self
+    }
+
+    pub(crate) fn with_pid(mut self, pid: i32) -> Self {
+        self.pid = pid;
+        self
+    }
+}
+
+/// Hyprland reports "no window" as the address `0x0`
+fn non_null_address<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<String>, D::Error> {
+    let address = Option::<String>::deserialize(deserializer)?;
+    Ok(address.filter(|a| a.trim_start_matches("0x").chars().any(|c| c != '0')))
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprWorkspace {
+    pub id: i32,
+    pub name: String,
+    pub monitor: String,
+    pub windows: i32,
+    pub hasfullscreen: bool,
+    pub lastwindow: String,
+    pub lastwindowtitle: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprMonitor {
+    pub id: i64,
+    pub name: String,
+    /// Make, model and serial; identifies the physical screen across ports
+    #[serde(default)]
+    pub description: String,
+    pub width: i32,
+    pub height: i32,
+    pub refresh_rate: f32,
+    pub x: i32,
+    pub y: i32,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    /// wl_output transform: 0 normal, 1-3 rotated 90/180/270, 4-7 flipped
+    #[serde(default)]
+    pub transform: i32,
+    /// Whether variable refresh rate is active
+    #[serde(default)]
+    pub vrr: bool,
+    pub active_workspace: HyprWorkspaceRef,
+    /// Special workspace (scratchpad) shown on top of this monitor, if any
+    #[serde(
+        default,
+        deserialize_with = "shown_special",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub special_workspace: Option<HyprWorkspaceRef>,
+}
+
+/// Hyprland reports "no special workspace shown" as id 0
+fn shown_special<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<HyprWorkspaceRef>, D::Error> {
+    let workspace = Option::<HyprWorkspaceRef>::deserialize(deserializer)?;
+    Ok(workspace.filter(|w| w.id != 0))
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+impl HyprMonitor {
+    /// The monitor in `hyprctl keyword monitor` syntax,
+    /// e.g. `DP-2,2560x1440@143.97,2560x0,1.25,transform,1,vrr,1`
+    pub fn to_keyword(&self) -> String {
+        let mut keyword = format!(
+            "{},{}x{}@{:.2},{}x{},{}",
+            self.name, self.width, self.height, self.refresh_rate, self.x, self.y, self.scale
+        );
+        if self.transform != 0 {
+            keyword.push_str(&format!(",transform,{}", self.transform));
+        }
+        keyword.push_str(if self.vrr { ",vrr,1" } else { ",vrr,0" });
+        keyword
+    }
+
+    /// Width and height in layout coordinates: the mode divided by the
+    /// scale, swapped when rotated by 90 or 270 degrees
+    pub fn logical_size(&self) -> [f64; 2] {
+        let scale = if self.scale > 0.0 { self.scale } else { 1.0 } as f64;
+        let (width, height) = (self.width as f64 / scale, self.height as f64 / scale);
+        if self.transform % 2 == 1 {
+            [height, width]
+        } else {
+            [width, height]
+        }
+    }
+
+    /// Whether this is a virtual output (e.g. for VNC or streaming) rather
+    /// than a physical screen
+    pub fn is_headless(&self) -> bool {
+        self.name.starts_with("HEADLESS-") || self.description.to_lowercase().contains("headless")
+    }
+}
+
+/// A `workspace = ...` rule as reported by `hyprctl -j workspacerules`.
+/// Hyprland only includes the settings a rule actually sets.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HyprWorkspaceRule {
+    pub workspace_string: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub monitor: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub persistent: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gaps_in: Option<Vec<i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub gaps_out: Option<Vec<i32>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border_size: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub border: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rounding: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decorate: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_created_empty_cmd: Option<String>,
+}
+
+impl HyprWorkspaceRule {
+    /// The rule in `hyprctl keyword workspace` syntax, e.g. `3, monitor:DP-1, persistent:true`
+    pub fn to_keyword(&self) -> String {
+        let join = |gaps: &[i32]| {
+            gaps.iter()
+                .map(|g| g.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut parts = vec![self.workspace_string.clone()];
+        if let Some(monitor) = &self.monitor {
+            parts.push(format!("monitor:{}", monitor));
+        }
+        if let Some(default) = self.default {
+            parts.push(format!("default:{}", default));
+        }
+        if let Some(persistent) = self.persistent {
+            parts.push(format!("persistent:{}", persistent));
+        }
+        if let Some(gaps) = &self.gaps_in {
+            parts.push(format!("gapsin:{}", join(gaps)));
+        }
+        if let Some(gaps) = &self.gaps_out {
+            parts.push(format!("gapsout:{}", join(gaps)));
+        }
+        if let Some(size) = self.border_size {
+            parts.push(format!("bordersize:{}", size));
+        }
+        if let Some(border) = self.border {
+            parts.push(format!("border:{}", border));
+        }
+        if let Some(rounding) = self.rounding {
+            parts.push(format!("rounding:{}", rounding));
+        }
+        if let Some(decorate) = self.decorate {
+            parts.push(format!("decorate:{}", decorate));
+        }
+        if let Some(shadow) = self.shadow {
+            parts.push(format!("shadow:{}", shadow));
+        }
+        if let Some(name) = &self.default_name {
+            parts.push(format!("defaultName:{}", name));
+        }
+        if let Some(command) = &self.on_created_empty_cmd {
+            parts.push(format!("on-created-empty:{}", command));
+        }
+        parts.join(", ")
+    }
+}
+
+/// A layer-shell surface (bar, wallpaper, launcher...) from `hyprctl -j layers`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HyprLayer {
+    pub monitor: String,
+    /// 0 background, 1 bottom, 2 top, 3 overlay
+    pub level: u8,
+    pub namespace: String,
+    pub address: String,
+    pub x: i32,
+    pub y: i32,
+    pub w: i32,
+    pub h: i32,
+    pub pid: i32,
+}
+
+/// A loaded compositor plugin from `hyprctl -j plugin list`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HyprPlugin {
+    pub name: String,
+    #[serde(default)]
+    pub author: String,
+    #[serde(default)]
+    pub version: String,
+}
+
+/// `hyprctl -j layers` nests surfaces as monitor -> levels -> level -> surfaces
+#[derive(Deserialize)]
+pub(super) struct RawMonitorLayers {
+    levels: BTreeMap<String, Vec<RawLayer>>,
+}
+
+#[derive(Deserialize)]
+struct RawLayer {
+    address: String,
+    x: i32,
+    y: i32,
+    w: i32,
+    h: i32,
+    namespace: String,
+    pid: i32,
+}
+
+pub(super) fn flatten_layers(raw: BTreeMap<String, RawMonitorLayers>) -> Vec<HyprLayer> {
+    let mut layers = vec![];
+    for (monitor, monitor_layers) in raw {
+        for (level, surfaces) in monitor_layers.levels {
+            let level = level.parse().unwrap_or(0);
+            layers.extend(surfaces.into_iter().map(|l| HyprLayer {
+                monitor: monitor.clone(),
+                level,
+                namespace: l.namespace,
+                address: l.address,
+                x: l.x,
+                y: l.y,
+                w: l.w,
+                h: l.h,
+                pid: l.pid,
+            }));
+        }
+    }
+    layers
+}
+
+/// A key or mouse binding from `hyprctl -j binds`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HyprBind {
+    #[serde(default)]
+    pub locked: bool,
+    #[serde(default)]
+    pub mouse: bool,
+    #[serde(default)]
+    pub release: bool,
+    #[serde(default)]
+    pub repeat: bool,
+    #[serde(default)]
+    pub non_consuming: bool,
+    /// Modifier bits: 1 Shift, 4 Ctrl, 8 Alt, 64 Super
+    pub modmask: u32,
+    /// Submap the binding belongs to; empty for the global one
+    #[serde(default)]
+    pub submap: String,
+    pub key: String,
+    #[serde(default)]
+    pub keycode: i32,
+    #[serde(default)]
+    pub description: String,
+    pub dispatcher: String,
+    #[serde(default)]
+    pub arg: String,
+}
+
+/// An input device from `hyprctl -j devices`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HyprDevice {
+    pub address: String,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct HyprKeyboard {
+    pub address: String,
+    pub name: String,
+    #[serde(default)]
+    pub layout: String,
+    #[serde(default)]
+    pub variant: String,
+    #[serde(default)]
+    pub options: String,
+    /// Name of the layout in use, e.g. `English (US)`
+    #[serde(default)]
+    pub active_keymap: String,
+    /// Whether this keyboard drives the layout shown to the user
+    #[serde(default)]
+    pub main: bool,
+}
+
+/// `hyprctl -j devices`, grouped by kind
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct HyprDevices {
+    #[serde(default)]
+    pub mice: Vec<HyprDevice>,
+    #[serde(default)]
+    pub keyboards: Vec<HyprKeyboard>,
+    #[serde(default)]
+    pub tablets: Vec<HyprDevice>,
+    #[serde(default)]
+    pub touch: Vec<HyprDevice>,
+    #[serde(default)]
+    pub switches: Vec<HyprDevice>,
+}
+
+/// Format version written into new snapshots; see `state::format` for
+/// reading older ones
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SessionSnapshot {
+    /// Format version; files without one are version 1
+    #[serde(default = "legacy_version")]
+    pub version: u32,
+    pub clients: Vec<HyprClient>,
+    pub workspaces: Vec<HyprWorkspace>,
+    pub monitors: Vec<HyprMonitor>,
+    /// Missing from snapshots taken before workspace rules were captured
+    #[serde(default)]
+    pub workspace_rules: Vec<HyprWorkspaceRule>,
+    /// Layer-shell surfaces present at save time, used to verify restores
+    #[serde(default)]
+    pub layers: Vec<HyprLayer>,
+    /// Keyboard and touchpad settings; only re-applied on request
+    #[serde(default)]
+    pub input: Option<InputSettings>,
+    /// Compositor plugins loaded at save time, used to warn when a layout
+    /// depends on one that is missing
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub plugins: Vec<HyprPlugin>,
+    /// Pointer position at save time; only restored on request
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cursor: Option<CursorPosition>,
+    /// Free-text annotation given with `--save NAME -m NOTE`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+fn legacy_version() -> u32 {
+    1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(x: i32, width: i32, height: i32, scale: f32) -> HyprMonitor {
+        serde_json::from_value(serde_json::json!({
+            "id": 0, "name": "DP-1", "width": width, "height": height,
+            "refreshRate": 60.0, "x": x, "y": 0, "scale": scale,
+            "activeWorkspace": { "id": 1, "name": "1" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_relative_geometry_scales_across_resolutions() {
+        let client = HyprClient {
+            at: [640, 360],
+            size: [1280, 720],
+            ..HyprClient::fixture("0x1", "mpv").with_floating(true)
+        };
+        // 4K at 1.5 is 2560x1440 in layout coordinates
+        let uhd = monitor(0, 3840, 2160, 1.5);
+        let relative = RelativeGeometry::of(&client, &uhd).unwrap();
+        assert_eq!(relative.at, [0.25, 0.25]);
+        assert_eq!(relative.size, [0.5, 0.5]);
+
+        assert_eq!(relative.on(&uhd), ([640, 360], [1280, 720]));
+        let laptop = monitor(2560, 1920, 1080, 1.0);
+        assert_eq!(relative.on(&laptop), ([3040, 270], [960, 540]));
+    }
+
+    #[test]
+    fn test_deserialize_client() {
+        let json = r#"{
+            "address": "0x1234",
+            "at": [10, 10],
+            "size": [800, 600],
+            "workspace": { "id": 1, "name": "1" },
+            "class": "kitty",
+            "title": "terminal",
+            "initialClass": "kitty",
+            "initialTitle": "terminal",
+            "floating": false,
+            "pinned": false,
+            "monitor": 0,
+            "fullscreen": 0,
+            "xwayland": false,
+            "pid": 1234
+        }"#;
+
+        let client: HyprClient = serde_json::from_str(json).expect("Failed to deserialize client");
+
+        assert_eq!(client.address, "0x1234");
+        assert_eq!(client.class, "kitty");
+        assert_eq!(client.workspace.id, 1);
+        assert_eq!(client.size, [800, 600]);
+    }
+
+    #[test]
+    fn test_focus_fields() {
+        let mut json = serde_json::to_value(HyprClient::fixture("0x1", "osk")).unwrap();
+        json["focusHistoryID"] = 0.into();
+        json["props"] = serde_json::json!({ "nofocus": "true" });
+        let client: HyprClient = serde_json::from_value(json).unwrap();
+        assert_eq!(client.focus_history_id, Some(0));
+        assert!(client.no_focus());
+
+        let json = serde_json::to_value(&client).unwrap();
+        assert_eq!(json["focusHistoryID"], 0);
+    }
+
+    #[test]
+    fn test_deserialize_workspace() {
+        let json = r#"{
+            "id": 1,
+            "name": "1",
+            "monitor": "eDP-1",
+            "windows": 5,
+            "hasfullscreen": false,
+            "lastwindow": "0x1234",
+            "lastwindowtitle": "terminal"
+        }"#;
+
+        let ws: HyprWorkspace =
+            serde_json::from_str(json).expect("Failed to deserialize workspace");
+
+        assert_eq!(ws.id, 1);
+        assert_eq!(ws.monitor, "eDP-1");
+        assert_eq!(ws.windows, 5);
+    }
+
+    #[test]
+    fn test_deserialize_monitor() {
+        let json = r#"{
+            "id": 0,
+            "name": "eDP-1",
+            "width": 1920,
+            "height": 1080,
+            "refreshRate": 60.0,
+            "x": 0,
+            "y": 0,
+            "activeWorkspace": { "id": 1, "name": "1" }
+        }"#;
+
+        let mon: HyprMonitor = serde_json::from_str(json).expect("Failed to deserialize monitor");
+
+        assert_eq!(mon.id, 0);
+        assert_eq!(mon.width, 1920);
+        assert_eq!(mon.active_workspace.id, 1);
+    }
+
+    #[test]
+    fn test_workspace_rule_round_trips_to_keyword() {
+        let json = r#"{
+            "workspaceString": "3",
+            "monitor": "DP-1",
+            "default": true,
+            "persistent": true,
+            "gapsIn": [5, 5, 5, 5],
+            "defaultName": "code"
+        }"#;
+
+        let rule: HyprWorkspaceRule =
+            serde_json::from_str(json).expect("Failed to deserialize workspace rule");
+
+        assert_eq!(
+            rule.to_keyword(),
+            "3, monitor:DP-1, default:true, persistent:true, gapsin:5 5 5 5, defaultName:code"
+        );
+    }
+
+    #[test]
+    fn test_deserialize_layers() {
+        let json = r#"{
+            "DP-1": {
+                "levels": {
+                    "0": [{ "address": "0xa", "x": 0, "y": 0, "w": 1920, "h": 1080,
+                            "namespace": "hyprpaper", "pid": 10 }],
+                    "1": [],
+                    "2": [{ "address": "0xb", "x": 0, "y": 0, "w": 1920, "h": 30,
+                            "namespace": "waybar", "pid": 11 }],
+                    "3": []
+                }
+            }
+        }"#;
+
+        let raw: BTreeMap<String, RawMonitorLayers> =
+            serde_json::from_str(json).expect("Failed to deserialize layers");
+        let layers = flatten_layers(raw);
+
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[1].namespace, "waybar");
+        assert_eq!(layers[1].level, 2);
+        assert_eq!(layers[1].monitor, "DP-1");
+    }
+
+    #[test]
+    fn test_monitor_keyword() {
+        let json = r#"{
+            "id": 1,
+            "name": "DP-2",
+            "description": "Dell Inc. DELL U2720Q 1234",
+            "width": 2560,
+            "height": 1440,
+            "refreshRate": 143.97,
+            "x": 2560,
+            "y": 0,
+            "scale": 1.25,
+            "transform": 1,
+            "vrr": true,
+            "activeWorkspace": { "id": 2, "name": "2" }
+        }"#;
+
+        let mon: HyprMonitor = serde_json::from_str(json).expect("Failed to deserialize monitor");
+
+        assert_eq!(
+            mon.to_keyword(),
+            "DP-2,2560x1440@143.97,2560x0,1.25,transform,1,vrr,1"
+        );
+    }
+
+    #[test]
+    fn test_snapshot_cursor_is_optional() {
+        let json = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        let snapshot: SessionSnapshot = serde_json::from_str(json).unwrap();
+        assert_eq!(snapshot.cursor, None);
+
+        let position: CursorPosition = serde_json::from_str(r#"{"x": 1280, "y": -40}"#).unwrap();
+        assert_eq!(position, CursorPosition { x: 1280, y: -40 });
+    }
+
+    #[test]
+    fn test_special_workspaces_are_targeted_by_name() {
+        use crate::ipc::Dispatch;
+
+        let saved = HyprClient {
+            workspace: HyprWorkspaceRef {
+                id: -98,
+                name: "special:music".to_string(),
+            },
+            ..HyprClient::fixture("0x1", "spotify")
+        };
+        let workspace = saved.workspace.target();
+        assert_eq!(
+            Dispatch::MoveToWorkspaceSilent {
+                workspace: workspace.clone(),
+                window: "0x1".into(),
+            }
+            .to_command_string()
+            .unwrap(),
+            "movetoworkspacesilent special:music,address:0x1"
+        );
+        assert_eq!(
+            format!("workspace {} silent", workspace),
+            "workspace special:music silent"
+        );
+        assert_eq!(
+            HyprClient::fixture("0x1", "kitty")
+                .with_workspace(3)
+                .workspace
+                .target(),
+            WorkspaceRef::Id(3)
+        );
+    }
+
+    #[test]
+    fn test_fullscreen_modes() {
+        let client = |fullscreen: i32, fullscreen_client: i32| HyprClient {
+            fullscreen,
+            fullscreen_client,
+            ..HyprClient::fixture("0x1", "firefox")
+        };
+
+        assert_eq!(client(0, 0).fullscreen_mode(), FullscreenMode::None);
+        assert_eq!(client(1, 1).fullscreen_mode(), FullscreenMode::Maximized);
+        assert_eq!(client(2, 2).fullscreen_mode(), FullscreenMode::Fullscreen);
+        assert_eq!(
+            client(0, 2).fullscreen_mode(),
+            FullscreenMode::FakeFullscreen
+        );
+        assert_eq!(FullscreenMode::FakeFullscreen.state(), (0, 2));
+        // Maximized and fake fullscreen at once keeps both
+        let both = client(1, 2).fullscreen_mode();
+        assert_eq!(both, FullscreenMode::MaximizedFakeFullscreen);
+        assert_eq!(both.state(), (1, 2));
+    }
+
+    #[test]
+    fn test_deserialize_binds_and_devices() {
+        let json = r#"[{
+            "locked": false, "mouse": false, "release": false, "repeat": false,
+            "longPress": false, "non_consuming": false, "has_description": false,
+            "modmask": 64, "submap": "", "key": "Q", "keycode": 0,
+            "catch_all": false, "description": "", "dispatcher": "exec", "arg": "kitty"
+        }]"#;
+        let binds: Vec<HyprBind> = serde_json::from_str(json).unwrap();
+        assert_eq!(binds[0].modmask, 64);
+        assert_eq!(
+            (binds[0].dispatcher.as_str(), binds[0].arg.as_str()),
+            ("exec", "kitty")
+        );
+
+        let json = r#"{
+            "mice": [{ "address": "0x1", "name": "logitech-mouse", "defaultSpeed": 0.0 }],
+            "keyboards": [{ "address": "0x2", "name": "at-keyboard", "rules": "", "model": "",
+                            "layout": "us,de", "variant": "", "options": "",
+                            "active_keymap": "English (US)", "capsLock": false,
+                            "numLock": true, "main": true }],
+            "tablets": [], "touch": [], "switches": []
+        }"#;
+        let devices: HyprDevices = serde_json::from_str(json).unwrap();
+        assert_eq!(devices.mice[0].name, "logitech-mouse");
+        assert_eq!(devices.keyboards[0].layout, "us,de");
+        assert!(devices.keyboards[0].main);
+    }
+}
@@ -0,0 +1,307 @@
+//! Bounded hand-off of events from a listener thread to a consumer.
+//!
+//! A consumer that falls behind (a slow hook, a blocked UI) must not make the
+//! buffer grow without limit, so the queue holds at most `capacity` events
+//! and applies an [`OverflowPolicy`] once it is full. Dropped and coalesced
+//! events are counted and logged.
+
+use super::events::HyprEvent;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+/// What to do with a new event when the buffer is full
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OverflowPolicy {
+    /// Wait for the consumer; nothing is lost, but the listener stalls
+    Block,
+    /// Discard the oldest queued event
+    #[default]
+    DropOldest,
+    /// Replace a queued event the new one supersedes (a later move or title
+    /// of the same window, a later focus change), else discard the oldest
+    Coalesce,
+}
+
+/// Size and overflow policy of an event buffer
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EventBufferConfig {
+    pub capacity: usize,
+    pub overflow: OverflowPolicy,
+}
+
+impl Default for EventBufferConfig {
+    fn default() -> Self {
+        Self {
+            capacity: 1024,
+            overflow: OverflowPolicy::default(),
+        }
+    }
+}
+
+/// Counters of events that never reached the consumer as sent
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QueueStats {
+    pub dropped: u64,
+    pub coalesced: u64,
+}
+
+struct State {
+    events: VecDeque<HyprEvent>,
+    stats: QueueStats,
+    closed: bool,
+}
+
+struct Shared {
+    state: Mutex<State>,
+    not_empty: Condvar,
+    not_full: Condvar,
+    capacity: usize,
+    policy: OverflowPolicy,
+}
+
+pub struct EventSender {
+    shared: Arc<Shared>,
+}
+
+pub struct EventReceiver {
+    shared: Arc<Shared>,
+}
+
+/// A queue holding at most `capacity` events (at least one)
+pub fn bounded(settings: &EventBufferConfig) -> (EventSender, EventReceiver) {
+    let shared = Arc::new(Shared {
+        state: Mutex::new(State {
+            events: VecDeque::new(),
+            stats: QueueStats::default(),
+            closed: false,
+        }),
+        not_empty: Condvar::new(),
+        not_full: Condvar::new(),
+        capacity: settings.capacity.max(1),
+        policy: settings.overflow,
+    });
+    (
+        EventSender {
+            shared: shared.clone(),
+        },
+        EventReceiver { shared },
+    )
+}
+
+/// Key of the state an event reports; a newer event with the same key makes
+/// the older one redundant
+fn coalesce_key(event: &HyprEvent) -> Option<(&str, &str)> {
+    match event {
+        HyprEvent::WindowMoved { address, .. }
+        | HyprEvent::WindowTitleChanged { address, .. }
+        | HyprEvent::FloatingChanged { address, .. }
+        | HyprEvent::Pinned { address, .. } => Some((event.name(), address)),
+        HyprEvent::ActiveLayout { keyboard, .. } => Some((event.name(), keyboard)),
+        HyprEvent::WorkspaceChanged { .. }
+        | HyprEvent::ActiveWindow { .. }
+        | HyprEvent::ActiveWindowAddress { .. }
+        | HyprEvent::FocusedMonitor { .. }
+        | HyprEvent::Fullscreen(_)
+        | HyprEvent::Submap(_) => Some((event.name(), "")),
+        _ => None,
+    }
+}
+
+impl EventSender {
+    /// Queue `event`, applying the overflow policy if the buffer is full.
+    /// Returns `false` once the receiver is gone.
+    pub fn send(&self, event: HyprEvent) -> bool {
+        let shared = &self.shared;
+        let mut state = shared.state.lock().unwrap();
+
+        if state.events.len() >= shared.capacity && !state.closed {
+            match shared.policy {
+                OverflowPolicy::Block => {
+                    state = shared
+                        .not_full
+                        .wait_while(state, |s| s.events.len() >= shared.capacity && !s.closed)
+                        .unwrap();
+                }
+                OverflowPolicy::Coalesce => {
+                    let key = coalesce_key(&event);
+                    let superseded = key.and_then(|key| {
+                        state
+                            .events
+                            .iter()
+                            .rposition(|queued| coalesce_key(queued) == Some(key))
+                    });
+                    if let Some(index) = superseded {
+                        state.events[index] = event;
+                        state.stats.coalesced += 1;
+                        shared.not_empty.notify_one();
+                        return true;
+                    }
+                    drop_oldest(&mut state, shared.capacity);
+                }
+                OverflowPolicy::DropOldest => drop_oldest(&mut state, shared.capacity),
+            }
+        }
+        if state.closed {
+            return false;
+        }
+
+        state.events.push_back(event);
+        shared.not_empty.notify_one();
+        true
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.shared.state.lock().unwrap().stats
+    }
+}
+
+fn drop_oldest(state: &mut State, capacity: usize) {
+    state.events.pop_front();
+    state.stats.dropped += 1;
+    // Log the 1st, 10th, 100th, ... drop so a long storm doesn't flood the log
+    let mut magnitude = state.stats.dropped;
+    while magnitude.is_multiple_of(10) {
+        magnitude /= 10;
+    }
+    if magnitude == 1 {
+        eprintln!(
+            "Warning: event buffer full ({} events); dropped {} so far",
+            capacity, state.stats.dropped
+        );
+    }
+}
+
+impl Drop for EventSender {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.not_empty.notify_all();
+    }
+}
+
+impl EventReceiver {
+    /// Wait for the next event; `None` once the sender is gone and the
+    /// buffer is drained
+    pub fn recv(&self) -> Option<HyprEvent> {
+        let shared = &self.shared;
+        let state = shared.state.lock().unwrap();
+        let mut state = shared
+            .not_empty
+            .wait_while(state, |s| s.events.is_empty() && !s.closed)
+            .unwrap();
+        let event = state.events.pop_front();
+        shared.not_full.notify_one();
+        event
+    }
+
+    /// Like [`recv`](Self::recv), giving up after `timeout`
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<HyprEvent> {
+        let shared = &self.shared;
+        let state = shared.state.lock().unwrap();
+        let (mut state, _) = shared
+            .not_empty
+            .wait_timeout_while(state, timeout, |s| s.events.is_empty() && !s.closed)
+            .unwrap();
+        let event = state.events.pop_front();
+        shared.not_full.notify_one();
+        event
+    }
+
+    /// Take everything queued right now without waiting
+    pub fn drain(&self) -> Vec<HyprEvent> {
+        let mut state = self.shared.state.lock().unwrap();
+        let events = state.events.drain(..).collect();
+        self.shared.not_full.notify_all();
+        events
+    }
+
+    /// Whether the sender is gone, e.g. because the event socket closed
+    pub fn is_closed(&self) -> bool {
+        self.shared.state.lock().unwrap().closed
+    }
+
+    pub fn stats(&self) -> QueueStats {
+        self.shared.state.lock().unwrap().stats
+    }
+}
+
+impl Drop for EventReceiver {
+    fn drop(&mut self) {
+        self.shared.state.lock().unwrap().closed = true;
+        self.shared.not_full.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+
+    fn moved(address: &str, workspace: &str) -> HyprEvent {
+        HyprEvent::WindowMoved {
+            address: address.to_string(),
+            workspace_id: None,
+            workspace: workspace.to_string(),
+        }
+    }
+
+    fn settings(capacity: usize, overflow: OverflowPolicy) -> EventBufferConfig {
+        EventBufferConfig { capacity, overflow }
+    }
+
+    #[test]
+    fn test_drop_oldest_keeps_newest() {
+        let (tx, rx) = bounded(&settings(2, OverflowPolicy::DropOldest));
+        for workspace in ["1", "2", "3"] {
+            assert!(tx.send(moved("a", workspace)));
+        }
+
+        assert_eq!(rx.drain(), vec![moved("a", "2"), moved("a", "3")]);
+        assert_eq!(rx.stats().dropped, 1);
+    }
+
+    #[test]
+    fn test_coalesce_replaces_superseded_event() {
+        let (tx, rx) = bounded(&settings(2, OverflowPolicy::Coalesce));
+        tx.send(moved("a", "1"));
+        tx.send(moved("b", "1"));
+        tx.send(moved("a", "5"));
+        // Nothing to coalesce with: falls back to dropping the oldest
+        tx.send(HyprEvent::ConfigReloaded);
+
+        assert_eq!(rx.drain(), vec![moved("b", "1"), HyprEvent::ConfigReloaded]);
+        assert_eq!(
+            rx.stats(),
+            QueueStats {
+                dropped: 1,
+                coalesced: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_block_waits_for_consumer() {
+        let (tx, rx) = bounded(&settings(1, OverflowPolicy::Block));
+        let producer = thread::spawn(move || {
+            for workspace in ["1", "2", "3"] {
+                tx.send(moved("a", workspace));
+            }
+        });
+
+        let received: Vec<HyprEvent> = std::iter::from_fn(|| rx.recv()).collect();
+        producer.join().unwrap();
+        assert_eq!(received.len(), 3);
+        assert_eq!(rx.stats(), QueueStats::default());
+    }
+
+    #[test]
+    fn test_send_fails_after_receiver_is_dropped() {
+        let (tx, rx) = bounded(&settings(1, OverflowPolicy::Block));
+        tx.send(HyprEvent::ConfigReloaded);
+        drop(rx);
+        assert!(!tx.send(HyprEvent::ConfigReloaded));
+    }
+}
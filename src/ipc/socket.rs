@@ -0,0 +1,353 @@
+use super::dispatch::Dispatch;
+use super::hypr_commands::{inseparable_replies, split_batch_reply, split_replies, unbatchable};
+use super::instance::{instance_signature, instance_socket_dir};
+use super::models::{HyprBind, HyprClient, HyprDevices, HyprMonitor, HyprWorkspace};
+use serde::de::DeserializeOwned;
+use std::env;
+use std::error::Error;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+/// Environment variable naming the command socket to use instead of the instance's
+pub const SOCKET_VAR: &str = "HYPRDROVER_SOCKET";
+/// Environment variable naming the event socket to use instead of the instance's
+pub const EVENT_SOCKET_VAR: &str = "HYPRDROVER_EVENT_SOCKET";
+/// Environment variable marking the compositor as running on another machine
+pub const REMOTE_VAR: &str = "HYPRDROVER_REMOTE";
+
+/// Explicit Hyprland socket paths, bypassing instance discovery.
+///
+/// Useful for mock sockets in tests and for runtime directories hyprdrover
+/// can't find on its own.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SocketOverrides {
+    /// Replacement for `.socket.sock`; commands then skip `hyprctl` entirely
+    pub command: Option<PathBuf>,
+    /// Replacement for `.socket2.sock`
+    pub event: Option<PathBuf>,
+    /// The sockets are forwarded from another machine (e.g. `ssh -L`), so the
+    /// compositor's processes can't be inspected through the local `/proc`
+    pub remote: bool,
+}
+
+impl SocketOverrides {
+    /// Overrides from `HYPRDROVER_SOCKET`, `HYPRDROVER_EVENT_SOCKET` and
+    /// `HYPRDROVER_REMOTE` (`1` or `true`)
+    pub fn from_env() -> Self {
+        let var = |name| {
+            env::var_os(name)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        };
+        Self {
+            command: var(SOCKET_VAR),
+            event: var(EVENT_SOCKET_VAR),
+            remote: env::var(REMOTE_VAR).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        }
+    }
+
+    /// `self`, with unset paths taken from `fallback`; remote if either is
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            command: self.command.or(fallback.command),
+            event: self.event.or(fallback.event),
+            remote: self.remote || fallback.remote,
+        }
+    }
+}
+
+static OVERRIDES: OnceLock<SocketOverrides> = OnceLock::new();
+
+/// Use `overrides` (falling back to the environment) for the rest of the
+/// process. Only the first call has an effect, so call it before talking to
+/// Hyprland.
+pub fn set_socket_overrides(overrides: SocketOverrides) {
+    let _ = OVERRIDES.set(overrides.or(SocketOverrides::from_env()));
+}
+
+/// The socket overrides in effect
+pub fn socket_overrides() -> &'static SocketOverrides {
+    OVERRIDES.get_or_init(SocketOverrides::from_env)
+}
+
+/// Whether the compositor runs on another machine, so local process
+/// inspection would describe the wrong system
+pub fn is_remote() -> bool {
+    socket_overrides().remote
+}
+
+/// Path of the command socket: the override, or the resolved instance's
+pub fn command_socket_path() -> Result<PathBuf, Box<dyn Error>> {
+    match &socket_overrides().command {
+        Some(path) => Ok(path.clone()),
+        None => Ok(instance_socket_dir(&instance_signature()?).join(".socket.sock")),
+    }
+}
+
+/// Path of the event socket: the override, or the resolved instance's
+pub fn event_socket_path() -> Result<PathBuf, Box<dyn Error>> {
+    match &socket_overrides().event {
+        Some(path) => Ok(path.clone()),
+        None => Ok(instance_socket_dir(&instance_signature()?).join(".socket2.sock")),
+    }
+}
+
+/// Talks to Hyprland's command socket directly, the way `hyprctl` does
+pub struct HyprCommandClient {
+    socket_path: PathBuf,
+}
+
+impl HyprCommandClient {
+    /// Client for the command socket in effect (see [`command_socket_path`])
+    pub fn new() -> Result<Self, Box<dyn Error>> {
+        Ok(Self::with_socket(command_socket_path()?))
+    }
+
+    pub fn with_socket(socket_path: impl AsRef<Path>) -> Self {
+        Self {
+            socket_path: socket_path.as_ref().to_path_buf(),
+        }
+    }
+
+    pub fn socket_path(&self) -> &Path {
+        &self.socket_path
+    }
+
+    /// Send `args` (e.g. `["dispatch", "exec", "kitty"]`) and return the
+    /// reply, which is JSON when `json` is set
+    pub fn request(&self, args: &[&str], json: bool) -> io::Result<String> {
+        // The flags prefix is mandatory: without it Hyprland would look for
+        // flags up to the first `/` anywhere in the arguments
+        let flags = if json { "j" } else { "" };
+        self.send_raw(&format!("{}/{}", flags, args.join(" ")))
+    }
+
+    /// Send `args` and parse the JSON reply into `T`
+    pub fn request_json<T: DeserializeOwned>(&self, args: &[&str]) -> Result<T, Box<dyn Error>> {
+        Ok(serde_json::from_str(&self.request(args, true)?)?)
+    }
+
+    pub fn clients(&self) -> Result<Vec<HyprClient>, Box<dyn Error>> {
+        self.request_json(&["clients"])
+    }
+
+    pub fn workspaces(&self) -> Result<Vec<HyprWorkspace>, Box<dyn Error>> {
+        self.request_json(&["workspaces"])
+    }
+
+    pub fn monitors(&self) -> Result<Vec<HyprMonitor>, Box<dyn Error>> {
+        self.request_json(&["monitors"])
+    }
+
+    pub fn binds(&self) -> Result<Vec<HyprBind>, Box<dyn Error>> {
+        self.request_json(&["binds"])
+    }
+
+    pub fn devices(&self) -> Result<HyprDevices, Box<dyn Error>> {
+        self.request_json(&["devices"])
+    }
+
+    /// Execute a dispatcher, failing with Hyprland's reply unless it is `ok`
+    pub fn dispatch_typed(&self, dispatch: Dispatch) -> Result<(), Box<dyn Error>> {
+        let reply = self.request(&["dispatch", &dispatch.to_command_string()?], false)?;
+        if reply.trim().eq_ignore_ascii_case("ok") {
+            Ok(())
+        } else {
+            Err(format!("Dispatch failed: {}", reply.trim()).into())
+        }
+    }
+
+    /// Send `commands` (e.g. `dispatch focuswindow address:0x1`) over one
+    /// connection, returning each command's reply in order; `None` when it
+    /// can't be told apart, as with [`batch`](super::batch)
+    pub fn batch(&self, commands: &[&str]) -> Result<Vec<Option<String>>, Box<dyn Error>> {
+        let reply = self.send_batch(commands)?;
+        Ok(split_batch_reply(&reply, commands.len()))
+    }
+
+    /// Send several requests, like [`request`](Self::request), over one
+    /// connection. Hyprland closes the socket after every reply, so this is
+    /// the way to avoid a connect per query. Unlike [`batch`](Self::batch),
+    /// fails when the compositor doesn't separate the replies, since they
+    /// can't be told apart.
+    pub fn request_many(
+        &self,
+        requests: &[&[&str]],
+        json: bool,
+    ) -> Result<Vec<String>, Box<dyn Error>> {
+        let flags = if json { "j" } else { "" };
+        let commands: Vec<String> = requests
+            .iter()
+            .map(|args| format!("{}/{}", flags, args.join(" ")))
+            .collect();
+        let commands: Vec<&str> = commands.iter().map(String::as_str).collect();
+        let reply = self.send_batch(&commands)?;
+        split_replies(&reply, requests.len()).ok_or_else(inseparable_replies)
+    }
+
+    /// Send `commands` as one `[[BATCH]]` request and return the raw reply
+    pub(super) fn send_batch(&self, commands: &[&str]) -> Result<String, Box<dyn Error>> {
+        if let Some(command) = commands.iter().find(|c| c.contains(';')) {
+            return Err(unbatchable(command));
+        }
+        Ok(self.send_raw(&format!("[[BATCH]]{}", commands.join(";")))?)
+    }
+
+    /// Send an already formatted request such as `j/clients`
+    pub fn send_raw(&self, request: &str) -> io::Result<String> {
+        let mut stream = UnixStream::connect(&self.socket_path)?;
+        stream.write_all(request.as_bytes())?;
+        let mut reply = String::new();
+        stream.read_to_string(&mut reply)?;
+        Ok(reply)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    #[test]
+    fn test_overrides_fall_back_per_socket() {
+        let flags = SocketOverrides {
+            command: Some(PathBuf::from("/tmp/cmd.sock")),
+            event: None,
+            remote: false,
+        };
+        let env = SocketOverrides {
+            command: Some(PathBuf::from("/tmp/env-cmd.sock")),
+            event: Some(PathBuf::from("/tmp/env-events.sock")),
+            remote: true,
+        };
+
+        assert_eq!(
+            flags.or(env),
+            SocketOverrides {
+                command: Some(PathBuf::from("/tmp/cmd.sock")),
+                event: Some(PathBuf::from("/tmp/env-events.sock")),
+                remote: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_batch_over_one_connection() {
+        let path = env::temp_dir().join(format!("hyprdrover-batch-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 128];
+            let n = stream.read(&mut buf).unwrap();
+            stream.write_all(b"ok\n\nInvalid dispatcher\n\n").unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        let client = HyprCommandClient::with_socket(&path);
+        let replies = client
+            .batch(&["dispatch workspace 2", "dispatch nope"])
+            .unwrap();
+        assert_eq!(
+            replies,
+            [
+                Some("ok".to_string()),
+                Some("Invalid dispatcher".to_string())
+            ]
+        );
+        assert_eq!(
+            server.join().unwrap(),
+            "[[BATCH]]dispatch workspace 2;dispatch nope"
+        );
+        assert!(client.batch(&["keyword exec a; b"]).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_request_many_needs_separated_replies() {
+        let path = env::temp_dir().join(format!("hyprdrover-many-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+            for reply in [&b"[]\n\n\n[{}]"[..], b"[][{}]"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 128];
+                let n = stream.read(&mut buf).unwrap();
+                stream.write_all(reply).unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).into_owned());
+            }
+            requests
+        });
+
+        let client = HyprCommandClient::with_socket(&path);
+        let queries: [&[&str]; 2] = [&["clients"], &["monitors", "all"]];
+        assert_eq!(client.request_many(&queries, true).unwrap(), ["[]", "[{}]"]);
+        assert!(client.request_many(&queries, true).is_err());
+        assert_eq!(
+            server.join().unwrap()[0],
+            "[[BATCH]]j/clients;j/monitors all"
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_dispatch_typed_needs_ok() {
+        let path = env::temp_dir().join(format!("hyprdrover-dispatch-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let mut requests = vec![];
+            for reply in [&b"ok"[..], b"No such window found"] {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0; 128];
+                let n = stream.read(&mut buf).unwrap();
+                stream.write_all(reply).unwrap();
+                requests.push(String::from_utf8_lossy(&buf[..n]).into_owned());
+            }
+            requests
+        });
+
+        let client = HyprCommandClient::with_socket(&path);
+        assert!(client
+            .dispatch_typed(Dispatch::FocusWindow("0x1".into()))
+            .is_ok());
+        assert!(client.dispatch_typed(Dispatch::Pin("0x2".into())).is_err());
+        assert_eq!(
+            server.join().unwrap(),
+            [
+                "/dispatch focuswindow address:0x1",
+                "/dispatch pin address:0x2"
+            ]
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_request_against_mock_socket() {
+        let path = env::temp_dir().join(format!("hyprdrover-mock-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0; 64];
+            let n = stream.read(&mut buf).unwrap();
+            stream.write_all(b"[]").unwrap();
+            String::from_utf8_lossy(&buf[..n]).into_owned()
+        });
+
+        let reply = HyprCommandClient::with_socket(&path)
+            .request(&["clients"], true)
+            .unwrap();
+        assert_eq!(reply, "[]");
+        assert_eq!(server.join().unwrap(), "j/clients");
+        let _ = std::fs::remove_file(&path);
+    }
+}
@@ -0,0 +1,253 @@
+//! Remembers the class and title of every open window, so events that only
+//! carry an address (`closewindow`, v1 `windowtitle`, `urgent`, ...) can be
+//! described in human terms.
+
+use super::events::HyprEvent;
+use super::HyprClient;
+use crate::runtime::window_ids::WindowIds;
+use std::collections::HashMap;
+
+/// What the tracker knows about one window
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackedWindow {
+    pub class: String,
+    pub title: String,
+    /// Stable window ID, when the tracker keeps [`WindowIds`]
+    pub id: Option<String>,
+}
+
+impl From<&HyprClient> for TrackedWindow {
+    fn from(client: &HyprClient) -> Self {
+        Self {
+            class: client.class.clone(),
+            title: client.title.clone(),
+            id: client.window_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct WindowTracker {
+    windows: HashMap<String, TrackedWindow>,
+    ids: Option<WindowIds>,
+}
+
+/// Events use bare hex addresses, `j/clients` prefixes them with `0x`
+fn key(address: &str) -> &str {
+    address.trim_start_matches("0x")
+}
+
+impl WindowTracker {
+    pub fn from_clients(clients: &[HyprClient]) -> Self {
+        Self {
+            windows: clients
+                .iter()
+                .map(|c| (key(&c.address).to_string(), TrackedWindow::from(c)))
+                .collect(),
+            ids: None,
+        }
+    }
+
+    /// Also report stable window IDs, giving new windows one. The IDs are
+    /// shared with captures and restores, so they are re-read on every use.
+    pub fn with_ids(mut self, ids: WindowIds) -> Self {
+        self.ids = Some(ids);
+        self
+    }
+
+    /// Whether events called `name` change what the tracker knows, so they
+    /// must reach [`observe`](Self::observe) even when nobody else wants them
+    pub fn tracks(name: &str) -> bool {
+        matches!(
+            name,
+            "openwindow" | "closewindow" | "windowtitle" | "windowtitlev2"
+        )
+    }
+
+    pub fn window(&self, address: &str) -> Option<&TrackedWindow> {
+        self.windows.get(key(address))
+    }
+
+    /// Record what `event` says about windows and return the window it
+    /// concerns, if any. A title change without the new title (v1
+    /// `windowtitle`) is filled in from `lookup`, which should query the live
+    /// client; `lookup` is also asked about addresses the tracker hasn't seen.
+    pub fn observe(
+        &mut self,
+        event: &mut HyprEvent,
+        lookup: impl FnOnce(&str) -> Option<HyprClient>,
+    ) -> Option<TrackedWindow> {
+        let mut window = self.observe_window(event, lookup)?;
+        if window.id.is_none() {
+            window.id = self.stable_id(event);
+        }
+        Some(window)
+    }
+
+    /// ID of the window `event` concerns: assigned when it opens, dropped
+    /// when it closes
+    fn stable_id(&mut self, event: &HyprEvent) -> Option<String> {
+        let ids = self.ids.as_mut()?;
+        let (_, address) = event.fields().into_iter().find(|(f, _)| *f == "address")?;
+        ids.reload();
+        let id = match event {
+            HyprEvent::WindowOpened { .. } => Some(ids.id_for(&address)),
+            HyprEvent::WindowClosed { .. } => {
+                let id = ids.get(&address).map(String::from);
+                ids.forget(&address);
+                id
+            }
+            _ => return ids.get(&address).map(String::from),
+        };
+        if let Err(e) = ids.save() {
+            eprintln!("Warning: could not record window IDs: {}", e);
+        }
+        id
+    }
+
+    fn observe_window(
+        &mut self,
+        event: &mut HyprEvent,
+        lookup: impl FnOnce(&str) -> Option<HyprClient>,
+    ) -> Option<TrackedWindow> {
+        match event {
+            HyprEvent::WindowOpened {
+                address,
+                class,
+                title,
+                ..
+            } => {
+                let window = TrackedWindow {
+                    class: class.clone(),
+                    title: title.clone(),
+                    id: None,
+                };
+                self.windows
+                    .insert(key(address).to_string(), window.clone());
+                Some(window)
+            }
+            HyprEvent::WindowClosed { address } => self.windows.remove(key(address)),
+            HyprEvent::WindowTitleChanged { address, title } => {
+                let fetched = match title {
+                    Some(_) => None,
+                    None => lookup(address),
+                };
+                let entry = self
+                    .windows
+                    .entry(key(address).to_string())
+                    .or_insert_with(|| TrackedWindow {
+                        class: fetched
+                            .as_ref()
+                            .map(|c| c.class.clone())
+                            .unwrap_or_default(),
+                        title: String::new(),
+                        id: None,
+                    });
+                match (title.as_ref(), fetched) {
+                    (Some(new), _) => entry.title = new.clone(),
+                    (None, Some(client)) => entry.title = client.title,
+                    // Best effort: the previous title beats none at all
+                    (None, None) => {}
+                }
+                *title = Some(entry.title.clone());
+                Some(entry.clone())
+            }
+            HyprEvent::ActiveWindowAddress { address }
+            | HyprEvent::WindowMoved { address, .. }
+            | HyprEvent::FloatingChanged { address, .. }
+            | HyprEvent::Pinned { address, .. }
+            | HyprEvent::Urgent { address } => {
+                if let Some(window) = self.windows.get(key(address)) {
+                    return Some(window.clone());
+                }
+                let window = TrackedWindow::from(&lookup(address)?);
+                self.windows
+                    .insert(key(address).to_string(), window.clone());
+                Some(window)
+            }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(address: &str, class: &str, title: &str) -> HyprClient {
+        HyprClient::fixture(address, class).with_title(title)
+    }
+
+    #[test]
+    fn test_v1_title_change_is_backfilled_from_lookup() {
+        let mut tracker = WindowTracker::from_clients(&[client("0x5f3a", "kitty", "zsh")]);
+        let mut event = HyprEvent::WindowTitleChanged {
+            address: "5f3a".to_string(),
+            title: None,
+        };
+
+        tracker.observe(&mut event, |address| {
+            assert_eq!(address, "5f3a");
+            Some(client("0x5f3a", "kitty", "vim notes.md"))
+        });
+
+        assert_eq!(
+            event,
+            HyprEvent::WindowTitleChanged {
+                address: "5f3a".to_string(),
+                title: Some("vim notes.md".to_string()),
+            }
+        );
+        assert_eq!(tracker.window("0x5f3a").unwrap().title, "vim notes.md");
+    }
+
+    #[test]
+    fn test_closed_window_is_described_from_cache() {
+        let mut tracker = WindowTracker::default();
+        let mut opened = HyprEvent::WindowOpened {
+            address: "77".to_string(),
+            workspace: "2".to_string(),
+            class: "firefox".to_string(),
+            title: "Inbox".to_string(),
+        };
+        tracker.observe(&mut opened, |_| None);
+
+        let mut closed = HyprEvent::WindowClosed {
+            address: "77".to_string(),
+        };
+        let window = tracker.observe(&mut closed, |_| panic!("no query needed"));
+
+        assert_eq!(window.unwrap().title, "Inbox");
+        assert!(tracker.window("77").is_none());
+    }
+
+    #[test]
+    fn test_ids_follow_open_and_close() {
+        let context = crate::runtime::RuntimeContext {
+            user: "test".to_string(),
+            uid: 0,
+            seat: "seat0".to_string(),
+            instance: Some("tracker".to_string()),
+            runtime_base: std::env::temp_dir()
+                .join(format!("hyprdrover-tracker-{}", std::process::id())),
+        };
+        let mut tracker = WindowTracker::default().with_ids(WindowIds::load(&context));
+        let mut opened = HyprEvent::WindowOpened {
+            address: "5f3a".to_string(),
+            workspace: "1".to_string(),
+            class: "kitty".to_string(),
+            title: "zsh".to_string(),
+        };
+        let mut closed = HyprEvent::WindowClosed {
+            address: "5f3a".to_string(),
+        };
+
+        let id = tracker.observe(&mut opened, |_| None).unwrap().id;
+        assert!(id.is_some());
+        // Another process (a capture) sees the same ID
+        assert_eq!(WindowIds::load(&context).get("0x5f3a"), id.as_deref());
+        assert_eq!(tracker.observe(&mut closed, |_| None).unwrap().id, id);
+        assert_eq!(WindowIds::load(&context).get("5f3a"), None);
+        let _ = std::fs::remove_dir_all(&context.runtime_base);
+    }
+}
@@ -0,0 +1,96 @@
+use super::hypr_commands::keyword_batch;
+use super::input::get_option;
+use serde_json::Value;
+use std::error::Error;
+
+/// Keywords applied together, with temporary changes undone at the end.
+///
+/// Queued keywords go out as one batched request on [`apply`]. Values set
+/// with [`set_temporarily`] are put back by [`revert`], or when the
+/// transaction is dropped, so an early return or `?` in a restore still
+/// restores them.
+///
+/// [`apply`]: KeywordTransaction::apply
+/// [`set_temporarily`]: KeywordTransaction::set_temporarily
+/// [`revert`]: KeywordTransaction::revert
+#[derive(Debug, Default)]
+pub struct KeywordTransaction {
+    pending: Vec<(String, String)>,
+    originals: Vec<(String, String)>,
+}
+
+impl KeywordTransaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a keyword that stays set
+    pub fn set(&mut self, name: &str, value: &str) {
+        self.pending.push((name.to_string(), value.to_string()));
+    }
+
+    /// Queue an option change that is reverted when the transaction ends.
+    /// Fails, queuing nothing, when the current value can't be read.
+    pub fn set_temporarily(&mut self, name: &str, value: &str) -> Result<(), Box<dyn Error>> {
+        let original = get_option(name)
+            .map(|v| keyword_value(&v))
+            .ok_or_else(|| format!("could not read option {}", name))?;
+        if original != value {
+            self.originals.push((name.to_string(), original));
+            self.set(name, value);
+        }
+        Ok(())
+    }
+
+    /// Send every queued keyword in one request
+    pub fn apply(&mut self) -> Result<(), Box<dyn Error>> {
+        let pending = std::mem::take(&mut self.pending);
+        keyword_batch(&pending)
+    }
+
+    /// Options changed with `set_temporarily`, with the values they go back to
+    pub fn originals(&self) -> &[(String, String)] {
+        &self.originals
+    }
+
+    /// Put the temporarily changed options back now
+    pub fn revert(&mut self) -> Result<(), Box<dyn Error>> {
+        if self.originals.is_empty() {
+            return Ok(());
+        }
+        // Undo in reverse, so an option changed twice ends up at its first value
+        let originals: Vec<(String, String)> = self.originals.drain(..).rev().collect();
+        keyword_batch(&originals)
+    }
+}
+
+impl Drop for KeywordTransaction {
+    fn drop(&mut self) {
+        if let Err(e) = self.revert() {
+            eprintln!(
+                "   ⚠️ Could not restore settings changed for the restore: {}",
+                e
+            );
+        }
+    }
+}
+
+/// An option value as `keyword` expects it
+fn keyword_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_value() {
+        assert_eq!(keyword_value(&Value::from(0)), "0");
+        assert_eq!(keyword_value(&Value::from(0.5)), "0.5");
+        assert_eq!(keyword_value(&Value::from("us,de")), "us,de");
+    }
+}
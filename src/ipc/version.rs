@@ -0,0 +1,149 @@
+use super::hypr_commands::run_hyprctl;
+use serde::Deserialize;
+use std::fmt;
+use std::sync::OnceLock;
+
+/// A parsed Hyprland release version (`major.minor.patch`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HyprVersion {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+}
+
+impl HyprVersion {
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parse tags like `v0.41.2`, `v0.41.2-31-gabcdef` or `0.45.0`
+    pub fn parse(tag: &str) -> Option<Self> {
+        let tag = tag.trim().trim_start_matches('v');
+        let core = tag.split(['-', '+', ' ']).next()?;
+        let mut parts = core.split('.').map(|p| p.parse::<u32>());
+
+        let major = parts.next()?.ok()?;
+        let minor = parts.next()?.ok()?;
+        let patch = match parts.next() {
+            Some(p) => p.ok()?,
+            None => 0,
+        };
+
+        Some(Self::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for HyprVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "v{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Features whose availability depends on the running compositor version
+#[derive(Debug, Clone, Copy)]
+pub struct Capabilities {
+    /// Detected version, `None` when probing failed
+    pub version: Option<HyprVersion>,
+    /// `workspacev2`/`createworkspacev2`/`destroyworkspacev2` events
+    pub v2_events: bool,
+    /// `movewindowv2` and `monitoraddedv2` events
+    pub move_monitor_v2_events: bool,
+    /// The `windowtitlev2` event
+    pub window_title_v2: bool,
+    /// The `fullscreenstate` dispatcher
+    pub fullscreen_state: bool,
+    /// The `tagwindow` dispatcher
+    pub tag_window: bool,
+    /// The `setfloating` and `settiled` dispatchers
+    pub set_floating: bool,
+}
+
+const V2_EVENTS_SINCE: HyprVersion = HyprVersion::new(0, 34, 0);
+const MOVE_MONITOR_V2_SINCE: HyprVersion = HyprVersion::new(0, 34, 0);
+const WINDOW_TITLE_V2_SINCE: HyprVersion = HyprVersion::new(0, 42, 0);
+const TAG_WINDOW_SINCE: HyprVersion = HyprVersion::new(0, 38, 0);
+const SET_FLOATING_SINCE: HyprVersion = HyprVersion::new(0, 38, 0);
+const FULLSCREEN_STATE_SINCE: HyprVersion = HyprVersion::new(0, 42, 0);
+
+impl Capabilities {
+    pub fn for_version(version: Option<HyprVersion>) -> Self {
+        // An unknown version is most likely a development build, so assume
+        // everything is available rather than crippling current compositors.
+        let at_least = |min: HyprVersion| version.is_none_or(|v| v >= min);
+
+        Self {
+            version,
+            v2_events: at_least(V2_EVENTS_SINCE),
+            move_monitor_v2_events: at_least(MOVE_MONITOR_V2_SINCE),
+            window_title_v2: at_least(WINDOW_TITLE_V2_SINCE),
+            fullscreen_state: at_least(FULLSCREEN_STATE_SINCE),
+            tag_window: at_least(TAG_WINDOW_SINCE),
+            set_floating: at_least(SET_FLOATING_SINCE),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct VersionInfo {
+    #[serde(default)]
+    version: Option<String>,
+    #[serde(default)]
+    tag: Option<String>,
+}
+
+/// Query `hyprctl version` and parse the running compositor version
+pub fn probe_version() -> Option<HyprVersion> {
+    let json = run_hyprctl(&["version"]).ok()?;
+    let info: VersionInfo = serde_json::from_str(&json).ok()?;
+
+    info.version
+        .as_deref()
+        .and_then(HyprVersion::parse)
+        .or_else(|| info.tag.as_deref().and_then(HyprVersion::parse))
+}
+
+/// Capabilities of the running compositor, probed once per process
+pub fn capabilities() -> Capabilities {
+    static CAPABILITIES: OnceLock<Capabilities> = OnceLock::new();
+    *CAPABILITIES.get_or_init(|| Capabilities::for_version(probe_version()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_version_tags() {
+        assert_eq!(
+            HyprVersion::parse("v0.41.2"),
+            Some(HyprVersion::new(0, 41, 2))
+        );
+        assert_eq!(
+            HyprVersion::parse("v0.41.2-31-gabcdef"),
+            Some(HyprVersion::new(0, 41, 2))
+        );
+        assert_eq!(HyprVersion::parse("0.45"), Some(HyprVersion::new(0, 45, 0)));
+        assert_eq!(HyprVersion::parse("unknown"), None);
+    }
+
+    #[test]
+    fn test_capabilities_gate_on_version() {
+        let old = Capabilities::for_version(Some(HyprVersion::new(0, 33, 1)));
+        assert!(!old.v2_events);
+        assert!(!old.fullscreen_state);
+        assert!(!old.set_floating);
+
+        let mid = Capabilities::for_version(Some(HyprVersion::new(0, 40, 0)));
+        assert!(mid.v2_events);
+        assert!(mid.tag_window);
+        assert!(mid.set_floating);
+        assert!(!mid.fullscreen_state);
+
+        let unknown = Capabilities::for_version(None);
+        assert!(unknown.fullscreen_state);
+    }
+}
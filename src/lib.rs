@@ -0,0 +1,24 @@
+//! hyprdrover: session snapshots and restore for the Hyprland compositor.
+//!
+//! The `hyprdrover` binary is a thin CLI over these modules; embedders can use
+//! them directly, e.g. to restore with a custom [`restore::matcher::WindowMatcher`]
+//! or [`restore::resolver::LaunchResolver`], or to follow a restore phase by
+//! phase through [`restore::engine::RestoreEngine`].
+
+pub mod activity;
+pub mod bridge;
+pub mod config;
+pub mod daemon;
+pub mod doctor;
+pub mod hooks;
+pub mod ipc;
+pub mod logging;
+pub mod paths;
+pub mod restore;
+pub mod runtime;
+pub mod setup;
+pub mod state;
+#[cfg(feature = "tray")]
+pub mod tray;
+#[cfg(feature = "tui")]
+pub mod tui;
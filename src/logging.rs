@@ -0,0 +1,253 @@
+//! Log files for long-running commands started outside systemd.
+//!
+//! Under systemd the journal already keeps (and trims) their output, so
+//! nothing changes there. Elsewhere, e.g. when started from Hyprland's
+//! `exec-once`, stdout and stderr would go nowhere; [`redirect_output`] sends
+//! them to a size-capped file instead, rotated like `daemon.log.1`,
+//! `daemon.log.2`, ...
+
+use crate::config::{Config, LogConfig};
+use chrono::Local;
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, IsTerminal, Read, Seek, SeekFrom, Write};
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+/// Whether systemd captures this process's output in the journal
+pub fn under_systemd() -> bool {
+    env::var_os("JOURNAL_STREAM").is_some() || env::var_os("INVOCATION_ID").is_some()
+}
+
+/// Path of the `n`th rotated log; 0 is the file being written
+pub fn rotated_path(path: &Path, n: usize) -> PathBuf {
+    if n == 0 {
+        return path.to_path_buf();
+    }
+    let mut rotated = path.as_os_str().to_os_string();
+    rotated.push(format!(".{}", n));
+    PathBuf::from(rotated)
+}
+
+/// A log file that starts over, keeping `keep` older files, once it grows
+/// past `max_bytes`
+pub struct RotatingLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    size: u64,
+    /// A write failed; reopen the file before the next one
+    broken: bool,
+}
+
+impl RotatingLog {
+    pub fn open(path: &Path, settings: &LogConfig) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            max_bytes: settings.max_size_kb.max(1) * 1024,
+            keep: settings.keep,
+            file,
+            size,
+            broken: false,
+        })
+    }
+
+    /// Append one line, rotating first if it would overflow the current file
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        if self.broken {
+            self.reopen()?;
+        }
+        let len = line.len() as u64 + 1;
+        let result = if self.size > 0 && self.size + len > self.max_bytes {
+            self.rotate()
+        } else {
+            Ok(())
+        }
+        .and_then(|()| writeln!(self.file, "{}", line));
+        self.broken = result.is_err();
+        self.size += len;
+        result
+    }
+
+    fn reopen(&mut self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        self.file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        self.size = self.file.metadata()?.len();
+        self.broken = false;
+        Ok(())
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep == 0 {
+            self.file.set_len(0)?;
+        } else {
+            let _ = fs::remove_file(rotated_path(&self.path, self.keep));
+            for n in (0..self.keep).rev() {
+                let from = rotated_path(&self.path, n);
+                if from.exists() {
+                    fs::rename(from, rotated_path(&self.path, n + 1))?;
+                }
+            }
+            self.file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+        }
+        self.size = 0;
+        Ok(())
+    }
+}
+
+/// Send this process's stdout and stderr to the configured log file, one
+/// timestamped line at a time, unless systemd or a terminal is watching them
+pub fn redirect_output(config: &Config) -> io::Result<bool> {
+    if under_systemd() || io::stdout().is_terminal() || io::stderr().is_terminal() {
+        return Ok(false);
+    }
+
+    let mut log = RotatingLog::open(&config.log_path(), &config.log)?;
+    let (reader, writer) = io::pipe()?;
+    for fd in [io::stdout().as_raw_fd(), io::stderr().as_raw_fd()] {
+        // SAFETY: both descriptors are open for the lifetime of the process
+        if unsafe { libc::dup2(writer.as_raw_fd(), fd) } < 0 {
+            return Err(io::Error::last_os_error());
+        }
+    }
+    drop(writer);
+
+    thread::spawn(move || {
+        pump(reader, |line| {
+            let stamped = format!("{} {}", Local::now().format("%Y-%m-%d %H:%M:%S"), line);
+            log.write_line(&stamped)
+        })
+    });
+    Ok(true)
+}
+
+/// Hand each line of `reader` to `write` until the pipe closes. Lines that
+/// fail to write are dropped rather than ending the loop: once nothing reads
+/// the pipe, the next `println!` would panic on a broken pipe.
+fn pump(reader: impl Read, mut write: impl FnMut(&str) -> io::Result<()>) {
+    let mut reader = BufReader::new(reader);
+    let mut buf = Vec::new();
+    loop {
+        buf.clear();
+        match reader.read_until(b'\n', &mut buf) {
+            Ok(0) => return,
+            Ok(_) => {
+                let line = String::from_utf8_lossy(&buf);
+                let _ = write(line.trim_end_matches('\n'));
+            }
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(_) => return,
+        }
+    }
+}
+
+/// Print the log, oldest rotated file first. With `follow`, keep printing
+/// new lines as they are written, like `tail -f`.
+pub fn print_logs(path: &Path, keep: usize, follow: bool) -> io::Result<()> {
+    let mut stdout = io::stdout().lock();
+    for n in (1..=keep).rev() {
+        if let Ok(content) = fs::read(rotated_path(path, n)) {
+            stdout.write_all(&content)?;
+        }
+    }
+
+    let mut file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && !follow => {
+            eprintln!("No log at {}", path.display());
+            return Ok(());
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => wait_for_file(path),
+        Err(e) => return Err(e),
+    };
+    io::copy(&mut file, &mut stdout)?;
+    stdout.flush()?;
+    if !follow {
+        return Ok(());
+    }
+
+    let mut inode = file.metadata()?.ino();
+    loop {
+        thread::sleep(Duration::from_millis(500));
+        // After a rotation the path names a new file; start it from the top
+        if fs::metadata(path).is_ok_and(|m| m.ino() != inode) {
+            file = wait_for_file(path);
+            inode = file.metadata()?.ino();
+            file.seek(SeekFrom::Start(0))?;
+        }
+        let mut new = Vec::new();
+        file.read_to_end(&mut new)?;
+        stdout.write_all(&new)?;
+        stdout.flush()?;
+    }
+}
+
+fn wait_for_file(path: &Path) -> File {
+    loop {
+        if let Ok(file) = File::open(path) {
+            return file;
+        }
+        thread::sleep(Duration::from_millis(500));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_bounded_files() {
+        let dir = env::temp_dir().join(format!("hyprdrover-logs-{}", std::process::id()));
+        let path = dir.join("daemon.log");
+        let settings = LogConfig {
+            max_size_kb: 1,
+            keep: 2,
+        };
+
+        let mut log = RotatingLog::open(&path, &settings).unwrap();
+        let line = "x".repeat(99);
+        for _ in 0..35 {
+            log.write_line(&line).unwrap();
+        }
+
+        // 35 lines of 100 bytes: 10 per KiB file
+        assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 5);
+        assert_eq!(fs::metadata(rotated_path(&path, 1)).unwrap().len(), 1000);
+        assert!(rotated_path(&path, 2).exists());
+        assert!(!rotated_path(&path, 3).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_failing_writer_keeps_draining() {
+        let input: &[u8] = b"one\ntwo\nthree\n";
+        let mut seen = vec![];
+        pump(input, |line| {
+            seen.push(line.to_string());
+            if seen.len() < 3 {
+                Err(io::Error::other("disk full"))
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(seen, ["one", "two", "three"]);
+    }
+}
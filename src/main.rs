@@ -1,16 +1,50 @@
-mod config;
-mod ipc;
-mod restore;
-mod state;
-
-use config::Config;
-use state::SessionManager;
+use chrono::Local;
+use hyprdrover::activity::export::{self, ExportFormat, TimeRange};
+use hyprdrover::activity::{self, ActivityLog, ActivityTracker};
+use hyprdrover::config::layers::{self, Override};
+use hyprdrover::config::{Config, CrashAction};
+use hyprdrover::daemon;
+use hyprdrover::ipc::bus::EventBus;
+use hyprdrover::ipc::events::{Backoff, EventCategory, HyprEvent};
+use hyprdrover::ipc::tracker::WindowTracker;
+use hyprdrover::logging;
+use hyprdrover::paths;
+use hyprdrover::restore::select::WindowSelector;
+use hyprdrover::runtime::crash::SessionMarker;
+use hyprdrover::runtime::freeze::Freezer;
+use hyprdrover::runtime::pause::Pauser;
+use hyprdrover::runtime::window_ids::WindowIds;
+use hyprdrover::runtime::{control, DaemonState, RuntimeContext};
+use hyprdrover::setup;
+use hyprdrover::state::edit::Edit;
+use hyprdrover::state::merge::MergeOptions;
+use hyprdrover::state::stats::{SessionStats, StatsComparison};
+use hyprdrover::state::{
+    anonymize_snapshot, archive, backup_path, summary, NotedSession, SessionManager,
+};
+use hyprdrover::{bridge, doctor, hooks, ipc, restore};
 use std::env;
-use std::path::PathBuf;
+use std::io::{self, ErrorKind, IsTerminal, Write};
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let config = Config::default();
+    let flags = match take_global_flags(env::args().collect()) {
+        Ok(flags) => flags,
+        Err(e) => {
+            eprintln!("{}", e);
+            print_usage();
+            return;
+        }
+    };
+    ipc::set_socket_overrides(flags.sockets);
+    let args = flags.args;
+    let config = Config::load_layered(&flags.config_path, &flags.overrides).unwrap_or_else(|e| {
+        eprintln!("Error loading config, using defaults: {}", e);
+        Config::from_environment()
+    });
     let manager = SessionManager::new(config.clone());
 
     if args.len() < 2 {
@@ -19,15 +53,58 @@ fn main() {
     }
 
     match args[1].as_str() {
-        "--save" => {
-            let name = args.get(2).map(|s| s.as_str());
-            match manager.snapshot(name) {
+        "--save" | "save" => {
+            let note = option_value(&args[2..], "-m");
+            // Skip the note by position; it may well equal the name
+            let note_index = args[2..].iter().position(|a| a == "-m").map(|i| i + 1);
+            let name = args[2..]
+                .iter()
+                .enumerate()
+                .filter(|&(i, _)| Some(i) != note_index)
+                .map(|(_, a)| a.as_str())
+                .find(|a| !a.starts_with('-'));
+            let json = args[2..].iter().any(|a| a == "--json");
+            match manager.snapshot(name, note) {
+                Ok(path) if json => println!("{}", serde_json::json!({ "path": path })),
                 Ok(path) => println!("Session saved to: {}", path.display()),
                 Err(e) => eprintln!("Error saving session: {}", e),
             }
         }
-        "--load" => {
-            let path = if let Some(arg) = args.get(2) {
+        "--load" | "restore" => {
+            let only = match option_value(&args[2..], "--only").map(str::parse) {
+                None => None,
+                Some(Ok(selector)) => Some(selector),
+                Some(Err(e)) => {
+                    eprintln!("--only: {}", e);
+                    return;
+                }
+            };
+            let options = restore::RestoreOptions {
+                confirm_each: args[2..].iter().any(|a| a == "--confirm-each"),
+                restore_input: args[2..].iter().any(|a| a == "--restore-input"),
+                restore_cursor: args[2..].iter().any(|a| a == "--restore-cursor"),
+                restore_focus: args[2..].iter().any(|a| a == "--restore-focus"),
+                only,
+                safe: args[2..].iter().any(|a| a == "--safe"),
+            };
+            let json = args[2..].iter().any(|a| a == "--json");
+            let backup = match option_value(&args[2..], "--backup").map(str::parse::<usize>) {
+                None => None,
+                Some(Ok(n)) if n > 0 => Some(n),
+                Some(_) => {
+                    eprintln!("--backup expects a number (1 is the most recent backup)");
+                    return;
+                }
+            };
+            let positional = args[2..]
+                .iter()
+                .enumerate()
+                .find(|&(i, a)| {
+                    !a.starts_with("--")
+                        && (i == 0 || !matches!(args[i + 1].as_str(), "--backup" | "--only"))
+                })
+                .map(|(_, a)| a);
+            let path = if let Some(arg) = positional {
                 let path = PathBuf::from(arg);
                 if path.exists() {
                     path
@@ -53,10 +130,12 @@ fn main() {
                 match manager.list_sessions() {
                     Ok(sessions) => {
                         if let Some(latest) = sessions.first() {
-                            println!(
-                                "No file specified, loading latest session: {}",
-                                latest.display()
-                            );
+                            if !json {
+                                println!(
+                                    "No file specified, loading latest session: {}",
+                                    latest.display()
+                                );
+                            }
                             latest.clone()
                         } else {
                             eprintln!("No saved sessions found.");
@@ -70,25 +149,200 @@ fn main() {
                 }
             };
 
-            if let Err(e) = manager.restore(&path) {
-                eprintln!("Error restoring session: {}", e);
-            } else {
-                println!("Session restored successfully.");
+            let path = match backup {
+                Some(n) => {
+                    let backup = backup_path(&path, n);
+                    if !backup.exists() {
+                        eprintln!(
+                            "{} has no backup {} ({} kept)",
+                            path.display(),
+                            n,
+                            manager.list_backups(&path).len()
+                        );
+                        return;
+                    }
+                    backup
+                }
+                None => path,
+            };
+
+            if args[2..].iter().any(|a| a == "--dry-run") {
+                let plan = stdout_to_stderr(|| manager.plan(&path, &options));
+                match plan {
+                    Ok(plan) if json => match serde_json::to_string_pretty(&plan) {
+                        Ok(json) => println!("{}", json),
+                        Err(e) => eprintln!("Error encoding plan: {}", e),
+                    },
+                    Ok(plan) => plan.print(),
+                    Err(e) => eprintln!("Error planning restore: {}", e),
+                }
+                return;
+            }
+            if !json {
+                match manager.restore(&path, &options) {
+                    Ok(_) => println!("Session restored successfully."),
+                    Err(e) => eprintln!("Error restoring session: {}", e),
+                }
+                return;
+            }
+            let result = stdout_to_stderr(|| manager.restore(&path, &options));
+            match result.map(|report| serde_json::to_string_pretty(&report)) {
+                Ok(Ok(json)) => println!("{}", json),
+                Ok(Err(e)) => eprintln!("Error encoding report: {}", e),
+                Err(e) => eprintln!("Error restoring session: {}", e),
             }
         }
-        "--list" => match manager.list_sessions() {
+        "--list" | "list" => match manager.find_sessions("") {
+            Ok(sessions) if args[2..].iter().any(|a| a == "--json") => {
+                let sessions: Vec<_> = sessions
+                    .iter()
+                    .map(|(path, note)| {
+                        serde_json::json!({
+                            "name": path.file_stem().map(|s| s.to_string_lossy()),
+                            "path": path,
+                            "note": note,
+                        })
+                    })
+                    .collect();
+                println!("{}", serde_json::Value::Array(sessions));
+            }
             Ok(sessions) => {
                 if sessions.is_empty() {
                     println!("No saved sessions found.");
                 } else {
                     println!("Saved sessions:");
-                    for session in sessions {
-                        println!("  {}", session.display());
-                    }
+                    print_sessions(&sessions);
                 }
             }
             Err(e) => eprintln!("Error listing sessions: {}", e),
         },
+        "find" => {
+            let Some(query) = args.get(2) else {
+                eprintln!("Usage: hyprdrover find TEXT");
+                return;
+            };
+            match manager.find_sessions(query) {
+                Ok(sessions) if sessions.is_empty() => println!("No sessions match {:?}.", query),
+                Ok(sessions) => print_sessions(&sessions),
+                Err(e) => eprintln!("Error searching sessions: {}", e),
+            }
+        }
+        "delete" | "--delete" => {
+            let json = args[2..].iter().any(|a| a == "--json");
+            let Some(session) = args[2..].iter().find(|a| !a.starts_with("--")) else {
+                eprintln!("Usage: hyprdrover delete NAME|FILE [--json]");
+                return;
+            };
+            match manager.delete(session) {
+                Ok(path) if json => println!("{}", serde_json::json!({ "deleted": path })),
+                Ok(path) => println!("Deleted session {}", path.display()),
+                Err(e) => eprintln!("Error deleting session: {}", e),
+            }
+        }
+        "export" => run_export(&config, &manager, &args[2..]),
+        "import" => {
+            let Some(file) = args.get(2) else {
+                eprintln!("Usage: hyprdrover import FILE [NAME]");
+                return;
+            };
+            let name = args.get(3).map(|s| s.as_str());
+            match archive::import_session(&config, &PathBuf::from(file), name) {
+                Ok((session, imports)) => {
+                    println!("Session imported to: {}", session.display());
+                    println!(
+                        "Its rules, companions and script are in {}; merge what you need into {}",
+                        imports.display(),
+                        Config::path().display()
+                    );
+                }
+                Err(e) => eprintln!("Error importing session: {}", e),
+            }
+        }
+        "merge" => run_merge(&manager, &args[2..]),
+        "edit" => run_edit(&manager, &args[2..]),
+        "bench" => match args.get(2).map(|s| s.as_str()) {
+            Some("restore") => bench_restore(&config, &manager, &args[3..]),
+            _ => eprintln!("Usage: hyprdrover bench restore [NAME|FILE] [--json]"),
+        },
+        "stats" => print_stats(&manager, &args[2..]),
+        "quicksave" => quicksave(&config, &manager),
+        "quickrestore" => quickrestore(&config, &manager),
+        "setup" => {
+            let prompter = setup::Prompter::new(args[2..].iter().any(|a| a == "--yes"));
+            if let Err(e) = setup::run(&flags.config_path, &prompter) {
+                eprintln!("Setup failed: {}", e);
+            }
+        }
+        "status" | "--status" => print_status(&config),
+        "doctor" | "--doctor" => {
+            let checks = doctor::run_checks(&config);
+            print!("{}", doctor::format_report(&checks));
+        }
+        "report" => match args.get(2).map(|s| s.as_str()) {
+            Some("last") | None => {
+                match restore::report::RestoreReport::load_last(&manager.reports_dir()) {
+                    Ok(Some(report)) => report.print_summary(),
+                    Ok(None) => println!("No restore reports found."),
+                    Err(e) => eprintln!("Error reading restore report: {}", e),
+                }
+            }
+            Some(other) => eprintln!("Unknown report: {} (expected `last`)", other),
+        },
+        "hooks" => run_hooks(&config),
+        "daemon" => run_daemon(&config),
+        "pause" | "resume" => {
+            let paused = args[1] == "pause";
+            match RuntimeContext::for_config(&config).set_paused(paused) {
+                Ok(()) if paused => {
+                    println!("Tracking paused; run `hyprdrover resume` to continue")
+                }
+                Ok(()) => println!("Tracking resumed"),
+                Err(e) => eprintln!("Error: {}", e),
+            }
+        }
+        "exit" => clean_exit(&config),
+        "activity" => match args.get(2).map(|s| s.as_str()) {
+            Some("today") | None => print_activity(&config, Local::now().date_naive()),
+            Some("export") => export_activity(&config, &args[3..]),
+            Some(other) => eprintln!(
+                "Unknown activity command: {} (expected `today` or `export`)",
+                other
+            ),
+        },
+        "logs" => {
+            let follow = args[2..].iter().any(|a| a == "-f" || a == "--follow");
+            if let Err(e) = logging::print_logs(&config.log_path(), config.log.keep, follow) {
+                eprintln!("Error reading logs: {}", e);
+            }
+        }
+        "bridge" => run_bridge(&args[2..]),
+        "events" => tail_events(&args[2..]),
+        "watch" => match hooks::hook_from_args(&args[2..]) {
+            Ok(hook) => listen_with_hooks(HookSubsystems::start(
+                &config,
+                hooks::HookRunner::new(vec![hook]),
+                None,
+                None,
+                None,
+            )),
+            Err(e) => {
+                eprintln!("{}", e);
+                eprintln!("Usage: hyprdrover watch --on EVENT [--FIELD VALUE]... --exec COMMAND");
+            }
+        },
+        "tui" => run_tui(&config),
+        "tray" => run_tray(&config),
+        "debug-bundle" => {
+            let anonymize = args[2..].iter().any(|a| a == "--anonymize");
+            let output = args[2..]
+                .iter()
+                .find(|a| !a.starts_with("--"))
+                .map(PathBuf::from);
+            match doctor::bundle::create_bundle(&config, output.as_deref(), anonymize) {
+                Ok(path) => println!("Debug bundle written to: {}", path.display()),
+                Err(e) => eprintln!("Error creating debug bundle: {}", e),
+            }
+        }
         "--install" => {
             if let Err(e) = install_binary() {
                 eprintln!("Error installing binary: {}", e);
@@ -101,19 +355,1014 @@ fn main() {
     }
 }
 
+fn print_stats(manager: &SessionManager, args: &[String]) {
+    let parsed = (|| -> Result<_, String> {
+        let (mut session, mut compare, mut json) = (None, None, false);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--json" => json = true,
+                "--compare" => compare = Some(args.next().ok_or("--compare needs a session")?),
+                _ if session.is_none() => session = Some(arg),
+                _ => return Err(format!("unexpected argument {:?}", arg)),
+            }
+        }
+        Ok((session, compare, json))
+    })();
+    let (session, compare, json) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: hyprdrover stats [NAME|FILE] [--compare NAME|FILE] [--json]");
+            return;
+        }
+    };
+
+    let load = |session: &str| manager.load(&manager.resolve(session));
+    let result = session
+        .map_or_else(|| manager.capture(false), |s| load(s))
+        .and_then(|snapshot| {
+            let baseline = compare.map(|c| load(c)).transpose()?;
+            Ok((snapshot, baseline))
+        });
+    let (snapshot, baseline) = match result {
+        Ok(loaded) => loaded,
+        Err(e) => {
+            eprintln!("Error reading session: {}", e);
+            return;
+        }
+    };
+    let stats = SessionStats::of(&snapshot);
+    let comparison = baseline.map(|b| StatsComparison::between(&SessionStats::of(&b), &stats));
+
+    if json {
+        let value = serde_json::json!({ "stats": stats, "comparison": comparison });
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&value).unwrap_or_default()
+        );
+        return;
+    }
+    stats.print();
+    if let (Some(comparison), Some(baseline)) = (comparison, compare) {
+        println!();
+        comparison.print(baseline);
+    }
+}
+
+fn bench_restore(config: &Config, manager: &SessionManager, args: &[String]) {
+    let json = args.iter().any(|a| a == "--json");
+    let path = match args.iter().find(|a| !a.starts_with("--")) {
+        Some(session) => manager.resolve(session),
+        None => match manager.list_sessions().map(|s| s.into_iter().next()) {
+            Ok(Some(latest)) => latest,
+            Ok(None) => {
+                eprintln!("No saved sessions found.");
+                return;
+            }
+            Err(e) => {
+                eprintln!("Error listing sessions: {}", e);
+                return;
+            }
+        },
+    };
+
+    let result = manager
+        .load(&path)
+        .and_then(|snapshot| restore::bench::bench_restore(&snapshot, config));
+    match result {
+        Ok((timings, _)) if json => match serde_json::to_string_pretty(&timings) {
+            Ok(json) => println!("{}", json),
+            Err(e) => eprintln!("Error encoding timings: {}", e),
+        },
+        Ok((timings, _)) => {
+            println!("Benchmarked restore of {}", path.display());
+            timings.print();
+        }
+        Err(e) => eprintln!("Error benchmarking restore: {}", e),
+    }
+}
+
+fn run_merge(manager: &SessionManager, args: &[String]) {
+    let parsed = (|| -> Result<_, String> {
+        let mut options = MergeOptions::default();
+        let (mut inputs, mut output) = (vec![], None);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+                "--workspaces" => options.workspaces = value()?.parse()?,
+                "--duplicates" => options.duplicates = value()?.parse()?,
+                _ => inputs.push(arg.as_str()),
+            }
+        }
+        match output {
+            Some(output) if inputs.len() >= 2 => Ok((inputs, output, options)),
+            _ => Err("merge needs at least two sessions and -o FILE".to_string()),
+        }
+    })();
+
+    let (inputs, output, options) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: hyprdrover merge SESSION SESSION... -o FILE [--workspaces shift|share] [--duplicates keep|skip]");
+            return;
+        }
+    };
+    match manager.merge(&inputs, &output, options) {
+        Ok(count) => println!("Merged {} windows into: {}", count, output.display()),
+        Err(e) => eprintln!("Error merging sessions: {}", e),
+    }
+}
+
+fn run_export(config: &Config, manager: &SessionManager, args: &[String]) {
+    let parsed = (|| -> Result<_, String> {
+        let (mut redact, mut markdown, mut positional) = (false, false, vec![]);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--redact" => redact = true,
+                "--format" => match args.next().map(String::as_str) {
+                    Some("archive") => markdown = false,
+                    Some("markdown") => markdown = true,
+                    other => {
+                        return Err(format!("expected `archive` or `markdown`, got {:?}", other))
+                    }
+                },
+                _ => positional.push(arg.as_str()),
+            }
+        }
+        match positional[..] {
+            [name] => Ok((name, None, redact, markdown)),
+            [name, file] => Ok((name, Some(PathBuf::from(file)), redact, markdown)),
+            _ => Err("export needs a session name and at most one output file".to_string()),
+        }
+    })();
+    let (name, output, redact, markdown) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!(
+                "Usage: hyprdrover export NAME [FILE] [--redact] [--format archive|markdown]"
+            );
+            return;
+        }
+    };
+
+    if !markdown {
+        match archive::export_session(config, name, output.as_deref(), redact) {
+            Ok(path) => println!("Session exported to: {}", path.display()),
+            Err(e) => eprintln!("Error exporting session: {}", e),
+        }
+        return;
+    }
+    let mut snapshot = match manager.load(&manager.resolve(name)) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            eprintln!("Error exporting session: {}", e);
+            return;
+        }
+    };
+    if redact {
+        anonymize_snapshot(&mut snapshot);
+        snapshot.note = None;
+    }
+    let text = summary::to_markdown(name.trim_end_matches(".json"), &snapshot);
+    match output {
+        Some(path) => match std::fs::write(&path, text) {
+            Ok(()) => println!("Session summary written to: {}", path.display()),
+            Err(e) => eprintln!("Error writing {}: {}", path.display(), e),
+        },
+        None => print!("{}", text),
+    }
+}
+
+fn run_edit(manager: &SessionManager, args: &[String]) {
+    let parsed = (|| -> Result<_, String> {
+        let arg = |i: usize| args.get(i).map(String::as_str);
+        let (Some(session), Some(operation), Some(selector)) = (arg(0), arg(1), arg(2)) else {
+            return Err("edit needs a session, an operation and a selector".to_string());
+        };
+        let selector: WindowSelector = selector.parse()?;
+        let edit = match (operation, arg(3)) {
+            ("remove", None) => Edit::Remove,
+            ("move", Some(workspace)) => Edit::MoveTo(
+                workspace
+                    .parse()
+                    .map_err(|_| format!("not a workspace id: {:?}", workspace))?,
+            ),
+            ("command", Some(_)) => Edit::Command(args[3..].join(" ")),
+            _ => return Err(format!("unknown or incomplete edit: {}", operation)),
+        };
+        Ok((session, selector, edit))
+    })();
+
+    let (session, selector, edit) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: hyprdrover edit SESSION remove SELECTOR");
+            eprintln!("       hyprdrover edit SESSION move SELECTOR WORKSPACE");
+            eprintln!("       hyprdrover edit SESSION command SELECTOR COMMAND...");
+            return;
+        }
+    };
+    match manager.edit(session, &selector, &edit) {
+        Ok(count) => println!("Edited {} window(s) in {}", count, session),
+        Err(e) => eprintln!("Error editing session: {}", e),
+    }
+}
+
+/// Options accepted before or after any command
+struct GlobalFlags {
+    /// The remaining arguments
+    args: Vec<String>,
+    config_path: PathBuf,
+    overrides: Vec<Override>,
+    sockets: ipc::SocketOverrides,
+}
+
+/// Remove `--config FILE`, `--set KEY=VALUE`, `--socket PATH`,
+/// `--event-socket PATH` and `--remote` from anywhere in `args`
+fn take_global_flags(args: Vec<String>) -> Result<GlobalFlags, String> {
+    let mut flags = GlobalFlags {
+        args: vec![],
+        config_path: Config::path(),
+        overrides: vec![],
+        sockets: ipc::SocketOverrides::default(),
+    };
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--config" => flags.config_path = PathBuf::from(value()?),
+            "--set" => flags.overrides.push(layers::parse_override(&value()?)?),
+            "--socket" => flags.sockets.command = Some(PathBuf::from(value()?)),
+            "--event-socket" => flags.sockets.event = Some(PathBuf::from(value()?)),
+            "--remote" => flags.sockets.remote = true,
+            _ => flags.args.push(arg),
+        }
+    }
+    Ok(flags)
+}
+
+/// The argument following `flag`, e.g. the note in `-m NOTE`
+fn option_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter()
+        .position(|a| a == flag)
+        .and_then(|i| args.get(i + 1))
+        .map(|s| s.as_str())
+}
+
+/// Run `f` with stdout pointing at stderr, so progress messages don't end up
+/// in JSON meant for scripts
+fn stdout_to_stderr<T>(f: impl FnOnce() -> T) -> T {
+    let _ = io::stdout().flush();
+    let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    if saved >= 0 {
+        unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) };
+    }
+    let result = f();
+    let _ = io::stdout().flush();
+    if saved >= 0 {
+        unsafe {
+            libc::dup2(saved, libc::STDOUT_FILENO);
+            libc::close(saved);
+        }
+    }
+    result
+}
+
+fn print_sessions(sessions: &[NotedSession]) {
+    for (path, note) in sessions {
+        match note {
+            Some(note) => println!("  {}  — {}", path.display(), note),
+            None => println!("  {}", path.display()),
+        }
+    }
+}
+
 fn print_usage() {
-    println!("Usage: hyprdrover [COMMAND]");
+    println!("Usage: hyprdrover [--config FILE] [--set KEY=VALUE]... [COMMAND]");
+    println!("                  [--socket PATH] [--event-socket PATH] [--remote]");
+    println!("Settings are read from the config file, then HYPRDROVER_<KEY> environment");
+    println!("variables, then --set flags, each overriding the previous one. --socket and");
+    println!("--event-socket (or HYPRDROVER_SOCKET/HYPRDROVER_EVENT_SOCKET) talk to the given");
+    println!("Hyprland sockets instead of the discovered instance's; add --remote (or");
+    println!("HYPRDROVER_REMOTE=1) when they are forwarded from another machine.");
+    println!("--save, --load, --list and --delete still work as aliases. With --json, save,");
+    println!("restore, list and delete print their result as JSON for scripts.");
     println!("Commands:");
-    println!("  --save [NAME]       Snapshot the current session (optional name)");
-    println!("  --load [NAME|FILE]  Restore a session (by name or path, defaults to latest)");
-    println!("  --list              List all saved sessions");
+    println!("  save [NAME] [-m NOTE] [--json]");
+    println!("                      Snapshot the current session (optional name and note)");
+    println!("  restore [NAME|FILE] [--backup N] [--confirm-each] [--restore-input]");
+    println!("          [--restore-cursor] [--restore-focus] [--only SELECTOR] [--safe]");
+    println!("          [--dry-run] [--json]");
+    println!("                      Restore a session (by name or path, defaults to latest),");
+    println!("                      or its Nth most recent backup, optionally asking before");
+    println!("                      each app is launched, re-applying saved keyboard/touchpad");
+    println!("                      settings and moving the pointer back. --only restores just");
+    println!("                      the windows matching e.g. class:obsidian or");
+    println!("                      workspace:2/title~notes. --restore-focus ends on the window");
+    println!("                      focused at save time; --safe only moves open windows;");
+    println!("                      --dry-run prints what would be done and changes nothing");
+    println!("  list [--json]       List all saved sessions with their notes");
+    println!("  delete NAME|FILE [--json]");
+    println!("                      Delete a session together with its backups");
+    println!("  find TEXT           List sessions whose name or note contains TEXT");
+    println!("  export NAME [FILE] [--redact] [--format archive|markdown]");
+    println!("                      Pack a session with its rules and metadata into a .tar.zst,");
+    println!("                      hashing titles and arguments with --redact. `markdown`");
+    println!("                      writes a readable summary instead (to stdout without FILE)");
+    println!("  import FILE [NAME]  Add a session from an exported archive");
+    println!("  merge SESSION SESSION... -o FILE [--workspaces shift|share]");
+    println!("        [--duplicates keep|skip]");
+    println!("                      Combine sessions into one, moving colliding workspaces");
+    println!("                      to free ids (or sharing them) and keeping or dropping");
+    println!("                      windows whose app is already present");
+    println!("  edit SESSION remove SELECTOR | move SELECTOR WORKSPACE | command SELECTOR CMD");
+    println!("                      Drop saved windows, move them to another workspace, or");
+    println!("                      set the command that relaunches them");
+    println!("  bench restore [NAME|FILE] [--json]");
+    println!("                      Restore a session and show how long each phase took");
+    println!("  daemon              Keep the session in memory and autosave it to the");
+    println!("                      `autosave` session every minute and when windows close");
     println!("  --install           Install the binary to ~/.local/bin/");
+    println!("  pause | resume      Stop or restart hooks and activity tracking in the `hooks`");
+    println!("                      runner, e.g. during a presentation");
+    println!("  stats [NAME|FILE] [--compare NAME|FILE] [--json]");
+    println!("                      Count windows per class and workspace in the current or a");
+    println!("                      saved session, optionally against another snapshot");
+    println!("  quicksave           Save without prompts and confirm with a Hyprland notification");
+    println!("  quickrestore        Restore the latest session and confirm with a notification");
+    println!("  setup [--yes]       First-run setup: check the environment, write a config,");
+    println!("                      install the daemon's systemd unit and keybindings, and");
+    println!("                      try a save and dry-run restore (--yes accepts defaults)");
+    println!("  status              Show which user/seat/instance hyprdrover serves");
+    println!("  doctor              Diagnose the environment (include this in bug reports)");
+    println!("  report last         Show what the most recent restore did");
+    println!("  events [--filter window|workspace|monitor|other]...");
+    println!("                      Print compositor events as they happen");
+    println!("  hooks               Run the event hooks from the config file in the foreground");
+    println!("  exit                Record a clean logout and quit Hyprland");
+    println!("  activity [today]    Show how long each app had focus today");
+    println!("  activity export [--format csv|json] [--from DATE] [--to DATE] [-o FILE]");
+    println!("                      Write the focus history, optionally limited to a time range");
+    println!("  logs [-f]           Show the log of background runs, following it with -f");
+    println!("  watch --on EVENT [--FIELD VALUE]... --exec COMMAND");
+    println!("                      Run COMMAND whenever a matching event occurs");
+    println!("  bridge serve ADDR [--token-file FILE] [--insecure]");
+    println!("                      Expose this compositor's sockets over TCP");
+    println!("  bridge connect ADDR DIR [--token-file FILE] [--insecure]");
+    println!("                      Mirror a bridged compositor's sockets into DIR");
+    println!("  tui                 Live dashboard of workspaces, windows, and snapshots");
+    println!("  tray                Show a tray icon with save/restore/pause actions");
+    println!("  debug-bundle [FILE] [--anonymize]");
+    println!("                      Pack diagnostics and the latest session into a tarball");
+}
+
+/// Short enough that a keybinding never feels laggy; the fallback path is only
+/// taken when no daemon is listening, which fails immediately
+const QUICK_TIMEOUT: Duration = Duration::from_millis(80);
+
+fn quicksave(config: &Config, manager: &SessionManager) {
+    // A running daemon already tracks the session, so asking it skips the
+    // hyprctl round trips of a full capture
+    let ctx = RuntimeContext::for_config(config);
+    let result = match control::request(&ctx.control_socket_path(), "save", QUICK_TIMEOUT) {
+        Ok(path) => Ok(path),
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => manager
+            .snapshot(None, None)
+            .map(|path| path.display().to_string())
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    match result {
+        Ok(path) => {
+            println!("Session saved to: {}", path);
+            quick_notify(config, ipc::NotifyIcon::Ok, 2000, "Session saved");
+        }
+        Err(e) => {
+            eprintln!("Error saving session: {}", e);
+            quick_notify(
+                config,
+                ipc::NotifyIcon::Error,
+                5000,
+                &format!("Saving session failed: {}", e),
+            );
+        }
+    }
+}
+
+fn quickrestore(config: &Config, manager: &SessionManager) {
+    let latest = match manager.list_sessions() {
+        Ok(sessions) => sessions
+            .into_iter()
+            .next()
+            .ok_or_else(|| "no saved sessions".to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+    restore_and_notify(config, manager, latest);
+}
+
+fn restore_and_notify(config: &Config, manager: &SessionManager, session: Result<PathBuf, String>) {
+    let result = session.and_then(|path| {
+        manager
+            .restore(&path, &restore::RestoreOptions::default())
+            .map_err(|e| e.to_string())
+    });
+
+    match result {
+        Ok(_) => {
+            quick_notify(config, ipc::NotifyIcon::Ok, 2000, "Session restored");
+        }
+        Err(e) => {
+            eprintln!("Error restoring session: {}", e);
+            quick_notify(
+                config,
+                ipc::NotifyIcon::Error,
+                5000,
+                &format!("Restoring session failed: {}", e),
+            );
+        }
+    }
+}
+
+/// Confirm a quick action with a Hyprland notification, unless turned off
+fn quick_notify(config: &Config, icon: ipc::NotifyIcon, timeout_ms: u32, message: &str) {
+    if config.notifications.quick {
+        let _ = ipc::notify(icon, timeout_ms, message);
+    }
+}
+
+fn print_status(config: &Config) {
+    let ctx = RuntimeContext::for_config(config);
+    println!("User:      {} (uid {})", ctx.user, ctx.uid);
+    println!("Seat:      {}", ctx.seat);
+    println!(
+        "Instance:  {}",
+        ctx.instance
+            .as_deref()
+            .unwrap_or("(HYPRLAND_INSTANCE_SIGNATURE not set)")
+    );
+    println!("Lock:      {}", ctx.lock_path().display());
+    println!("Control:   {}", ctx.control_socket_path().display());
+    let sockets = ipc::socket::socket_overrides();
+    if let Some(path) = &sockets.command {
+        println!("Commands:  {} (override)", path.display());
+    }
+    if let Some(path) = &sockets.event {
+        println!("Events:    {} (override)", path.display());
+    }
+    if sockets.remote {
+        println!("Remote:    yes (local process inspection disabled)");
+    }
+    let caps = ipc::capabilities();
+    match caps.version {
+        Some(version) => {
+            let mut features = vec![];
+            if caps.v2_events {
+                features.push("v2 events");
+            }
+            if caps.fullscreen_state {
+                features.push("fullscreenstate");
+            }
+            if caps.tag_window {
+                features.push("tagwindow");
+            }
+            if features.is_empty() {
+                features.push("legacy feature set");
+            }
+            println!("Hyprland:  {} ({})", version, features.join(", "));
+        }
+        None => println!("Hyprland:  version unknown"),
+    }
+    if ctx.is_paused() {
+        println!("Tracking:  paused (hyprdrover resume)");
+    }
+    match ctx.daemon_state() {
+        DaemonState::Running(pid) => println!("Daemon:    running (pid {})", pid),
+        DaemonState::Stale(pid) => println!("Daemon:    not running (stale lock from pid {})", pid),
+        DaemonState::NotRunning => println!("Daemon:    not running"),
+    }
+}
+
+fn run_daemon(config: &Config) {
+    if let Err(e) = logging::redirect_output(config) {
+        eprintln!("Warning: could not open the log file: {}", e);
+    }
+    // The hook subsystems share the daemon's connection to the event socket
+    let bus = EventBus::new();
+    let hooks = config.daemon.hooks.then(|| {
+        if !hooks_configured(config) {
+            eprintln!(
+                "Warning: `daemon.hooks` is set, but no hooks, activity tracking, \
+                 crash recovery or freezing are configured"
+            );
+        }
+        let mut subsystems = start_hooks(config);
+        let events = bus.subscribe("hooks", &config.event_buffer, |_| true);
+        thread::spawn(move || {
+            while let Some(event) = events.recv() {
+                if subsystems.wants(event.name()) {
+                    subsystems.handle(event);
+                }
+            }
+            subsystems.stop();
+        })
+    });
+    if let Err(e) = daemon::run(config, &bus) {
+        eprintln!("Error: {}", e);
+    }
+    bus.close();
+    if let Some(hooks) = hooks {
+        let _ = hooks.join();
+    }
+}
+
+fn run_hooks(config: &Config) {
+    if !hooks_configured(config) {
+        eprintln!(
+            "No hooks configured; activity tracking, crash recovery and freezing are off in {}",
+            Config::path().display()
+        );
+        return;
+    }
+    if let Err(e) = logging::redirect_output(config) {
+        eprintln!("Warning: could not open the log file: {}", e);
+    }
+    listen_with_hooks(start_hooks(config));
+}
+
+fn hooks_configured(config: &Config) -> bool {
+    !config.hooks.is_empty()
+        || config.activity.enabled
+        || config.crash.action != CrashAction::Off
+        || !config.freeze.classes.is_empty()
+}
+
+/// Recover from a crash if configured, and set up the hook subsystems
+fn start_hooks(config: &Config) -> HookSubsystems {
+    let marker = (config.crash.action != CrashAction::Off)
+        .then(|| recover_from_crash(config))
+        .flatten();
+
+    let activity = config
+        .activity
+        .enabled
+        .then(|| ActivityLog::new(config.activity_dir()));
+    let freezer = (!config.freeze.classes.is_empty()).then(|| {
+        Freezer::new(&config.freeze.classes)
+            .with_state(RuntimeContext::for_config(config).frozen_path())
+    });
+    let pauser = Pauser::new(
+        &RuntimeContext::for_config(config),
+        &config.pause.fullscreen_classes,
+    );
+    let mut subsystems = HookSubsystems::start(
+        config,
+        hooks::HookRunner::new(config.hooks.clone()),
+        activity,
+        freezer,
+        Some(pauser),
+    );
+    subsystems.marker = marker;
+    subsystems
+}
+
+/// Start tracking this session, and offer or perform a restore if the
+/// previous one didn't end cleanly. Returns the marker to finish when the
+/// session ends.
+fn recover_from_crash(config: &Config) -> Option<SessionMarker> {
+    let ctx = RuntimeContext::for_config(config);
+    let marker = config.session_marker(&ctx.seat);
+    let signature = ipc::instance_signature().unwrap_or_default();
+    let unclean = match marker.begin(&signature) {
+        Ok(unclean) => unclean,
+        Err(e) => {
+            eprintln!("Warning: could not write the session marker: {}", e);
+            return None;
+        }
+    };
+    if unclean {
+        after_crash(config);
+    }
+    Some(marker)
+}
+
+/// Offer or perform a restore, as `crash.action` says
+fn after_crash(config: &Config) {
+    println!("The previous session did not exit cleanly");
+    match config.crash.action {
+        CrashAction::Restore => {
+            // The daemon's autosave is the session that was running
+            let manager = SessionManager::new(config.clone());
+            let autosave = manager.resolve(&config.daemon.session);
+            if autosave.exists() {
+                restore_and_notify(config, &manager, Ok(autosave));
+            } else {
+                quickrestore(config, &manager);
+            }
+        }
+        CrashAction::Offer => {
+            let _ = ipc::notify(
+                ipc::NotifyIcon::Info,
+                15000,
+                "Hyprland did not exit cleanly. Run `hyprdrover quickrestore` to bring back the last session",
+            );
+        }
+        CrashAction::Off => {}
+    }
+}
+
+/// Record a clean logout, then ask Hyprland to quit
+fn clean_exit(config: &Config) {
+    let ctx = RuntimeContext::for_config(config);
+    if let Err(e) = config.session_marker(&ctx.seat).end() {
+        eprintln!("Warning: could not clear the session marker: {}", e);
+    }
+    if let Err(e) = ipc::dispatch_typed(ipc::Dispatch::Exit) {
+        eprintln!("Error: {}", e);
+    }
+}
+
+fn print_activity(config: &Config, day: chrono::NaiveDate) {
+    if !config.activity.enabled {
+        println!(
+            "Activity tracking is off; set `activity.enabled = true` and run `hyprdrover hooks`."
+        );
+    }
+    let entries = match ActivityLog::new(config.activity_dir()).day(day) {
+        Ok(entries) => entries,
+        Err(e) => {
+            eprintln!("Error reading the activity log: {}", e);
+            return;
+        }
+    };
+    if entries.is_empty() {
+        println!("No activity recorded on {}.", day);
+        return;
+    }
+
+    let total: u64 = entries.iter().map(|e| e.duration_secs).sum();
+    println!(
+        "Activity on {} ({} focused):",
+        day,
+        activity::format_duration(total)
+    );
+    for (class, secs) in activity::summarize(&entries) {
+        println!("  {:>8}  {}", activity::format_duration(secs), class);
+    }
+}
+
+fn export_activity(config: &Config, args: &[String]) {
+    let parsed = (|| -> Result<_, String> {
+        let (mut format, mut range, mut output) = (ExportFormat::Csv, TimeRange::default(), None);
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or(format!("{} needs a value", arg));
+            match arg.as_str() {
+                "--format" => format = value()?.parse()?,
+                "--from" => range.from = Some(export::parse_time(value()?, false)?),
+                "--to" => range.to = Some(export::parse_time(value()?, true)?),
+                "-o" | "--output" => output = Some(PathBuf::from(value()?)),
+                other => return Err(format!("unexpected argument {:?}", other)),
+            }
+        }
+        Ok((format, range, output))
+    })();
+    let (format, range, output) = match parsed {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            eprintln!("{}", e);
+            eprintln!("Usage: hyprdrover activity export [--format csv|json] [--from DATE] [--to DATE] [-o FILE]");
+            return;
+        }
+    };
+
+    let text = match ActivityLog::new(config.activity_dir())
+        .range(&range)
+        .and_then(|entries| export::render(&entries, format))
+    {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error exporting activity: {}", e);
+            return;
+        }
+    };
+    match output {
+        Some(path) => {
+            if let Err(e) = std::fs::write(&path, text) {
+                eprintln!("Error writing {}: {}", path.display(), e);
+            }
+        }
+        None => print!("{}", text),
+    }
+}
+
+fn listen_with_hooks(mut subsystems: HookSubsystems) {
+    let listener = match ipc::events::IpcEventListener::new() {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error connecting to Hyprland events: {}", e);
+            return;
+        }
+    };
+    let backoff = Backoff {
+        give_up: Some(daemon::RECONNECT_GRACE),
+        ..Backoff::default()
+    };
+    listener.listen_with_reconnect(backoff, |event| {
+        if subsystems.wants(event.name()) {
+            subsystems.handle(event);
+        }
+        true
+    });
+    println!("Hyprland is gone; stopping");
+    subsystems.stop();
+}
+
+/// What `hooks` and `watch` do with events: run hooks, record activity,
+/// freeze parked apps and pause automatically
+struct HookSubsystems {
+    runner: hooks::HookRunner,
+    activity: Option<ActivityLog>,
+    freezer: Option<Freezer>,
+    pauser: Option<Pauser>,
+    tracker: WindowTracker,
+    focus: ActivityTracker,
+    /// Set when crash recovery is on
+    marker: Option<SessionMarker>,
+    config: Config,
+}
+
+impl HookSubsystems {
+    fn start(
+        config: &Config,
+        runner: hooks::HookRunner,
+        activity: Option<ActivityLog>,
+        mut freezer: Option<Freezer>,
+        mut pauser: Option<Pauser>,
+    ) -> Self {
+        // Fills in titles that events like `closewindow` don't carry
+        let clients = ipc::capture_state()
+            .map(|state| state.clients)
+            .unwrap_or_default();
+        let ids = WindowIds::load(&RuntimeContext::for_config(config));
+        let tracker = WindowTracker::from_clients(&clients).with_ids(ids);
+        let workspace = ipc::get_active_workspace().map(|ws| ws.name);
+        let focus = ActivityTracker::starting_on(&workspace.unwrap_or_default());
+        if let Some(freezer) = &mut freezer {
+            update_freezer(freezer);
+        }
+        if let Some(pauser) = &mut pauser {
+            pauser.update(&clients);
+        }
+        Self {
+            runner,
+            activity,
+            freezer,
+            pauser,
+            tracker,
+            focus,
+            marker: None,
+            config: config.clone(),
+        }
+    }
+
+    /// Whether events named `name` matter to any subsystem
+    fn wants(&self, name: &str) -> bool {
+        self.runner.wants(name)
+            || (self.activity.is_some() && ActivityTracker::tracks(name))
+            || WindowTracker::tracks(name)
+            || (self.freezer.is_some() && Freezer::tracks(name))
+            || self.pauser.as_ref().is_some_and(|p| p.tracks(name))
+            || name == "reconnected"
+    }
+
+    fn handle(&mut self, mut event: HyprEvent) {
+        if event == HyprEvent::Reconnected {
+            return self.reconnected();
+        }
+        let name = event.name();
+        let wanted = self.runner.wants(name);
+        let timed = self.activity.is_some() && ActivityTracker::tracks(name);
+        if let Some(freezer) = self.freezer.as_mut().filter(|_| Freezer::tracks(name)) {
+            update_freezer(freezer);
+        }
+        if let Some(pauser) = self.pauser.as_mut().filter(|p| p.tracks(name)) {
+            match ipc::get_clients() {
+                Ok(clients) => pauser.update(&clients),
+                Err(e) => eprintln!("Warning: could not check fullscreen apps: {}", e),
+            }
+        }
+        if !wanted && !timed && !WindowTracker::tracks(name) {
+            return;
+        }
+        // The window tracker keeps up while paused, so titles stay right after resuming
+        let paused = self.pauser.as_ref().is_some_and(Pauser::is_paused);
+        let window = self.tracker.observe(&mut event, |address| {
+            ipc::find_client(address).ok().flatten()
+        });
+        if wanted && !paused {
+            self.runner.handle_window(&event, window.as_ref());
+        }
+        // A pause ends the current stretch of focus so paused time isn't counted
+        let ended = if paused {
+            self.focus.finish(Local::now())
+        } else {
+            self.focus.observe(&event, Local::now())
+        };
+        if let (Some(log), Some(entry)) = (&self.activity, ended) {
+            if let Err(e) = log.append(&entry) {
+                eprintln!("Warning: could not record activity: {}", e);
+            }
+        }
+    }
+
+    /// The compositor restarted, and events now come from the new instance
+    fn reconnected(&mut self) {
+        println!("Reconnected to a restarted Hyprland");
+        if let Some(freezer) = &mut self.freezer {
+            update_freezer(freezer);
+        }
+        let Some(marker) = &self.marker else {
+            return;
+        };
+        match marker.restart(&ipc::instance_signature().unwrap_or_default()) {
+            Ok(true) => after_crash(&self.config),
+            Ok(false) => {}
+            Err(e) => eprintln!("Warning: could not write the session marker: {}", e),
+        }
+    }
+
+    /// The event stream ended, normally because the compositor exited
+    fn stop(&mut self) {
+        if let Some(freezer) = &mut self.freezer {
+            freezer.thaw_all();
+        }
+        if let Some(marker) = &self.marker {
+            if let Err(e) = marker.finish() {
+                eprintln!("Warning: could not clear the session marker: {}", e);
+            }
+        }
+    }
+}
+
+fn update_freezer(freezer: &mut Freezer) {
+    match (ipc::get_clients(), ipc::get_monitors()) {
+        (Ok(clients), Ok(monitors)) => freezer.update(&clients, &monitors),
+        (Err(e), _) | (_, Err(e)) => eprintln!("Warning: could not check parked apps: {}", e),
+    }
+}
+
+fn tail_events(args: &[String]) {
+    let mut categories = vec![];
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let value = match arg.as_str() {
+            "--filter" => args.next().map(|s| s.as_str()).unwrap_or_default(),
+            other => other.strip_prefix("--filter=").unwrap_or(other),
+        };
+        for name in value.split(',') {
+            match EventCategory::parse(name) {
+                Some(category) => categories.push(category),
+                None => {
+                    eprintln!(
+                        "Unknown event filter: {:?} (expected window, workspace, monitor or other)",
+                        name
+                    );
+                    return;
+                }
+            }
+        }
+    }
+
+    let listener = match ipc::events::IpcEventListener::new() {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error connecting to Hyprland events: {}", e);
+            return;
+        }
+    };
+
+    let color = io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none();
+    let result = listener.listen(|event| {
+        if categories.is_empty() || categories.contains(&event.category()) {
+            let time = Local::now().format("%H:%M:%S%.3f").to_string();
+            println!("{}", event.pretty(&time, color));
+        }
+    });
+    if let Err(e) = result {
+        eprintln!("Event stream closed: {}", e);
+    }
+}
+
+fn run_bridge(args: &[String]) {
+    // Not taken from the command line, where `ps` would show it
+    let token = match option_value(args, "--token-file") {
+        Some(path) => match bridge::read_token_file(Path::new(path)) {
+            Ok(token) => Some(token),
+            Err(e) => {
+                eprintln!("Could not read the bridge token from {}: {}", path, e);
+                return;
+            }
+        },
+        None => env::var(bridge::TOKEN_VAR).ok(),
+    };
+    let Some(token) = token.filter(|t| !t.is_empty()) else {
+        eprintln!(
+            "The bridge needs a shared token: set {} or pass --token-file FILE",
+            bridge::TOKEN_VAR
+        );
+        return;
+    };
+    let insecure = args.iter().any(|a| a == "--insecure");
+    let mut positional = vec![];
+    let mut rest = args.iter();
+    while let Some(arg) = rest.next() {
+        if arg == "--token-file" {
+            rest.next();
+        } else if arg != "--insecure" {
+            positional.push(arg);
+        }
+    }
+    // The token and every command cross the network in clear otherwise
+    if let Some(addr) = positional.get(1).filter(|_| !insecure) {
+        match bridge::is_loopback(addr) {
+            Ok(true) => {}
+            Ok(false) => {
+                eprintln!(
+                    "Refusing to bridge over {}: the connection is not encrypted. Use a loopback \
+                     address behind an SSH tunnel or TLS terminator, or pass --insecure.",
+                    addr
+                );
+                return;
+            }
+            Err(e) => {
+                eprintln!("Bridge error: {}: {}", addr, e);
+                return;
+            }
+        }
+    }
+
+    let result = match positional.as_slice() {
+        [mode, addr] if mode.as_str() == "serve" => {
+            let sockets = ipc::socket::command_socket_path()
+                .and_then(|c| Ok((c, ipc::socket::event_socket_path()?)));
+            sockets.and_then(|(command, event)| {
+                let listener = TcpListener::bind(addr.as_str())?;
+                println!("Bridging {} to {}", command.display(), addr);
+                Ok(bridge::BridgeServer::new(&token, command, event).serve(listener)?)
+            })
+        }
+        [mode, addr, dir] if mode.as_str() == "connect" => {
+            println!("Sockets for {} in {}", addr, dir);
+            bridge::connect(addr, &token, &PathBuf::from(dir))
+        }
+        _ => {
+            eprintln!(
+                "Usage: hyprdrover bridge serve ADDR | bridge connect ADDR DIR [--token-file FILE] [--insecure]"
+            );
+            return;
+        }
+    };
+    if let Err(e) = result {
+        eprintln!("Bridge error: {}", e);
+    }
+}
+
+#[cfg(feature = "tui")]
+fn run_tui(config: &Config) {
+    if let Err(e) = hyprdrover::tui::run(config) {
+        eprintln!("Error running dashboard: {}", e);
+    }
+}
+
+#[cfg(not(feature = "tui"))]
+fn run_tui(_config: &Config) {
+    eprintln!("hyprdrover was built without the dashboard; rebuild with `--features tui`");
+}
+
+#[cfg(feature = "tray")]
+fn run_tray(config: &Config) {
+    if let Err(e) = hyprdrover::tray::run(config) {
+        eprintln!("Error running tray icon: {}", e);
+    }
+}
+
+#[cfg(not(feature = "tray"))]
+fn run_tray(_config: &Config) {
+    eprintln!("hyprdrover was built without the tray icon; rebuild with `--features tray`");
 }
 
 fn install_binary() -> Result<(), Box<dyn std::error::Error>> {
     let current_exe = env::current_exe()?;
-    let home_dir = env::var("HOME")?;
-    let target_dir = PathBuf::from(home_dir).join(".local/bin");
+    let target_dir = paths::bin_dir();
 
     if !target_dir.exists() {
         std::fs::create_dir_all(&target_dir)?;
@@ -0,0 +1,129 @@
+//! Every location hyprdrover reads or writes, following the XDG base
+//! directory spec. Config settings (`session_dir`, `state_dir`,
+//! `runtime_dir`) override these defaults per path.
+
+use std::env;
+use std::fs::{self, DirBuilder};
+use std::io;
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Subdirectory name used inside each base directory
+pub const APP_DIR: &str = "hyprdrover";
+
+pub fn home() -> PathBuf {
+    PathBuf::from(env::var("HOME").unwrap_or_else(|_| ".".to_string()))
+}
+
+/// `$var` if it holds an absolute path (the spec says to ignore relative
+/// ones), otherwise `$HOME/<fallback>`
+fn xdg_base(var: &str, fallback: &str) -> PathBuf {
+    env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| home().join(fallback))
+}
+
+/// `$XDG_CONFIG_HOME/hyprdrover`
+pub fn config_dir() -> PathBuf {
+    xdg_base("XDG_CONFIG_HOME", ".config").join(APP_DIR)
+}
+
+/// `$XDG_CONFIG_HOME/hypr`, home of `hyprland.conf`
+pub fn hypr_config_dir() -> PathBuf {
+    xdg_base("XDG_CONFIG_HOME", ".config").join("hypr")
+}
+
+/// `$XDG_CONFIG_HOME/systemd/user`, where user units go
+pub fn systemd_user_dir() -> PathBuf {
+    xdg_base("XDG_CONFIG_HOME", ".config")
+        .join("systemd")
+        .join("user")
+}
+
+/// The config file: `$HYPRDROVER_CONFIG` if set, otherwise `config.toml`
+/// in [`config_dir`]
+pub fn config_file() -> PathBuf {
+    match env::var_os("HYPRDROVER_CONFIG").filter(|p| !p.is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => config_dir().join("config.toml"),
+    }
+}
+
+/// Snapshots live next to the config, where earlier releases put them, so
+/// upgrading never hides existing sessions
+pub fn session_dir() -> PathBuf {
+    config_dir().join("sessions")
+}
+
+/// A new directory only this user can open, for files about to be packed or
+/// unpacked: `hyprdrover-<kind>-<pid>-<n>` in `$XDG_RUNTIME_DIR`, or in
+/// `fallback` without one. An existing directory is never reused, so nobody
+/// can plant files in it beforehand.
+pub fn staging_dir(kind: &str, fallback: &Path) -> io::Result<PathBuf> {
+    let parent = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .unwrap_or_else(|| fallback.to_path_buf());
+    fs::create_dir_all(&parent)?;
+    let mut n = 0;
+    loop {
+        let dir = parent.join(format!("{}-{}-{}-{}", APP_DIR, kind, process::id(), n));
+        match DirBuilder::new().mode(0o700).create(&dir) {
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists && n < 100 => n += 1,
+            result => return result.map(|()| dir),
+        }
+    }
+}
+
+/// Directories holding desktop entries, most important first:
+/// `$XDG_DATA_HOME/applications`, then `applications` in each of
+/// `$XDG_DATA_DIRS` (`/usr/local/share:/usr/share` when unset)
+pub fn application_dirs() -> Vec<PathBuf> {
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    std::iter::once(xdg_base("XDG_DATA_HOME", ".local/share"))
+        .chain(
+            data_dirs
+                .split(':')
+                .map(PathBuf::from)
+                .filter(|dir| dir.is_absolute()),
+        )
+        .map(|dir| dir.join("applications"))
+        .collect()
+}
+
+/// `$XDG_STATE_HOME/hyprdrover`: restore reports, imports and other history
+pub fn state_dir() -> PathBuf {
+    xdg_base("XDG_STATE_HOME", ".local/state").join(APP_DIR)
+}
+
+/// `$XDG_CACHE_HOME/hyprdrover`: anything that can be rebuilt when deleted
+pub fn cache_dir() -> PathBuf {
+    xdg_base("XDG_CACHE_HOME", ".cache").join(APP_DIR)
+}
+
+/// `$XDG_CACHE_HOME/hyprland`, where Hyprland writes its crash reports
+pub fn hyprland_cache_dir() -> PathBuf {
+    xdg_base("XDG_CACHE_HOME", ".cache").join("hyprland")
+}
+
+/// Per-user base for runtime files (daemon lock, control socket).
+///
+/// `$XDG_RUNTIME_DIR` is already per-user; the `/tmp` fallback gets the uid
+/// appended so two users never share it.
+pub fn runtime_dir(uid: u32) -> PathBuf {
+    env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join(APP_DIR))
+        .unwrap_or_else(|| PathBuf::from(format!("/tmp/{}-{}", APP_DIR, uid)))
+}
+
+/// `~/.local/bin`, where `--install` puts the binary
+pub fn bin_dir() -> PathBuf {
+    home().join(".local").join("bin")
+}
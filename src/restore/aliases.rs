@@ -0,0 +1,65 @@
+use std::collections::BTreeMap;
+
+/// Curated class → command mapping, embedded in the binary
+const BUILTIN: &str = include_str!("aliases.toml");
+
+/// Launch commands for window classes whose command isn't simply the
+/// lowercased class, e.g. `brave-browser` → `brave`
+#[derive(Debug, Clone, Default)]
+pub struct AliasTable {
+    commands: BTreeMap<String, String>,
+}
+
+impl AliasTable {
+    /// The built-in aliases overridden by `user` (the `[aliases]` config table)
+    pub fn with_overrides(user: &BTreeMap<String, String>) -> Self {
+        let mut commands = builtin();
+        commands.extend(
+            user.iter()
+                .map(|(class, command)| (class.to_lowercase(), command.clone())),
+        );
+        Self { commands }
+    }
+
+    /// Command for `class`, compared case-insensitively
+    pub fn command(&self, class: &str) -> Option<&str> {
+        self.commands.get(&class.to_lowercase()).map(String::as_str)
+    }
+}
+
+fn builtin() -> BTreeMap<String, String> {
+    // Checked by a test, so this can't fail in a release
+    toml::from_str(BUILTIN).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_aliases_parse_with_lowercase_keys() {
+        let builtin: BTreeMap<String, String> = toml::from_str(BUILTIN).unwrap();
+        assert!(builtin.len() > 30);
+        assert!(builtin.keys().all(|class| *class == class.to_lowercase()));
+    }
+
+    #[test]
+    fn test_user_aliases_override_builtin() {
+        let user = BTreeMap::from([
+            (
+                "Brave-Browser".to_string(),
+                "brave --profile-directory=Work".to_string(),
+            ),
+            ("MyApp".to_string(), "my-app --gui".to_string()),
+        ]);
+        let aliases = AliasTable::with_overrides(&user);
+
+        assert_eq!(
+            aliases.command("brave-browser"),
+            Some("brave --profile-directory=Work")
+        );
+        assert_eq!(aliases.command("myapp"), Some("my-app --gui"));
+        assert_eq!(aliases.command("jetbrains-IDEA"), Some("idea"));
+        assert_eq!(aliases.command("kitty"), None);
+    }
+}
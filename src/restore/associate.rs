@@ -0,0 +1,174 @@
+use crate::ipc::HyprClient;
+use std::collections::HashMap;
+
+/// Bonus for a live window owned by the same process as the saved one,
+/// which survives a compositor restart when the app does
+const SAME_PID: u32 = 1000;
+
+/// Pair saved windows with live ones before anything is launched.
+///
+/// Addresses change whenever Hyprland restarts, so windows are paired on
+/// what `score` says about class and title, plus the owning process. The
+/// pairing maximizes the total score over the whole snapshot (Hungarian
+/// method) rather than going in restore order, so an early window can't
+/// claim the live window that fits a later one better and leave that one to
+/// be launched again. Returns live addresses by saved address.
+pub fn associate(
+    saved: &[HyprClient],
+    current: &[HyprClient],
+    score: impl Fn(&HyprClient, &HyprClient) -> Option<u32>,
+) -> HashMap<String, String> {
+    let weights: Vec<Vec<Option<u32>>> = saved
+        .iter()
+        .map(|saved_client| {
+            current
+                .iter()
+                .map(|current_client| {
+                    let points = score(saved_client, current_client)?;
+                    if saved_client.pid > 0 && saved_client.pid == current_client.pid {
+                        return Some(points.saturating_add(SAME_PID));
+                    }
+                    Some(points)
+                })
+                .collect()
+        })
+        .collect();
+
+    best_assignment(&weights)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(s, c)| Some((saved[s].address.clone(), current[c?].address.clone())))
+        .collect()
+}
+
+/// The column assigned to each row so the summed weight is as large as
+/// possible; `None` weights are pairs that may not be made
+fn best_assignment(weights: &[Vec<Option<u32>>]) -> Vec<Option<usize>> {
+    let (rows, cols) = (weights.len(), weights.first().map_or(0, Vec::len));
+    let n = rows.max(cols);
+    if n == 0 || weights.iter().flatten().all(Option::is_none) {
+        return vec![None; rows];
+    }
+
+    // Minimize cost instead: a forbidden or padding pair costs as much as
+    // leaving both sides unpaired
+    let unpaired = weights
+        .iter()
+        .flatten()
+        .flatten()
+        .max()
+        .map_or(0, |&w| w as i64)
+        + 1;
+    let cost = |r: usize, c: usize| -> i64 {
+        match weights.get(r).and_then(|row| row.get(c)).copied().flatten() {
+            Some(w) => unpaired - w as i64,
+            None => unpaired,
+        }
+    };
+
+    // Kuhn-Munkres with potentials over a square n x n matrix, 1-based;
+    // `owner[c]` is the row holding column `c`
+    let (mut u, mut v) = (vec![0i64; n + 1], vec![0i64; n + 1]);
+    let (mut owner, mut way) = (vec![0usize; n + 1], vec![0usize; n + 1]);
+    for row in 1..=n {
+        owner[0] = row;
+        let mut col = 0;
+        let mut min_to = vec![i64::MAX; n + 1];
+        let mut used = vec![false; n + 1];
+        loop {
+            used[col] = true;
+            let r = owner[col];
+            let (mut delta, mut next) = (i64::MAX, 0);
+            for c in 1..=n {
+                if used[c] {
+                    continue;
+                }
+                let reduced = cost(r - 1, c - 1) - u[r] - v[c];
+                if reduced < min_to[c] {
+                    min_to[c] = reduced;
+                    way[c] = col;
+                }
+                if min_to[c] < delta {
+                    delta = min_to[c];
+                    next = c;
+                }
+            }
+            for c in 0..=n {
+                if used[c] {
+                    u[owner[c]] += delta;
+                    v[c] -= delta;
+                } else {
+                    min_to[c] -= delta;
+                }
+            }
+            col = next;
+            if owner[col] == 0 {
+                break;
+            }
+        }
+        while col != 0 {
+            let prev = way[col];
+            owner[col] = owner[prev];
+            col = prev;
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for c in 1..=n {
+        let r = owner[c] - 1;
+        if r < rows && c <= cols && weights[r][c - 1].is_some() {
+            assignment[r] = Some(c - 1);
+        }
+    }
+    assignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::restore::matcher::{DefaultMatcher, WindowMatcher};
+
+    fn client(address: &str, title: &str, pid: i32) -> HyprClient {
+        HyprClient {
+            title: title.to_string(),
+            ..HyprClient::fixture(address, "kitty").with_pid(pid)
+        }
+    }
+
+    #[test]
+    fn test_associate_pairs_best_first() {
+        // The first saved window fits both live ones equally; restoring in
+        // order would hand it the `htop` window the second one needs
+        let saved = vec![client("0xa", "vim", 10), client("0xb", "htop", 11)];
+        let current = vec![client("0x1", "htop", 20), client("0x2", "zsh", 21)];
+
+        let pairs = associate(&saved, &current, |s, c| DefaultMatcher.score(s, c));
+        assert_eq!(pairs["0xb"], "0x1");
+        assert_eq!(pairs["0xa"], "0x2");
+    }
+
+    #[test]
+    fn test_best_assignment_maximizes_total() {
+        // Greedy would take the 9 and be left with 1; 8 + 7 is better
+        let weights = vec![
+            vec![Some(9), Some(8)],
+            vec![Some(7), None],
+            vec![None, None],
+        ];
+        assert_eq!(best_assignment(&weights), [Some(1), Some(0), None]);
+        assert_eq!(
+            best_assignment(&[vec![Some(1), Some(5), Some(2)]]),
+            [Some(1)]
+        );
+        assert_eq!(best_assignment(&[vec![None]]), [None]);
+    }
+
+    #[test]
+    fn test_associate_prefers_same_process() {
+        let saved = vec![client("0xa", "notes", 30)];
+        let current = vec![client("0x1", "notes", 20), client("0x2", "other", 30)];
+
+        let pairs = associate(&saved, &current, |s, c| DefaultMatcher.score(s, c));
+        assert_eq!(pairs["0xa"], "0x2");
+    }
+}
@@ -0,0 +1,161 @@
+use super::engine::{RestoreEngine, RestoreObserver};
+use super::report::RestoreReport;
+use crate::config::Config;
+use crate::ipc::{HyprClient, SessionSnapshot};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+/// Where the time of one restore went, in milliseconds. Spawn waits and
+/// positioning are summed over all windows.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct PhaseTimings {
+    /// Reading the compositor's current state
+    pub capture_ms: u64,
+    /// Monitors, companions, rules and workspace rules, up to the window plan
+    pub plan_ms: u64,
+    /// Waiting for launched apps to show a window
+    pub spawn_wait_ms: u64,
+    /// Moving and resizing windows into place
+    pub positioning_ms: u64,
+    pub total_ms: u64,
+    pub windows: usize,
+    pub launched: usize,
+}
+
+impl PhaseTimings {
+    pub fn print(&self) {
+        println!(
+            "Windows:      {} ({} launched)",
+            self.windows, self.launched
+        );
+        println!("Capture:      {:>7} ms", self.capture_ms);
+        println!("Plan:         {:>7} ms", self.plan_ms);
+        println!("Spawn wait:   {:>7} ms", self.spawn_wait_ms);
+        println!("Positioning:  {:>7} ms", self.positioning_ms);
+        println!("Total:        {:>7} ms", self.total_ms);
+    }
+}
+
+/// Observer stamping each phase; the timings are shared with the caller
+/// because the engine owns its observers
+struct PhaseTimer {
+    started: Instant,
+    last: Instant,
+    spawned: HashMap<String, Instant>,
+    // When each window became available, by saved address
+    ready: HashMap<String, Instant>,
+    timings: Rc<RefCell<PhaseTimings>>,
+}
+
+impl PhaseTimer {
+    fn new(timings: Rc<RefCell<PhaseTimings>>) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            last: now,
+            spawned: HashMap::new(),
+            ready: HashMap::new(),
+            timings,
+        }
+    }
+
+    /// Time since the previous phase boundary
+    fn lap(&mut self) -> u64 {
+        let now = Instant::now();
+        let elapsed = now - self.last;
+        self.last = now;
+        millis(elapsed)
+    }
+}
+
+impl RestoreObserver for PhaseTimer {
+    fn captured(&mut self, _current: &SessionSnapshot) {
+        self.timings.borrow_mut().capture_ms = self.lap();
+    }
+
+    fn plan_built(&mut self, windows: &[HyprClient]) {
+        let plan_ms = self.lap();
+        let mut timings = self.timings.borrow_mut();
+        timings.plan_ms = plan_ms;
+        timings.windows = windows.len();
+    }
+
+    fn window_matched(&mut self, saved: &HyprClient, _current: &HyprClient) {
+        self.ready.insert(saved.address.clone(), Instant::now());
+    }
+
+    fn spawn_issued(&mut self, saved: &HyprClient, _command: &str) {
+        self.spawned.insert(saved.address.clone(), Instant::now());
+    }
+
+    fn window_appeared(&mut self, saved: &HyprClient, _current: &HyprClient) {
+        let now = Instant::now();
+        if let Some(spawned) = self.spawned.remove(&saved.address) {
+            let mut timings = self.timings.borrow_mut();
+            timings.spawn_wait_ms += millis(now - spawned);
+            timings.launched += 1;
+        }
+        self.ready.insert(saved.address.clone(), now);
+    }
+
+    fn positioned(&mut self, saved: &HyprClient, _current: &HyprClient) {
+        if let Some(ready) = self.ready.remove(&saved.address) {
+            self.timings.borrow_mut().positioning_ms += millis(ready.elapsed());
+        }
+    }
+
+    fn finished(&mut self, _report: &RestoreReport) {
+        self.timings.borrow_mut().total_ms = millis(self.started.elapsed());
+    }
+}
+
+fn millis(duration: Duration) -> u64 {
+    duration.as_millis() as u64
+}
+
+/// Restore `snapshot` and report how long each phase took
+pub fn bench_restore(
+    snapshot: &SessionSnapshot,
+    config: &Config,
+) -> Result<(PhaseTimings, RestoreReport), Box<dyn Error>> {
+    let timings = Rc::new(RefCell::new(PhaseTimings::default()));
+    let report = RestoreEngine::new(config)
+        .observe(Box::new(PhaseTimer::new(timings.clone())))
+        .run(snapshot)?;
+    let timings = timings.borrow().clone();
+    Ok((timings, report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    fn client(address: &str) -> HyprClient {
+        HyprClient::fixture(address, "kitty")
+    }
+
+    #[test]
+    fn test_phase_timer_sums_spawn_waits() {
+        let timings = Rc::new(RefCell::new(PhaseTimings::default()));
+        let mut timer = PhaseTimer::new(timings.clone());
+        let (launched, matched) = (client("0x1"), client("0x2"));
+
+        timer.plan_built(&[launched.clone(), matched.clone()]);
+        timer.window_matched(&matched, &matched);
+        timer.positioned(&matched, &matched);
+        timer.spawn_issued(&launched, "kitty");
+        sleep(Duration::from_millis(20));
+        timer.window_appeared(&launched, &launched);
+        timer.positioned(&launched, &launched);
+
+        let timings = timings.borrow();
+        assert_eq!((timings.windows, timings.launched), (2, 1));
+        assert!(timings.spawn_wait_ms >= 20);
+        assert!(timings.positioning_ms < timings.spawn_wait_ms);
+    }
+}
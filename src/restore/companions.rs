@@ -0,0 +1,129 @@
+use crate::config::CompanionConfig;
+use crate::ipc::{self, HyprLayer};
+use std::fs;
+
+/// The kernel truncates process names in `/proc/<pid>/comm` to 15 bytes
+const COMM_LEN: usize = 15;
+
+/// Whether `companion` shows up as a layer surface or a running process
+pub fn is_running(companion: &CompanionConfig, layers: &[HyprLayer], processes: &[String]) -> bool {
+    if let Some(namespace) = &companion.layer {
+        if layers.iter().any(|l| &l.namespace == namespace) {
+            return true;
+        }
+    }
+
+    let process = companion.process.as_deref().unwrap_or(&companion.name);
+    let comm: String = process.chars().take(COMM_LEN).collect();
+    processes.contains(&comm)
+}
+
+/// Names of the current user's running processes
+fn running_processes() -> Vec<String> {
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return vec![];
+    };
+
+    entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter_map(|e| fs::read_to_string(e.path().join("comm")).ok())
+        .map(|comm| comm.trim_end().to_string())
+        .collect()
+}
+
+/// Start every configured companion that isn't running, returning the names started.
+///
+/// Companions are launched through Hyprland so they inherit the compositor's
+/// environment, exactly as `exec-once` would start them.
+pub fn start_missing(companions: &[CompanionConfig]) -> Vec<String> {
+    let mut started = vec![];
+    for companion in missing(companions) {
+        println!("   Starting companion: {}", companion.name);
+        match ipc::dispatch_exec(&companion.command) {
+            Ok(()) => started.push(companion.name.clone()),
+            Err(e) => eprintln!("   ⚠️ Failed to start {}: {}", companion.name, e),
+        }
+    }
+    started
+}
+
+/// Companions that aren't running and can safely be started
+pub fn missing(companions: &[CompanionConfig]) -> Vec<&CompanionConfig> {
+    if companions.is_empty() {
+        return vec![];
+    }
+
+    let layers = ipc::get_layers().unwrap_or_default();
+    let remote = ipc::socket::is_remote();
+    let processes = if remote { vec![] } else { running_processes() };
+    let mut missing = vec![];
+
+    for companion in companions {
+        if is_running(companion, &layers, &processes) {
+            continue;
+        }
+        // Without the remote process list a missing layer is the only
+        // evidence; starting anything else could run a second copy
+        if remote && companion.layer.is_none() {
+            println!(
+                "   Not checking companion {}: its processes are on the remote machine",
+                companion.name
+            );
+            continue;
+        }
+        missing.push(companion);
+    }
+    missing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn companion(name: &str, layer: Option<&str>, process: Option<&str>) -> CompanionConfig {
+        CompanionConfig {
+            name: name.to_string(),
+            command: name.to_string(),
+            layer: layer.map(String::from),
+            process: process.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_presence_by_layer_or_process() {
+        let layers = vec![HyprLayer {
+            monitor: "DP-1".to_string(),
+            level: 2,
+            namespace: "waybar".to_string(),
+            address: "0x1".to_string(),
+            x: 0,
+            y: 0,
+            w: 1920,
+            h: 30,
+            pid: 1,
+        }];
+        let processes = vec!["nm-applet".to_string(), "polkit-gnome-au".to_string()];
+
+        assert!(is_running(
+            &companion("waybar", Some("waybar"), None),
+            &layers,
+            &[]
+        ));
+        assert!(is_running(
+            &companion("nm-applet", None, None),
+            &[],
+            &processes
+        ));
+        assert!(is_running(
+            &companion("polkit", None, Some("polkit-gnome-authentication-agent-1")),
+            &[],
+            &processes
+        ));
+        assert!(!is_running(
+            &companion("hyprpaper", Some("hyprpaper"), None),
+            &layers,
+            &processes
+        ));
+    }
+}
@@ -0,0 +1,90 @@
+use std::io::{self, BufRead, Write};
+
+/// A reply to the per-launch confirmation prompt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Answer {
+    Yes,
+    No,
+    /// Launch this and every remaining app without asking
+    All,
+    /// Skip this and every remaining app without asking
+    SkipRest,
+}
+
+impl Answer {
+    /// Parse a typed reply; anything unrecognised (including an empty line) is `No`
+    pub fn parse(input: &str) -> Self {
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => Answer::Yes,
+            "a" | "all" => Answer::All,
+            "s" | "skip" => Answer::SkipRest,
+            _ => Answer::No,
+        }
+    }
+}
+
+/// Asks on the terminal before each missing app is launched, for `--confirm-each`
+#[derive(Debug, Default)]
+pub struct LaunchPrompt {
+    // Set once the user answers "all" or "skip rest"
+    sticky: Option<bool>,
+}
+
+impl LaunchPrompt {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether `command` may be launched onto `workspace`
+    pub fn confirm(&mut self, command: &str, workspace: i32) -> bool {
+        if let Some(decision) = self.sticky {
+            return decision;
+        }
+
+        print!(
+            "      Launch {} on workspace {}? [y/N/a(ll)/s(kip rest)] ",
+            command, workspace
+        );
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        // EOF or an unreadable terminal counts as the default answer
+        let _ = io::stdin().lock().read_line(&mut line);
+        self.answer(Answer::parse(&line))
+    }
+
+    /// Record `answer`, returning whether the current app should launch
+    pub fn answer(&mut self, answer: Answer) -> bool {
+        match answer {
+            Answer::Yes => true,
+            Answer::No => false,
+            Answer::All => *self.sticky.insert(true),
+            Answer::SkipRest => *self.sticky.insert(false),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_defaults_to_no() {
+        assert_eq!(Answer::parse("y\n"), Answer::Yes);
+        assert_eq!(Answer::parse("A"), Answer::All);
+        assert_eq!(Answer::parse("s"), Answer::SkipRest);
+        assert_eq!(Answer::parse(""), Answer::No);
+        assert_eq!(Answer::parse("maybe"), Answer::No);
+    }
+
+    #[test]
+    fn test_all_and_skip_rest_stick() {
+        let mut prompt = LaunchPrompt::new();
+        assert!(prompt.answer(Answer::All));
+        assert!(prompt.confirm("brave", 2));
+
+        let mut prompt = LaunchPrompt::new();
+        assert!(!prompt.answer(Answer::SkipRest));
+        assert!(!prompt.confirm("brave", 2));
+    }
+}
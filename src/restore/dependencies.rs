@@ -0,0 +1,178 @@
+use crate::config::DependencyConfig;
+use crate::ipc::HyprClient;
+use std::fmt;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// One entry of a dependency's `after` list
+#[derive(Debug, Clone, PartialEq)]
+pub enum Requirement {
+    /// A window of this class must exist
+    Window(String),
+    /// This shell command must exit successfully
+    Hook(String),
+}
+
+impl Requirement {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        match spec.split_once(':') {
+            Some(("class", class)) if !class.is_empty() => Ok(Self::Window(class.to_string())),
+            Some(("hook", command)) if !command.is_empty() => Ok(Self::Hook(command.to_string())),
+            _ => Err(format!(
+                "expected `class:NAME` or `hook:COMMAND`, got {:?}",
+                spec
+            )),
+        }
+    }
+
+    /// Whether the requirement holds, given the live windows
+    pub fn is_met(&self, live: &[HyprClient]) -> bool {
+        match self {
+            Self::Window(class) => live.iter().any(|c| has_class(c, class)),
+            Self::Hook(command) => Command::new("sh")
+                .arg("-c")
+                .arg(command)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()
+                .is_ok_and(|status| status.success()),
+        }
+    }
+}
+
+impl fmt::Display for Requirement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Window(class) => write!(f, "class:{}", class),
+            Self::Hook(command) => write!(f, "hook:{}", command),
+        }
+    }
+}
+
+pub fn has_class(client: &HyprClient, class: &str) -> bool {
+    client.class.eq_ignore_ascii_case(class) || client.initial_class.eq_ignore_ascii_case(class)
+}
+
+/// Requirements declared for the app of `client`; invalid specs are skipped
+/// (config validation reports them)
+pub fn requirements_for(
+    dependencies: &[DependencyConfig],
+    client: &HyprClient,
+) -> Vec<Requirement> {
+    dependencies
+        .iter()
+        .filter(|d| has_class(client, &d.class))
+        .flat_map(|d| {
+            d.after
+                .iter()
+                .filter_map(|spec| Requirement::parse(spec).ok())
+        })
+        .collect()
+}
+
+/// Poll `check` until it holds or `timeout` passes
+pub fn wait_until(timeout: Duration, mut check: impl FnMut() -> bool) -> bool {
+    let start = Instant::now();
+    loop {
+        if check() {
+            return true;
+        }
+        if start.elapsed() >= timeout {
+            return false;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// A chain of classes that (transitively) wait for themselves, if any
+pub fn find_cycle(dependencies: &[DependencyConfig]) -> Option<Vec<String>> {
+    let edges = |class: &str| -> Vec<String> {
+        dependencies
+            .iter()
+            .filter(|d| d.class.eq_ignore_ascii_case(class))
+            .flat_map(|d| d.after.iter())
+            .filter_map(|spec| match Requirement::parse(spec) {
+                Ok(Requirement::Window(class)) => Some(class.to_lowercase()),
+                _ => None,
+            })
+            .collect()
+    };
+
+    fn visit(
+        class: String,
+        path: &mut Vec<String>,
+        edges: &dyn Fn(&str) -> Vec<String>,
+    ) -> Option<Vec<String>> {
+        if let Some(start) = path.iter().position(|c| *c == class) {
+            let mut cycle = path[start..].to_vec();
+            cycle.push(class);
+            return Some(cycle);
+        }
+        path.push(class.clone());
+        for next in edges(&class) {
+            if let Some(cycle) = visit(next, path, edges) {
+                return Some(cycle);
+            }
+        }
+        path.pop();
+        None
+    }
+
+    dependencies
+        .iter()
+        .find_map(|d| visit(d.class.to_lowercase(), &mut vec![], &edges))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(class: &str, after: &[&str]) -> DependencyConfig {
+        DependencyConfig {
+            class: class.to_string(),
+            after: after.iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_parse_requirements() {
+        assert_eq!(
+            Requirement::parse("class:keepassxc"),
+            Ok(Requirement::Window("keepassxc".to_string()))
+        );
+        assert_eq!(
+            Requirement::parse("hook:pgrep -x syncthing"),
+            Ok(Requirement::Hook("pgrep -x syncthing".to_string()))
+        );
+        assert!(Requirement::parse("keepassxc").is_err());
+        assert!(Requirement::parse("class:").is_err());
+    }
+
+    #[test]
+    fn test_hook_requirement_uses_exit_status() {
+        assert!(Requirement::Hook("true".to_string()).is_met(&[]));
+        assert!(!Requirement::Hook("false".to_string()).is_met(&[]));
+    }
+
+    #[test]
+    fn test_find_cycle() {
+        let acyclic = vec![
+            dependency("thunderbird", &["class:keepassxc"]),
+            dependency("firefox", &["class:KeePassXC", "hook:true"]),
+        ];
+        assert_eq!(find_cycle(&acyclic), None);
+
+        let cyclic = vec![
+            dependency("a", &["class:b"]),
+            dependency("b", &["class:c"]),
+            dependency("c", &["class:a"]),
+        ];
+        assert_eq!(
+            find_cycle(&cyclic),
+            Some(vec!["a".into(), "b".into(), "c".into(), "a".into()])
+        );
+    }
+}
@@ -0,0 +1,128 @@
+//! Library entry point for driving a restore from another program.
+//!
+//! GUI front-ends build a [`RestoreEngine`], register [`RestoreObserver`]s to
+//! follow each window through the restore, and run it on a snapshot:
+//!
+//! ```no_run
+//! use hyprdrover::config::Config;
+//! use hyprdrover::ipc::{HyprClient, SessionSnapshot};
+//! use hyprdrover::restore::engine::{RestoreEngine, RestoreObserver};
+//!
+//! struct Log;
+//!
+//! impl RestoreObserver for Log {
+//!     fn positioned(&mut self, saved: &HyprClient, _current: &HyprClient) {
+//!         println!("{} is back", saved.class);
+//!     }
+//! }
+//!
+//! # fn main() -> Result<(), Box<dyn std::error::Error>> {
+//! let config = Config::load()?;
+//! let snapshot: SessionSnapshot = serde_json::from_str(&std::fs::read_to_string("work.json")?)?;
+//! let report = RestoreEngine::new(&config).observe(Box::new(Log)).run(&snapshot)?;
+//! println!("{} windows", report.entries.len());
+//! # Ok(())
+//! # }
+//! ```
+
+use super::matcher::{self, WindowMatcher};
+use super::plan::RestorePlan;
+use super::report::RestoreReport;
+use super::RestoreOptions;
+use crate::config::Config;
+use crate::ipc::{HyprClient, SessionSnapshot};
+use std::error::Error;
+
+/// Callbacks for each phase of a restore; every method defaults to doing nothing.
+///
+/// `saved` is always the window as recorded in the snapshot (after restore
+/// rules were applied) and `current` the live window standing in for it.
+pub trait RestoreObserver {
+    /// The compositor's current state was read; nothing has been changed yet
+    fn captured(&mut self, _current: &SessionSnapshot) {}
+
+    /// The windows to restore are known, in the order they will be handled
+    /// (dependencies may pull some forward)
+    fn plan_built(&mut self, _windows: &[HyprClient]) {}
+
+    /// An already running window was chosen for `saved`
+    fn window_matched(&mut self, _saved: &HyprClient, _current: &HyprClient) {}
+
+    /// `command` was handed to Hyprland to start the app for `saved`
+    fn spawn_issued(&mut self, _saved: &HyprClient, _command: &str) {}
+
+    /// A launched app's window showed up
+    fn window_appeared(&mut self, _saved: &HyprClient, _current: &HyprClient) {}
+
+    /// `current` was moved to where `saved` was
+    fn positioned(&mut self, _saved: &HyprClient, _current: &HyprClient) {}
+
+    /// The restore is over; called before [`RestoreEngine::run`] returns
+    fn finished(&mut self, _report: &RestoreReport) {}
+}
+
+/// Restores a snapshot with the given config, options, matcher and observers
+pub struct RestoreEngine<'a> {
+    config: &'a Config,
+    options: RestoreOptions,
+    matcher: Option<Box<dyn WindowMatcher>>,
+    observers: Vec<Box<dyn RestoreObserver>>,
+}
+
+impl<'a> RestoreEngine<'a> {
+    pub fn new(config: &'a Config) -> Self {
+        Self {
+            config,
+            options: RestoreOptions::default(),
+            matcher: None,
+            observers: vec![],
+        }
+    }
+
+    pub fn options(mut self, options: RestoreOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Match windows with `matcher` instead of the one named in the config
+    pub fn matcher(mut self, matcher: Box<dyn WindowMatcher>) -> Self {
+        self.matcher = Some(matcher);
+        self
+    }
+
+    pub fn observe(mut self, observer: Box<dyn RestoreObserver>) -> Self {
+        self.observers.push(observer);
+        self
+    }
+
+    pub fn run(mut self, snapshot: &SessionSnapshot) -> Result<RestoreReport, Box<dyn Error>> {
+        let matcher = self.take_matcher();
+        super::run_restore(
+            snapshot,
+            self.config,
+            &self.options,
+            matcher,
+            self.observers,
+        )
+    }
+
+    /// What [`run`](Self::run) would do, without changing anything; observers
+    /// aren't called
+    pub fn plan(mut self, snapshot: &SessionSnapshot) -> Result<RestorePlan, Box<dyn Error>> {
+        let matcher = self.take_matcher();
+        super::plan::run_plan(snapshot, self.config, &self.options, matcher)
+    }
+
+    fn take_matcher(&mut self) -> Box<dyn WindowMatcher> {
+        let config = self.config;
+        self.matcher.take().unwrap_or_else(|| {
+            matcher::by_name(&config.matcher).unwrap_or_else(|| {
+                eprintln!(
+                    "Warning: unknown matcher `{}`, using the default",
+                    config.matcher
+                );
+                Box::new(matcher::DefaultMatcher)
+            })
+        })
+    }
+}
@@ -0,0 +1,104 @@
+use crate::ipc::HyprClient;
+use std::collections::BTreeMap;
+
+/// Apps that hand a second launch to their running copy, with the
+/// arguments that make that copy open another window. An empty string
+/// marks apps that only ever have one window.
+const BUILTIN: &[(&str, &str)] = &[
+    ("firefox", "--new-window"),
+    ("firefox-esr", "--new-window"),
+    ("librewolf", "--new-window"),
+    ("floorp", "--new-window"),
+    ("brave-browser", "--new-window"),
+    ("google-chrome", "--new-window"),
+    ("chromium", "--new-window"),
+    ("vivaldi-stable", "--new-window"),
+    ("microsoft-edge", "--new-window"),
+    ("code", "--new-window"),
+    ("code-oss", "--new-window"),
+    ("vscodium", "--new-window"),
+    ("cursor", "--new-window"),
+    ("discord", ""),
+    ("vesktop", ""),
+    ("slack", ""),
+    ("spotify", ""),
+    ("obsidian", ""),
+    ("signal", ""),
+    ("org.telegram.desktop", ""),
+];
+
+/// Knows which apps are single-instance and how to get another window from them
+#[derive(Debug, Clone, Default)]
+pub struct SingleInstanceApps {
+    new_window: BTreeMap<String, String>,
+}
+
+impl SingleInstanceApps {
+    /// The built-in list overridden by `user` (the `[single_instance]` config table)
+    pub fn with_overrides(user: &BTreeMap<String, String>) -> Self {
+        let mut new_window: BTreeMap<String, String> = BUILTIN
+            .iter()
+            .map(|(class, args)| (class.to_string(), args.to_string()))
+            .collect();
+        new_window.extend(
+            user.iter()
+                .map(|(class, args)| (class.to_lowercase(), args.clone())),
+        );
+        Self { new_window }
+    }
+
+    /// Arguments asking the running copy of `client`'s app for another
+    /// window, or `None` if the app isn't single-instance. `Some("")` means
+    /// the app can't open another window at all.
+    pub fn new_window_args(&self, client: &HyprClient) -> Option<&str> {
+        let listed = [&client.initial_class, &client.class]
+            .into_iter()
+            .find_map(|class| self.new_window.get(&class.to_lowercase()));
+        if let Some(args) = listed {
+            return Some(args);
+        }
+        // Electron apps without an entry keep to a single window
+        client
+            .exec_path
+            .as_deref()
+            .filter(|path| path.contains("electron"))
+            .map(|_| "")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str, exec_path: Option<&str>) -> HyprClient {
+        HyprClient {
+            exec_path: exec_path.map(String::from),
+            ..HyprClient::fixture("0x1", class)
+        }
+    }
+
+    #[test]
+    fn test_new_window_args() {
+        let user = BTreeMap::from([
+            ("Code".to_string(), "-n".to_string()),
+            ("zed".to_string(), "--new".to_string()),
+        ]);
+        let apps = SingleInstanceApps::with_overrides(&user);
+
+        assert_eq!(
+            apps.new_window_args(&client("firefox", None)),
+            Some("--new-window")
+        );
+        assert_eq!(apps.new_window_args(&client("Code", None)), Some("-n"));
+        assert_eq!(apps.new_window_args(&client("zed", None)), Some("--new"));
+        assert_eq!(apps.new_window_args(&client("Spotify", None)), Some(""));
+        assert_eq!(
+            apps.new_window_args(&client("notes", Some("/usr/lib/electron30/electron"))),
+            Some("")
+        );
+        assert_eq!(
+            apps.new_window_args(&client("kitty", Some("/usr/bin/kitty"))),
+            None
+        );
+    }
+}
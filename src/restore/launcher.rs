@@ -0,0 +1,329 @@
+//! Launch commands from the XDG desktop entries of installed apps.
+//!
+//! An entry belongs to a window class when its `StartupWMClass` names the
+//! class or, failing that, when its desktop id or file name (minus
+//! `.desktop`) does, as for `org.gnome.Nautilus.desktop` or
+//! `firefox.desktop`. Its `Exec` line, unquoted as the spec describes and
+//! without the `%f`/`%u`-style field codes, is the command.
+
+use super::scope::shell_quote;
+use crate::paths;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The parts of a `[Desktop Entry]` group needed to launch the app
+#[derive(Debug, Clone, PartialEq)]
+pub struct DesktopEntry {
+    /// Desktop id: the path below the applications directory without
+    /// `.desktop`, `/` replaced by `-` (`kde/foo.desktop` is `kde-foo`)
+    pub id: String,
+    pub exec: String,
+    pub wm_class: Option<String>,
+    /// File name without `.desktop`, which differs from `id` in subdirectories
+    file_stem: String,
+}
+
+impl DesktopEntry {
+    /// Parse an entry; `None` unless it is a visible application with an
+    /// `Exec` line that doesn't need a terminal
+    pub fn parse(id: &str, content: &str) -> Option<Self> {
+        let mut in_main_group = false;
+        let (mut exec, mut wm_class, mut application) = (None, None, false);
+        for line in content.lines().map(str::trim) {
+            if line.starts_with('[') {
+                in_main_group = line == "[Desktop Entry]";
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=').filter(|_| in_main_group) else {
+                continue;
+            };
+            match (key.trim(), value.trim()) {
+                ("Type", kind) => application = kind == "Application",
+                ("Exec", value) => exec = Some(exec_command(value)),
+                ("StartupWMClass", value) => wm_class = Some(value.to_string()),
+                ("Hidden" | "Terminal", "true") => return None,
+                _ => {}
+            }
+        }
+        Some(Self {
+            id: id.to_string(),
+            file_stem: id.to_string(),
+            exec: exec.filter(|e| !e.is_empty() && application)?,
+            wm_class,
+        })
+    }
+}
+
+/// The shell command for an `Exec` value: its arguments unquoted as the
+/// desktop entry spec describes, without field codes (`%u`, `%F`, ...),
+/// which only make sense when opening files, and without the `@@` markers
+/// of Flatpak exports, then quoted again for the shell where needed
+fn exec_command(exec: &str) -> String {
+    exec_args(&unescape(exec))
+        .into_iter()
+        .filter(|arg| !matches!(arg.as_str(), "@@" | "@@u"))
+        .map(|arg| {
+            if !arg.is_empty() && arg.chars().all(is_shell_safe) {
+                arg
+            } else {
+                shell_quote(&arg)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// The escapes every desktop entry string value may contain
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('s') => unescaped.push(' '),
+            Some('n') => unescaped.push('\n'),
+            Some('t') => unescaped.push('\t'),
+            Some('r') => unescaped.push('\r'),
+            Some(other) => unescaped.push(other),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+/// Split an `Exec` command line into arguments. Double quotes group, and
+/// within them a backslash escapes `"`, `` ` ``, `$` and `\`. A field code
+/// that makes up a whole argument expands to nothing, and so removes it.
+fn exec_args(exec: &str) -> Vec<String> {
+    let mut args = vec![];
+    let mut chars = exec.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            return args;
+        }
+        let mut arg = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next() {
+            match c {
+                '"' => quoted = !quoted,
+                '\\' if quoted => {
+                    if let Some(escaped) = chars.next_if(|c| matches!(c, '"' | '`' | '$' | '\\')) {
+                        arg.push(escaped);
+                    } else {
+                        arg.push('\\');
+                    }
+                }
+                c if c.is_whitespace() && !quoted => break,
+                c => arg.push(c),
+            }
+        }
+        if is_field_code(&arg) {
+            continue;
+        }
+        args.push(expand_percent(&arg));
+    }
+}
+
+fn is_field_code(arg: &str) -> bool {
+    let mut chars = arg.chars();
+    chars.next() == Some('%')
+        && chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.next().is_none()
+}
+
+/// `%%` is a literal `%`; other codes inside an argument expand to nothing
+fn expand_percent(arg: &str) -> String {
+    let mut expanded = String::with_capacity(arg.len());
+    let mut chars = arg.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+        } else if chars.next() == Some('%') {
+            expanded.push('%');
+        }
+    }
+    expanded
+}
+
+fn is_shell_safe(c: char) -> bool {
+    c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c)
+}
+
+/// The desktop entries installed for the user and system-wide
+#[derive(Debug, Clone, Default)]
+pub struct DesktopEntries {
+    entries: Vec<DesktopEntry>,
+}
+
+impl DesktopEntries {
+    /// Entries from the XDG application directories
+    pub fn load() -> Self {
+        Self::from_dirs(&paths::application_dirs())
+    }
+
+    /// Entries from `dirs` and their subdirectories; an entry in an earlier
+    /// directory hides one with the same desktop id in a later one
+    pub fn from_dirs(dirs: &[PathBuf]) -> Self {
+        let mut seen = HashSet::new();
+        let mut entries = vec![];
+        for dir in dirs {
+            for path in desktop_files(dir) {
+                let Some(id) = desktop_id(dir, &path) else {
+                    continue;
+                };
+                if !seen.insert(id.clone()) {
+                    continue;
+                }
+                if let Some(mut entry) = fs::read_to_string(&path)
+                    .ok()
+                    .and_then(|content| DesktopEntry::parse(&id, &content))
+                {
+                    if let Some(stem) = path.file_stem() {
+                        entry.file_stem = stem.to_string_lossy().into_owned();
+                    }
+                    entries.push(entry);
+                }
+            }
+        }
+        Self { entries }
+    }
+
+    /// Command starting the app whose windows have `class`, compared
+    /// case-insensitively
+    pub fn command(&self, class: &str) -> Option<&str> {
+        let by_wm_class = self.entries.iter().find(|e| {
+            e.wm_class
+                .as_ref()
+                .is_some_and(|c| c.eq_ignore_ascii_case(class))
+        });
+        let by_id = |e: &&DesktopEntry| e.id.eq_ignore_ascii_case(class);
+        // `kde/org.kde.dolphin.desktop` still belongs to `org.kde.dolphin` windows
+        let by_file = |e: &&DesktopEntry| e.file_stem.eq_ignore_ascii_case(class);
+        by_wm_class
+            .or_else(|| self.entries.iter().find(by_id))
+            .or_else(|| self.entries.iter().find(by_file))
+            .map(|e| e.exec.as_str())
+    }
+}
+
+/// The desktop id of the entry at `path` below `dir`
+fn desktop_id(dir: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(dir).ok()?.to_str()?;
+    Some(relative.strip_suffix(".desktop")?.replace('/', "-"))
+}
+
+/// `.desktop` files under `dir`, sorted so the result doesn't depend on
+/// directory order. Symlinked directories aren't followed, so a link loop
+/// can't keep the search going; symlinked files are read.
+fn desktop_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files = vec![];
+    let Ok(read) = fs::read_dir(dir) else {
+        return files;
+    };
+    for entry in read.flatten() {
+        let path = entry.path();
+        if entry.file_type().is_ok_and(|t| t.is_dir()) {
+            files.extend(desktop_files(&path));
+        } else if path.extension().is_some_and(|ext| ext == "desktop") {
+            files.push(path);
+        }
+    }
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_parse_strips_field_codes() {
+        let entry = DesktopEntry::parse(
+            "org.mozilla.firefox",
+            "[Desktop Entry]\n\
+             Name=Firefox\n\
+             Type=Application\n\
+             Exec=/usr/bin/flatpak run --command=firefox --file-forwarding org.mozilla.firefox @@u %u @@\n\
+             StartupWMClass=firefox\n\
+             \n\
+             [Desktop Action new-private-window]\n\
+             Exec=firefox --private-window %u\n",
+        )
+        .unwrap();
+        assert_eq!(
+            entry.exec,
+            "/usr/bin/flatpak run --command=firefox --file-forwarding org.mozilla.firefox"
+        );
+        assert_eq!(entry.wm_class.as_deref(), Some("firefox"));
+
+        assert_eq!(
+            exec_command("app --progress 100%% %F"),
+            "app --progress 100%"
+        );
+        let terminal = "[Desktop Entry]\nType=Application\nExec=htop\nTerminal=true\n";
+        assert_eq!(DesktopEntry::parse("htop", terminal), None);
+        assert_eq!(
+            DesktopEntry::parse("link", "[Desktop Entry]\nType=Link\nExec=x\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_exec_unquoting() {
+        // As written in the file, with the string escapes doubling backslashes
+        assert_eq!(
+            exec_command(r#""/opt/My App/app" --name "a \\"b\\"" "%u""#),
+            r#"'/opt/My App/app' --name 'a "b"'"#
+        );
+        assert_eq!(
+            exec_command(r#"sh -c "echo \\$HOME""#),
+            "sh -c 'echo $HOME'"
+        );
+        // `\s` is a space, which separates arguments like any other
+        assert_eq!(exec_command("app\\s--new\\s%U"), "app --new");
+    }
+
+    #[test]
+    fn test_lookup_prefers_wm_class_and_earlier_dirs() {
+        let root = env::temp_dir().join(format!("hyprdrover-launcher-{}", std::process::id()));
+        let (user, system) = (root.join("user"), root.join("system"));
+        fs::create_dir_all(system.join("kde")).unwrap();
+        fs::create_dir_all(&user).unwrap();
+        let write = |path: PathBuf, exec: &str, wm_class: &str| {
+            let content = format!(
+                "[Desktop Entry]\nType=Application\nExec={}\n{}",
+                exec, wm_class
+            );
+            fs::write(path, content).unwrap();
+        };
+        write(
+            system.join("code.desktop"),
+            "/usr/bin/code %F",
+            "StartupWMClass=Code\n",
+        );
+        write(
+            user.join("code.desktop"),
+            "code --profile work %F",
+            "StartupWMClass=Code\n",
+        );
+        write(system.join("kde/org.kde.dolphin.desktop"), "dolphin %u", "");
+        write(system.join("kde/org.kde.kate.desktop"), "kate %U", "");
+        // Same desktop id as kde/org.kde.kate.desktop, so it hides it
+        write(user.join("kde-org.kde.kate.desktop"), "kate -n %U", "");
+        std::os::unix::fs::symlink(&system, system.join("kde/loop")).unwrap();
+
+        let entries = DesktopEntries::from_dirs(&[user, system]);
+        assert_eq!(entries.command("code"), Some("code --profile work"));
+        assert_eq!(entries.command("org.kde.dolphin"), Some("dolphin"));
+        assert_eq!(entries.command("kde-org.kde.dolphin"), Some("dolphin"));
+        assert_eq!(entries.command("kde-org.kde.kate"), Some("kate -n"));
+        assert_eq!(entries.command("unknown"), None);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,46 @@
+use std::fs;
+
+/// Environment variable carrying the launch id into everything a launch spawns
+pub const LAUNCH_VAR: &str = "HYPRDROVER_LAUNCH";
+
+/// Prefix `command` so the launched process and all of its descendants carry
+/// `id` in their environment, even after they re-exec or daemonize
+pub fn tag(command: &str, id: &str) -> String {
+    format!("export {}={}; {}", LAUNCH_VAR, id, command)
+}
+
+/// Whether `pid` descends from the launch tagged `id`; `None` when its
+/// environment can't be read (exited, or owned by someone else)
+pub fn descends_from(pid: i32, id: &str) -> Option<bool> {
+    let environ = fs::read(format!("/proc/{}/environ", pid)).ok()?;
+    Some(has_marker(&environ, id))
+}
+
+fn has_marker(environ: &[u8], id: &str) -> bool {
+    let wanted = format!("{}={}", LAUNCH_VAR, id);
+    environ
+        .split(|&b| b == 0)
+        .any(|entry| entry == wanted.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_marker_in_environ() {
+        let environ = b"HOME=/home/me\0HYPRDROVER_LAUNCH=42-3\0PATH=/usr/bin\0";
+
+        assert!(has_marker(environ, "42-3"));
+        assert!(!has_marker(environ, "42-31"));
+        assert!(!has_marker(b"HOME=/home/me\0", "42-3"));
+    }
+
+    #[test]
+    fn test_tag_exports_for_compound_commands() {
+        assert_eq!(
+            tag("cd ~/src && code .", "7-1"),
+            "export HYPRDROVER_LAUNCH=7-1; cd ~/src && code ."
+        );
+    }
+}
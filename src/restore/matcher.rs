@@ -0,0 +1,149 @@
+use crate::ipc::HyprClient;
+use std::collections::HashSet;
+
+/// Decides which live window corresponds to a saved one.
+///
+/// `score` returns `None` when `current` cannot be the saved window, and a
+/// higher score for a better candidate otherwise. Restore picks the highest
+/// scoring unclaimed window.
+pub trait WindowMatcher {
+    fn score(&self, saved: &HyprClient, current: &HyprClient) -> Option<u32>;
+}
+
+/// Built-in matchers selectable with the `matcher` config key
+pub fn by_name(name: &str) -> Option<Box<dyn WindowMatcher>> {
+    match name {
+        "default" => Some(Box::new(DefaultMatcher)),
+        "class" => Some(Box::new(ClassMatcher)),
+        _ => None,
+    }
+}
+
+/// Same app: identical executable, or class/initial class overlap (case-insensitive)
+fn same_app(current: &HyprClient, saved: &HyprClient) -> bool {
+    if let (Some(current_path), Some(saved_path)) = (&current.exec_path, &saved.exec_path) {
+        if current_path == saved_path {
+            return true;
+        }
+    }
+
+    let saved_class = saved.class.to_lowercase();
+    let saved_initial_class = saved.initial_class.to_lowercase();
+    let current_class = current.class.to_lowercase();
+    let current_initial_class = current.initial_class.to_lowercase();
+
+    (!saved_class.is_empty()
+        && (current_class == saved_class || current_initial_class == saved_class))
+        || (!saved_initial_class.is_empty()
+            && (current_class == saved_initial_class
+                || current_initial_class == saved_initial_class))
+}
+
+/// Matches on app identity only; the first window of the right app wins
+pub struct ClassMatcher;
+
+impl WindowMatcher for ClassMatcher {
+    fn score(&self, saved: &HyprClient, current: &HyprClient) -> Option<u32> {
+        same_app(current, saved).then_some(1)
+    }
+}
+
+/// Matches on app identity, then prefers windows that also share the
+/// address, executable, title, initial title, and workspace, so several
+/// windows of one app are told apart
+pub struct DefaultMatcher;
+
+impl WindowMatcher for DefaultMatcher {
+    fn score(&self, saved: &HyprClient, current: &HyprClient) -> Option<u32> {
+        if !same_app(current, saved) {
+            return None;
+        }
+
+        let mut score = 100;
+        // Addresses only survive within one compositor session, where they
+        // identify the window outright
+        if current.address == saved.address {
+            score += 50;
+        }
+        if current.exec_path.is_some() && current.exec_path == saved.exec_path {
+            score += 20;
+        }
+        score += title_similarity(&saved.title, &current.title);
+        if !saved.initial_title.is_empty() && current.initial_title == saved.initial_title {
+            score += 3;
+        }
+        if current.workspace.id == saved.workspace.id {
+            score += 5;
+        }
+        Some(score)
+    }
+}
+
+/// 10 for the same title, else up to 8 for the share of words in common, so
+/// `vim main.rs` still finds `vim main.rs [+]`
+fn title_similarity(saved: &str, current: &str) -> u32 {
+    if saved.is_empty() {
+        return 0;
+    }
+    if saved == current {
+        return 10;
+    }
+    let words = |title: &str| -> HashSet<String> {
+        title
+            .split(|c: char| !c.is_alphanumeric())
+            .filter(|w| !w.is_empty())
+            .map(str::to_lowercase)
+            .collect()
+    };
+    let (saved, current) = (words(saved), words(current));
+    let union = saved.union(&current).count();
+    if union == 0 {
+        return 0;
+    }
+    (saved.intersection(&current).count() * 8 / union) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str, title: &str, workspace: i32) -> HyprClient {
+        HyprClient::fixture("0x1", class)
+            .with_title(title)
+            .with_workspace(workspace)
+    }
+
+    #[test]
+    fn test_default_matcher_prefers_same_title() {
+        let saved = client("kitty", "htop", 2);
+        let same_title = DefaultMatcher.score(&saved, &client("kitty", "htop", 1));
+        let other_title = DefaultMatcher.score(&saved, &client("kitty", "zsh", 1));
+
+        assert!(same_title > other_title);
+        assert_eq!(
+            DefaultMatcher.score(&saved, &client("firefox", "htop", 2)),
+            None
+        );
+    }
+
+    #[test]
+    fn test_title_similarity() {
+        assert_eq!(title_similarity("vim main.rs", "vim main.rs"), 10);
+        assert_eq!(title_similarity("vim main.rs", "vim main.rs [+]"), 8);
+        assert_eq!(title_similarity("vim main.rs", "vim lib.rs"), 4);
+        assert_eq!(title_similarity("", "zsh"), 0);
+    }
+
+    #[test]
+    fn test_class_matcher_is_case_insensitive() {
+        let saved = client("Code", "", 1);
+        assert_eq!(ClassMatcher.score(&saved, &client("code", "", 3)), Some(1));
+    }
+
+    #[test]
+    fn test_by_name() {
+        assert!(by_name("default").is_some());
+        assert!(by_name("class").is_some());
+        assert!(by_name("fuzzy").is_none());
+    }
+}
@@ -0,0 +1,121 @@
+use crate::ipc::events::{HyprEvent, IpcEventListener};
+use crate::ipc::queue::{EventBufferConfig, EventReceiver};
+use crate::ipc::{self, HyprClient};
+use std::time::{Duration, Instant};
+
+/// Watches `openwindow` events while apps are launched, so the launched
+/// window is picked up as soon as it opens, and one that ignored its
+/// `[workspace N silent]` rule is moved before it is noticed on the
+/// focused workspace
+#[derive(Default)]
+pub struct MisplacedWindows {
+    events: Option<EventReceiver>,
+}
+
+impl MisplacedWindows {
+    /// Without an event socket this degrades to waiting, and the poll's
+    /// regular positioning puts windows back
+    pub fn watch(settings: &EventBufferConfig) -> Self {
+        Self {
+            events: IpcEventListener::new()
+                .ok()
+                .map(|listener| listener.spawn(settings)),
+        }
+    }
+
+    /// Whether `openwindow` events arrive; without them launches are
+    /// found by polling alone
+    pub fn is_watching(&self) -> bool {
+        self.events.is_some()
+    }
+
+    /// Wait up to `timeout` for a window that looks like `saved` to open and
+    /// return its address, after moving it to `saved`'s workspace if it
+    /// opened elsewhere. `taken` tells which addresses already belong to
+    /// another saved window.
+    pub fn wait(
+        &self,
+        timeout: Duration,
+        saved: &HyprClient,
+        taken: impl Fn(&str) -> bool,
+    ) -> Option<String> {
+        let Some(events) = &self.events else {
+            std::thread::sleep(timeout);
+            return None;
+        };
+        let deadline = Instant::now() + timeout;
+        while let Some(left) = deadline.checked_duration_since(Instant::now()) {
+            let event = events.recv_timeout(left)?;
+            let Some((address, elsewhere)) = opened(&event, saved) else {
+                continue;
+            };
+            if taken(&address) {
+                continue;
+            }
+            if elsewhere {
+                println!(
+                    "      -> Opened outside workspace {}, moving it back",
+                    saved.workspace.name
+                );
+                if let Err(e) = ipc::move_window_to_workspace(&address, saved.workspace.target()) {
+                    eprintln!("   ⚠️ {}", e);
+                }
+            }
+            return Some(address);
+        }
+        None
+    }
+}
+
+/// Address (`0x`-prefixed, like `j/clients`) of a window `event` reports
+/// opening with `saved`'s class, and whether it opened on another workspace
+fn opened(event: &HyprEvent, saved: &HyprClient) -> Option<(String, bool)> {
+    let HyprEvent::WindowOpened {
+        address,
+        workspace,
+        class,
+        ..
+    } = event
+    else {
+        return None;
+    };
+    let ours = class.eq_ignore_ascii_case(&saved.class)
+        || class.eq_ignore_ascii_case(&saved.initial_class);
+    if !ours {
+        return None;
+    }
+    Some((
+        format!("0x{}", address.trim_start_matches("0x")),
+        *workspace != saved.workspace.name,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_event(workspace: &str, class: &str) -> HyprEvent {
+        HyprEvent::WindowOpened {
+            address: "5f3a".to_string(),
+            workspace: workspace.to_string(),
+            class: class.to_string(),
+            title: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_opened_flags_saved_class_elsewhere() {
+        let saved = HyprClient::fixture("0x1", "Slack").with_workspace(4);
+
+        assert_eq!(
+            opened(&open_event("1", "slack"), &saved),
+            Some(("0x5f3a".to_string(), true))
+        );
+        assert_eq!(
+            opened(&open_event("4", "Slack"), &saved),
+            Some(("0x5f3a".to_string(), false))
+        );
+        assert_eq!(opened(&open_event("1", "kitty"), &saved), None);
+        assert_eq!(opened(&HyprEvent::ConfigReloaded, &saved), None);
+    }
+}
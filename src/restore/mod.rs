@@ -1,148 +1,728 @@
+pub mod aliases;
+pub mod associate;
+pub mod bench;
+pub mod companions;
+pub mod confirm;
+pub mod dependencies;
+pub mod engine;
+pub mod instances;
+pub mod launcher;
+pub mod lineage;
+pub mod matcher;
+pub mod misplaced;
+pub mod monitors;
+pub mod notify;
+pub mod pacing;
+pub mod plan;
 pub mod position;
+pub mod report;
+pub mod resolver;
+pub mod rules;
+pub mod scope;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod select;
+pub mod spawn;
+pub mod swallow;
+pub mod template;
+pub mod verify;
+pub mod waves;
 
-use crate::ipc::{self, SessionSnapshot};
-use std::error::Error;
+pub use plan::{restore_session_plan, RestorePlan};
+
+use crate::config::{Config, DependencyConfig, PacingConfig};
+use crate::ipc::{self, Dispatch, KeywordTransaction, SessionSnapshot, WorkspaceRef};
+use crate::runtime::window_ids::WindowIds;
+use crate::runtime::{freeze, RuntimeContext};
+use aliases::AliasTable;
+use confirm::LaunchPrompt;
+use engine::{RestoreEngine, RestoreObserver};
+use instances::SingleInstanceApps;
+use launcher::DesktopEntries;
+use matcher::WindowMatcher;
+use misplaced::MisplacedWindows;
+use notify::ProgressNotifier;
+use pacing::PendingLaunches;
+use report::{Outcome, RestoreReport, Rollback, Skipped};
+use resolver::LaunchResolver;
+use rules::{Decision, RuleSet};
+use select::WindowSelector;
+use std::cell::OnceCell;
+use std::cmp::Reverse;
 use std::collections::{HashMap, HashSet};
+use std::error::Error;
 use std::time::{Duration, Instant};
 
-/// Orchestrates the restoration of a session
-pub fn restore_session(snapshot: &SessionSnapshot) -> Result<(), Box<dyn Error>> {
+/// Per-run choices made by the caller rather than the config file
+#[derive(Debug, Clone, Default)]
+pub struct RestoreOptions {
+    /// Ask on the terminal before launching each missing app
+    pub confirm_each: bool,
+    /// Re-apply the keyboard and touchpad settings saved in the snapshot
+    pub restore_input: bool,
+    /// Put the pointer back where it was at save time
+    pub restore_cursor: bool,
+    /// Finish with focus on the window that had it at save time, rather
+    /// than on the one that had it when the restore started
+    pub restore_focus: bool,
+    /// Restore only the saved windows this selects, leaving monitors,
+    /// companions and every other window alone
+    pub only: Option<WindowSelector>,
+    /// Only match and move windows that are already open: nothing is
+    /// launched, and monitors, keywords and companions are left alone
+    pub safe: bool,
+}
+
+/// Orchestrates the restoration of a session, returning a report of what happened per window
+pub fn restore_session(
+    snapshot: &SessionSnapshot,
+    config: &Config,
+    options: &RestoreOptions,
+) -> Result<RestoreReport, Box<dyn Error>> {
+    RestoreEngine::new(config)
+        .options(options.clone())
+        .run(snapshot)
+}
+
+/// Like [`restore_session`], but with a caller-supplied matching strategy
+pub fn restore_session_with_matcher(
+    snapshot: &SessionSnapshot,
+    config: &Config,
+    options: &RestoreOptions,
+    matcher: Box<dyn WindowMatcher>,
+) -> Result<RestoreReport, Box<dyn Error>> {
+    RestoreEngine::new(config)
+        .options(options.clone())
+        .matcher(matcher)
+        .run(snapshot)
+}
+
+fn run_restore(
+    snapshot: &SessionSnapshot,
+    config: &Config,
+    options: &RestoreOptions,
+    matcher: Box<dyn WindowMatcher>,
+    mut observers: Vec<Box<dyn RestoreObserver>>,
+) -> Result<RestoreReport, Box<dyn Error>> {
+    let started = Instant::now();
+
     // 1. Get current state
     let current_state = ipc::capture_state()?;
-    let mut available_clients = current_state.clients;
+    for observer in &mut observers {
+        observer.captured(&current_state);
+    }
+    // Frozen scratchpad apps could not react to being moved
+    freeze::thaw(&current_state.clients, &config.freeze.classes);
 
-    // Baseline addresses to identify newly spawned windows after launching
-    let baseline_addresses: HashSet<String> = available_clients
+    // A partial restore only places the selected windows
+    let whole_session = options.only.is_none();
+    // Monitor, keyword and companion changes are off limits in safe mode
+    let reconfigure = whole_session && !options.safe;
+
+    // Rotation and scale change every monitor's logical size, so they go
+    // first, together with the saved workspace rules (persistent, default
+    // monitor, gaps) and the new homes of workspaces whose monitor is gone,
+    // so that they are in place before any window lands
+    let mut keywords = KeywordTransaction::new();
+    if config.disable_animations && !options.safe {
+        if let Err(e) = keywords.set_temporarily("animations:enabled", "0") {
+            eprintln!("   ⚠️ Leaving animations on: {}", e);
+        }
+    }
+    if reconfigure {
+        monitors::restore_monitors(&snapshot.monitors, &current_state.monitors, &mut keywords);
+        restore_workspace_rules(&snapshot.workspace_rules, &mut keywords);
+        monitors::remap_workspaces(
+            &snapshot.monitors,
+            &snapshot.workspaces,
+            &config.monitor_map,
+            &mut keywords,
+        );
+    }
+    if let Err(e) = keywords.apply() {
+        eprintln!(
+            "   ⚠️ Failed to apply monitor and workspace settings: {}",
+            e
+        );
+    }
+    let missing_plugins = if whole_session {
+        verify_plugins(&snapshot.plugins)
+    } else {
+        vec![]
+    };
+    let focused_before = current_state
+        .clients
         .iter()
-        .map(|c| c.address.clone())
-        .collect();
+        .find(|c| c.focus_history_id == Some(0))
+        .map(|c| c.address.clone());
+    let mut restorer = Restorer::new(current_state.clients, config, matcher);
+    restorer.observers = observers;
+    if options.confirm_each {
+        restorer.prompt = Some(LaunchPrompt::new());
+    }
+    restorer.safe = options.safe;
+    restorer.misplaced = MisplacedWindows::watch(&config.event_buffer);
+
+    if reconfigure {
+        // Bring up bars and other companions first: bars reserve screen
+        // space, which changes where tiled windows end up.
+        companions::start_missing(&config.companions);
+        restore_input(snapshot.input.as_ref(), options.restore_input);
+    }
 
     // Preserve the currently active workspace so restore doesn't leave you elsewhere.
     // This reflects the workspace on the currently focused monitor (where you ran the command).
-    let original_workspace_id = ipc::get_active_workspace()
-        .map(|ws| ws.id)
-        .unwrap_or(1);
+    let original_workspace = ipc::get_active_workspace().ok();
+    let original_workspace_id = original_workspace.as_ref().map_or(1, |ws| ws.id);
 
-    // Track restored windows to avoid double-matching later
-    let mut restored_addresses: HashSet<String> = HashSet::new();
+    // 2. Apply restore rules, then restore per-workspace to allow
+    // deterministic tiling order reconstruction.
+    let clients = restorer.select(snapshot, config, options)?;
+    restorer.progress.set_total(clients.len());
+    restorer.emit(|o| o.plan_built(&clients));
 
-    // 2. Restore per-workspace to allow deterministic tiling order reconstruction.
-    let mut by_workspace: HashMap<i32, Vec<ipc::HyprClient>> = HashMap::new();
-    for client in &snapshot.clients {
-        by_workspace
-            .entry(client.workspace.id)
-            .or_default()
-            .push(client.clone());
+    for (delay, workspaces) in waves::by_workspace(waves::group(clients, &config.waves)) {
+        if !delay.is_zero() {
+            println!(
+                "   Waiting {} ms before the next launch wave",
+                delay.as_millis()
+            );
+            std::thread::sleep(delay);
+        }
+        for (workspace_id, saved_clients) in &workspaces {
+            restore_workspace(&mut restorer, *workspace_id, saved_clients);
+        }
     }
 
-    let mut workspace_ids: Vec<i32> = by_workspace.keys().copied().collect();
-    workspace_ids.sort_unstable();
+    // 3. Return to the original workspace (best effort).
+    let _ = ipc::dispatch_typed(Dispatch::Workspace(WorkspaceRef::Id(original_workspace_id)));
+    // Switching workspaces closes scratchpads, so they are re-shown after it
+    if reconfigure {
+        monitors::restore_special_workspaces(
+            &snapshot.monitors,
+            original_workspace
+                .as_ref()
+                .map_or("", |ws| ws.monitor.as_str()),
+        );
+    }
+    if let Some(cursor) = snapshot.cursor.filter(|_| options.restore_cursor) {
+        if let Err(e) = ipc::move_cursor(cursor.x, cursor.y) {
+            eprintln!("   ⚠️ Failed to restore the cursor position: {}", e);
+        }
+    }
 
-    for workspace_id in workspace_ids {
-        let Some(saved_clients) = by_workspace.get(&workspace_id) else {
-            continue;
-        };
+    // Focus goes back where it was unless asked otherwise, whatever the
+    // restore focused along the way; `nofocus` windows are never focused
+    let saved_focus = options
+        .restore_focus
+        .then(|| saved_focus(&snapshot.clients, &restorer.attempted))
+        .flatten();
+    if let Some(address) = saved_focus.or(focused_before) {
+        let _ = ipc::focus_window(&address);
+    }
 
-        // Move focus to the workspace we're restoring (best effort).
-        let _ = ipc::dispatch(&format!("workspace {}", workspace_id));
+    let mut report = restorer.report;
+    if whole_session {
+        report.missing_layers = verify_layers(&snapshot.layers, config);
+    }
+    report.missing_plugins = missing_plugins;
+    let temporary = keywords.originals().to_vec();
+    let reverted = keywords.revert();
+    report.rollbacks = temporary
+        .into_iter()
+        .map(|(setting, value)| Rollback {
+            setting,
+            value,
+            error: reverted.as_ref().err().map(|e| e.to_string()),
+        })
+        .collect();
+    link_window_ids(&report, config);
+    restorer.progress.finish(&format!(
+        "{} matched, {} launched, {} failed",
+        report.count(Outcome::Matched),
+        report.count(Outcome::Launched),
+        report.count(Outcome::Failed)
+    ));
+    report.duration_ms = started.elapsed().as_millis() as u64;
+    for observer in &mut restorer.observers {
+        observer.finished(&report);
+    }
+    Ok(report)
+}
 
-        // Partition: tiling windows first (tree restore), floating/pinned after.
-        let mut tiled: Vec<ipc::HyprClient> = Vec::new();
-        let mut floating_or_pinned: Vec<ipc::HyprClient> = Vec::new();
-        for c in saved_clients {
-            if c.pinned || c.floating {
-                floating_or_pinned.push(c.clone());
-            } else {
-                tiled.push(c.clone());
-            }
+/// Let restored windows carry on under their saved IDs, for hooks and the
+/// next snapshot
+fn link_window_ids(report: &RestoreReport, config: &Config) {
+    let mut ids = WindowIds::load(&RuntimeContext::for_config(config));
+    for entry in &report.entries {
+        if let (Some(address), Some(id)) = (&entry.address, &entry.window_id) {
+            ids.link(address, id);
+        }
+    }
+    if let Err(e) = ids.save() {
+        eprintln!("   ⚠️ Could not record window IDs: {}", e);
+    }
+}
+
+/// Restore one workspace's windows from a wave: tiled windows in split-tree
+/// order first, then floating and pinned ones
+fn restore_workspace(
+    restorer: &mut Restorer,
+    workspace_id: i32,
+    saved_clients: &[ipc::HyprClient],
+) {
+    // Move focus to the workspace we're restoring (best effort). Special
+    // workspaces are left hidden; they are shown again at the end.
+    if workspace_id >= 0 {
+        let _ = ipc::dispatch_typed(Dispatch::Workspace(WorkspaceRef::Id(workspace_id)));
+    }
+
+    // Partition: tiling windows first (tree restore), floating/pinned after.
+    let mut tiled: Vec<ipc::HyprClient> = Vec::new();
+    let mut floating_or_pinned: Vec<ipc::HyprClient> = Vec::new();
+    for c in saved_clients {
+        if c.pinned || c.floating {
+            floating_or_pinned.push(c.clone());
+        } else {
+            tiled.push(c.clone());
         }
+    }
 
-        if tiled.len() == 1 {
-            // Single tiled window: just ensure it exists and is moved.
-            let saved = &tiled[0];
-            let _ = ensure_restored(
-                saved,
-                &mut available_clients,
-                &baseline_addresses,
-                &mut restored_addresses,
-                Duration::from_secs(10),
+    if tiled.len() == 1 {
+        // Single tiled window: just ensure it exists and is moved.
+        let _ = restorer.ensure_restored(&tiled[0]);
+    } else if tiled.len() > 1 {
+        // Build a balanced split tree from saved geometry and replay it using dwindle preselect.
+        let rects: Vec<Rect> = tiled.iter().map(Rect::from_client).collect();
+        let indices: Vec<usize> = (0..tiled.len()).collect();
+        let tree = build_split_tree(&rects, &indices);
+        if let Err(e) = restorer.restore_split_tree(&tree, &tiled) {
+            eprintln!(
+                "   ⚠️ Failed to restore tiling order for workspace {}: {}",
+                workspace_id, e
             );
-        } else if tiled.len() > 1 {
-            // Build a balanced split tree from saved geometry and replay it using dwindle preselect.
-            let rects: Vec<Rect> = tiled.iter().map(Rect::from_client).collect();
-            let indices: Vec<usize> = (0..tiled.len()).collect();
-            let tree = build_split_tree(&rects, &indices);
-            if let Err(e) = restore_split_tree(
-                &tree,
-                workspace_id,
-                &tiled,
-                &mut available_clients,
-                &baseline_addresses,
-                &mut restored_addresses,
-                Duration::from_secs(10),
-            ) {
-                eprintln!(
-                    "   ⚠️ Failed to restore tiling order for workspace {}: {}",
-                    workspace_id, e
-                );
-                // Best-effort fallback: restore remaining tiled windows without ordering.
-                for saved in &tiled {
-                    let _ = ensure_restored(
-                        saved,
-                        &mut available_clients,
-                        &baseline_addresses,
-                        &mut restored_addresses,
-                        Duration::from_secs(10),
-                    );
-                }
+            // Best-effort fallback: restore remaining tiled windows without ordering.
+            for saved in &tiled {
+                let _ = restorer.ensure_restored(saved);
             }
         }
+    }
 
-        // Restore floating/pinned windows (pixel placement if floating).
-        for saved in &floating_or_pinned {
-            let _ = ensure_restored(
-                saved,
-                &mut available_clients,
-                &baseline_addresses,
-                &mut restored_addresses,
-                Duration::from_secs(10),
-            );
+    // Restore floating/pinned windows (pixel placement if floating).
+    for saved in &floating_or_pinned {
+        let _ = restorer.ensure_restored(saved);
+    }
+}
+
+/// Input settings are personal enough that they are only applied when asked
+/// for; otherwise the user is told when the snapshot's settings differ
+fn restore_input(saved: Option<&ipc::InputSettings>, apply: bool) {
+    let Some(saved) = saved else {
+        return;
+    };
+
+    if apply {
+        match saved.apply() {
+            Ok(0) => {}
+            Ok(count) => println!("   Re-applied {} input settings", count),
+            Err(e) => eprintln!("   ⚠️ Failed to restore input settings: {}", e),
         }
+    } else if !saved.differences(&ipc::InputSettings::capture()).is_empty() {
+        println!(
+            "   Input settings differ from the snapshot; pass --restore-input to re-apply them"
+        );
     }
+}
 
-    // 3. Return to the original workspace (best effort).
-    let _ = ipc::dispatch(&format!("workspace {}", original_workspace_id));
+/// Re-apply saved workspace rules for workspaces that currently have none,
+/// leaving rules from the user's Hyprland config untouched
+fn restore_workspace_rules(saved: &[ipc::HyprWorkspaceRule], keywords: &mut KeywordTransaction) {
+    if saved.is_empty() {
+        return;
+    }
 
-    Ok(())
+    let current = ipc::get_workspace_rules().unwrap_or_default();
+    for rule in new_workspace_rules(saved, &current) {
+        let value = rule.to_keyword();
+        println!("   Applying workspace rule: {}", value);
+        keywords.set("workspace", &value);
+    }
 }
 
-fn resolve_command(class: &str) -> String {
-    let lower = class.to_lowercase();
-    match lower.as_str() {
-        "brave-browser" => "brave".to_string(),
-        "code" => "code".to_string(), // VS Code often has class "Code"
-        "google-chrome" => "google-chrome-stable".to_string(),
-        "com.mitchellh.ghostty" => "ghostty".to_string(),
-        _ => lower,
+/// Saved workspace rules for workspaces that have none now
+fn new_workspace_rules<'a>(
+    saved: &'a [ipc::HyprWorkspaceRule],
+    current: &[ipc::HyprWorkspaceRule],
+) -> Vec<&'a ipc::HyprWorkspaceRule> {
+    saved
+        .iter()
+        .filter(|r| {
+            !current
+                .iter()
+                .any(|c| c.workspace_string == r.workspace_string)
+        })
+        .collect()
+}
+
+/// Address of the restored window that had focus at save time, unless it
+/// doesn't take focus
+fn saved_focus(
+    saved: &[ipc::HyprClient],
+    restored: &HashMap<String, Option<ipc::HyprClient>>,
+) -> Option<String> {
+    let focused = saved
+        .iter()
+        .find(|c| c.focus_history_id == Some(0) && !c.no_focus())?;
+    let current = restored.get(&focused.address)?.as_ref()?;
+    Some(current.address.clone())
+}
+
+/// Warn about bars, wallpapers and similar surfaces that were running at
+/// save time but aren't now; hyprdrover doesn't launch these itself
+fn verify_layers(saved: &[ipc::HyprLayer], config: &Config) -> Vec<String> {
+    if saved.is_empty() {
+        return vec![];
+    }
+
+    let current = ipc::get_layers().unwrap_or_default();
+    let missing = verify::missing_layers(saved, &current, &config.ignored_layers);
+    for namespace in &missing {
+        eprintln!(
+            "   ⚠️ Layer surface `{}` is missing; start it from exec-once or your session",
+            namespace
+        );
     }
+    missing
 }
 
-fn launched_window_matches(current: &ipc::HyprClient, saved: &ipc::HyprClient) -> bool {
-    if let (Some(current_path), Some(saved_path)) = (&current.exec_path, &saved.exec_path) {
-        if current_path == saved_path {
-            return true;
+/// Warn before any window is placed about plugins the saved layout may rely
+/// on (e.g. a tiling layout plugin); hyprdrover doesn't load plugins itself
+fn verify_plugins(saved: &[ipc::HyprPlugin]) -> Vec<String> {
+    if saved.is_empty() {
+        return vec![];
+    }
+
+    let current = ipc::get_plugins().unwrap_or_default();
+    let missing = verify::missing_plugins(saved, &current);
+    for name in &missing {
+        eprintln!(
+            "   ⚠️ Plugin `{}` was loaded when the session was saved but isn't now; windows may be laid out differently",
+            name
+        );
+    }
+    missing
+}
+
+/// State shared by every window restored during one `restore_session` run
+struct Restorer {
+    available_clients: Vec<ipc::HyprClient>,
+    // Baseline addresses to identify newly spawned windows after launching
+    baseline_addresses: HashSet<String>,
+    // Processes that already had windows; a new window from one of them may
+    // be a single-instance app handing a launch over
+    baseline_pids: HashSet<i32>,
+    // Track restored windows to avoid double-matching later
+    restored_addresses: HashSet<String>,
+    launch_timeout: Duration,
+    dependency_timeout: Duration,
+    pacing: PacingConfig,
+    // Launches whose memory may not show up as used yet
+    pending: PendingLaunches,
+    report: RestoreReport,
+    matcher: Box<dyn WindowMatcher>,
+    resolvers: Vec<Box<dyn LaunchResolver>>,
+    aliases: AliasTable,
+    // Read on the first launch that needs them
+    desktop_entries: OnceCell<DesktopEntries>,
+    single_instance: SingleInstanceApps,
+    misplaced: MisplacedWindows,
+    // Live window paired with each saved one up front, by saved address
+    associations: HashMap<String, String>,
+    // Rule decisions per saved window address
+    decisions: HashMap<String, Decision>,
+    progress: ProgressNotifier,
+    // Present with `--confirm-each`
+    prompt: Option<LaunchPrompt>,
+    // Set with `--safe`: missing windows are reported, never launched
+    safe: bool,
+    // Scope unit of the app launched for the window being restored
+    launched_unit: Option<String>,
+    // Every window this run restores, so a dependency can be restored early
+    saved: Vec<ipc::HyprClient>,
+    // Outcome per saved window address, so early-restored windows aren't redone
+    attempted: HashMap<String, Option<ipc::HyprClient>>,
+    // Saved windows whose dependencies are being brought up
+    waiting: HashSet<String>,
+    dependencies: Vec<DependencyConfig>,
+    compositor_log_lines: usize,
+    // Terminal each saved window swallowed, by the swallowing window's address
+    swallowed: HashMap<String, ipc::HyprClient>,
+    // Saved monitor names by id, for `{monitor}` in launch commands
+    monitor_names: HashMap<i64, String>,
+    observers: Vec<Box<dyn RestoreObserver>>,
+    #[cfg(feature = "scripting")]
+    script: Option<script::RestoreScript>,
+}
+
+impl Restorer {
+    fn new(
+        available_clients: Vec<ipc::HyprClient>,
+        config: &Config,
+        matcher: Box<dyn WindowMatcher>,
+    ) -> Self {
+        let baseline_addresses = available_clients
+            .iter()
+            .map(|c| c.address.clone())
+            .collect();
+
+        #[cfg(not(feature = "scripting"))]
+        if config.script.is_some() {
+            eprintln!(
+                "Warning: `script` is set but hyprdrover was built without scripting support"
+            );
+        }
+
+        let baseline_pids = available_clients.iter().map(|c| c.pid).collect();
+
+        Self {
+            available_clients,
+            baseline_addresses,
+            baseline_pids,
+            restored_addresses: HashSet::new(),
+            launch_timeout: Duration::from_secs(config.timeouts.launch_secs),
+            dependency_timeout: Duration::from_secs(config.timeouts.dependency_secs),
+            // Load and memory are only meaningful for a local compositor
+            pacing: if ipc::socket::is_remote() {
+                PacingConfig::default()
+            } else {
+                config.pacing.clone()
+            },
+            pending: PendingLaunches::new(),
+            report: RestoreReport::start(),
+            matcher,
+            resolvers: resolver::load_plugins(&config.plugins),
+            aliases: AliasTable::with_overrides(&config.aliases),
+            desktop_entries: OnceCell::new(),
+            single_instance: SingleInstanceApps::with_overrides(&config.single_instance),
+            misplaced: MisplacedWindows::default(),
+            associations: HashMap::new(),
+            decisions: HashMap::new(),
+            progress: ProgressNotifier::new(config.notifications.progress),
+            prompt: None,
+            safe: false,
+            launched_unit: None,
+            saved: vec![],
+            attempted: HashMap::new(),
+            waiting: HashSet::new(),
+            dependencies: config.dependencies.clone(),
+            compositor_log_lines: config.compositor_log_lines,
+            swallowed: HashMap::new(),
+            monitor_names: HashMap::new(),
+            observers: vec![],
+            #[cfg(feature = "scripting")]
+            script: config.script.as_deref().and_then(|path| {
+                script::RestoreScript::load(path)
+                    .map_err(|e| eprintln!("Warning: ignoring restore script {}: {}", path, e))
+                    .ok()
+            }),
+        }
+    }
+
+    /// The saved windows to restore once rules, swallowed terminals and
+    /// `--only` are applied, paired up front with the open windows that
+    /// stand in for them
+    fn select(
+        &mut self,
+        snapshot: &SessionSnapshot,
+        config: &Config,
+        options: &RestoreOptions,
+    ) -> Result<Vec<ipc::HyprClient>, Box<dyn Error>> {
+        let rules = RuleSet::from_config(config);
+        let mut clients = vec![];
+        for client in &snapshot.clients {
+            let decision = rules.evaluate(client);
+            if decision.ignore {
+                println!("   Ignoring window: {} ({})", client.class, client.title);
+                continue;
+            }
+
+            let mut client = client.clone();
+            RuleSet::apply(&decision, &mut client);
+            self.decisions.insert(client.address.clone(), decision);
+            clients.push(client);
+        }
+
+        let (mut clients, terminals) = swallow::pair_up(clients);
+        // Selected after pairing, so a selected app still gets its terminal back
+        if let Some(only) = &options.only {
+            clients.retain(|c| only.matches(c));
+            if clients.is_empty() {
+                return Err("no saved window matches the --only selection".into());
+            }
+        }
+        for client in clients
+            .iter()
+            .filter(|c| terminals.contains_key(&c.address))
+        {
+            println!(
+                "   {} swallowed a {} window; restoring them as one",
+                client.class, terminals[&client.address].class
+            );
+        }
+        self.swallowed = terminals;
+        self.monitor_names = snapshot
+            .monitors
+            .iter()
+            .map(|m| (m.id, m.name.clone()))
+            .collect();
+
+        self.saved = clients.clone();
+        // Windows that are always relaunched never claim a running one
+        let associations = associate::associate(&clients, &self.available_clients, |s, c| {
+            self.score(c, s)
+                .filter(|_| !self.decision(s).always_relaunch)
+        });
+        if !associations.is_empty() {
+            println!(
+                "   {} of {} saved windows are already open",
+                associations.len(),
+                clients.len()
+            );
+        }
+        self.associations = associations;
+        Ok(clients)
+    }
+
+    fn emit(&mut self, mut callback: impl FnMut(&mut dyn RestoreObserver)) {
+        for observer in &mut self.observers {
+            callback(observer.as_mut());
         }
     }
 
-    let saved_class = saved.class.to_lowercase();
-    let saved_initial_class = saved.initial_class.to_lowercase();
-    let current_class = current.class.to_lowercase();
-    let current_initial_class = current.initial_class.to_lowercase();
+    /// How well `current` matches `saved`; a script decision overrides the matcher
+    fn score(&self, current: &ipc::HyprClient, saved: &ipc::HyprClient) -> Option<u32> {
+        #[cfg(feature = "scripting")]
+        if let Some(decision) = self
+            .script
+            .as_ref()
+            .and_then(|script| script.matches(saved, current))
+        {
+            return decision.then_some(u32::MAX);
+        }
+
+        self.matcher.score(saved, current)
+    }
 
-    (!saved_class.is_empty()
-        && (current_class == saved_class || current_initial_class == saved_class))
-        || (!saved_initial_class.is_empty()
-            && (current_class == saved_initial_class || current_initial_class == saved_initial_class))
+    /// How likely a new window is to belong to the launch tagged
+    /// `launch_id`: 2 if its process descends from it, 0 if it is a fresh
+    /// process that doesn't, 1 when that can't be told (remote, a process
+    /// that was already running, an unreadable environment). Apps started
+    /// through D-Bus activation or a launcher service lose the marker, so
+    /// this only breaks ties between matching windows.
+    fn lineage_rank(&self, client: &ipc::HyprClient, launch_id: &str) -> u8 {
+        if ipc::socket::is_remote() || self.baseline_pids.contains(&client.pid) {
+            return 1;
+        }
+        match lineage::descends_from(client.pid, launch_id) {
+            Some(true) => 2,
+            Some(false) => 0,
+            None => 1,
+        }
+    }
+
+    /// For a single-instance app that is running now, the arguments that
+    /// make it open another window (empty if it can't)
+    fn new_window_args(&mut self, saved: &ipc::HyprClient) -> Option<String> {
+        let args = self.single_instance.new_window_args(saved)?;
+        let same_app = |c: &ipc::HyprClient| {
+            c.class.eq_ignore_ascii_case(&saved.class)
+                || c.initial_class.eq_ignore_ascii_case(&saved.initial_class)
+        };
+        let running: Vec<i32> = ipc::get_clients()
+            .unwrap_or_default()
+            .iter()
+            .filter(|c| same_app(c))
+            .map(|c| c.pid)
+            .collect();
+        if running.is_empty() {
+            return None;
+        }
+        self.baseline_pids.extend(running);
+        Some(args.to_string())
+    }
+
+    fn decision(&self, saved: &ipc::HyprClient) -> Decision {
+        self.decisions
+            .get(&saved.address)
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// `command` with the placeholders filled in from `saved`
+    fn expand(&self, command: &str, saved: &ipc::HyprClient) -> String {
+        let monitor = self.monitor_names.get(&saved.monitor);
+        template::expand(command, saved, monitor.map(String::as_str))
+    }
+
+    /// Command for a missing window: a rule wins, then the script, plugins,
+    /// aliases and desktop entries, then the saved executable or the class
+    fn launch_command(&self, saved: &ipc::HyprClient) -> String {
+        if let Some(command) = &saved.launch_command {
+            return self.expand(command, saved);
+        }
+        if let Some(command) = self.decision(saved).command {
+            return self.expand(&command, saved);
+        }
+
+        #[cfg(feature = "scripting")]
+        if let Some(command) = self
+            .script
+            .as_ref()
+            .and_then(|script| script.launch_command(saved))
+        {
+            return command;
+        }
+
+        if let Some((name, command)) = resolver::resolve_with(&self.resolvers, saved) {
+            println!("      -> Resolved by plugin {}", name);
+            return command;
+        }
+
+        // An app started from a terminal comes back inside a fresh one
+        if let (Some(terminal), Some(argv)) =
+            (self.swallowed.get(&saved.address), &saved.command_line)
+        {
+            return swallow::terminal_command(&self.launch_command(terminal), argv);
+        }
+
+        let raw_name = if !saved.initial_class.is_empty() {
+            &saved.initial_class
+        } else {
+            &saved.class
+        };
+        // Aliases go before the executable path: Electron apps run as a
+        // shared `electron` binary that wouldn't open the app
+        if let Some(command) = self
+            .aliases
+            .command(raw_name)
+            .or_else(|| self.aliases.command(&saved.class))
+        {
+            return self.expand(command, saved);
+        }
+        // Installed apps say how they are started, which beats guessing
+        // from the executable (Flatpaks, wrapper scripts)
+        let entries = self.desktop_entries.get_or_init(DesktopEntries::load);
+        if let Some(command) = entries
+            .command(&saved.class)
+            .or_else(|| entries.command(raw_name))
+        {
+            return command.to_string();
+        }
+        match &saved.exec_path {
+            Some(path) => path.clone(),
+            None => raw_name.to_lowercase(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -161,6 +741,16 @@ enum SplitTree {
     },
 }
 
+impl SplitTree {
+    /// The leaf whose window a node's restore returns as its pivot
+    fn first_leaf(&self) -> usize {
+        match self {
+            SplitTree::Leaf(idx) => *idx,
+            SplitTree::Node { first, .. } => first.first_leaf(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Rect {
     x: i32,
@@ -239,152 +829,212 @@ fn build_split_tree(rects: &[Rect], indices: &[usize]) -> SplitTree {
     }
 }
 
-fn restore_split_tree(
-    tree: &SplitTree,
-    workspace_id: i32,
-    saved_clients: &[ipc::HyprClient],
-    available_clients: &mut Vec<ipc::HyprClient>,
-    baseline_addresses: &HashSet<String>,
-    restored_addresses: &mut HashSet<String>,
-    launch_timeout: Duration,
-) -> Result<String, Box<dyn Error>> {
-    match tree {
-        SplitTree::Leaf(idx) => {
-            let saved = &saved_clients[*idx];
-            let current = ensure_restored(
-                saved,
-                available_clients,
-                baseline_addresses,
-                restored_addresses,
-                launch_timeout,
-            )?;
-            Ok(current.address)
-        }
-        SplitTree::Node { axis, first, second } => {
-            // Restore the first subtree; then use preselect to create the split and restore the second.
-            let pivot_addr = restore_split_tree(
-                first,
-                workspace_id,
-                saved_clients,
-                available_clients,
-                baseline_addresses,
-                restored_addresses,
-                launch_timeout,
-            )?;
-
-            // Focus pivot and preselect direction for the next window.
-            let _ = ipc::dispatch(&format!("focuswindow address:{}", pivot_addr));
-            let dir = match axis {
-                SplitAxis::X => "r",
-                SplitAxis::Y => "d",
-            };
-            // If user isn't on dwindle layout, this may fail; ignore and continue best-effort.
-            let _ = ipc::dispatch(&format!("layoutmsg preselect {}", dir));
-
-            let _second_addr = restore_split_tree(
-                second,
-                workspace_id,
-                saved_clients,
-                available_clients,
-                baseline_addresses,
-                restored_addresses,
-                launch_timeout,
-            )?;
-
-            Ok(pivot_addr)
+impl Restorer {
+    fn restore_split_tree(
+        &mut self,
+        tree: &SplitTree,
+        saved_clients: &[ipc::HyprClient],
+    ) -> Result<String, Box<dyn Error>> {
+        match tree {
+            SplitTree::Leaf(idx) => {
+                let current = self.ensure_restored(&saved_clients[*idx])?;
+                Ok(current.address)
+            }
+            SplitTree::Node { axis, first, second } => {
+                // Restore the first subtree; then use preselect to create the split and restore the second.
+                let pivot_addr = self.restore_split_tree(first, saved_clients)?;
+
+                // Focus pivot and preselect direction for the next window,
+                // unless the pivot must not take focus; the split is then
+                // left to the layout.
+                if !saved_clients[first.first_leaf()].no_focus() {
+                    let _ = ipc::focus_window(&pivot_addr);
+                    let dir = match axis {
+                        SplitAxis::X => "r",
+                        SplitAxis::Y => "d",
+                    };
+                    // If user isn't on dwindle layout, this may fail; ignore and continue best-effort.
+                    let _ = ipc::dispatch_typed(Dispatch::LayoutMsg(format!("preselect {}", dir)));
+                }
+
+                let _second_addr = self.restore_split_tree(second, saved_clients)?;
+
+                Ok(pivot_addr)
+            }
         }
     }
-}
 
-fn ensure_restored(
-    saved_client: &ipc::HyprClient,
-    available_clients: &mut Vec<ipc::HyprClient>,
-    baseline_addresses: &HashSet<String>,
-    restored_addresses: &mut HashSet<String>,
-    timeout: Duration,
-) -> Result<ipc::HyprClient, Box<dyn Error>> {
-    // 1) Try to match an already-running client first.
-    if let Some(index) = available_clients
-        .iter()
-        .position(|c| launched_window_matches(c, saved_client))
-    {
-        let current_client = available_clients.remove(index);
-        println!(
-            "   Restoring window: {} ({})",
-            current_client.class, current_client.title
+    fn ensure_restored(
+        &mut self,
+        saved_client: &ipc::HyprClient,
+    ) -> Result<ipc::HyprClient, Box<dyn Error>> {
+        // Already brought up early as another app's dependency
+        if let Some(done) = self.attempted.get(&saved_client.address) {
+            return done
+                .clone()
+                .ok_or_else(|| "already attempted as a dependency".into());
+        }
+
+        let start = Instant::now();
+        let result = self.match_or_launch(saved_client);
+        self.attempted.insert(
+            saved_client.address.clone(),
+            result.as_ref().ok().map(|(client, _)| client.clone()),
         );
-        position::restore_window_position(&current_client, saved_client)?;
-        restored_addresses.insert(current_client.address.clone());
-        return Ok(current_client);
+        self.report.record(saved_client, &result, start.elapsed());
+        if let Some(entry) = self.report.entries.last_mut() {
+            entry.unit = self.launched_unit.take().map(|u| format!("{}.scope", u));
+            if entry.outcome == Outcome::Failed && self.compositor_log_lines > 0 {
+                // Shows why Hyprland rejected a dispatch, if it logged it
+                let log = ipc::get_rolling_log().unwrap_or_default();
+                let skip = log.len().saturating_sub(self.compositor_log_lines);
+                entry.compositor_log = log[skip..].to_vec();
+            }
+        }
+        self.progress.advance(self.report.entries.len());
+        result.map(|(client, _)| client)
     }
 
-    // 2) Launch missing app (target workspace is best-effort; we still explicitly move it).
-    println!("   ⚠️ Window missing: {}", saved_client.class);
-    let _ = std::process::Command::new("notify-send")
-        .arg("Restoring Session")
-        .arg(format!("Launching {}...", saved_client.class))
-        .spawn();
+    /// Hold a launch until its dependencies are up, restoring dependencies
+    /// that are part of this session first. Gives up after
+    /// `timeouts.dependency_secs` and launches anyway.
+    fn wait_for_dependencies(&mut self, saved_client: &ipc::HyprClient) {
+        let requirements = dependencies::requirements_for(&self.dependencies, saved_client);
+        if requirements.is_empty() {
+            return;
+        }
 
-    let command = if let Some(path) = &saved_client.exec_path {
-        path.clone()
-    } else {
-        let raw_name = if !saved_client.initial_class.is_empty() {
-            &saved_client.initial_class
-        } else {
-            &saved_client.class
-        };
-        resolve_command(raw_name)
-    };
+        self.waiting.insert(saved_client.address.clone());
+        for requirement in requirements {
+            if let dependencies::Requirement::Window(class) = &requirement {
+                let pending = self
+                    .saved
+                    .iter()
+                    .find(|c| {
+                        dependencies::has_class(c, class)
+                            && !self.attempted.contains_key(&c.address)
+                            && !self.waiting.contains(&c.address)
+                    })
+                    .cloned();
+                if let Some(dependency) = pending {
+                    println!("      -> Restoring dependency {} first", dependency.class);
+                    let _ = self.ensure_restored(&dependency);
+                }
+            }
 
-    println!("      -> Launching: {}", command);
-    let exec_arg = format!(
-        "[workspace {} silent] {}",
-        saved_client.workspace.id, command
-    );
-
-    let output = std::process::Command::new("hyprctl")
-        .arg("dispatch")
-        .arg("exec")
-        .arg(&exec_arg)
-        .output();
-
-    match output {
-        Ok(out) => {
-            if !out.status.success() {
-                return Err(format!(
-                    "Failed to launch {}: {}",
-                    command,
-                    String::from_utf8_lossy(&out.stderr)
-                )
-                .into());
+            let met = dependencies::wait_until(self.dependency_timeout, || {
+                let live = match requirement {
+                    dependencies::Requirement::Window(_) => ipc::capture_state()
+                        .map(|state| state.clients)
+                        .unwrap_or_default(),
+                    dependencies::Requirement::Hook(_) => vec![],
+                };
+                requirement.is_met(&live)
+            });
+            if !met {
+                eprintln!(
+                    "      ⚠️ Dependency {} of {} not satisfied; launching anyway",
+                    requirement, saved_client.class
+                );
             }
         }
-        Err(e) => return Err(format!("Failed to execute hyprctl: {}", e).into()),
+        self.waiting.remove(&saved_client.address);
     }
 
-    // 3) Poll until the newly spawned window appears.
-    let poll_interval = Duration::from_millis(250);
-    let start = Instant::now();
+    /// Index among the available windows of the one to use for `saved`:
+    /// the one paired with it up front, else the best-scoring candidate not
+    /// paired with another (ties go to the earliest window)
+    fn find_running(&self, saved: &ipc::HyprClient) -> Option<usize> {
+        let associated = self.associations.get(&saved.address);
+        associated
+            .and_then(|address| {
+                self.available_clients
+                    .iter()
+                    .position(|c| &c.address == address)
+            })
+            .or_else(|| {
+                if self.decision(saved).always_relaunch {
+                    return None;
+                }
+                self.available_clients
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, c)| !self.associations.values().any(|a| *a == c.address))
+                    .filter_map(|(i, c)| self.score(c, saved).map(|s| (i, s)))
+                    .min_by_key(|&(_, score)| Reverse(score))
+                    .map(|(i, _)| i)
+            })
+    }
 
-    while start.elapsed() < timeout {
-        let new_state = ipc::capture_state()?;
-        if let Some(current_client) = new_state.clients.iter().find(|c| {
-            !baseline_addresses.contains(&c.address)
-                && !restored_addresses.contains(&c.address)
-                && launched_window_matches(c, saved_client)
-        }) {
-            println!("   Positioning launched window: {}", saved_client.class);
-            position::restore_window_position(current_client, saved_client)?;
-            restored_addresses.insert(current_client.address.clone());
-            return Ok(current_client.clone());
+    /// The command starting the app of `saved`, which isn't running, or why
+    /// it stays missing
+    fn missing_command(&mut self, saved: &ipc::HyprClient) -> Result<String, Skipped> {
+        if !self.decision(saved).may_launch() {
+            return Err(Skipped(
+                "not running and a rule forbids relaunching it".to_string(),
+            ));
+        }
+        if self.safe {
+            return Err(Skipped(
+                "not running, and safe mode never launches apps".to_string(),
+            ));
+        }
+        let command = self.launch_command(saved);
+        match self.new_window_args(saved).as_deref() {
+            None => Ok(command),
+            Some("") => Err(Skipped(
+                "a single-instance app that is already running".to_string(),
+            )),
+            Some(args) => Ok(format!("{} {}", command, args)),
         }
-
-        std::thread::sleep(poll_interval);
     }
 
-    Err(format!(
-        "Could not find launched window for positioning (timed out): {}",
-        saved_client.class
-    )
-    .into())
+    fn match_or_launch(
+        &mut self,
+        saved_client: &ipc::HyprClient,
+    ) -> Result<(ipc::HyprClient, Outcome), Box<dyn Error>> {
+        // 1) Try to match an already-running client first
+        let decision = self.decision(saved_client);
+        if let Some(index) = self.find_running(saved_client) {
+            let current_client = self.available_clients.remove(index);
+            println!(
+                "   Restoring window: {} ({})",
+                current_client.class, current_client.title
+            );
+            self.emit(|o| o.window_matched(saved_client, &current_client));
+            if decision.position_only {
+                position::restore_placement(&current_client, saved_client)?;
+            } else {
+                // Safe mode only moves windows; their properties stay as they are
+                position::restore_window_position(&current_client, saved_client, !self.safe)?;
+            }
+            self.emit(|o| o.positioned(saved_client, &current_client));
+            self.restored_addresses
+                .insert(current_client.address.clone());
+            return Ok((current_client, Outcome::Matched));
+        }
+
+        // 2) Launch missing app (target workspace is best-effort; we still explicitly move it).
+        println!("   ⚠️ Window missing: {}", saved_client.class);
+        let command = self.missing_command(saved_client).map_err(|skipped| {
+            println!("      -> Not launching: {}", skipped);
+            Box::new(skipped)
+        })?;
+        if let Some(prompt) = &mut self.prompt {
+            if !prompt.confirm(&command, saved_client.workspace.id) {
+                return Err(Box::new(Skipped("declined at the prompt".to_string())));
+            }
+        }
+
+        self.wait_for_dependencies(saved_client);
+        if !pacing::wait_for_headroom(&self.pacing, &saved_client.class, &mut self.pending) {
+            return Err(Box::new(Skipped(
+                "skipped at the low memory prompt".to_string(),
+            )));
+        }
+        self.progress
+            .launching(self.report.entries.len(), &saved_client.class);
+
+        self.spawn(saved_client, &command)
+    }
 }
@@ -0,0 +1,356 @@
+use crate::ipc::{
+    self, Dispatch, HyprMonitor, HyprWorkspace, KeywordTransaction, OutputBackend, WorkspaceRef,
+};
+use std::collections::BTreeMap;
+
+/// The live monitor that is the same physical screen as `saved`.
+///
+/// Screens are recognised by description (make, model, serial) on the same
+/// connector; snapshots from before descriptions were saved fall back to the
+/// connector name alone.
+pub fn same_hardware<'a>(
+    saved: &HyprMonitor,
+    current: &'a [HyprMonitor],
+) -> Option<&'a HyprMonitor> {
+    current.iter().find(|m| {
+        m.name == saved.name && (saved.description.is_empty() || m.description == saved.description)
+    })
+}
+
+/// Whether the mode, VRR, orientation or scale of `current` differs from `saved`
+pub fn needs_update(saved: &HyprMonitor, current: &HyprMonitor) -> bool {
+    (saved.width, saved.height) != (current.width, current.height)
+        // Reported rates wobble in the last decimals between identical modes
+        || (saved.refresh_rate - current.refresh_rate).abs() > 0.5
+        || saved.vrr != current.vrr
+        || saved.transform != current.transform
+        || (saved.scale - current.scale).abs() > 0.001
+}
+
+/// Saved virtual outputs that no longer exist
+pub fn missing_virtual_outputs<'a>(
+    saved: &'a [HyprMonitor],
+    current: &[HyprMonitor],
+) -> Vec<&'a HyprMonitor> {
+    saved
+        .iter()
+        .filter(|m| m.is_headless() && !current.iter().any(|c| c.name == m.name))
+        .collect()
+}
+
+/// Monitor-configuration phase: recreate saved virtual outputs, then
+/// re-apply the saved mode (resolution and refresh rate), VRR, rotation and
+/// scale to screens that are still connected. The `monitor` keywords are
+/// queued on `keywords`.
+pub fn restore_monitors(
+    saved: &[HyprMonitor],
+    current: &[HyprMonitor],
+    keywords: &mut KeywordTransaction,
+) {
+    let mut failed = vec![];
+    for monitor in missing_virtual_outputs(saved, current) {
+        println!("   Creating virtual output: {}", monitor.name);
+        if let Err(e) = ipc::create_output(OutputBackend::Headless, Some(&monitor.name)) {
+            eprintln!("   ⚠️ {}", e);
+            failed.push(monitor.name.as_str());
+        }
+    }
+
+    for monitor in to_configure(saved, current) {
+        if failed.contains(&monitor.name.as_str()) {
+            continue;
+        }
+        let value = monitor.to_keyword();
+        println!("   Configuring monitor: {}", value);
+        keywords.set("monitor", &value);
+    }
+}
+
+/// Saved monitors whose settings must be applied: connected screens whose
+/// settings changed, and saved virtual outputs that don't exist (a new
+/// output starts with a default mode)
+pub fn to_configure<'a>(saved: &'a [HyprMonitor], current: &[HyprMonitor]) -> Vec<&'a HyprMonitor> {
+    let missing = missing_virtual_outputs(saved, current);
+    saved
+        .iter()
+        .filter(|m| {
+            missing.iter().any(|v| v.name == m.name)
+                || same_hardware(m, current).is_some_and(|live| needs_update(m, live))
+        })
+        .collect()
+}
+
+/// The output taking over the workspaces of each saved monitor that isn't
+/// connected, by saved monitor name. In order of preference: the output
+/// `map` names for the monitor (by connector or description), the same
+/// screen on another connector, the output `map` names for `*`, and the
+/// largest connected output.
+pub fn remap<'a>(
+    saved: &[HyprMonitor],
+    current: &'a [HyprMonitor],
+    map: &BTreeMap<String, String>,
+) -> BTreeMap<String, &'a str> {
+    let connected = |name: &str| current.iter().find(|c| c.name == name);
+    let largest = current.iter().max_by(|a, b| {
+        let area = |m: &HyprMonitor| m.logical_size()[0] * m.logical_size()[1];
+        area(a).total_cmp(&area(b)).then(b.id.cmp(&a.id))
+    });
+
+    saved
+        .iter()
+        .filter(|m| connected(&m.name).is_none())
+        .filter_map(|m| {
+            let target = map
+                .get(&m.name)
+                .or_else(|| map.get(&m.description))
+                .and_then(|name| connected(name))
+                .or_else(|| {
+                    current
+                        .iter()
+                        .find(|c| !m.description.is_empty() && c.description == m.description)
+                })
+                .or_else(|| map.get("*").and_then(|name| connected(name)))
+                .or(largest)?;
+            Some((m.name.clone(), target.name.as_str()))
+        })
+        .collect()
+}
+
+/// Send the workspaces of saved monitors that aren't connected to the
+/// outputs [`remap`] picks: a workspace rule queued on `keywords` places
+/// them when they are created, and those open already are moved now
+pub fn remap_workspaces(
+    saved_monitors: &[HyprMonitor],
+    saved_workspaces: &[HyprWorkspace],
+    map: &BTreeMap<String, String>,
+    keywords: &mut KeywordTransaction,
+) {
+    let current = ipc::get_monitors().unwrap_or_default();
+    if current.is_empty() {
+        return;
+    }
+    let open = ipc::get_workspaces().unwrap_or_default();
+    for (workspace, target) in remapped_workspaces(saved_monitors, saved_workspaces, &current, map)
+    {
+        println!(
+            "   Workspace {} was on {}, which isn't connected; using {}",
+            workspace.name, workspace.monitor, target
+        );
+        keywords.set(
+            "workspace",
+            &format!("{}, monitor:{}", workspace.id, target),
+        );
+        let misplaced = open
+            .iter()
+            .any(|w| w.id == workspace.id && w.monitor != target);
+        if misplaced {
+            let moved = ipc::dispatch_typed(Dispatch::MoveWorkspaceToMonitor {
+                workspace: WorkspaceRef::Id(workspace.id),
+                monitor: target.to_string(),
+            });
+            if let Err(e) = moved {
+                eprintln!("   ⚠️ Failed to move workspace {}: {}", workspace.name, e);
+            }
+        }
+    }
+}
+
+/// Saved workspaces whose monitor isn't connected, with the output
+/// [`remap`] picks for each. Special workspaces follow whichever monitor
+/// shows them, so they are left out.
+pub fn remapped_workspaces<'a, 'b>(
+    saved_monitors: &[HyprMonitor],
+    saved_workspaces: &'a [HyprWorkspace],
+    current: &'b [HyprMonitor],
+    map: &BTreeMap<String, String>,
+) -> Vec<(&'a HyprWorkspace, &'b str)> {
+    let targets = remap(saved_monitors, current, map);
+    saved_workspaces
+        .iter()
+        .filter(|w| w.id > 0)
+        .filter_map(|w| Some((w, *targets.get(&w.monitor)?)))
+        .collect()
+}
+
+/// Special workspaces to show again, as (monitor, special workspace name)
+/// pairs: those shown on a saved monitor that is connected but no longer
+/// shows it
+pub fn hidden_specials<'a>(
+    saved: &'a [HyprMonitor],
+    current: &[HyprMonitor],
+) -> Vec<(&'a str, &'a str)> {
+    saved
+        .iter()
+        .filter_map(|m| Some((m, m.special_workspace.as_ref()?)))
+        .filter(|(m, special)| {
+            current.iter().any(|c| {
+                c.name == m.name
+                    && c.special_workspace.as_ref().map(|s| &s.name) != Some(&special.name)
+            })
+        })
+        .map(|(m, special)| (m.name.as_str(), special.name.as_str()))
+        .collect()
+}
+
+/// Re-show the special workspaces each monitor had open, then give focus
+/// back to `focused_monitor`
+pub fn restore_special_workspaces(saved: &[HyprMonitor], focused_monitor: &str) {
+    let current = ipc::get_monitors().unwrap_or_default();
+    let hidden = hidden_specials(saved, &current);
+    if hidden.is_empty() {
+        return;
+    }
+
+    for (monitor, special) in hidden {
+        println!("   Showing {} on {}", special, monitor);
+        let name = special.strip_prefix("special:").unwrap_or(special);
+        let shown =
+            ipc::dispatch_typed(Dispatch::FocusMonitor(monitor.to_string())).and_then(|_| {
+                ipc::dispatch_typed(Dispatch::ToggleSpecialWorkspace(Some(name.to_string())))
+            });
+        if let Err(e) = shown {
+            eprintln!("   ⚠️ Failed to show {} on {}: {}", special, monitor, e);
+        }
+    }
+    if !focused_monitor.is_empty() {
+        let _ = ipc::dispatch_typed(Dispatch::FocusMonitor(focused_monitor.to_string()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::models::HyprWorkspaceRef;
+
+    fn monitor(name: &str, description: &str, scale: f32, transform: i32) -> HyprMonitor {
+        serde_json::from_value(serde_json::json!({
+            "id": 0, "name": name, "description": description,
+            "width": 1920, "height": 1080, "refreshRate": 59.95,
+            "x": 0, "y": 0, "scale": scale, "transform": transform,
+            "activeWorkspace": { "id": 1, "name": "1" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_remap_prefers_map_then_same_screen_then_largest() {
+        let saved = vec![
+            monitor("eDP-1", "BOE 0x0BCA", 1.0, 0),
+            monitor("DP-1", "LG 27GL850 A", 1.0, 0),
+            monitor("DP-2", "Dell U2720Q", 1.0, 0),
+            monitor("HDMI-A-1", "Samsung", 1.0, 0),
+        ];
+        // The LG moved to another port; the laptop panel is scaled
+        let current = vec![
+            monitor("eDP-1", "BOE 0x0BCA", 2.0, 0),
+            monitor("DP-3", "LG 27GL850 A", 1.0, 0),
+        ];
+        let map = BTreeMap::from([("Dell U2720Q".to_string(), "eDP-1".to_string())]);
+
+        let targets = remap(&saved, &current, &map);
+        assert_eq!(targets.len(), 3);
+        assert_eq!(targets["DP-1"], "DP-3");
+        assert_eq!(targets["DP-2"], "eDP-1");
+        assert_eq!(targets["HDMI-A-1"], "DP-3");
+
+        // `*` beats the largest output; a map naming a missing output is skipped
+        let map = BTreeMap::from([
+            ("*".to_string(), "eDP-1".to_string()),
+            ("HDMI-A-1".to_string(), "DP-9".to_string()),
+        ]);
+        assert_eq!(remap(&saved, &current, &map)["HDMI-A-1"], "eDP-1");
+    }
+
+    #[test]
+    fn test_same_hardware_requires_matching_description() {
+        let current = vec![monitor("DP-1", "LG 27GL850 A", 1.0, 0)];
+
+        assert!(same_hardware(&monitor("DP-1", "LG 27GL850 A", 1.0, 1), &current).is_some());
+        assert!(same_hardware(&monitor("DP-1", "", 1.0, 1), &current).is_some());
+        assert!(same_hardware(&monitor("DP-1", "Dell U2720Q B", 1.0, 1), &current).is_none());
+        assert!(same_hardware(&monitor("HDMI-A-1", "LG 27GL850 A", 1.0, 1), &current).is_none());
+    }
+
+    #[test]
+    fn test_missing_virtual_outputs() {
+        let saved = vec![
+            monitor("DP-1", "LG 27GL850 A", 1.0, 0),
+            monitor("HEADLESS-2", "", 1.0, 0),
+            monitor("stream", "Headless output", 1.0, 0),
+        ];
+        let current = vec![monitor("stream", "Headless output", 1.0, 0)];
+
+        let missing: Vec<&str> = missing_virtual_outputs(&saved, &current)
+            .iter()
+            .map(|m| m.name.as_str())
+            .collect();
+        assert_eq!(missing, vec!["HEADLESS-2"]);
+    }
+
+    #[test]
+    fn test_hidden_specials() {
+        let special = |name: &str| {
+            Some(HyprWorkspaceRef {
+                id: -98,
+                name: name.to_string(),
+            })
+        };
+        let saved = vec![
+            HyprMonitor {
+                special_workspace: special("special:term"),
+                ..monitor("DP-1", "", 1.0, 0)
+            },
+            HyprMonitor {
+                special_workspace: special("special:music"),
+                ..monitor("DP-2", "", 1.0, 0)
+            },
+            HyprMonitor {
+                special_workspace: special("special:notes"),
+                ..monitor("HDMI-A-1", "", 1.0, 0)
+            },
+        ];
+        let current = vec![
+            monitor("DP-1", "", 1.0, 0),
+            HyprMonitor {
+                special_workspace: special("special:music"),
+                ..monitor("DP-2", "", 1.0, 0)
+            },
+        ];
+
+        assert_eq!(
+            hidden_specials(&saved, &current),
+            vec![("DP-1", "special:term")]
+        );
+    }
+
+    #[test]
+    fn test_needs_update_on_rotation_or_scale() {
+        let live = monitor("DP-1", "", 1.0, 0);
+
+        assert!(needs_update(&monitor("DP-1", "", 1.0, 1), &live));
+        assert!(needs_update(&monitor("DP-1", "", 1.25, 0), &live));
+        assert!(!needs_update(&monitor("DP-1", "", 1.0, 0), &live));
+    }
+
+    #[test]
+    fn test_needs_update_on_mode_or_vrr() {
+        let live = monitor("DP-1", "", 1.0, 0);
+
+        let fast = HyprMonitor {
+            refresh_rate: 143.97,
+            ..monitor("DP-1", "", 1.0, 0)
+        };
+        let adaptive = HyprMonitor {
+            vrr: true,
+            ..monitor("DP-1", "", 1.0, 0)
+        };
+        let wobble = HyprMonitor {
+            refresh_rate: 60.0,
+            ..monitor("DP-1", "", 1.0, 0)
+        };
+
+        assert!(needs_update(&fast, &live));
+        assert!(needs_update(&adaptive, &live));
+        assert!(!needs_update(&wobble, &live));
+    }
+}
@@ -0,0 +1,96 @@
+use std::process::Command;
+
+const SUMMARY: &str = "Restoring Session";
+
+/// A single desktop notification updated in place as a restore progresses.
+///
+/// Uses `notify-send --print-id` once and `--replace-id` afterwards, so the
+/// notification daemon shows one entry instead of one per launched app. Nothing
+/// is shown until the first app has to be launched, keeping restores that only
+/// reposition windows silent.
+#[derive(Debug, Default)]
+pub struct ProgressNotifier {
+    enabled: bool,
+    id: Option<u32>,
+    total: usize,
+    shown: bool,
+}
+
+impl ProgressNotifier {
+    /// A notifier that stays silent unless `enabled`
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Self::default()
+        }
+    }
+
+    pub fn set_total(&mut self, total: usize) {
+        self.total = total;
+    }
+
+    /// An app is about to be launched; `done` windows are already handled
+    pub fn launching(&mut self, done: usize, class: &str) {
+        if !self.enabled {
+            return;
+        }
+        self.shown = true;
+        self.send(
+            &progress_text(done, self.total),
+            &format!("Launching {}…", class),
+        );
+    }
+
+    /// Another window has been handled
+    pub fn advance(&mut self, done: usize) {
+        if self.shown {
+            self.send(&progress_text(done, self.total), "");
+        }
+    }
+
+    pub fn finish(&mut self, summary: &str) {
+        if self.shown {
+            self.send("Session restored", summary);
+        }
+    }
+
+    fn send(&mut self, title: &str, body: &str) {
+        let mut command = Command::new("notify-send");
+        command.arg("--app-name=hyprdrover").arg("--print-id");
+        if let Some(id) = self.id {
+            command.arg(format!("--replace-id={}", id));
+        }
+        command
+            .arg(SUMMARY)
+            .arg(format!("{}\n{}", title, body).trim_end());
+
+        // Best effort: a missing notify-send or daemon must not affect the restore
+        if let Ok(output) = command.output() {
+            if let Some(id) = parse_id(&String::from_utf8_lossy(&output.stdout)) {
+                self.id = Some(id);
+            }
+        }
+    }
+}
+
+fn progress_text(done: usize, total: usize) -> String {
+    format!("Restoring session: {}/{} windows", done, total)
+}
+
+/// `notify-send --print-id` prints the notification id on its own line
+fn parse_id(stdout: &str) -> Option<u32> {
+    stdout.trim().parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_progress_text_and_id_parsing() {
+        assert_eq!(progress_text(7, 15), "Restoring session: 7/15 windows");
+        assert_eq!(parse_id("42\n"), Some(42));
+        // Versions without --print-id print nothing
+        assert_eq!(parse_id(""), None);
+    }
+}
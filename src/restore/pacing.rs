@@ -0,0 +1,267 @@
+use crate::config::{MemoryAction, PacingConfig};
+use std::fs;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How long a launched app is assumed to take to reach its expected memory
+const SETTLE_TIME: Duration = Duration::from_secs(30);
+
+/// Memory (MiB) that apps launched moments ago are expected to take but
+/// may not have allocated yet, so `MemAvailable` still counts it
+#[derive(Debug, Default)]
+pub struct PendingLaunches {
+    launches: Vec<(Instant, u64)>,
+}
+
+impl PendingLaunches {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn record(&mut self, pacing: &PacingConfig, class: &str) {
+        if let Some(mb) = expected_memory_mb(pacing, class) {
+            self.launches.push((Instant::now(), mb));
+        }
+    }
+
+    /// MiB expected from launches that haven't settled yet
+    fn expected_mb(&mut self) -> u64 {
+        self.launches.retain(|(at, _)| at.elapsed() < SETTLE_TIME);
+        self.launches.iter().map(|(_, mb)| mb).sum()
+    }
+}
+
+/// Current 1-minute load average and available memory in MiB
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SystemLoad {
+    pub load: Option<f64>,
+    pub available_mb: Option<u64>,
+}
+
+impl SystemLoad {
+    pub fn read() -> Self {
+        Self {
+            load: fs::read_to_string("/proc/loadavg")
+                .ok()
+                .and_then(|s| parse_loadavg(&s)),
+            available_mb: fs::read_to_string("/proc/meminfo")
+                .ok()
+                .and_then(|s| parse_available_mb(&s)),
+        }
+    }
+
+    /// Why launching now would be too much, if it would
+    pub fn over_limit(&self, pacing: &PacingConfig) -> Option<String> {
+        if let (Some(load), Some(max)) = (self.load, pacing.max_load) {
+            if load > max {
+                return Some(format!("load {:.2} > {:.2}", load, max));
+            }
+        }
+        if let (Some(available), Some(min)) = (self.available_mb, pacing.min_available_mb) {
+            if available < min {
+                return Some(format!("{} MiB available < {} MiB", available, min));
+            }
+        }
+        None
+    }
+
+    /// Why launching `class` would likely push the machine into swap, if it
+    /// would. `pending_mb` is what earlier launches are still expected to
+    /// take out of the memory available now.
+    pub fn memory_shortfall(
+        &self,
+        pacing: &PacingConfig,
+        class: &str,
+        pending_mb: u64,
+    ) -> Option<String> {
+        let expected = expected_memory_mb(pacing, class);
+        if expected.is_none() && pending_mb == 0 {
+            return None;
+        }
+        let expected = expected.unwrap_or(0);
+        let available = self.available_mb?;
+        let needed = expected + pending_mb + pacing.min_available_mb.unwrap_or(0);
+        (available < needed).then(|| {
+            let pending = if pending_mb > 0 {
+                format!(
+                    " ({} MiB of it spoken for by apps still starting)",
+                    pending_mb
+                )
+            } else {
+                String::new()
+            };
+            format!(
+                "{} needs ~{} MiB, {} MiB available{}",
+                class, expected, available, pending
+            )
+        })
+    }
+}
+
+fn expected_memory_mb(pacing: &PacingConfig, class: &str) -> Option<u64> {
+    pacing
+        .memory_mb
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(class))
+        .map(|(_, mb)| *mb)
+}
+
+fn parse_loadavg(content: &str) -> Option<f64> {
+    content.split_whitespace().next()?.parse().ok()
+}
+
+fn parse_available_mb(content: &str) -> Option<u64> {
+    let line = content
+        .lines()
+        .find(|line| line.starts_with("MemAvailable:"))?;
+    let kb: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kb / 1024)
+}
+
+/// Block until the machine has room to launch `class` or `max_wait_secs`
+/// have passed, counting what `pending` launches are still expected to
+/// take, and record the launch there. Returns false when the user chose to
+/// skip the app at the memory prompt.
+pub fn wait_for_headroom(
+    pacing: &PacingConfig,
+    class: &str,
+    pending: &mut PendingLaunches,
+) -> bool {
+    if pacing.max_load.is_none() && pacing.min_available_mb.is_none() && pacing.memory_mb.is_empty()
+    {
+        return true;
+    }
+    let start = Instant::now();
+
+    let max_wait = Duration::from_secs(pacing.max_wait_secs);
+    let mut announced = false;
+    let mut prompt = pacing.on_memory_pressure == MemoryAction::Prompt && io::stdin().is_terminal();
+    loop {
+        let load = SystemLoad::read();
+        let shortfall = load.memory_shortfall(pacing, class, pending.expected_mb());
+        let Some(reason) = load.over_limit(pacing).or_else(|| shortfall.clone()) else {
+            break;
+        };
+        if let Some(shortfall) = shortfall.filter(|_| prompt) {
+            match ask(&shortfall) {
+                PromptReply::Launch => break,
+                PromptReply::Skip => return false,
+                PromptReply::Wait => prompt = false,
+            }
+        }
+        if start.elapsed() >= max_wait {
+            println!("      -> Still busy ({}), launching anyway", reason);
+            break;
+        }
+        if !announced {
+            println!("      -> Waiting for the system to settle ({})", reason);
+            announced = true;
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+    pending.record(pacing, class);
+    true
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PromptReply {
+    Launch,
+    Wait,
+    Skip,
+}
+
+impl PromptReply {
+    /// Anything unrecognised, including an empty line, skips the app
+    fn parse(input: &str) -> Self {
+        match input.trim().to_lowercase().as_str() {
+            "y" | "yes" => Self::Launch,
+            "w" | "wait" => Self::Wait,
+            _ => Self::Skip,
+        }
+    }
+}
+
+fn ask(shortfall: &str) -> PromptReply {
+    print!(
+        "      Low memory: {}. Launch anyway? [y/N/w(ait)] ",
+        shortfall
+    );
+    let _ = io::stdout().flush();
+    let mut line = String::new();
+    let _ = io::stdin().lock().read_line(&mut line);
+    PromptReply::parse(&line)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_proc_files() {
+        assert_eq!(parse_loadavg("3.42 2.10 1.05 2/1234 5678\n"), Some(3.42));
+        let meminfo = "MemTotal:        7989464 kB\nMemFree:          204800 kB\nMemAvailable:    1048576 kB\n";
+        assert_eq!(parse_available_mb(meminfo), Some(1024));
+        assert_eq!(parse_available_mb("MemTotal: 1 kB\n"), None);
+    }
+
+    #[test]
+    fn test_over_limit() {
+        let pacing = PacingConfig {
+            max_load: Some(4.0),
+            min_available_mb: Some(512),
+            ..PacingConfig::default()
+        };
+        let calm = SystemLoad {
+            load: Some(1.5),
+            available_mb: Some(2048),
+        };
+        let busy = SystemLoad {
+            load: Some(6.0),
+            ..calm
+        };
+        let starved = SystemLoad {
+            available_mb: Some(300),
+            ..calm
+        };
+
+        assert_eq!(calm.over_limit(&pacing), None);
+        assert!(busy.over_limit(&pacing).unwrap().contains("load"));
+        assert!(starved.over_limit(&pacing).unwrap().contains("MiB"));
+        assert_eq!(busy.over_limit(&PacingConfig::default()), None);
+    }
+
+    #[test]
+    fn test_memory_shortfall_uses_class_weight() {
+        let pacing = PacingConfig {
+            min_available_mb: Some(512),
+            memory_mb: [("firefox".to_string(), 1500)].into(),
+            ..PacingConfig::default()
+        };
+        let load = SystemLoad {
+            load: None,
+            available_mb: Some(1800),
+        };
+
+        // 1800 MiB covers the 512 MiB floor but not Firefox on top of it
+        assert_eq!(load.over_limit(&pacing), None);
+        assert!(load
+            .memory_shortfall(&pacing, "Firefox", 0)
+            .unwrap()
+            .contains("~1500 MiB"));
+        assert_eq!(load.memory_shortfall(&pacing, "kitty", 0), None);
+        // A Firefox launched moments ago hasn't taken its share yet
+        let mut pending = PendingLaunches::new();
+        pending.record(&pacing, "firefox");
+        pending.record(&pacing, "kitty");
+        assert_eq!(pending.expected_mb(), 1500);
+        assert!(load
+            .memory_shortfall(&pacing, "kitty", pending.expected_mb())
+            .unwrap()
+            .contains("1500 MiB of it spoken for"));
+        assert_eq!(PromptReply::parse("w\n"), PromptReply::Wait);
+        assert_eq!(PromptReply::parse(""), PromptReply::Skip);
+    }
+}
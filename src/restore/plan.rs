@@ -0,0 +1,390 @@
+//! What a restore would do, worked out without changing anything.
+//!
+//! The plan is computed from the compositor's current state with the same
+//! rules, matching and launch logic as the restore itself. Launches that
+//! depend on what earlier launches open (single-instance apps, windows a
+//! dependency brings up) can still turn out differently when it runs.
+
+use super::launcher::DesktopEntries;
+use super::matcher::WindowMatcher;
+use super::report::RestoreReport;
+use super::{companions, monitors, new_workspace_rules, position, waves};
+use super::{restore_session, RestoreOptions, Restorer};
+use crate::config::Config;
+use crate::ipc::{self, HyprClient, HyprWorkspaceRule, SessionSnapshot};
+use serde::Serialize;
+use std::error::Error;
+use std::fmt;
+
+/// One step of a restore
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "action", rename_all = "kebab-case")]
+pub enum PlannedAction {
+    /// Create the saved virtual output `name`
+    CreateOutput {
+        name: String,
+    },
+    /// Apply a saved monitor setting, in `monitor` keyword syntax
+    ConfigureMonitor {
+        keyword: String,
+    },
+    /// Apply a saved workspace rule, in `workspace` keyword syntax
+    WorkspaceRule {
+        keyword: String,
+    },
+    /// Move a workspace whose monitor is gone
+    MoveWorkspace {
+        workspace: String,
+        from: String,
+        to: String,
+    },
+    StartCompanion {
+        name: String,
+    },
+    /// Move the open window `address` where the saved one was
+    Match {
+        class: String,
+        title: String,
+        address: String,
+        workspace: String,
+    },
+    Launch {
+        class: String,
+        command: String,
+        workspace: String,
+    },
+    /// Give a floating window its saved geometry
+    Place {
+        class: String,
+        at: [i32; 2],
+        size: [i32; 2],
+    },
+    /// Leave a saved window missing
+    Skip {
+        class: String,
+        title: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for PlannedAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CreateOutput { name } => write!(f, "create virtual output {}", name),
+            Self::ConfigureMonitor { keyword } => write!(f, "monitor = {}", keyword),
+            Self::WorkspaceRule { keyword } => write!(f, "workspace = {}", keyword),
+            Self::MoveWorkspace {
+                workspace,
+                from,
+                to,
+            } => write!(f, "move workspace {} from {} to {}", workspace, from, to),
+            Self::StartCompanion { name } => write!(f, "start companion {}", name),
+            Self::Match {
+                class,
+                title,
+                address,
+                workspace,
+            } => write!(
+                f,
+                "move {} ({}) [{}] to workspace {}",
+                class, title, address, workspace
+            ),
+            Self::Launch {
+                class,
+                command,
+                workspace,
+            } => write!(
+                f,
+                "launch {} on workspace {}: {}",
+                class, workspace, command
+            ),
+            Self::Place { class, at, size } => write!(
+                f,
+                "float {} at {},{} size {}x{}",
+                class, at[0], at[1], size[0], size[1]
+            ),
+            Self::Skip {
+                class,
+                title,
+                reason,
+            } => write!(f, "leave {} ({}) missing: {}", class, title, reason),
+        }
+    }
+}
+
+/// The actions a restore of `snapshot` would take, in order
+#[derive(Debug, Clone, Serialize)]
+pub struct RestorePlan {
+    pub actions: Vec<PlannedAction>,
+    #[serde(skip)]
+    snapshot: SessionSnapshot,
+    #[serde(skip)]
+    options: RestoreOptions,
+}
+
+impl RestorePlan {
+    pub fn print(&self) {
+        if self.actions.is_empty() {
+            println!("Nothing to restore.");
+            return;
+        }
+        println!("Restore plan ({} actions):", self.actions.len());
+        for (i, action) in self.actions.iter().enumerate() {
+            println!("  {:>3}. {}", i + 1, action);
+        }
+    }
+
+    /// Run the restore this plan describes. Matching and launching are
+    /// decided again against the live state, so windows opened or closed
+    /// since planning are taken into account.
+    pub fn execute(&self, config: &Config) -> Result<RestoreReport, Box<dyn Error>> {
+        restore_session(&self.snapshot, config, &self.options)
+    }
+}
+
+/// Work out what restoring `snapshot` would do, without dispatching anything
+pub fn restore_session_plan(
+    snapshot: &SessionSnapshot,
+    config: &Config,
+    options: &RestoreOptions,
+) -> Result<RestorePlan, Box<dyn Error>> {
+    super::engine::RestoreEngine::new(config)
+        .options(options.clone())
+        .plan(snapshot)
+}
+
+pub(super) fn run_plan(
+    snapshot: &SessionSnapshot,
+    config: &Config,
+    options: &RestoreOptions,
+    matcher: Box<dyn WindowMatcher>,
+) -> Result<RestorePlan, Box<dyn Error>> {
+    let current = ipc::capture_state()?;
+    let rules = if snapshot.workspace_rules.is_empty() {
+        vec![]
+    } else {
+        ipc::get_workspace_rules().unwrap_or_default()
+    };
+    build(snapshot, current, &rules, config, options, matcher, None)
+}
+
+/// The plan for restoring `snapshot` onto the `current` state, whose
+/// workspace rules are `current_rules`. Launch commands come from `entries`,
+/// or from the installed desktop entries when `None`.
+fn build(
+    snapshot: &SessionSnapshot,
+    current: SessionSnapshot,
+    current_rules: &[HyprWorkspaceRule],
+    config: &Config,
+    options: &RestoreOptions,
+    matcher: Box<dyn WindowMatcher>,
+    entries: Option<DesktopEntries>,
+) -> Result<RestorePlan, Box<dyn Error>> {
+    let mut actions = vec![];
+    if options.only.is_none() && !options.safe {
+        session_actions(snapshot, &current, current_rules, config, &mut actions);
+    }
+
+    let mut restorer = Restorer::new(current.clients, config, matcher);
+    restorer.safe = options.safe;
+    if let Some(entries) = entries {
+        let _ = restorer.desktop_entries.set(entries);
+    }
+    let clients = restorer.select(snapshot, config, options)?;
+    for (_, workspaces) in waves::by_workspace(waves::group(clients, &config.waves)) {
+        // Tiled windows go first, as in the restore
+        for saved_clients in workspaces.values() {
+            let (floating, tiled): (Vec<_>, Vec<_>) =
+                saved_clients.iter().partition(|c| c.pinned || c.floating);
+            for saved in tiled.into_iter().chain(floating) {
+                actions.extend(window_actions(&mut restorer, saved));
+            }
+        }
+    }
+
+    Ok(RestorePlan {
+        actions,
+        snapshot: snapshot.clone(),
+        options: options.clone(),
+    })
+}
+
+/// Monitor, workspace and companion changes, which come before any window
+fn session_actions(
+    snapshot: &SessionSnapshot,
+    current: &SessionSnapshot,
+    current_rules: &[HyprWorkspaceRule],
+    config: &Config,
+    actions: &mut Vec<PlannedAction>,
+) {
+    for monitor in monitors::missing_virtual_outputs(&snapshot.monitors, &current.monitors) {
+        actions.push(PlannedAction::CreateOutput {
+            name: monitor.name.clone(),
+        });
+    }
+    for monitor in monitors::to_configure(&snapshot.monitors, &current.monitors) {
+        actions.push(PlannedAction::ConfigureMonitor {
+            keyword: monitor.to_keyword(),
+        });
+    }
+    for rule in new_workspace_rules(&snapshot.workspace_rules, current_rules) {
+        actions.push(PlannedAction::WorkspaceRule {
+            keyword: rule.to_keyword(),
+        });
+    }
+    for (workspace, to) in monitors::remapped_workspaces(
+        &snapshot.monitors,
+        &snapshot.workspaces,
+        &current.monitors,
+        &config.monitor_map,
+    ) {
+        actions.push(PlannedAction::MoveWorkspace {
+            workspace: workspace.name.clone(),
+            from: workspace.monitor.clone(),
+            to: to.to_string(),
+        });
+    }
+    for companion in companions::missing(&config.companions) {
+        actions.push(PlannedAction::StartCompanion {
+            name: companion.name.clone(),
+        });
+    }
+}
+
+/// What happens to `saved`: the open window it takes over or the launch
+/// that brings it back, then its floating geometry
+fn window_actions(restorer: &mut Restorer, saved: &HyprClient) -> Vec<PlannedAction> {
+    let mut actions = vec![];
+    if let Some(index) = restorer.find_running(saved) {
+        let current = restorer.available_clients.remove(index);
+        actions.push(PlannedAction::Match {
+            class: saved.class.clone(),
+            title: current.title,
+            address: current.address,
+            workspace: saved.workspace.name.clone(),
+        });
+    } else {
+        match restorer.missing_command(saved) {
+            Ok(command) => actions.push(PlannedAction::Launch {
+                class: saved.class.clone(),
+                command,
+                workspace: saved.workspace.name.clone(),
+            }),
+            Err(skipped) => {
+                return vec![PlannedAction::Skip {
+                    class: saved.class.clone(),
+                    title: saved.title.clone(),
+                    reason: skipped.0,
+                }]
+            }
+        }
+    }
+    if saved.floating && !restorer.decision(saved).position_only {
+        let (at, size) = position::floating_geometry(saved);
+        actions.push(PlannedAction::Place {
+            class: saved.class.clone(),
+            at,
+            size,
+        });
+    }
+    actions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::restore::matcher::DefaultMatcher;
+
+    fn client(address: &str, class: &str, workspace: i32, floating: bool) -> HyprClient {
+        HyprClient {
+            at: [10, 20],
+            size: [300, 200],
+            title: class.to_string(),
+            ..HyprClient::fixture(address, class)
+                .with_workspace(workspace)
+                .with_floating(floating)
+        }
+    }
+
+    fn snapshot(clients: Vec<HyprClient>) -> SessionSnapshot {
+        let mut snapshot: SessionSnapshot =
+            serde_json::from_str(r#"{ "clients": [], "workspaces": [], "monitors": [] }"#).unwrap();
+        snapshot.clients = clients;
+        snapshot
+    }
+
+    /// The defaults, minus anything taken from the environment
+    fn config() -> Config {
+        Config {
+            session_dir: "/nonexistent/sessions".to_string(),
+            state_dir: "/nonexistent/state".to_string(),
+            runtime_dir: "/nonexistent/run".to_string(),
+            ..Config::default()
+        }
+    }
+
+    fn plan(
+        saved: &SessionSnapshot,
+        current: SessionSnapshot,
+        options: &RestoreOptions,
+    ) -> Vec<PlannedAction> {
+        build(
+            saved,
+            current,
+            &[],
+            &config(),
+            options,
+            Box::new(DefaultMatcher),
+            Some(DesktopEntries::default()),
+        )
+        .unwrap()
+        .actions
+    }
+
+    #[test]
+    fn test_plan_matches_open_windows_and_launches_the_rest() {
+        let saved = snapshot(vec![
+            client("0xa", "pavucontrol", 1, true),
+            client("0xb", "kitty", 1, false),
+            client("0xc", "kitty", 2, false),
+        ]);
+        let current = snapshot(vec![client("0x1", "kitty", 5, false)]);
+
+        let actions = plan(&saved, current, &RestoreOptions::default());
+        assert_eq!(actions.len(), 4);
+        assert_eq!(
+            actions[0],
+            PlannedAction::Match {
+                class: "kitty".to_string(),
+                title: "kitty".to_string(),
+                address: "0x1".to_string(),
+                workspace: "1".to_string(),
+            }
+        );
+        assert!(
+            matches!(&actions[1], PlannedAction::Launch { class, .. } if class == "pavucontrol")
+        );
+        assert_eq!(
+            actions[2],
+            PlannedAction::Place {
+                class: "pavucontrol".to_string(),
+                at: [10, 20],
+                size: [300, 200],
+            }
+        );
+        assert!(matches!(&actions[3], PlannedAction::Launch { workspace, .. } if workspace == "2"));
+    }
+
+    #[test]
+    fn test_safe_plan_launches_nothing() {
+        let saved = snapshot(vec![client("0xa", "kitty", 1, false)]);
+        let options = RestoreOptions {
+            safe: true,
+            ..RestoreOptions::default()
+        };
+
+        let actions = plan(&saved, snapshot(vec![]), &options);
+        assert!(matches!(&actions[..], [PlannedAction::Skip { .. }]));
+        assert_eq!(serde_json::to_value(&actions[0]).unwrap()["action"], "skip");
+    }
+}
@@ -1,45 +1,133 @@
-use crate::ipc::{self, HyprClient};
+use crate::ipc::{self, CommandBatch, Dispatch, FullscreenMode, HyprClient, HyprMonitor};
 use std::error::Error;
 
-/// Restores the position and workspace of a single window
+/// Restores the position and workspace of a single window, and its saved
+/// properties (opacity, dim, ...) when `props` is set. Everything but the
+/// fullscreen mode goes to Hyprland as one batch.
 pub fn restore_window_position(
     current_client: &HyprClient,
     saved_client: &HyprClient,
+    props: bool,
 ) -> Result<(), Box<dyn Error>> {
-    // Move to workspace
-    if current_client.workspace.id != saved_client.workspace.id {
-        ipc::move_window_to_workspace(&current_client.address, saved_client.workspace.id)?;
+    let address = &current_client.address;
+    // The window may have changed mode since it was matched (window rules,
+    // the tree restore), so set the saved mode rather than toggling. Only
+    // toggles need a fresh look at the window.
+    let live = if saved_client.floating || !ipc::capabilities().set_floating {
+        ipc::find_client(address)?.ok_or("the window closed")?
+    } else {
+        current_client.clone()
+    };
+    let mut batch = CommandBatch::new();
+    move_to_workspace(&mut batch, current_client, saved_client);
+    batch.set_floating(&live, saved_client.floating);
+    if saved_client.floating {
+        let (at, size) = floating_geometry(saved_client);
+        batch
+            .move_window_pixel(address, at[0], at[1])
+            .resize_window_pixel(address, size[0], size[1])
+            .set_pinned(&live, saved_client.pinned);
     }
+    // Tiled windows can't be given pixel positions without floating them;
+    // moving them to the workspace is all that can be done here.
+    let placement = batch.len();
 
-    // Move to position & Resize
-    if saved_client.floating {
-        if !current_client.floating {
-            ipc::dispatch(&format!(
-                "togglefloating address:{}",
-                current_client.address
-            ))?;
-        }
-        ipc::move_window_pixel(
-            &current_client.address,
-            saved_client.at[0],
-            saved_client.at[1],
-        )?;
-        ipc::resize_window_pixel(
-            &current_client.address,
-            saved_client.size[0],
-            saved_client.size[1],
-        )?;
-    } else {
-        // Saved as tiled
-        if current_client.floating {
-            ipc::dispatch(&format!(
-                "togglefloating address:{}",
-                current_client.address
-            ))?;
-        }
-        // For tiled windows, we can't easily force pixel positions without floating them.
-        // We just move them to the workspace for now.
+    for (prop, value) in saved_client.props.iter().filter(|_| props) {
+        batch.set_prop(address, prop, value);
+    }
+    let results = batch.send()?;
+    if let Some(Err(e)) = results[..placement].iter().find(|r| r.is_err()) {
+        return Err(format!("Dispatch failed: {}", e).into());
     }
+    // Opacity and dim are cosmetic; a failure shouldn't fail the window
+    for e in results[placement..].iter().filter_map(|r| r.as_ref().err()) {
+        eprintln!("   ⚠️ setprop failed: {}", e);
+    }
+
+    if let Err(e) = restore_fullscreen(current_client, saved_client) {
+        eprintln!("   ⚠️ Failed to restore fullscreen mode: {}", e);
+    }
+
+    Ok(())
+}
 
+/// Move a window to its saved workspace and, when it floats both now and in
+/// the snapshot, its saved geometry. Its mode, fullscreen state and
+/// properties stay as they are (the `position-only` policy).
+pub fn restore_placement(
+    current_client: &HyprClient,
+    saved_client: &HyprClient,
+) -> Result<(), Box<dyn Error>> {
+    let mut batch = CommandBatch::new();
+    move_to_workspace(&mut batch, current_client, saved_client);
+    if current_client.floating && saved_client.floating {
+        let (at, size) = floating_geometry(saved_client);
+        batch
+            .move_window_pixel(&current_client.address, at[0], at[1])
+            .resize_window_pixel(&current_client.address, size[0], size[1]);
+    }
+    if let Some(Err(e)) = batch.send()?.into_iter().find(|r| r.is_err()) {
+        return Err(format!("Dispatch failed: {}", e).into());
+    }
+    Ok(())
+}
+
+fn move_to_workspace(batch: &mut CommandBatch, current: &HyprClient, saved: &HyprClient) {
+    if current.workspace.id != saved.workspace.id {
+        batch.move_to_workspace(&current.address, saved.workspace.target());
+    }
+}
+
+/// The saved floating geometry, scaled to the monitor now showing the saved
+/// workspace. Snapshots without relative geometry keep their pixels.
+pub(super) fn floating_geometry(saved: &HyprClient) -> ([i32; 2], [i32; 2]) {
+    let Some(relative) = saved.relative else {
+        return (saved.at, saved.size);
+    };
+    match target_monitor(saved.workspace.id) {
+        Some(monitor) => relative.on(&monitor),
+        None => (saved.at, saved.size),
+    }
+}
+
+fn target_monitor(workspace: i32) -> Option<HyprMonitor> {
+    let workspaces = ipc::get_workspaces().ok()?;
+    let name = &workspaces.iter().find(|w| w.id == workspace)?.monitor;
+    ipc::get_monitors()
+        .ok()?
+        .into_iter()
+        .find(|m| &m.name == name)
+}
+
+/// Put the window back into its saved fullscreen, maximized or fake
+/// fullscreen mode. Both dispatchers act on the focused window.
+fn restore_fullscreen(current: &HyprClient, saved: &HyprClient) -> Result<(), Box<dyn Error>> {
+    let mode = saved.fullscreen_mode();
+    if mode == current.fullscreen_mode() {
+        return Ok(());
+    }
+
+    // Both dispatchers need focus, which a `nofocus` window must not get
+    if saved.no_focus() {
+        return Err("the window doesn't take focus".into());
+    }
+    ipc::focus_window(&current.address)?;
+    if ipc::capabilities().fullscreen_state {
+        let (internal, client) = mode.state();
+        return ipc::dispatch_typed(Dispatch::FullscreenState { internal, client });
+    }
+
+    // Older compositors toggle each mode separately
+    let current = current.fullscreen_mode();
+    let fullscreen = FullscreenMode::Fullscreen;
+    if (current == fullscreen) != (mode == fullscreen) {
+        ipc::dispatch_typed(Dispatch::Fullscreen { maximize: false })?;
+    }
+    if current.is_maximized() != mode.is_maximized() {
+        ipc::dispatch_typed(Dispatch::Fullscreen { maximize: true })?;
+    }
+    if current.is_fake() != mode.is_fake() {
+        ipc::dispatch_typed(Dispatch::FakeFullscreen)?;
+    }
     Ok(())
 }
@@ -0,0 +1,269 @@
+use crate::ipc::HyprClient;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How many reports to keep in the reports directory
+const MAX_REPORTS: usize = 20;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Outcome {
+    /// An already-running window was matched and repositioned
+    Matched,
+    /// The app was launched and its window positioned
+    Launched,
+    /// A rule kept the window from being relaunched
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportEntry {
+    pub class: String,
+    pub title: String,
+    pub workspace: i32,
+    pub outcome: Outcome,
+    /// Address of the live window the saved one ended up as
+    pub address: Option<String>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+    /// systemd scope the app was launched in, with a `scope` rule
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unit: Option<String>,
+    /// Tail of the compositor log when the window failed, with
+    /// `compositor_log_lines` set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub compositor_log: Vec<String>,
+    /// Stable ID of the saved window, which the live one now carries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window_id: Option<String>,
+}
+
+/// A setting changed for the restore (e.g. animations turned off) and put
+/// back at the end
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Rollback {
+    pub setting: String,
+    pub value: String,
+    /// Why it could not be put back
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Error for a window deliberately left alone; recorded as [`Outcome::Skipped`]
+#[derive(Debug)]
+pub struct Skipped(pub String);
+
+impl fmt::Display for Skipped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for Skipped {}
+
+/// Structured record of a single restore run
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RestoreReport {
+    pub session: String,
+    pub started_at: String,
+    pub duration_ms: u64,
+    pub entries: Vec<ReportEntry>,
+    /// Layer surfaces (bars, wallpapers) saved with the session but absent afterwards
+    #[serde(default)]
+    pub missing_layers: Vec<String>,
+    /// Compositor plugins loaded at save time but not during the restore
+    #[serde(default)]
+    pub missing_plugins: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rollbacks: Vec<Rollback>,
+}
+
+impl RestoreReport {
+    /// Begin a report timestamped now
+    pub fn start() -> Self {
+        Self {
+            session: String::new(),
+            started_at: Local::now().to_rfc3339(),
+            duration_ms: 0,
+            entries: vec![],
+            missing_layers: vec![],
+            missing_plugins: vec![],
+            rollbacks: vec![],
+        }
+    }
+
+    pub fn record(
+        &mut self,
+        saved: &HyprClient,
+        result: &Result<(HyprClient, Outcome), Box<dyn Error>>,
+        elapsed: Duration,
+    ) {
+        let (outcome, address, error) = match result {
+            Ok((current, outcome)) => (*outcome, Some(current.address.clone()), None),
+            Err(e) if e.is::<Skipped>() => (Outcome::Skipped, None, Some(e.to_string())),
+            Err(e) => (Outcome::Failed, None, Some(e.to_string())),
+        };
+
+        self.entries.push(ReportEntry {
+            class: saved.class.clone(),
+            title: saved.title.clone(),
+            workspace: saved.workspace.id,
+            outcome,
+            address,
+            duration_ms: elapsed.as_millis() as u64,
+            error,
+            unit: None,
+            compositor_log: vec![],
+            window_id: saved.window_id.clone(),
+        });
+    }
+
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.entries.iter().filter(|e| e.outcome == outcome).count()
+    }
+
+    /// Write the report into `dir`, pruning the oldest reports beyond the
+    /// limit. Reports within the same millisecond get a `_2`, `_3`... suffix
+    /// rather than replacing each other.
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let content = serde_json::to_string_pretty(self)?;
+        let stem = format!("restore_{}", Local::now().format("%Y-%m-%d_%H-%M-%S-%3f"));
+        let mut path = dir.join(format!("{}.json", stem));
+        for n in 2.. {
+            match fs::OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&path)
+            {
+                Ok(mut file) => {
+                    file.write_all(content.as_bytes())?;
+                    break;
+                }
+                Err(e) if e.kind() == ErrorKind::AlreadyExists => {
+                    path = dir.join(format!("{}_{}.json", stem, n));
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        let reports = list_reports(dir)?;
+        for old in reports.iter().skip(MAX_REPORTS) {
+            let _ = fs::remove_file(old);
+        }
+
+        Ok(path)
+    }
+
+    /// Load the most recent report in `dir`, if any
+    pub fn load_last(dir: &Path) -> Result<Option<Self>, Box<dyn Error>> {
+        let Some(latest) = list_reports(dir)?.into_iter().next() else {
+            return Ok(None);
+        };
+        let content = fs::read_to_string(latest)?;
+        Ok(Some(serde_json::from_str(&content)?))
+    }
+
+    pub fn print_summary(&self) {
+        println!("Session:  {}", self.session);
+        println!("Started:  {}", self.started_at);
+        println!("Duration: {} ms", self.duration_ms);
+        println!(
+            "Result:   {} matched, {} launched, {} skipped, {} failed",
+            self.count(Outcome::Matched),
+            self.count(Outcome::Launched),
+            self.count(Outcome::Skipped),
+            self.count(Outcome::Failed)
+        );
+        println!();
+
+        for entry in &self.entries {
+            let mark = match entry.outcome {
+                Outcome::Matched | Outcome::Launched => "✔",
+                Outcome::Skipped => "-",
+                Outcome::Failed => "✘",
+            };
+            println!(
+                "{} [ws {}] {} ({:?}, {} ms)",
+                mark, entry.workspace, entry.class, entry.outcome, entry.duration_ms
+            );
+            if let Some(error) = &entry.error {
+                println!("      {}", error);
+            }
+            for line in &entry.compositor_log {
+                println!("      | {}", line);
+            }
+        }
+
+        for namespace in &self.missing_layers {
+            println!("⚠ layer surface `{}` is missing", namespace);
+        }
+        for plugin in &self.missing_plugins {
+            println!("⚠ plugin `{}` is not loaded", plugin);
+        }
+        for rollback in &self.rollbacks {
+            match &rollback.error {
+                None => println!("↺ {} put back to {}", rollback.setting, rollback.value),
+                Some(e) => println!(
+                    "⚠ {} could not be put back to {}: {}",
+                    rollback.setting, rollback.value, e
+                ),
+            }
+        }
+    }
+}
+
+/// Report files sorted newest first
+fn list_reports(dir: &Path) -> Result<Vec<PathBuf>, Box<dyn Error>> {
+    if !dir.exists() {
+        return Ok(vec![]);
+    }
+
+    let mut reports = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().and_then(|s| s.to_str()) == Some("json") {
+            reports.push(path);
+        }
+    }
+    reports.sort_by(|a, b| b.cmp(a));
+    Ok(reports)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_last() {
+        let dir = std::env::temp_dir().join(format!("hyprdrover-reports-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        assert!(RestoreReport::load_last(&dir).unwrap().is_none());
+
+        let mut report = RestoreReport::start();
+        report.session = "work".to_string();
+        report.save(&dir).unwrap();
+
+        let loaded = RestoreReport::load_last(&dir).unwrap().unwrap();
+        assert_eq!(loaded.session, "work");
+
+        // Reports saved back to back are all kept, newest last
+        let first = report.save(&dir).unwrap();
+        report.session = "play".to_string();
+        let second = report.save(&dir).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(list_reports(&dir).unwrap().len(), 3);
+        let loaded = RestoreReport::load_last(&dir).unwrap().unwrap();
+        assert_eq!(loaded.session, "play");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
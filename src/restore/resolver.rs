@@ -0,0 +1,170 @@
+use crate::ipc::HyprClient;
+
+/// Decides how to relaunch a saved window that is missing from the session.
+///
+/// Resolvers are asked in order before the built-in logic; the first one
+/// returning `Some` wins.
+pub trait LaunchResolver {
+    /// Human-readable name used in log output
+    fn name(&self) -> &str;
+
+    /// Command line that starts `client`, or `None` to defer to the next resolver
+    fn resolve(&self, client: &HyprClient) -> Option<String>;
+}
+
+/// Ask each resolver in turn, returning the first command and the resolver that produced it
+pub fn resolve_with<'a>(
+    resolvers: &'a [Box<dyn LaunchResolver>],
+    client: &HyprClient,
+) -> Option<(&'a str, String)> {
+    resolvers
+        .iter()
+        .find_map(|r| r.resolve(client).map(|command| (r.name(), command)))
+}
+
+/// Load resolver plugins from the given shared library paths.
+/// Libraries that fail to load are reported and skipped.
+#[cfg(feature = "plugins")]
+pub fn load_plugins(paths: &[String]) -> Vec<Box<dyn LaunchResolver>> {
+    paths
+        .iter()
+        .filter_map(|path| match plugin::DylibResolver::load(path) {
+            Ok(resolver) => Some(Box::new(resolver) as Box<dyn LaunchResolver>),
+            Err(e) => {
+                eprintln!("Warning: failed to load resolver plugin {}: {}", path, e);
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(not(feature = "plugins"))]
+pub fn load_plugins(paths: &[String]) -> Vec<Box<dyn LaunchResolver>> {
+    if !paths.is_empty() {
+        eprintln!("Warning: `plugins` is set but hyprdrover was built without plugin support");
+    }
+    vec![]
+}
+
+/// Shared-library plugins.
+///
+/// A plugin exports these C symbols:
+///
+/// ```c
+/// uint32_t hyprdrover_resolver_abi(void);       // must return PLUGIN_ABI_VERSION
+/// const char *hyprdrover_resolver_name(void);   // static, never freed
+/// char *hyprdrover_resolve(const char *client); // client as JSON; NULL to defer
+/// void hyprdrover_free(char *command);          // frees a hyprdrover_resolve result
+/// ```
+///
+/// The saved client is passed as the same JSON used in session files, so
+/// plugins keep working when hyprdrover adds fields.
+#[cfg(feature = "plugins")]
+mod plugin {
+    use super::LaunchResolver;
+    use crate::ipc::HyprClient;
+    use libloading::{Library, Symbol};
+    use std::error::Error;
+    use std::ffi::{c_char, CStr, CString};
+
+    pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+    type AbiFn = unsafe extern "C" fn() -> u32;
+    type NameFn = unsafe extern "C" fn() -> *const c_char;
+    type ResolveFn = unsafe extern "C" fn(*const c_char) -> *mut c_char;
+    type FreeFn = unsafe extern "C" fn(*mut c_char);
+
+    pub struct DylibResolver {
+        name: String,
+        library: Library,
+    }
+
+    impl DylibResolver {
+        pub fn load(path: &str) -> Result<Self, Box<dyn Error>> {
+            // SAFETY: loading a plugin runs its initializers; plugins are
+            // trusted code the user explicitly listed in the config.
+            let library = unsafe { Library::new(path)? };
+
+            let name = unsafe {
+                let abi: Symbol<AbiFn> = library.get(b"hyprdrover_resolver_abi")?;
+                let version = abi();
+                if version != PLUGIN_ABI_VERSION {
+                    return Err(format!(
+                        "plugin ABI {} is not supported (expected {})",
+                        version, PLUGIN_ABI_VERSION
+                    )
+                    .into());
+                }
+
+                // Fail at load time rather than mid-restore if a symbol is missing
+                library.get::<ResolveFn>(b"hyprdrover_resolve")?;
+                library.get::<FreeFn>(b"hyprdrover_free")?;
+
+                let name: Symbol<NameFn> = library.get(b"hyprdrover_resolver_name")?;
+                CStr::from_ptr(name()).to_string_lossy().into_owned()
+            };
+
+            Ok(Self { name, library })
+        }
+    }
+
+    impl LaunchResolver for DylibResolver {
+        fn name(&self) -> &str {
+            &self.name
+        }
+
+        fn resolve(&self, client: &HyprClient) -> Option<String> {
+            let json = CString::new(serde_json::to_string(client).ok()?).ok()?;
+
+            // SAFETY: symbols were checked in `load`; the returned string is
+            // owned by the plugin and handed back to its own free function.
+            unsafe {
+                let resolve: Symbol<ResolveFn> = self.library.get(b"hyprdrover_resolve").ok()?;
+                let free: Symbol<FreeFn> = self.library.get(b"hyprdrover_free").ok()?;
+
+                let result = resolve(json.as_ptr());
+                if result.is_null() {
+                    return None;
+                }
+                let command = CStr::from_ptr(result).to_string_lossy().into_owned();
+                free(result);
+                Some(command).filter(|c| !c.trim().is_empty())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Fixed(&'static str, &'static str, &'static str);
+
+    impl LaunchResolver for Fixed {
+        fn name(&self) -> &str {
+            self.0
+        }
+
+        fn resolve(&self, client: &HyprClient) -> Option<String> {
+            (client.class == self.1).then(|| self.2.to_string())
+        }
+    }
+
+    fn client(class: &str) -> HyprClient {
+        HyprClient::fixture("0x1", class)
+    }
+
+    #[test]
+    fn test_first_resolver_with_an_answer_wins() {
+        let resolvers: Vec<Box<dyn LaunchResolver>> = vec![
+            Box::new(Fixed("jetbrains", "jetbrains-idea", "idea")),
+            Box::new(Fixed("fallback", "jetbrains-idea", "java -jar idea.jar")),
+        ];
+
+        assert_eq!(
+            resolve_with(&resolvers, &client("jetbrains-idea")),
+            Some(("jetbrains", "idea".to_string()))
+        );
+        assert_eq!(resolve_with(&resolvers, &client("kitty")), None);
+    }
+}
@@ -0,0 +1,287 @@
+use crate::config::Config;
+use crate::ipc::HyprClient;
+use serde::{Deserialize, Serialize};
+
+/// What a rule does to the saved windows it matches
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RuleAction {
+    /// Leave the window out of snapshots and restores
+    Ignore,
+    /// Restore the window onto another workspace
+    RemapWorkspace(i32),
+    /// Restore the window as floating, whatever its saved state
+    ForceFloat,
+    /// Launch the window with this command when it is missing
+    Command(String),
+    /// Reposition the window if it is running, but never launch it
+    NeverRelaunch,
+    /// Launch the window in its own `systemd-run --user --scope` unit
+    Scope,
+    /// Save the window even if it looks like a dialog or splash screen
+    Keep,
+    /// Launch a fresh window even if a matching one is already running
+    AlwaysRelaunch,
+    /// Only move a running window to its saved workspace and geometry,
+    /// leaving its floating and fullscreen state alone; never launch it
+    PositionOnly,
+}
+
+/// How much of the restore applies to a class, set per class in the
+/// `[policies]` config section
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestorePolicy {
+    AlwaysRelaunch,
+    /// Restore the window if it is running, but never launch it
+    MatchOnly,
+    PositionOnly,
+    Ignore,
+}
+
+impl RestorePolicy {
+    /// The rule action that enforces the policy
+    pub fn action(self) -> RuleAction {
+        match self {
+            Self::AlwaysRelaunch => RuleAction::AlwaysRelaunch,
+            Self::MatchOnly => RuleAction::NeverRelaunch,
+            Self::PositionOnly => RuleAction::PositionOnly,
+            Self::Ignore => RuleAction::Ignore,
+        }
+    }
+}
+
+/// A restore rule from the `[[rules]]` config section.
+///
+/// All criteria given must match: `class` compares case-insensitively
+/// against the class or initial class, `title` is a case-insensitive
+/// substring, and `workspace` is the saved workspace id.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Rule {
+    #[serde(default)]
+    pub class: Option<String>,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub workspace: Option<i32>,
+    pub action: RuleAction,
+}
+
+impl Rule {
+    pub fn matches(&self, client: &HyprClient) -> bool {
+        let class_ok = self.class.as_ref().is_none_or(|class| {
+            client.class.eq_ignore_ascii_case(class)
+                || client.initial_class.eq_ignore_ascii_case(class)
+        });
+        let title_ok = self
+            .title
+            .as_ref()
+            .is_none_or(|title| client.title.to_lowercase().contains(&title.to_lowercase()));
+        let workspace_ok = self.workspace.is_none_or(|id| client.workspace.id == id);
+
+        class_ok && title_ok && workspace_ok
+    }
+}
+
+/// The combined effect of every rule matching one saved window
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Decision {
+    pub ignore: bool,
+    pub workspace: Option<i32>,
+    pub force_float: bool,
+    pub command: Option<String>,
+    pub never_relaunch: bool,
+    pub scope: bool,
+    pub keep: bool,
+    pub always_relaunch: bool,
+    pub position_only: bool,
+}
+
+impl Decision {
+    /// Whether a missing window may be launched
+    pub fn may_launch(&self) -> bool {
+        !self.never_relaunch && !self.position_only
+    }
+}
+
+/// Ordered rules; for each kind of decision the earliest matching rule wins
+#[derive(Debug, Clone, Default)]
+pub struct RuleSet {
+    rules: Vec<Rule>,
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Rule>) -> Self {
+        Self { rules }
+    }
+
+    /// Rules from the config, then a rule per class policy, then an
+    /// `ignore` rule per entry of `ignored_classes`, so explicit rules take
+    /// priority
+    pub fn from_config(config: &Config) -> Self {
+        let mut rules = config.rules.clone();
+        rules.extend(config.policies.iter().map(|(class, policy)| Rule {
+            class: Some(class.clone()),
+            title: None,
+            workspace: None,
+            action: policy.action(),
+        }));
+        rules.extend(config.ignored_classes.iter().map(|class| Rule {
+            class: Some(class.clone()),
+            title: None,
+            workspace: None,
+            action: RuleAction::Ignore,
+        }));
+        Self::new(rules)
+    }
+
+    pub fn evaluate(&self, client: &HyprClient) -> Decision {
+        let mut decision = Decision::default();
+
+        for rule in self.rules.iter().filter(|r| r.matches(client)) {
+            match &rule.action {
+                RuleAction::Ignore => decision.ignore = true,
+                RuleAction::RemapWorkspace(id) => {
+                    decision.workspace.get_or_insert(*id);
+                }
+                RuleAction::ForceFloat => decision.force_float = true,
+                RuleAction::Command(command) => {
+                    decision.command.get_or_insert_with(|| command.clone());
+                }
+                RuleAction::NeverRelaunch => decision.never_relaunch = true,
+                RuleAction::Scope => decision.scope = true,
+                RuleAction::Keep => decision.keep = true,
+                RuleAction::AlwaysRelaunch => decision.always_relaunch = true,
+                RuleAction::PositionOnly => decision.position_only = true,
+            }
+        }
+
+        decision
+    }
+
+    /// Apply workspace and floating decisions to a saved window
+    pub fn apply(decision: &Decision, client: &mut HyprClient) {
+        if let Some(id) = decision.workspace {
+            client.workspace.id = id;
+            client.workspace.name = id.to_string();
+        }
+        if decision.force_float {
+            client.floating = true;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str, title: &str, workspace: i32) -> HyprClient {
+        HyprClient::fixture("0x1", class)
+            .with_title(title)
+            .with_workspace(workspace)
+    }
+
+    fn rule(class: Option<&str>, title: Option<&str>, action: RuleAction) -> Rule {
+        Rule {
+            class: class.map(String::from),
+            title: title.map(String::from),
+            workspace: None,
+            action,
+        }
+    }
+
+    #[test]
+    fn test_earliest_rule_wins_per_decision() {
+        let rules = RuleSet::new(vec![
+            rule(Some("spotify"), None, RuleAction::RemapWorkspace(9)),
+            rule(Some("Spotify"), None, RuleAction::RemapWorkspace(3)),
+            rule(None, Some("music"), RuleAction::ForceFloat),
+        ]);
+
+        let decision = rules.evaluate(&client("Spotify", "My Music", 1));
+        assert_eq!(decision.workspace, Some(9));
+        assert!(decision.force_float);
+        assert!(!decision.ignore);
+    }
+
+    #[test]
+    fn test_ignored_classes_become_rules() {
+        let config = Config {
+            rules: vec![rule(Some("keepassxc"), None, RuleAction::NeverRelaunch)],
+            ..Config::default()
+        };
+        let rules = RuleSet::from_config(&config);
+
+        assert!(rules.evaluate(&client("rofi", "", 1)).ignore);
+        assert!(rules.evaluate(&client("keepassxc", "", 1)).never_relaunch);
+        assert_eq!(rules.evaluate(&client("kitty", "", 1)), Decision::default());
+    }
+
+    #[test]
+    fn test_policies_follow_rules() {
+        let config: Config = toml::from_str(
+            r#"
+            [policies]
+            keepassxc = "position-only"
+            firefox = "always-relaunch"
+            "1password" = "match-only"
+
+            [[rules]]
+            class = "firefox"
+            action = "ignore"
+            "#,
+        )
+        .unwrap();
+        let rules = RuleSet::from_config(&config);
+
+        let keepass = rules.evaluate(&client("KeePassXC", "", 1));
+        assert!(keepass.position_only && !keepass.may_launch());
+        assert!(!rules.evaluate(&client("1password", "", 1)).may_launch());
+        let firefox = rules.evaluate(&client("firefox", "", 1));
+        assert!(firefox.always_relaunch && firefox.ignore);
+    }
+
+    #[test]
+    fn test_workspace_criterion() {
+        let rules = RuleSet::new(vec![Rule {
+            workspace: Some(2),
+            ..rule(
+                Some("kitty"),
+                None,
+                RuleAction::Command("kitty -e htop".into()),
+            )
+        }]);
+
+        assert!(rules.evaluate(&client("kitty", "", 2)).command.is_some());
+        assert!(rules.evaluate(&client("kitty", "", 3)).command.is_none());
+    }
+
+    #[test]
+    fn test_actions_parse_from_toml() {
+        #[derive(Deserialize)]
+        struct Rules {
+            rules: Vec<Rule>,
+        }
+
+        let parsed: Rules = toml::from_str(
+            r#"
+            [[rules]]
+            class = "keepassxc"
+            action = "never-relaunch"
+
+            [[rules]]
+            class = "spotify"
+            action = { remap-workspace = 9 }
+
+            [[rules]]
+            class = "steam"
+            action = "scope"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(parsed.rules[0].action, RuleAction::NeverRelaunch);
+        assert_eq!(parsed.rules[1].action, RuleAction::RemapWorkspace(9));
+        assert_eq!(parsed.rules[2].action, RuleAction::Scope);
+    }
+}
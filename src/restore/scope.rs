@@ -0,0 +1,101 @@
+use std::fs;
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A unique systemd unit name for one launch of `class`, e.g.
+/// `hyprdrover-org.gnome.Nautilus-4711-1`
+pub fn unit_name(class: &str, launch: usize) -> String {
+    let class: String = class
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let class = if class.is_empty() { "app" } else { &class };
+    // The pid and clock keep names unique across restores in one login
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default();
+    format!(
+        "hyprdrover-{}-{}-{}-{}",
+        class,
+        process::id(),
+        stamp,
+        launch
+    )
+}
+
+/// Wrap `command` so it runs in its own transient user scope named `unit`.
+///
+/// The scope gives the app its own cgroup, so it can be resource-limited and
+/// torn down as a whole with `systemctl --user stop UNIT.scope`. `--collect`
+/// removes the unit once the app exits, even if it failed.
+pub fn wrap(command: &str, unit: &str) -> String {
+    format!(
+        "systemd-run --user --scope --collect --quiet --unit={} -- sh -c {}",
+        unit,
+        shell_quote(command)
+    )
+}
+
+/// The cgroup a process belongs to, from `/proc/<pid>/cgroup`
+pub fn cgroup_of(pid: i32) -> Option<String> {
+    parse_cgroup(&fs::read_to_string(format!("/proc/{}/cgroup", pid)).ok()?)
+}
+
+/// The unified (v2) hierarchy path, e.g. `/user.slice/.../app.slice/x.scope`
+fn parse_cgroup(content: &str) -> Option<String> {
+    content
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(String::from)
+}
+
+/// Whether `cgroup` is the scope `unit` or nested inside it. Apps that
+/// re-exec through a launcher still stay in the scope they started in.
+pub fn in_unit(cgroup: &str, unit: &str) -> bool {
+    let scope = format!("{}.scope", unit);
+    cgroup.split('/').any(|part| part == scope)
+}
+
+/// Quote `s` as a single shell word
+pub(crate) fn shell_quote(s: &str) -> String {
+    format!("'{}'", s.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unit_name_is_sanitized() {
+        let name = unit_name("Steam Friends/List", 3);
+        assert!(name.starts_with("hyprdrover-Steam_Friends_List-"));
+        assert!(name.ends_with("-3"));
+        assert!(unit_name("", 0).starts_with("hyprdrover-app-"));
+    }
+
+    #[test]
+    fn test_cgroup_membership() {
+        let content = "0::/user.slice/user-1000.slice/user@1000.service/app.slice/hyprdrover-steam-7-1.scope\n";
+        let cgroup = parse_cgroup(content).unwrap();
+
+        assert!(in_unit(&cgroup, "hyprdrover-steam-7-1"));
+        assert!(!in_unit(&cgroup, "hyprdrover-steam-7"));
+        assert!(in_unit(&format!("{}/sub", cgroup), "hyprdrover-steam-7-1"));
+        assert_eq!(parse_cgroup("12:cpu:/foo\n"), None);
+    }
+
+    #[test]
+    fn test_wrap_quotes_command() {
+        assert_eq!(
+            wrap("kitty -e sh -c 'htop'", "hyprdrover-kitty-1"),
+            r"systemd-run --user --scope --collect --quiet --unit=hyprdrover-kitty-1 -- sh -c 'kitty -e sh -c '\''htop'\'''"
+        );
+    }
+}
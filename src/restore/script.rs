@@ -0,0 +1,150 @@
+use crate::ipc::HyprClient;
+use rhai::{Dynamic, Engine, Map, Scope, AST};
+use std::error::Error;
+use std::path::Path;
+
+/// A user-supplied Rhai script consulted during restore.
+///
+/// The script may define either or both of:
+///
+/// ```rhai
+/// // Return true/false to decide, or nothing to use the built-in matching
+/// fn matches(saved, current) { ... }
+///
+/// // Return the command to launch `saved` with, or nothing for the default
+/// fn launch_command(saved) { ... }
+/// ```
+///
+/// Windows are passed as maps with `address`, `class`, `title`,
+/// `initial_class`, `initial_title`, `workspace`, `workspace_name`,
+/// `floating`, `pinned`, `pid` and `exec_path`.
+pub struct RestoreScript {
+    engine: Engine,
+    ast: AST,
+}
+
+impl RestoreScript {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, Box<dyn Error>> {
+        let engine = Engine::new();
+        let ast = engine.compile_file(path.as_ref().to_path_buf())?;
+        Ok(Self { engine, ast })
+    }
+
+    /// Ask the script whether `current` is the live instance of `saved`
+    pub fn matches(&self, saved: &HyprClient, current: &HyprClient) -> Option<bool> {
+        self.call("matches", (client_map(saved), client_map(current)))?
+            .as_bool()
+            .ok()
+    }
+
+    /// Ask the script for the command that relaunches `saved`
+    pub fn launch_command(&self, saved: &HyprClient) -> Option<String> {
+        self.call("launch_command", (client_map(saved),))?
+            .into_string()
+            .ok()
+            .filter(|command| !command.trim().is_empty())
+    }
+
+    /// Call `name` if the script defines it. Script errors are reported and
+    /// treated as "no opinion" so a broken script never aborts a restore.
+    fn call(&self, name: &str, args: impl rhai::FuncArgs) -> Option<Dynamic> {
+        if !self.ast.iter_functions().any(|f| f.name == name) {
+            return None;
+        }
+
+        match self
+            .engine
+            .call_fn::<Dynamic>(&mut Scope::new(), &self.ast, name, args)
+        {
+            Ok(value) if value.is_unit() => None,
+            Ok(value) => Some(value),
+            Err(e) => {
+                eprintln!("   ⚠️ Restore script `{}` failed: {}", name, e);
+                None
+            }
+        }
+    }
+}
+
+fn client_map(client: &HyprClient) -> Map {
+    let mut map = Map::new();
+    map.insert("address".into(), client.address.clone().into());
+    map.insert("class".into(), client.class.clone().into());
+    map.insert("title".into(), client.title.clone().into());
+    map.insert("initial_class".into(), client.initial_class.clone().into());
+    map.insert("initial_title".into(), client.initial_title.clone().into());
+    map.insert("workspace".into(), (client.workspace.id as i64).into());
+    map.insert(
+        "workspace_name".into(),
+        client.workspace.name.clone().into(),
+    );
+    map.insert("floating".into(), client.floating.into());
+    map.insert("pinned".into(), client.pinned.into());
+    map.insert("pid".into(), (client.pid as i64).into());
+    map.insert(
+        "exec_path".into(),
+        client
+            .exec_path
+            .clone()
+            .map(Dynamic::from)
+            .unwrap_or(Dynamic::UNIT),
+    );
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn client(class: &str, title: &str) -> HyprClient {
+        HyprClient::fixture("0x1", class)
+            .with_title(title)
+            .with_workspace(2)
+    }
+
+    fn script(source: &str) -> RestoreScript {
+        let path = std::env::temp_dir().join(format!(
+            "hyprdrover-script-{}-{}.rhai",
+            std::process::id(),
+            source.len()
+        ));
+        fs::write(&path, source).unwrap();
+        let script = RestoreScript::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        script
+    }
+
+    #[test]
+    fn test_script_decides_matches_and_commands() {
+        let script = script(
+            r#"
+            fn matches(saved, current) {
+                if saved.class == "code" { return saved.title == current.title; }
+            }
+            fn launch_command(saved) {
+                if saved.class == "code" { "code --new-window" }
+            }
+            "#,
+        );
+
+        let saved = client("code", "notes");
+        assert_eq!(script.matches(&saved, &client("code", "notes")), Some(true));
+        assert_eq!(
+            script.matches(&saved, &client("code", "other")),
+            Some(false)
+        );
+        assert_eq!(script.matches(&client("kitty", ""), &saved), None);
+        assert_eq!(
+            script.launch_command(&saved).as_deref(),
+            Some("code --new-window")
+        );
+        assert_eq!(script.launch_command(&client("kitty", "")), None);
+    }
+
+    #[test]
+    fn test_script_without_functions_has_no_opinion() {
+        let script = script("let x = 1;");
+        assert_eq!(script.matches(&client("a", ""), &client("a", "")), None);
+    }
+}
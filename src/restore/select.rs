@@ -0,0 +1,114 @@
+use crate::ipc::HyprClient;
+use std::str::FromStr;
+
+/// Picks saved windows for a partial restore, e.g. `class:obsidian` or
+/// `workspace:2/title~notes`.
+///
+/// Criteria are joined with `/` and must all match. `field:value` compares
+/// the whole value and `field~value` looks for a substring, both ignoring
+/// case. Fields are `class` (class or initial class), `title` and
+/// `workspace` (id or name).
+#[derive(Debug, Clone, PartialEq)]
+pub struct WindowSelector {
+    criteria: Vec<Criterion>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Class,
+    Title,
+    Workspace,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Criterion {
+    field: Field,
+    value: String,
+    substring: bool,
+}
+
+impl Criterion {
+    fn matches(&self, client: &HyprClient) -> bool {
+        let workspace_id = client.workspace.id.to_string();
+        let candidates = match self.field {
+            Field::Class => [client.class.as_str(), client.initial_class.as_str()],
+            Field::Title => [client.title.as_str(), client.initial_title.as_str()],
+            Field::Workspace => [workspace_id.as_str(), client.workspace.name.as_str()],
+        };
+        candidates.iter().any(|candidate| {
+            let candidate = candidate.to_lowercase();
+            if self.substring {
+                candidate.contains(&self.value)
+            } else {
+                candidate == self.value
+            }
+        })
+    }
+}
+
+impl WindowSelector {
+    pub fn matches(&self, client: &HyprClient) -> bool {
+        self.criteria.iter().all(|c| c.matches(client))
+    }
+}
+
+impl FromStr for WindowSelector {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let criteria = s
+            .split('/')
+            .map(|part| {
+                let at = part.find([':', '~']).ok_or_else(|| {
+                    format!("expected FIELD:VALUE or FIELD~VALUE, got {:?}", part)
+                })?;
+                let field = match &part[..at] {
+                    "class" => Field::Class,
+                    "title" => Field::Title,
+                    "workspace" => Field::Workspace,
+                    other => {
+                        return Err(format!(
+                            "unknown field {:?} (expected class, title or workspace)",
+                            other
+                        ))
+                    }
+                };
+                Ok(Criterion {
+                    field,
+                    value: part[at + 1..].to_lowercase(),
+                    substring: part[at..].starts_with('~'),
+                })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { criteria })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str, title: &str, workspace: i32) -> HyprClient {
+        HyprClient::fixture("0x1", class)
+            .with_title(title)
+            .with_workspace(workspace)
+    }
+
+    #[test]
+    fn test_selector_criteria() {
+        let by_class: WindowSelector = "class:Obsidian".parse().unwrap();
+        assert!(by_class.matches(&client("obsidian", "Vault", 3)));
+        assert!(!by_class.matches(&client("obsidian-helper", "", 3)));
+
+        let combined: WindowSelector = "workspace:2/title~notes".parse().unwrap();
+        assert!(combined.matches(&client("kitty", "nvim Notes.md", 2)));
+        assert!(!combined.matches(&client("kitty", "nvim Notes.md", 3)));
+        assert!(!combined.matches(&client("kitty", "htop", 2)));
+    }
+
+    #[test]
+    fn test_selector_rejects_bad_syntax() {
+        assert!("obsidian".parse::<WindowSelector>().is_err());
+        assert!("pid:12".parse::<WindowSelector>().is_err());
+    }
+}
@@ -0,0 +1,126 @@
+//! Launching a missing app and waiting for its window. The window is only
+//! positioned once it exists: `openwindow` events of the saved class wake
+//! the wait, and a fresh query confirms which window the launch produced.
+
+use super::report::Outcome;
+use super::{lineage, position, scope, Restorer};
+use crate::ipc::{self, Dispatch, HyprClient};
+use std::cmp::Reverse;
+use std::error::Error;
+use std::time::{Duration, Instant};
+
+/// How often to look again when no matching window has announced itself.
+/// Some apps only set their class after opening, and a window in the
+/// launch's scope counts whatever its class.
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Without the event socket, polling is all there is
+const POLL_INTERVAL_UNWATCHED: Duration = Duration::from_millis(250);
+
+impl Restorer {
+    /// Launch `command` on `saved`'s workspace, wait for the window it opens
+    /// and put that window where `saved` was
+    pub(super) fn spawn(
+        &mut self,
+        saved: &HyprClient,
+        command: &str,
+    ) -> Result<(HyprClient, Outcome), Box<dyn Error>> {
+        println!("      -> Launching: {}", command);
+        // A unit left from an earlier launch would claim this window's match
+        self.launched_unit = None;
+        let command = if self.decision(saved).scope {
+            let unit = scope::unit_name(&saved.class, self.report.entries.len());
+            println!("         in scope {}.scope", unit);
+            let command = scope::wrap(command, &unit);
+            self.launched_unit = Some(unit);
+            command
+        } else {
+            command.to_string()
+        };
+        let launch_id = format!("{}-{}", std::process::id(), self.report.entries.len());
+        ipc::dispatch_typed(Dispatch::Exec {
+            rules: vec![format!("workspace {} silent", saved.workspace.target())],
+            cmd: lineage::tag(&command, &launch_id),
+        })
+        .map_err(|e| format!("Failed to launch {}: {}", command, e))?;
+        self.emit(|o| o.spawn_issued(saved, &command));
+
+        let window = self.wait_for_window(saved, &launch_id)?.ok_or_else(|| {
+            format!(
+                "Could not find launched window for positioning (timed out): {}",
+                saved.class
+            )
+        })?;
+
+        // Later launches of a single-instance app are handed to this process
+        self.baseline_pids.insert(window.pid);
+        println!("   Positioning launched window: {}", saved.class);
+        self.emit(|o| o.window_appeared(saved, &window));
+        position::restore_window_position(&window, saved, true)?;
+        self.emit(|o| o.positioned(saved, &window));
+        self.restored_addresses.insert(window.address.clone());
+        Ok((window, Outcome::Launched))
+    }
+
+    /// The window launch `launch_id` opened for `saved`, or `None` once the
+    /// launch timeout passes
+    fn wait_for_window(
+        &self,
+        saved: &HyprClient,
+        launch_id: &str,
+    ) -> Result<Option<HyprClient>, Box<dyn Error>> {
+        let poll = if self.misplaced.is_watching() {
+            POLL_INTERVAL
+        } else {
+            POLL_INTERVAL_UNWATCHED
+        };
+        let deadline = Instant::now() + self.launch_timeout;
+        loop {
+            let clients = ipc::capture_state()?.clients;
+            if let Some(window) = self.launched_window(&clients, saved, launch_id) {
+                return Ok(Some(window.clone()));
+            }
+            let Some(left) = deadline.checked_duration_since(Instant::now()) else {
+                return Ok(None);
+            };
+            self.misplaced.wait(left.min(poll), saved, |address| {
+                self.baseline_addresses.contains(address)
+                    || self.restored_addresses.contains(address)
+            });
+        }
+    }
+
+    /// Among windows that weren't there before the restore, the one in the
+    /// launch's own scope, else the best match for `saved`, preferring
+    /// windows whose process descends from the launch
+    fn launched_window<'a>(
+        &self,
+        clients: &'a [HyprClient],
+        saved: &HyprClient,
+        launch_id: &str,
+    ) -> Option<&'a HyprClient> {
+        let new_clients: Vec<&HyprClient> = clients
+            .iter()
+            .filter(|c| {
+                !self.baseline_addresses.contains(&c.address)
+                    && !self.restored_addresses.contains(&c.address)
+            })
+            .collect();
+        let in_scope = self.launched_unit.as_ref().and_then(|unit| {
+            new_clients.iter().copied().find(|c| {
+                !ipc::socket::is_remote()
+                    && scope::cgroup_of(c.pid).is_some_and(|cg| scope::in_unit(&cg, unit))
+            })
+        });
+        in_scope.or_else(|| {
+            new_clients
+                .into_iter()
+                .filter_map(|c| {
+                    let score = self.score(c, saved)?;
+                    Some((c, (self.lineage_rank(c, launch_id), score)))
+                })
+                .min_by_key(|&(_, rank)| Reverse(rank))
+                .map(|(c, _)| c)
+        })
+    }
+}
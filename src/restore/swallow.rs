@@ -0,0 +1,79 @@
+use super::scope::shell_quote;
+use crate::ipc::HyprClient;
+use std::collections::HashMap;
+
+/// Split out terminals swallowed by another saved window: they come back when
+/// the swallowing app is relaunched from a terminal, not as windows of their
+/// own. Returns the remaining clients and each swallowed terminal keyed by
+/// the address of the window that swallowed it.
+pub fn pair_up(clients: Vec<HyprClient>) -> (Vec<HyprClient>, HashMap<String, HyprClient>) {
+    let swallowed: HashMap<&str, &str> = clients
+        .iter()
+        .filter_map(|c| Some((c.swallowing.as_deref()?, c.address.as_str())))
+        .collect();
+
+    let mut terminals = HashMap::new();
+    let mut rest = vec![];
+    for client in &clients {
+        match swallowed.get(client.address.as_str()) {
+            Some(swallower) => {
+                terminals.insert(swallower.to_string(), client.clone());
+            }
+            None => rest.push(client.clone()),
+        }
+    }
+    (rest, terminals)
+}
+
+/// `terminal` running `argv`, using the `-e` flag most terminals accept
+pub fn terminal_command(terminal: &str, argv: &[String]) -> String {
+    let args: Vec<String> = argv.iter().map(|arg| shell_quote(arg)).collect();
+    format!("{} -e {}", terminal, args.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(address: &str, class: &str, swallowing: Option<&str>) -> HyprClient {
+        HyprClient {
+            swallowing: swallowing.map(String::from),
+            ..HyprClient::fixture(address, class)
+        }
+    }
+
+    #[test]
+    fn test_null_swallowing_address_is_none() {
+        let swallowing = |address: &str| -> HyprClient {
+            let mut json = serde_json::to_value(HyprClient::fixture("0x2", "mpv")).unwrap();
+            json["swallowing"] = address.into();
+            serde_json::from_value(json).unwrap()
+        };
+        assert_eq!(swallowing("0x0").swallowing, None);
+        assert_eq!(swallowing("0x1").swallowing.as_deref(), Some("0x1"));
+    }
+
+    #[test]
+    fn test_pair_up_drops_swallowed_terminals() {
+        let clients = vec![
+            client("0x1", "kitty", None),
+            client("0x2", "mpv", Some("0x1")),
+            client("0x3", "kitty", None),
+        ];
+
+        let (rest, terminals) = pair_up(clients);
+        let addresses: Vec<&str> = rest.iter().map(|c| c.address.as_str()).collect();
+
+        assert_eq!(addresses, vec!["0x2", "0x3"]);
+        assert_eq!(terminals["0x2"].address, "0x1");
+    }
+
+    #[test]
+    fn test_terminal_command_quotes_arguments() {
+        let argv = vec!["mpv".to_string(), "my video.mkv".to_string()];
+        assert_eq!(
+            terminal_command("kitty", &argv),
+            "kitty -e 'mpv' 'my video.mkv'"
+        );
+    }
+}
@@ -0,0 +1,53 @@
+use super::scope::shell_quote;
+use crate::ipc::HyprClient;
+
+/// Fill in `{workspace}`, `{monitor}`, `{title}` and `{cwd}` in a
+/// configured launch command from the saved window, quoting each value as
+/// one shell word. `monitor` is the name of the saved window's monitor. An
+/// unknown working directory becomes the home directory.
+pub fn expand(command: &str, saved: &HyprClient, monitor: Option<&str>) -> String {
+    if !command.contains('{') {
+        return command.to_string();
+    }
+    let cwd = match &saved.cwd {
+        Some(cwd) => shell_quote(cwd),
+        None => "\"$HOME\"".to_string(),
+    };
+    command
+        .replace("{workspace}", &shell_quote(&saved.workspace.name))
+        .replace("{monitor}", &shell_quote(monitor.unwrap_or_default()))
+        .replace("{title}", &shell_quote(&saved.title))
+        .replace("{cwd}", &cwd)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(title: &str, cwd: Option<&str>) -> HyprClient {
+        HyprClient {
+            title: title.to_string(),
+            cwd: cwd.map(String::from),
+            ..HyprClient::fixture("0x1", "kitty").with_workspace(3)
+        }
+    }
+
+    #[test]
+    fn test_expand_quotes_values() {
+        let saved = client("it's done", Some("/home/me/my project"));
+
+        assert_eq!(
+            expand("kitty --directory {cwd} --title {title}", &saved, None),
+            r"kitty --directory '/home/me/my project' --title 'it'\''s done'"
+        );
+        assert_eq!(
+            expand("app --ws {workspace} --on {monitor}", &saved, Some("DP-1")),
+            "app --ws '3' --on 'DP-1'"
+        );
+        assert_eq!(
+            expand("kitty --directory {cwd}", &client("", None), None),
+            "kitty --directory \"$HOME\""
+        );
+        assert_eq!(expand("awk '{print}'", &saved, None), "awk '{print}'");
+    }
+}
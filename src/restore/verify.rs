@@ -0,0 +1,100 @@
+use crate::ipc::{HyprLayer, HyprPlugin};
+use std::collections::BTreeSet;
+
+/// Overlay-level surfaces are mostly transient (launchers, notifications,
+/// OSDs), so only the levels below it are expected to survive a restore
+const OVERLAY_LEVEL: u8 = 3;
+
+/// Namespaces of long-lived layer surfaces (bars, wallpapers, docks) that
+/// were present at save time but are missing now.
+///
+/// Namespaces in `ignored` (the `ignored_layers` setting) are skipped.
+pub fn missing_layers(
+    saved: &[HyprLayer],
+    current: &[HyprLayer],
+    ignored: &[String],
+) -> Vec<String> {
+    let current: BTreeSet<&str> = current.iter().map(|l| l.namespace.as_str()).collect();
+
+    saved
+        .iter()
+        .filter(|l| l.level < OVERLAY_LEVEL)
+        .map(|l| l.namespace.as_str())
+        .filter(|ns| !ns.is_empty() && !current.contains(ns))
+        .filter(|ns| !ignored.iter().any(|class| class.eq_ignore_ascii_case(ns)))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+/// Names of plugins loaded at save time but not now
+pub fn missing_plugins(saved: &[HyprPlugin], current: &[HyprPlugin]) -> Vec<String> {
+    saved
+        .iter()
+        .filter(|p| !current.iter().any(|c| c.name.eq_ignore_ascii_case(&p.name)))
+        .map(|p| p.name.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(namespace: &str, level: u8) -> HyprLayer {
+        HyprLayer {
+            monitor: "DP-1".to_string(),
+            level,
+            namespace: namespace.to_string(),
+            address: "0x1".to_string(),
+            x: 0,
+            y: 0,
+            w: 100,
+            h: 30,
+            pid: 1,
+        }
+    }
+
+    #[test]
+    fn test_missing_layers_skips_overlays_and_ignored() {
+        let saved = vec![
+            layer("waybar", 2),
+            layer("waybar", 2),
+            layer("hyprpaper", 0),
+            layer("rofi", 2),
+            layer("notifications", 3),
+        ];
+        let current = vec![layer("hyprpaper", 0)];
+
+        assert_eq!(
+            missing_layers(&saved, &current, &["rofi".to_string()]),
+            vec!["waybar".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_default_config_reports_missing_bar() {
+        // waybar is an ignored class by default, but a missing bar still counts
+        let config = crate::config::Config::default();
+        assert_eq!(
+            missing_layers(&[layer("waybar", 2)], &[], &config.ignored_layers),
+            vec!["waybar".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_missing_plugins() {
+        let plugin = |name: &str| HyprPlugin {
+            name: name.to_string(),
+            author: String::new(),
+            version: String::new(),
+        };
+        let saved = vec![plugin("hy3"), plugin("hyprexpo")];
+
+        assert_eq!(
+            missing_plugins(&saved, &[plugin("Hyprexpo")]),
+            vec!["hy3".to_string()]
+        );
+        assert!(missing_plugins(&saved, &saved).is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+use crate::config::LaunchWave;
+use crate::ipc::HyprClient;
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+
+/// Saved windows of one wave and the pause before restoring them
+#[derive(Debug, Clone)]
+pub struct Wave {
+    pub delay: Duration,
+    pub clients: Vec<HyprClient>,
+}
+
+/// Index of the first configured wave listing the window's class, if any
+fn wave_of(waves: &[LaunchWave], client: &HyprClient) -> Option<usize> {
+    waves.iter().position(|wave| {
+        wave.classes.iter().any(|class| {
+            client.class.eq_ignore_ascii_case(class)
+                || client.initial_class.eq_ignore_ascii_case(class)
+        })
+    })
+}
+
+/// Split `clients` into waves: windows in no configured wave come first with
+/// no delay, then each configured wave in order. Empty waves are dropped, and
+/// the original order is kept within each wave.
+pub fn group(clients: Vec<HyprClient>, waves: &[LaunchWave]) -> Vec<Wave> {
+    let mut grouped: Vec<Wave> = std::iter::once(Duration::ZERO)
+        .chain(waves.iter().map(|w| Duration::from_millis(w.delay_ms)))
+        .map(|delay| Wave {
+            delay,
+            clients: vec![],
+        })
+        .collect();
+
+    for client in clients {
+        let index = wave_of(waves, &client).map_or(0, |i| i + 1);
+        grouped[index].clients.push(client);
+    }
+
+    grouped.retain(|wave| !wave.clients.is_empty());
+    grouped
+}
+
+/// Per wave, its delay and the saved windows to restore on each workspace.
+///
+/// A workspace's split tree can only be rebuilt from all of its tiled
+/// windows at once, so they are restored together, in the last wave that
+/// has one of them; floating and pinned windows stay in their own wave.
+pub fn by_workspace(waves: Vec<Wave>) -> Vec<(Duration, BTreeMap<i32, Vec<HyprClient>>)> {
+    let tiled = |c: &HyprClient| !c.floating && !c.pinned;
+    let mut tree_wave: HashMap<i32, usize> = HashMap::new();
+    for (i, wave) in waves.iter().enumerate() {
+        for client in wave.clients.iter().filter(|c| tiled(c)) {
+            tree_wave.insert(client.workspace.id, i);
+        }
+    }
+
+    let mut deferred: HashMap<i32, Vec<HyprClient>> = HashMap::new();
+    let mut grouped = vec![];
+    for (i, wave) in waves.into_iter().enumerate() {
+        let mut workspaces: BTreeMap<i32, Vec<HyprClient>> = BTreeMap::new();
+        for client in wave.clients {
+            let id = client.workspace.id;
+            if tiled(&client) && tree_wave[&id] != i {
+                deferred.entry(id).or_default().push(client);
+                continue;
+            }
+            if tree_wave.get(&id) == Some(&i) {
+                if let Some(earlier) = deferred.remove(&id) {
+                    workspaces.entry(id).or_default().extend(earlier);
+                }
+            }
+            workspaces.entry(id).or_default().push(client);
+        }
+        grouped.push((wave.delay, workspaces));
+    }
+    grouped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str) -> HyprClient {
+        HyprClient::fixture(class, class)
+    }
+
+    fn wave(classes: &[&str], delay_ms: u64) -> LaunchWave {
+        LaunchWave {
+            classes: classes.iter().map(|c| c.to_string()).collect(),
+            delay_ms,
+        }
+    }
+
+    #[test]
+    fn test_group_orders_waves() {
+        let waves = vec![
+            wave(&["kitty"], 0),
+            wave(&["firefox"], 2000),
+            wave(&["discord", "Slack"], 4000),
+        ];
+        let clients = vec![
+            client("slack"),
+            client("firefox"),
+            client("mpv"),
+            client("kitty"),
+        ];
+
+        let grouped = group(clients, &waves);
+        let classes: Vec<Vec<&str>> = grouped
+            .iter()
+            .map(|w| w.clients.iter().map(|c| c.class.as_str()).collect())
+            .collect();
+
+        assert_eq!(
+            classes,
+            vec![vec!["mpv"], vec!["kitty"], vec!["firefox"], vec!["slack"]]
+        );
+        assert_eq!(grouped[2].delay, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_tiled_windows_of_a_workspace_stay_together() {
+        let mut editor = client("code");
+        editor.workspace.id = 2;
+        let mut player = client("mpv");
+        player.floating = true;
+        let waves = vec![
+            wave(&["kitty", "mpv"], 0),
+            wave(&["firefox"], 2000),
+            wave(&["code"], 4000),
+        ];
+        let grouped = by_workspace(group(
+            vec![client("kitty"), player, client("firefox"), editor],
+            &waves,
+        ));
+
+        let classes: Vec<Vec<(i32, Vec<&str>)>> = grouped
+            .iter()
+            .map(|(_, workspaces)| {
+                workspaces
+                    .iter()
+                    .map(|(&id, clients)| (id, clients.iter().map(|c| c.class.as_str()).collect()))
+                    .collect()
+            })
+            .collect();
+        // kitty waits for firefox, its tiled neighbour on workspace 1; the
+        // floating player doesn't
+        assert_eq!(
+            classes,
+            vec![
+                vec![(1, vec!["mpv"])],
+                vec![(1, vec!["kitty", "firefox"])],
+                vec![(2, vec!["code"])],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_without_waves_is_one_pass() {
+        let grouped = group(vec![client("a"), client("b")], &[]);
+        assert_eq!(grouped.len(), 1);
+        assert_eq!(grouped[0].delay, Duration::ZERO);
+    }
+}
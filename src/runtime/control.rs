@@ -0,0 +1,72 @@
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::Duration;
+
+/// Send one command to the daemon's control socket and wait for its reply.
+///
+/// The protocol is line based: the client writes a command such as `save`,
+/// and the daemon answers with `ok <detail>` or `error <message>`. An
+/// `error` reply is returned as an `io::Error` of kind `Other`.
+pub fn request(socket: &Path, command: &str, timeout: Duration) -> io::Result<String> {
+    let mut stream = UnixStream::connect(socket)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+
+    writeln!(stream, "{}", command)?;
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    parse_reply(&reply).map_err(io::Error::other)
+}
+
+fn parse_reply(reply: &str) -> Result<String, String> {
+    let reply = reply.trim_end();
+    let (status, detail) = reply.split_once(' ').unwrap_or((reply, ""));
+    match status {
+        "ok" => Ok(detail.to_string()),
+        "error" => Err(detail.to_string()),
+        _ => Err(format!("unexpected reply from daemon: {:?}", reply)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+    use std::thread;
+
+    #[test]
+    fn test_parse_reply() {
+        assert_eq!(
+            parse_reply("ok /tmp/a.json\n"),
+            Ok("/tmp/a.json".to_string())
+        );
+        assert_eq!(parse_reply("ok"), Ok(String::new()));
+        assert_eq!(
+            parse_reply("error no session"),
+            Err("no session".to_string())
+        );
+        assert!(parse_reply("").is_err());
+    }
+
+    #[test]
+    fn test_request_round_trip() {
+        let path =
+            std::env::temp_dir().join(format!("hyprdrover-control-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            assert_eq!(line, "save\n");
+            writeln!(&stream, "ok saved").unwrap();
+        });
+
+        let reply = request(&path, "save", Duration::from_secs(1)).unwrap();
+        assert_eq!(reply, "saved");
+        server.join().unwrap();
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,117 @@
+use crate::paths;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Records whether the last graphical session ended cleanly.
+///
+/// The long-running listener drops a marker naming the Hyprland instance
+/// and its own pid when it starts, and removes it when it stops normally:
+/// on `hyprdrover exit`, or when the compositor closes the event socket on
+/// logout without leaving a crash report behind. A marker that is still
+/// there when another instance starts means the session ended some other
+/// way: a compositor crash, a power loss or a hard reset. It lives in the
+/// state directory because the runtime directory does not survive a reboot.
+pub struct SessionMarker {
+    path: PathBuf,
+    crash_reports: PathBuf,
+}
+
+impl SessionMarker {
+    pub fn new(state_dir: impl AsRef<Path>, seat: &str) -> Self {
+        Self {
+            path: state_dir.as_ref().join(format!("session-{}.running", seat)),
+            crash_reports: paths::hyprland_cache_dir(),
+        }
+    }
+
+    /// Look for Hyprland crash reports in `dir` instead of the default
+    pub fn with_crash_reports(mut self, dir: impl AsRef<Path>) -> Self {
+        self.crash_reports = dir.as_ref().to_path_buf();
+        self
+    }
+
+    /// Mark the session of Hyprland instance `signature` as running;
+    /// returns whether the previous one ended without a clean exit. A
+    /// marker left by the same instance, as when the listener is restarted
+    /// mid-session, or by a process that is still running doesn't count.
+    pub fn begin(&self, signature: &str) -> io::Result<bool> {
+        let unclean = match fs::read_to_string(&self.path) {
+            Ok(previous) => is_unclean(&previous, signature),
+            Err(_) => false,
+        };
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(
+            &self.path,
+            format!("{}\n{}\n", signature, std::process::id()),
+        )?;
+        Ok(unclean)
+    }
+
+    /// The listener stayed up while the compositor was replaced by instance
+    /// `signature`; returns whether the old one crashed, leaving a report
+    pub fn restart(&self, signature: &str) -> io::Result<bool> {
+        let crashed = fs::metadata(&self.path)
+            .and_then(|m| m.modified())
+            .is_ok_and(|since| crash_reported_since(&self.crash_reports, since));
+        self.begin(signature)?;
+        Ok(crashed)
+    }
+
+    /// The listener is stopping because the compositor went away. That is
+    /// a clean end unless Hyprland wrote a crash report since the marker.
+    pub fn finish(&self) -> io::Result<()> {
+        let Ok(since) = fs::metadata(&self.path).and_then(|m| m.modified()) else {
+            return Ok(());
+        };
+        if crash_reported_since(&self.crash_reports, since) {
+            return Ok(());
+        }
+        self.end()
+    }
+
+    /// The session is ending on purpose
+    pub fn end(&self) -> io::Result<()> {
+        match fs::remove_file(&self.path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Whether a marker with content `previous` means the last session ended
+/// uncleanly, now that instance `signature` is starting. Markers from
+/// older versions, which only held a pid, can't tell and count as clean.
+fn is_unclean(previous: &str, signature: &str) -> bool {
+    let mut lines = previous.lines();
+    let (Some(previous_signature), Some(pid)) = (lines.next(), lines.next()) else {
+        return false;
+    };
+    // After a reboot the pid may belong to something else entirely
+    let writer_alive = pid.trim().parse::<u32>().is_ok_and(|pid| {
+        fs::read_to_string(format!("/proc/{}/comm", pid))
+            .is_ok_and(|comm| comm.trim() == "hyprdrover")
+    });
+    previous_signature != signature && !writer_alive
+}
+
+/// Whether Hyprland left a `hyprlandCrashReport*.txt` in `dir` at or after
+/// `since`
+fn crash_reported_since(dir: &Path, since: SystemTime) -> bool {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return false;
+    };
+    entries.flatten().any(|entry| {
+        entry
+            .file_name()
+            .to_string_lossy()
+            .starts_with("hyprlandCrashReport")
+            && entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified >= since)
+    })
+}
@@ -0,0 +1,320 @@
+use crate::ipc::{HyprClient, HyprMonitor};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicI32, Ordering};
+
+/// Stops (`SIGSTOP`) allowlisted apps while all of their windows sit on
+/// hidden special workspaces, and continues them (`SIGCONT`) once one of
+/// those windows can be seen again. Child processes (e.g. Electron
+/// renderers) are stopped and continued along with the app.
+#[derive(Debug, Default)]
+pub struct Freezer {
+    classes: Vec<String>,
+    frozen: HashSet<i32>,
+    /// Where stopped pids are recorded, so a later run can thaw them
+    state: Option<PathBuf>,
+}
+
+impl Freezer {
+    /// `classes` are compared case-insensitively
+    pub fn new(classes: &[String]) -> Self {
+        Self {
+            classes: classes.iter().map(|c| c.to_lowercase()).collect(),
+            frozen: HashSet::new(),
+            state: None,
+        }
+    }
+
+    /// Record stopped pids in `path` and thaw them on SIGTERM, SIGINT or
+    /// SIGHUP. Anything a previous run left stopped there (it crashed or was
+    /// killed) is continued first.
+    pub fn with_state(mut self, path: PathBuf) -> Self {
+        for pid in read_state(&path) {
+            signal(pid, libc::SIGCONT);
+        }
+        let _ = fs::remove_file(&path);
+        thaw_on_signals();
+        self.state = Some(path);
+        self
+    }
+
+    /// Events after which a process may have to be frozen or thawed
+    pub fn tracks(name: &str) -> bool {
+        matches!(
+            name,
+            "activespecial" | "openwindow" | "closewindow" | "movewindow"
+        )
+    }
+
+    /// Bring the signals in line with where windows are now
+    pub fn update(&mut self, clients: &[HyprClient], monitors: &[HyprMonitor]) {
+        let parked = parked_pids(clients, monitors, &self.classes);
+        // A child with a window of its own (e.g. launched from a parked
+        // terminal) is only stopped if that window is parked too
+        let windowed: HashSet<i32> = clients.iter().map(|c| c.pid).collect();
+        let parked = with_descendants(&parked, &children(), &windowed);
+        for &pid in self.frozen.difference(&parked) {
+            signal(pid, libc::SIGCONT);
+        }
+        // Record before stopping, so a crash in between still thaws them
+        self.record(&parked);
+        for &pid in parked.difference(&self.frozen) {
+            signal(pid, libc::SIGSTOP);
+        }
+        self.frozen = parked;
+    }
+
+    /// Continue every process this freezer stopped, e.g. on shutdown
+    pub fn thaw_all(&mut self) {
+        for pid in self.frozen.drain() {
+            signal(pid, libc::SIGCONT);
+        }
+        self.record(&HashSet::new());
+    }
+
+    fn record(&self, pids: &HashSet<i32>) {
+        publish(pids);
+        let Some(path) = &self.state else {
+            return;
+        };
+        if let Err(e) = write_state(path, pids) {
+            eprintln!("Warning: could not record frozen apps: {}", e);
+        }
+    }
+}
+
+/// Continue the processes of allowlisted windows, whoever stopped them.
+/// Restores call this so parked apps can answer while they are moved.
+pub fn thaw(clients: &[HyprClient], classes: &[String]) {
+    let classes: Vec<String> = classes.iter().map(|c| c.to_lowercase()).collect();
+    let pids: HashSet<i32> = clients
+        .iter()
+        .filter(|c| c.pid > 0 && classes.contains(&c.class.to_lowercase()))
+        .map(|c| c.pid)
+        .collect();
+    for pid in with_descendants(&pids, &children(), &HashSet::new()) {
+        signal(pid, libc::SIGCONT);
+    }
+}
+
+/// Pids of allowlisted apps with no window outside a hidden special workspace
+fn parked_pids(
+    clients: &[HyprClient],
+    monitors: &[HyprMonitor],
+    classes: &[String],
+) -> HashSet<i32> {
+    let shown: HashSet<i32> = monitors
+        .iter()
+        .filter_map(|m| m.special_workspace.as_ref().map(|ws| ws.id))
+        .collect();
+    let hidden = |c: &HyprClient| c.workspace.id < 0 && !shown.contains(&c.workspace.id);
+
+    let visible: HashSet<i32> = clients
+        .iter()
+        .filter(|c| !hidden(c))
+        .map(|c| c.pid)
+        .collect();
+    clients
+        .iter()
+        .filter(|c| c.pid > 0 && hidden(c) && !visible.contains(&c.pid))
+        .filter(|c| classes.contains(&c.class.to_lowercase()))
+        .map(|c| c.pid)
+        .collect()
+}
+
+/// `roots` plus every process below them, leaving out `skip` (other than
+/// the roots) and what is below it
+fn with_descendants(
+    roots: &HashSet<i32>,
+    children: &HashMap<i32, Vec<i32>>,
+    skip: &HashSet<i32>,
+) -> HashSet<i32> {
+    let mut all = HashSet::new();
+    let mut pending: Vec<i32> = roots.iter().copied().collect();
+    while let Some(pid) = pending.pop() {
+        if (roots.contains(&pid) || !skip.contains(&pid)) && all.insert(pid) {
+            pending.extend(children.get(&pid).into_iter().flatten());
+        }
+    }
+    all
+}
+
+/// Child pids by parent, from `/proc`
+fn children() -> HashMap<i32, Vec<i32>> {
+    let mut children: HashMap<i32, Vec<i32>> = HashMap::new();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return children;
+    };
+    for entry in entries.flatten() {
+        let Some(pid) = entry.file_name().to_str().and_then(|s| s.parse().ok()) else {
+            continue;
+        };
+        if let Some(ppid) = stat_field(pid, 1) {
+            children.entry(ppid as i32).or_default().push(pid);
+        }
+    }
+    children
+}
+
+/// A numeric field of `/proc/<pid>/stat`, counting from the state after
+/// the command name (1 is the parent pid, 19 the start time)
+fn stat_field(pid: i32, index: usize) -> Option<u64> {
+    let stat = fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+    // The command name is parenthesized and may contain spaces
+    let rest = &stat[stat.rfind(')')? + 1..];
+    rest.split_whitespace().nth(index)?.parse().ok()
+}
+
+fn start_time(pid: i32) -> Option<u64> {
+    stat_field(pid, 19)
+}
+
+/// One `pid start-time` line per process, so a pid reused since isn't
+/// mistaken for the one that was stopped
+fn write_state(path: &PathBuf, pids: &HashSet<i32>) -> io::Result<()> {
+    if pids.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let content: String = pids
+        .iter()
+        .filter_map(|&pid| Some(format!("{} {}\n", pid, start_time(pid)?)))
+        .collect();
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, content)?;
+    fs::rename(tmp, path)
+}
+
+/// Recorded pids that still belong to the process that was stopped
+fn read_state(path: &PathBuf) -> Vec<i32> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    content
+        .lines()
+        .filter_map(|line| {
+            let (pid, started) = line.split_once(' ')?;
+            let pid: i32 = pid.parse().ok()?;
+            (start_time(pid)? == started.parse::<u64>().ok()?).then_some(pid)
+        })
+        .collect()
+}
+
+// --- Thawing on termination ---
+
+/// Stopped pids, readable from a signal handler; 0 marks a free slot
+static FROZEN: [AtomicI32; 512] = [const { AtomicI32::new(0) }; 512];
+
+fn publish(pids: &HashSet<i32>) {
+    let mut pids = pids.iter();
+    for slot in &FROZEN {
+        slot.store(pids.next().copied().unwrap_or(0), Ordering::SeqCst);
+    }
+}
+
+fn thaw_on_signals() {
+    let handler = thaw_and_exit as extern "C" fn(libc::c_int);
+    for sig in [libc::SIGTERM, libc::SIGINT, libc::SIGHUP] {
+        unsafe {
+            libc::signal(sig, handler as libc::sighandler_t);
+        }
+    }
+}
+
+/// Continue everything stopped, then die of `sig` as if unhandled. Only
+/// async-signal-safe calls here.
+extern "C" fn thaw_and_exit(sig: libc::c_int) {
+    for slot in &FROZEN {
+        let pid = slot.load(Ordering::SeqCst);
+        if pid > 0 {
+            signal(pid, libc::SIGCONT);
+        }
+    }
+    unsafe {
+        libc::signal(sig, libc::SIG_DFL);
+        libc::raise(sig);
+    }
+}
+
+fn signal(pid: i32, signal: libc::c_int) {
+    // Best effort: the process may have exited since the last update
+    unsafe {
+        libc::kill(pid, signal);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(pid: i32, class: &str, workspace: i32) -> HyprClient {
+        HyprClient::fixture(&format!("0x{}", pid), class)
+            .with_workspace(workspace)
+            .with_pid(pid)
+    }
+
+    fn monitor(special: i32) -> HyprMonitor {
+        serde_json::from_value(serde_json::json!({
+            "id": 0, "name": "DP-1", "description": "",
+            "width": 1920, "height": 1080, "refreshRate": 60.0,
+            "x": 0, "y": 0, "scale": 1.0, "transform": 0,
+            "activeWorkspace": { "id": 1, "name": "1" },
+            "specialWorkspace": { "id": special, "name": "special:music" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_parked_pids_only_hidden_allowlisted_apps() {
+        let classes = vec!["spotify".to_string(), "discord".to_string()];
+        let clients = vec![
+            client(10, "Spotify", -98),
+            client(20, "discord", -98),
+            // A second window of the same process is still on screen
+            client(20, "discord", 2),
+            client(30, "kitty", -98),
+        ];
+
+        let parked = parked_pids(&clients, &[monitor(0)], &classes);
+        assert_eq!(parked, HashSet::from([10]));
+
+        // Showing the scratchpad thaws it
+        assert!(parked_pids(&clients, &[monitor(-98)], &classes).is_empty());
+    }
+
+    #[test]
+    fn test_with_descendants_follows_the_process_tree() {
+        let children = HashMap::from([(10, vec![11, 12]), (12, vec![13]), (20, vec![21])]);
+        let roots = HashSet::from([10]);
+        assert_eq!(
+            with_descendants(&roots, &children, &HashSet::new()),
+            HashSet::from([10, 11, 12, 13])
+        );
+        // 12 has a window of its own on screen
+        assert_eq!(
+            with_descendants(&roots, &children, &HashSet::from([10, 12])),
+            HashSet::from([10, 11])
+        );
+    }
+
+    #[test]
+    fn test_state_skips_reused_pids() {
+        let path = std::env::temp_dir().join(format!("hyprdrover-frozen-{}", std::process::id()));
+        let me = std::process::id() as i32;
+        write_state(&path, &HashSet::from([me])).unwrap();
+        assert_eq!(read_state(&path), vec![me]);
+
+        // Same pid, but a different process started under it
+        let started = start_time(me).unwrap();
+        fs::write(&path, format!("{} {}\n", me, started + 1)).unwrap();
+        assert!(read_state(&path).is_empty());
+
+        write_state(&path, &HashSet::new()).unwrap();
+        assert!(!path.exists());
+    }
+}
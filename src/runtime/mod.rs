@@ -0,0 +1,146 @@
+pub mod control;
+pub mod crash;
+pub mod freeze;
+pub mod pause;
+#[cfg(test)]
+mod tests;
+pub mod window_ids;
+
+use crate::config::Config;
+use crate::paths;
+use std::env;
+use std::fs;
+use std::io;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_SEAT: &str = "seat0";
+
+/// Identifies which user, seat, and Hyprland instance this process is serving.
+///
+/// Shared machines can run several graphical sessions at once, so anything that
+/// lives outside the user's home (daemon lock, control socket) is namespaced by
+/// all three to keep sessions from stepping on each other.
+#[derive(Debug, Clone)]
+pub struct RuntimeContext {
+    pub user: String,
+    pub uid: u32,
+    pub seat: String,
+    pub instance: Option<String>,
+    /// Per-user directory the per-seat, per-instance directories live in
+    pub runtime_base: PathBuf,
+}
+
+/// Whether a daemon currently holds the lock for this context
+#[derive(Debug)]
+pub enum DaemonState {
+    Running(u32),
+    Stale(u32),
+    NotRunning,
+}
+
+impl RuntimeContext {
+    pub fn detect() -> Self {
+        let uid = fs::metadata("/proc/self").map(|m| m.uid()).unwrap_or(0);
+        let user = env::var("USER").unwrap_or_else(|_| uid.to_string());
+        let seat = env::var("XDG_SEAT")
+            .ok()
+            .filter(|s| !s.is_empty())
+            .unwrap_or_else(|| DEFAULT_SEAT.to_string());
+        let instance = crate::ipc::instance_signature().ok();
+
+        Self {
+            user,
+            uid,
+            seat,
+            instance,
+            runtime_base: paths::runtime_dir(uid),
+        }
+    }
+
+    /// The detected context, with runtime files under the configured
+    /// `runtime_dir` if one is set
+    pub fn for_config(config: &Config) -> Self {
+        let detected = Self::detect();
+        if config.runtime_dir.is_empty() {
+            return detected;
+        }
+        Self {
+            runtime_base: PathBuf::from(&config.runtime_dir),
+            ..detected
+        }
+    }
+
+    /// Directory holding per-instance runtime files (lock, control socket)
+    pub fn runtime_dir(&self) -> PathBuf {
+        self.runtime_base
+            .join(&self.seat)
+            .join(self.instance.as_deref().unwrap_or("default"))
+    }
+
+    pub fn lock_path(&self) -> PathBuf {
+        self.runtime_dir().join("daemon.lock")
+    }
+
+    pub fn control_socket_path(&self) -> PathBuf {
+        self.runtime_dir().join("control.sock")
+    }
+
+    /// Pids the freezer has stopped. Per user rather than per instance, so
+    /// a run after a compositor crash still finds them.
+    pub fn frozen_path(&self) -> PathBuf {
+        self.runtime_base.join("frozen")
+    }
+
+    /// Marker file whose presence tells the daemon to stop tracking and autosaving
+    pub fn pause_path(&self) -> PathBuf {
+        self.runtime_dir().join("paused")
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.pause_path().exists()
+    }
+
+    pub fn set_paused(&self, paused: bool) -> io::Result<()> {
+        let path = self.pause_path();
+        if paused {
+            fs::create_dir_all(self.runtime_dir())?;
+            fs::write(path, "")
+        } else {
+            match fs::remove_file(path) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                _ => Ok(()),
+            }
+        }
+    }
+
+    /// Snapshots are kept per seat rather than per instance: the instance
+    /// signature changes every time Hyprland starts, which would orphan
+    /// every snapshot taken before a restart.
+    pub fn namespace_session_dir(&self, session_dir: &Path) -> PathBuf {
+        if self.seat == DEFAULT_SEAT {
+            session_dir.to_path_buf()
+        } else {
+            session_dir.join(&self.seat)
+        }
+    }
+
+    /// Inspect the daemon lock to see whether a daemon serves this context
+    pub fn daemon_state(&self) -> DaemonState {
+        let Some(pid) = read_lock_pid(&self.lock_path()) else {
+            return DaemonState::NotRunning;
+        };
+
+        if Path::new(&format!("/proc/{}", pid)).exists() {
+            DaemonState::Running(pid)
+        } else {
+            DaemonState::Stale(pid)
+        }
+    }
+}
+
+/// The lock file stores the owning daemon's pid on its first line
+fn read_lock_pid(path: &Path) -> Option<u32> {
+    let content = fs::read_to_string(path).ok()?;
+    content.lines().next()?.trim().parse().ok()
+}
@@ -0,0 +1,88 @@
+use super::RuntimeContext;
+use crate::ipc::HyprClient;
+use std::path::PathBuf;
+
+/// Whether event-driven work (hooks, activity tracking) should be skipped:
+/// paused by hand with `hyprdrover pause`, or automatically while a listed
+/// app such as a game is fullscreen
+pub struct Pauser {
+    marker: PathBuf,
+    classes: Vec<String>,
+    // Class of the listed app that is fullscreen now, if any
+    fullscreen: Option<String>,
+}
+
+impl Pauser {
+    /// `classes` are compared case-insensitively; a trailing `*` matches any
+    /// suffix, e.g. `steam_app_*`
+    pub fn new(context: &RuntimeContext, classes: &[String]) -> Self {
+        Self {
+            marker: context.pause_path(),
+            classes: classes.iter().map(|c| c.to_lowercase()).collect(),
+            fullscreen: None,
+        }
+    }
+
+    /// Events after which a listed app may have entered or left fullscreen
+    pub fn tracks(&self, name: &str) -> bool {
+        !self.classes.is_empty()
+            && matches!(
+                name,
+                "fullscreen" | "activewindow" | "openwindow" | "closewindow"
+            )
+    }
+
+    /// Recheck the auto-pause rule against the live windows
+    pub fn update(&mut self, clients: &[HyprClient]) {
+        let now = fullscreen_match(clients, &self.classes).map(|c| c.class.clone());
+        match (&self.fullscreen, &now) {
+            (None, Some(class)) => println!("Pausing tracking while {} is fullscreen", class),
+            (Some(class), None) => println!("{} left fullscreen; resuming tracking", class),
+            _ => {}
+        }
+        self.fullscreen = now;
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.fullscreen.is_some() || self.marker.exists()
+    }
+}
+
+/// A fullscreen window whose class matches one of `classes` (lowercase)
+fn fullscreen_match<'a>(clients: &'a [HyprClient], classes: &[String]) -> Option<&'a HyprClient> {
+    clients.iter().filter(|c| c.fullscreen != 0).find(|c| {
+        let class = c.class.to_lowercase();
+        classes
+            .iter()
+            .any(|pattern| match pattern.strip_suffix('*') {
+                Some(prefix) => class.starts_with(prefix),
+                None => class == *pattern,
+            })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str, fullscreen: i32) -> HyprClient {
+        HyprClient {
+            fullscreen,
+            ..HyprClient::fixture("0x1", class)
+        }
+    }
+
+    #[test]
+    fn test_fullscreen_match() {
+        let classes = vec!["steam_app_*".to_string(), "cs2".to_string()];
+
+        let playing = [client("kitty", 2), client("steam_app_1091500", 2)];
+        assert_eq!(
+            fullscreen_match(&playing, &classes).map(|c| c.class.as_str()),
+            Some("steam_app_1091500")
+        );
+        // Windowed games and other fullscreen apps don't pause
+        assert!(fullscreen_match(&[client("CS2", 0), client("mpv", 2)], &classes).is_none());
+        assert!(fullscreen_match(&[client("CS2", 1)], &classes).is_some());
+    }
+}
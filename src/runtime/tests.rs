@@ -0,0 +1,81 @@
+use crate::runtime::crash::SessionMarker;
+use crate::runtime::{DaemonState, RuntimeContext};
+use std::path::Path;
+
+fn context(seat: &str, instance: Option<&str>) -> RuntimeContext {
+    RuntimeContext {
+        user: "alice".to_string(),
+        uid: 1000,
+        seat: seat.to_string(),
+        instance: instance.map(|s| s.to_string()),
+        runtime_base: std::env::temp_dir().join("hyprdrover-runtime-tests"),
+    }
+}
+
+#[test]
+fn test_runtime_dir_is_namespaced_per_seat_and_instance() {
+    let a = context("seat0", Some("abc_123"));
+    let b = context("seat1", Some("abc_123"));
+    let c = context("seat0", Some("def_456"));
+
+    assert!(a.runtime_dir().ends_with("seat0/abc_123"));
+    assert_ne!(a.lock_path(), b.lock_path());
+    assert_ne!(a.control_socket_path(), c.control_socket_path());
+}
+
+#[test]
+fn test_session_dir_namespaced_only_for_extra_seats() {
+    let dir = Path::new("/home/alice/.config/hyprdrover/sessions");
+
+    assert_eq!(context("seat0", None).namespace_session_dir(dir), dir);
+    assert_eq!(
+        context("seat1", None).namespace_session_dir(dir),
+        dir.join("seat1")
+    );
+}
+
+#[test]
+fn test_daemon_state_without_lock() {
+    let ctx = context("seat-test-none", Some("no-such-instance"));
+    assert!(matches!(ctx.daemon_state(), DaemonState::NotRunning));
+}
+
+#[test]
+fn test_pause_marker_round_trip() {
+    let instance = format!("pause-test-{}", std::process::id());
+    let ctx = context("seat0", Some(&instance));
+
+    assert!(!ctx.is_paused());
+    ctx.set_paused(true).unwrap();
+    assert!(ctx.is_paused());
+    ctx.set_paused(false).unwrap();
+    ctx.set_paused(false).unwrap();
+    assert!(!ctx.is_paused());
+
+    let _ = std::fs::remove_dir_all(ctx.runtime_dir());
+}
+
+#[test]
+fn test_session_marker_detects_unclean_exit() {
+    let dir = std::env::temp_dir().join(format!("hyprdrover-marker-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let marker = SessionMarker::new(&dir, "seat0").with_crash_reports(dir.join("reports"));
+
+    assert!(!marker.begin("first").unwrap());
+    // Restarted within the same compositor instance
+    assert!(!marker.begin("first").unwrap());
+    // Another instance started without `end`: the last session crashed
+    assert!(marker.begin("second").unwrap());
+    marker.end().unwrap();
+    assert!(!marker.begin("third").unwrap());
+
+    // The compositor went away on logout
+    marker.finish().unwrap();
+    assert!(!marker.begin("fourth").unwrap());
+    // ... or after writing a crash report
+    std::fs::create_dir_all(dir.join("reports")).unwrap();
+    std::fs::write(dir.join("reports/hyprlandCrashReport42.txt"), "").unwrap();
+    marker.finish().unwrap();
+    assert!(marker.begin("fifth").unwrap());
+    let _ = std::fs::remove_dir_all(&dir);
+}
@@ -0,0 +1,140 @@
+use super::RuntimeContext;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Stable window IDs by live address.
+///
+/// Addresses only last as long as the window, and change when a restore
+/// launches a window again, so each window gets an ID of its own. Snapshots
+/// store it, and a restore links the new address to the saved ID, so hooks,
+/// logs and reports can follow "the same window" across restores. Addresses
+/// belong to one compositor instance, so the file lives in its runtime
+/// directory.
+#[derive(Debug, Default)]
+pub struct WindowIds {
+    path: PathBuf,
+    ids: BTreeMap<String, String>,
+}
+
+/// Bare hex, as in events; `j/clients` prefixes addresses with `0x`
+fn key(address: &str) -> String {
+    address.trim_start_matches("0x").to_string()
+}
+
+impl WindowIds {
+    /// The IDs recorded for `context`'s instance; none if unreadable
+    pub fn load(context: &RuntimeContext) -> Self {
+        let path = context.runtime_dir().join("window-ids.json");
+        let ids = fs::read_to_string(&path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        Self { path, ids }
+    }
+
+    /// Pick up changes made by other processes since loading, keeping the
+    /// current IDs if the file can't be read
+    pub fn reload(&mut self) {
+        let ids = fs::read_to_string(&self.path)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok());
+        if let Some(ids) = ids {
+            self.ids = ids;
+        }
+    }
+
+    pub fn get(&self, address: &str) -> Option<&str> {
+        self.ids.get(&key(address)).map(String::as_str)
+    }
+
+    /// The window's ID, assigning a new one if it has none yet
+    pub fn id_for(&mut self, address: &str) -> String {
+        self.ids.entry(key(address)).or_insert_with(new_id).clone()
+    }
+
+    /// Record that the window at `address` is the one known as `id`
+    pub fn link(&mut self, address: &str, id: &str) {
+        self.ids.insert(key(address), id.to_string());
+    }
+
+    pub fn forget(&mut self, address: &str) {
+        self.ids.remove(&key(address));
+    }
+
+    /// Drop windows that are gone
+    pub fn retain<'a>(&mut self, live: impl IntoIterator<Item = &'a str>) {
+        let live: HashSet<String> = live.into_iter().map(key).collect();
+        self.ids.retain(|address, _| live.contains(address));
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        // Readers in other processes must never see a half-written file
+        let tmp = self
+            .path
+            .with_extension(format!("json.{}.tmp", std::process::id()));
+        fs::write(&tmp, serde_json::to_string(&self.ids)?)?;
+        fs::rename(&tmp, &self.path)
+    }
+}
+
+/// 12 hex digits, unique enough for the windows of one user
+pub fn new_id() -> String {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos());
+    let seed = format!(
+        "{}-{}-{}",
+        std::process::id(),
+        nanos,
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    Sha256::digest(seed.as_bytes())[..6]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_follow_links_and_survive_saving() {
+        let dir = std::env::temp_dir().join(format!("hyprdrover-ids-{}", std::process::id()));
+        let mut ids = WindowIds {
+            path: dir.join("window-ids.json"),
+            ids: BTreeMap::new(),
+        };
+
+        let first = ids.id_for("0x5f3a");
+        assert_eq!(ids.id_for("5f3a"), first);
+        assert_ne!(ids.id_for("0x77"), first);
+
+        // A restore relaunched the window at a new address
+        ids.link("0x9e01", &first);
+        ids.retain(["0x9e01"]);
+        ids.save().unwrap();
+
+        let loaded: BTreeMap<String, String> =
+            serde_json::from_str(&fs::read_to_string(&ids.path).unwrap()).unwrap();
+        assert_eq!(
+            loaded,
+            BTreeMap::from([("9e01".to_string(), first.clone())])
+        );
+
+        // A file that can't be parsed leaves the known IDs alone
+        fs::write(&ids.path, "{\"9e0").unwrap();
+        ids.reload();
+        assert_eq!(ids.get("0x9e01"), Some(first.as_str()));
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,405 @@
+//! `hyprdrover setup`: first-run onboarding.
+//!
+//! Checks the environment, writes a starter config, offers to install a
+//! systemd user unit for the daemon and a Hyprland config snippet with
+//! keybindings, then tries a save and a dry-run restore of the current
+//! session. Nothing is written without asking unless `--yes` is given, and
+//! files that already exist are left alone.
+
+use crate::config::Config;
+use crate::doctor;
+use crate::ipc::{self, HyprBind, HyprClient};
+use crate::paths;
+use crate::restore::plan::PlannedAction;
+use crate::restore::{self, RestoreOptions};
+use crate::state::SessionManager;
+use std::error::Error;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::process::Command;
+
+/// Helpers and dialogs that are worth keeping out of snapshots when they
+/// happen to be open, on top of the default `ignored_classes`
+const UTILITY_CLASSES: &[&str] = &[
+    "hyprpolkitagent",
+    "org.kde.polkit-kde-authentication-agent-1",
+    "xdg-desktop-portal-gtk",
+    "xdg-desktop-portal-kde",
+    "wofi",
+    "fuzzel",
+    "mako",
+    "swaync",
+    "nm-applet",
+];
+
+/// Asks yes/no questions on the terminal, or answers them all with their
+/// default under `--yes`
+pub struct Prompter {
+    assume_yes: bool,
+}
+
+impl Prompter {
+    pub fn new(assume_yes: bool) -> Self {
+        Self { assume_yes }
+    }
+
+    pub fn ask(&self, question: &str, default: bool) -> bool {
+        let hint = if default { "[Y/n]" } else { "[y/N]" };
+        if self.assume_yes {
+            println!("{} {} {}", question, hint, if default { "y" } else { "n" });
+            return default;
+        }
+        print!("{} {} ", question, hint);
+        let _ = io::stdout().flush();
+        let mut line = String::new();
+        // EOF or an unreadable terminal counts as the default answer
+        let _ = io::stdin().lock().read_line(&mut line);
+        parse_answer(&line, default)
+    }
+}
+
+/// A typed reply; an empty or unrecognised one is `default`
+fn parse_answer(input: &str, default: bool) -> bool {
+    match input.trim().to_lowercase().as_str() {
+        "y" | "yes" => true,
+        "n" | "no" => false,
+        _ => default,
+    }
+}
+
+/// Run the wizard, writing the config to `config_path`
+pub fn run(config_path: &Path, prompter: &Prompter) -> Result<(), Box<dyn Error>> {
+    println!("Checking the environment...\n");
+    let config = Config::load_layered(config_path, &[]).unwrap_or_default();
+    let checks = doctor::run_checks(&config);
+    print!("{}", doctor::format_report(&checks));
+    let connected = ipc::capture_state().is_ok();
+    if !connected {
+        println!("Hyprland isn't reachable; setup continues, but the trial restore is skipped.");
+    }
+
+    println!();
+    write_config(config_path, prompter, connected)?;
+    let config = Config::load_layered(config_path, &[])?;
+
+    println!();
+    let exe = std::env::current_exe()?;
+    let unit_installed = install_unit(&exe, prompter)?;
+
+    println!();
+    let binds = if connected {
+        ipc::get_binds().unwrap_or_default()
+    } else {
+        vec![]
+    };
+    install_snippet(&exe, prompter, unit_installed, &binds)?;
+
+    if connected {
+        println!();
+        trial_restore(&config);
+    }
+
+    println!("\nSetup finished. `hyprdrover doctor` repeats the checks at any time.");
+    Ok(())
+}
+
+fn write_config(path: &Path, prompter: &Prompter, connected: bool) -> Result<(), Box<dyn Error>> {
+    if path.exists() {
+        println!("Keeping the existing config at {}", path.display());
+        return Ok(());
+    }
+    if !prompter.ask(&format!("Create a config at {}?", path.display()), true) {
+        return Ok(());
+    }
+    let open = if connected {
+        ipc::get_clients().unwrap_or_default()
+    } else {
+        vec![]
+    };
+    let extra = detected_ignores(&open, &Config::default().ignored_classes);
+    for class in &extra {
+        println!("   Ignoring {}, which is open now", class);
+    }
+    write_new(path, &default_config(&extra))
+}
+
+/// Classes of open utility windows that the default ignore list misses
+fn detected_ignores(open: &[HyprClient], ignored: &[String]) -> Vec<String> {
+    let mut found: Vec<String> = UTILITY_CLASSES
+        .iter()
+        .filter(|class| open.iter().any(|c| c.class.eq_ignore_ascii_case(class)))
+        .filter(|class| !ignored.iter().any(|i| i.eq_ignore_ascii_case(class)))
+        .map(|class| class.to_string())
+        .collect();
+    found.dedup();
+    found
+}
+
+/// A starter config spelling out the ignore list, so it is easy to extend
+fn default_config(extra_ignored: &[String]) -> String {
+    let ignored: Vec<String> = Config::default()
+        .ignored_classes
+        .into_iter()
+        .chain(extra_ignored.iter().cloned())
+        .map(|class| format!("    {:?},", class))
+        .collect();
+    format!(
+        "# hyprdrover configuration; see the README for every setting.\n\
+         \n\
+         # Windows of these classes are never saved or restored\n\
+         ignored_classes = [\n{}\n]\n\
+         \n\
+         # Rotated copies kept when a named session is overwritten\n\
+         backups = 3\n\
+         \n\
+         # Turn animations off while restoring\n\
+         disable_animations = true\n\
+         \n\
+         [daemon]\n\
+         # Let the daemon also run hooks, activity tracking and crash recovery\n\
+         hooks = true\n\
+         \n\
+         [crash]\n\
+         # After a compositor crash, offer to restore the last session\n\
+         action = \"offer\"\n",
+        ignored.join("\n")
+    )
+}
+
+/// Hyprland's environment, which the systemd user manager doesn't have
+/// unless the session hands it over
+const SESSION_ENV: &str = "HYPRLAND_INSTANCE_SIGNATURE WAYLAND_DISPLAY XDG_CURRENT_DESKTOP";
+
+/// A user unit running the daemon for the length of the graphical session.
+/// Plain Hyprland never reaches `graphical-session.target` (only uwsm and
+/// the like do), so the snippet starts the unit as well.
+fn systemd_unit(exe: &Path) -> String {
+    format!(
+        "[Unit]\n\
+         Description=hyprdrover session daemon\n\
+         PartOf=graphical-session.target\n\
+         After=graphical-session.target\n\
+         ConditionEnvironment=HYPRLAND_INSTANCE_SIGNATURE\n\
+         \n\
+         [Service]\n\
+         ExecStart={} daemon\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=graphical-session.target\n",
+        exe.display()
+    )
+}
+
+/// Whether the daemon now has a unit, installed now or before
+fn install_unit(exe: &Path, prompter: &Prompter) -> Result<bool, Box<dyn Error>> {
+    let path = paths::systemd_user_dir().join("hyprdrover.service");
+    if path.exists() {
+        println!("A systemd user unit already exists at {}", path.display());
+        return Ok(true);
+    }
+    if !prompter.ask(
+        "Install a systemd user unit that runs the hyprdrover daemon?",
+        true,
+    ) {
+        return Ok(false);
+    }
+    write_new(&path, &systemd_unit(exe))?;
+    if prompter.ask("Enable and start it now?", true) {
+        let systemctl = |args: &[&str]| Command::new("systemctl").arg("--user").args(args).status();
+        let mut import = vec!["import-environment"];
+        import.extend(SESSION_ENV.split(' '));
+        let enabled = systemctl(&["daemon-reload"])
+            .and_then(|_| systemctl(&import))
+            .and_then(|_| systemctl(&["enable", "--now", "hyprdrover.service"]));
+        match enabled {
+            Ok(status) if status.success() => println!("   Daemon started"),
+            Ok(status) => eprintln!("   ⚠️ systemctl failed ({})", status),
+            Err(e) => eprintln!("   ⚠️ Could not run systemctl: {}", e),
+        }
+    }
+    Ok(true)
+}
+
+/// Keybindings for `quicksave` and `quickrestore`, plus an `exec-once`
+/// starting the daemon: through its systemd unit when there is one, after
+/// handing the unit Hyprland's environment. Keys already in `binds` are
+/// left commented out.
+fn hyprland_snippet(exe: &Path, unit: bool, binds: &[HyprBind]) -> String {
+    let mut snippet = String::from("# hyprdrover\n");
+    if unit {
+        snippet.push_str(&format!(
+            "exec-once = dbus-update-activation-environment --systemd {} \
+             && systemctl --user start hyprdrover.service\n",
+            SESSION_ENV
+        ));
+    } else {
+        snippet.push_str(&format!("exec-once = {} daemon\n", exe.display()));
+    }
+    for (key, command) in [("S", "quicksave"), ("R", "quickrestore")] {
+        let bind = format!(
+            "bind = SUPER SHIFT, {}, exec, {} {}",
+            key,
+            exe.display(),
+            command
+        );
+        if is_bound(binds, SUPER_SHIFT, key) {
+            println!(
+                "   SUPER SHIFT+{} is already bound; leaving {} unbound",
+                key, command
+            );
+            snippet.push_str(&format!("# {}  (SUPER SHIFT, {} is taken)\n", bind, key));
+        } else {
+            snippet.push_str(&bind);
+            snippet.push('\n');
+        }
+    }
+    snippet
+}
+
+/// `modmask` bits for Super and Shift
+const SUPER_SHIFT: u32 = 64 | 1;
+
+/// Whether `modmask` + `key` already does something outside any submap
+fn is_bound(binds: &[HyprBind], modmask: u32, key: &str) -> bool {
+    binds
+        .iter()
+        .any(|b| b.submap.is_empty() && b.modmask == modmask && b.key.eq_ignore_ascii_case(key))
+}
+
+fn install_snippet(
+    exe: &Path,
+    prompter: &Prompter,
+    unit: bool,
+    binds: &[HyprBind],
+) -> Result<(), Box<dyn Error>> {
+    let hypr_dir = paths::hypr_config_dir();
+    let path = hypr_dir.join("hyprdrover.conf");
+    if path.exists() {
+        println!(
+            "Keeping the existing Hyprland snippet at {}",
+            path.display()
+        );
+        return Ok(());
+    }
+    let snippet = hyprland_snippet(exe, unit, binds);
+    if !prompter.ask(
+        &format!("Write Hyprland keybindings to {}?", path.display()),
+        true,
+    ) {
+        println!(
+            "You can add these lines to hyprland.conf yourself:\n\n{}",
+            snippet
+        );
+        return Ok(());
+    }
+    write_new(&path, &snippet)?;
+
+    let source = format!("source = {}", path.display());
+    let main = hypr_dir.join("hyprland.conf");
+    let included = fs::read_to_string(&main).is_ok_and(|conf| conf.contains(&source));
+    if !included && prompter.ask(&format!("Add `{}` to {}?", source, main.display()), false) {
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&main)?;
+        writeln!(file, "\n{}", source)?;
+        println!("   Added; it takes effect on the next `hyprctl reload`");
+    } else if !included {
+        println!("Add `{}` to {} to use them.", source, main.display());
+    }
+    Ok(())
+}
+
+/// Capture the current session and plan restoring it, without saving or
+/// changing anything. Every open window should be matched.
+fn trial_restore(config: &Config) {
+    println!("Trying a save and a dry-run restore of the current session...");
+    let manager = SessionManager::new(config.clone());
+    let snapshot = match manager.capture(false) {
+        Ok(snapshot) => snapshot,
+        Err(e) => {
+            eprintln!("   ⚠️ Saving failed: {}", e);
+            return;
+        }
+    };
+    println!("   Save: {} windows captured", snapshot.clients.len());
+    match restore::restore_session_plan(&snapshot, config, &RestoreOptions::default()) {
+        Ok(plan) => {
+            let count =
+                |f: fn(&PlannedAction) -> bool| plan.actions.iter().filter(|a| f(a)).count();
+            println!(
+                "   Restore: {} would be matched, {} launched, {} skipped",
+                count(|a| matches!(a, PlannedAction::Match { .. })),
+                count(|a| matches!(a, PlannedAction::Launch { .. })),
+                count(|a| matches!(a, PlannedAction::Skip { .. })),
+            );
+        }
+        Err(e) => eprintln!("   ⚠️ Planning the restore failed: {}", e),
+    }
+}
+
+/// Write `content` to the new file `path`, creating its directory
+fn write_new(path: &Path, content: &str) -> Result<(), Box<dyn Error>> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, content)?;
+    println!("   Wrote {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str) -> HyprClient {
+        HyprClient::fixture("0x1", class)
+    }
+
+    #[test]
+    fn test_default_config_parses_with_detected_ignores() {
+        let open = [client("kitty"), client("Fuzzel"), client("rofi")];
+        let extra = detected_ignores(&open, &["rofi".to_string()]);
+        assert_eq!(extra, ["fuzzel"]);
+
+        let config: Config = toml::from_str(&default_config(&extra)).unwrap();
+        assert!(config.ignored_classes.contains(&"waybar".to_string()));
+        assert!(config.ignored_classes.contains(&"fuzzel".to_string()));
+        assert!(config.daemon.hooks);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_snippet_always_starts_daemon() {
+        let exe = Path::new("/usr/bin/hyprdrover");
+        assert!(
+            hyprland_snippet(exe, false, &[]).contains("exec-once = /usr/bin/hyprdrover daemon")
+        );
+        let with_unit = hyprland_snippet(exe, true, &[]);
+        assert!(with_unit.contains("--systemd HYPRLAND_INSTANCE_SIGNATURE"));
+        assert!(with_unit.contains("systemctl --user start hyprdrover.service"));
+        assert!(systemd_unit(exe).contains("ExecStart=/usr/bin/hyprdrover daemon"));
+    }
+
+    #[test]
+    fn test_snippet_skips_taken_keys() {
+        let bind: HyprBind = serde_json::from_value(serde_json::json!({
+            "modmask": 65, "submap": "", "key": "s", "dispatcher": "exec", "arg": "grim"
+        }))
+        .unwrap();
+        let snippet = hyprland_snippet(Path::new("/usr/bin/hyprdrover"), false, &[bind]);
+        assert!(snippet.contains("\n# bind = SUPER SHIFT, S,"));
+        assert!(snippet.contains("\nbind = SUPER SHIFT, R,"));
+    }
+
+    #[test]
+    fn test_parse_answer_falls_back_to_default() {
+        assert!(parse_answer("\n", true));
+        assert!(!parse_answer("", false));
+        assert!(parse_answer("Y\n", false));
+        assert!(!parse_answer("no", true));
+    }
+}
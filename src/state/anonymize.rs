@@ -0,0 +1,82 @@
+use crate::ipc::SessionSnapshot;
+use std::collections::hash_map::DefaultHasher;
+use std::env;
+use std::hash::{Hash, Hasher};
+
+/// Replace personal data in a snapshot while keeping its structure intact.
+///
+/// Titles, command-line arguments and each component of working
+/// directories are hashed rather than blanked so windows sharing them still
+/// look alike. The home directory becomes `~`; flags such as `-e` are kept.
+/// Notes are free text, so they are dropped.
+pub fn anonymize_snapshot(snapshot: &mut SessionSnapshot) {
+    let home = env::var("HOME").ok().filter(|h| !h.is_empty());
+    snapshot.note = None;
+
+    for client in &mut snapshot.clients {
+        client.title = hash_text(&client.title);
+        client.initial_title = hash_text(&client.initial_title);
+        client.launch_command = client.launch_command.as_deref().map(hash_text);
+        if let (Some(path), Some(home)) = (&client.exec_path, &home) {
+            client.exec_path = Some(path.replace(home.as_str(), "~"));
+        }
+        client.cwd = client
+            .cwd
+            .as_deref()
+            .map(|cwd| hash_path(cwd, home.as_deref()));
+        if let Some(argv) = &mut client.command_line {
+            for (i, arg) in argv.iter_mut().enumerate() {
+                *arg = match &home {
+                    Some(home) if i == 0 => arg.replace(home.as_str(), "~"),
+                    _ if i == 0 || is_flag(arg) => arg.clone(),
+                    _ => hash_text(arg),
+                };
+            }
+        }
+    }
+
+    for workspace in &mut snapshot.workspaces {
+        workspace.lastwindowtitle = hash_text(&workspace.lastwindowtitle);
+    }
+}
+
+/// Hash each component of `path`, keeping its shape: the home directory
+/// becomes `~` and the rest `redacted-…` components
+fn hash_path(path: &str, home: Option<&str>) -> String {
+    let (prefix, rest) = match home.and_then(|home| path.strip_prefix(home)) {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => ("~", rest),
+        _ => ("", path),
+    };
+    let components: Vec<String> = rest.split('/').map(hash_text).collect();
+    format!("{}{}", prefix, components.join("/"))
+}
+
+fn is_flag(arg: &str) -> bool {
+    arg.starts_with('-') && !arg.contains('=')
+}
+
+pub(crate) fn hash_text(text: &str) -> String {
+    if text.is_empty() {
+        return String::new();
+    }
+    let mut hasher = DefaultHasher::new();
+    text.hash(&mut hasher);
+    format!("redacted-{:08x}", hasher.finish() as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_path_keeps_shape() {
+        let home = Some("/home/ann");
+        let hashed = hash_path("/home/ann/clients/acme", home);
+        assert!(hashed.starts_with("~/redacted-"));
+        assert!(!hashed.contains("acme") && !hashed.contains("clients"));
+        assert_eq!(hashed.matches('/').count(), 2);
+        assert_eq!(hash_path("/home/ann", home), "~");
+        assert!(!hash_path("/home/anna/x", home).starts_with('~'));
+        assert!(hash_path("/srv/acme", None).starts_with("/redacted-"));
+    }
+}
@@ -0,0 +1,260 @@
+use super::{anonymize_snapshot, format, integrity, SessionManager};
+use crate::config::{CompanionConfig, Config};
+use crate::paths;
+use crate::restore::rules::Rule;
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+/// Top-level directory inside every session archive
+const ARCHIVE_ROOT: &str = "hyprdrover-session";
+
+/// Describes what an archive contains and where it came from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct ArchiveMetadata {
+    /// Session name on the exporting machine
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+    pub exported_at: String,
+    /// hyprdrover version that wrote the archive
+    pub version: String,
+    /// File name of the bundled restore script, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+}
+
+/// The parts of the config a snapshot depends on to restore the same way elsewhere
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct ConfigFragment {
+    pub ignored_classes: Vec<String>,
+    pub matcher: String,
+    pub rules: Vec<Rule>,
+    pub companions: Vec<CompanionConfig>,
+}
+
+impl ConfigFragment {
+    pub fn from_config(config: &Config) -> Self {
+        Self {
+            ignored_classes: config.ignored_classes.clone(),
+            matcher: config.matcher.clone(),
+            rules: config.rules.clone(),
+            companions: config.companions.clone(),
+        }
+    }
+}
+
+/// Pack the named session, its config fragment, the restore script and
+/// metadata into a zstd-compressed tarball, made by the system `tar`.
+///
+/// With `redact`, the snapshot is anonymized (see [`anonymize_snapshot`]),
+/// the home directory is hidden in the config fragment, and the note and
+/// script are left out, so the archive can be shared publicly.
+pub fn export_session(
+    config: &Config,
+    name: &str,
+    output: Option<&Path>,
+    redact: bool,
+) -> Result<PathBuf, Box<dyn Error>> {
+    let name = name.trim_end_matches(".json");
+    let session_path = Path::new(&config.session_dir).join(format!("{}.json", name));
+    if !session_path.exists() {
+        return Err(format!("session not found: {}", session_path.display()).into());
+    }
+    // Verifies the checksum so a corrupt snapshot isn't shipped elsewhere
+    let mut snapshot = SessionManager::new(config.clone()).load(&session_path)?;
+    if redact {
+        anonymize_snapshot(&mut snapshot);
+    }
+
+    let output = output
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|| PathBuf::from(format!("{}.tar.zst", name)));
+
+    check_tar_zstd()?;
+    let staging_root = paths::staging_dir("export", Path::new(&config.state_dir))?;
+    let staging = staging_root.join(ARCHIVE_ROOT);
+    fs::create_dir(&staging)?;
+
+    let result = (|| -> Result<(), Box<dyn Error>> {
+        if redact {
+            fs::write(
+                staging.join("session.json"),
+                serde_json::to_string_pretty(&snapshot)?,
+            )?;
+        } else {
+            fs::copy(&session_path, staging.join("session.json"))?;
+        }
+        let fragment = toml::to_string_pretty(&ConfigFragment::from_config(config))?;
+        fs::write(
+            staging.join("config.toml"),
+            if redact {
+                redact_home(&fragment)
+            } else {
+                fragment
+            },
+        )?;
+
+        let script = match &config.script {
+            Some(script) if !redact && Path::new(script).exists() => {
+                fs::copy(script, staging.join("script.rhai"))?;
+                Some("script.rhai".to_string())
+            }
+            _ => None,
+        };
+
+        let metadata = ArchiveMetadata {
+            name: name.to_string(),
+            note: snapshot.note.clone(),
+            exported_at: Local::now().to_rfc3339(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            script,
+        };
+        fs::write(
+            staging.join("metadata.json"),
+            serde_json::to_string_pretty(&metadata)?,
+        )?;
+
+        run_tar(&[
+            "--zstd".as_ref(),
+            "-cf".as_ref(),
+            output.as_os_str(),
+            "-C".as_ref(),
+            staging_root.as_os_str(),
+            ARCHIVE_ROOT.as_ref(),
+        ])
+    })();
+
+    let _ = fs::remove_dir_all(&staging_root);
+    result.map(|_| output)
+}
+
+/// Unpack an archive made by [`export_session`], saving the snapshot under
+/// `name` (default: its original name) and the config fragment and script
+/// under `<state_dir>/imports/<name>/` for review.
+///
+/// Existing sessions are never overwritten.
+pub fn import_session(
+    config: &Config,
+    archive: &Path,
+    name: Option<&str>,
+) -> Result<(PathBuf, PathBuf), Box<dyn Error>> {
+    check_tar_zstd()?;
+    let staging_root = paths::staging_dir("import", Path::new(&config.state_dir))?;
+
+    let result = run_tar(&[
+        "--zstd".as_ref(),
+        "-xf".as_ref(),
+        archive.as_os_str(),
+        "-C".as_ref(),
+        staging_root.as_os_str(),
+    ])
+    .and_then(|_| install_archive(config, &staging_root.join(ARCHIVE_ROOT), name));
+
+    let _ = fs::remove_dir_all(&staging_root);
+    result
+}
+
+pub(super) fn install_archive(
+    config: &Config,
+    dir: &Path,
+    name: Option<&str>,
+) -> Result<(PathBuf, PathBuf), Box<dyn Error>> {
+    let metadata: ArchiveMetadata =
+        serde_json::from_str(&fs::read_to_string(dir.join("metadata.json"))?)
+            .map_err(|e| format!("not a hyprdrover session archive: {}", e))?;
+    let name = name
+        .unwrap_or(&metadata.name)
+        .trim_end_matches(".json")
+        .to_string();
+    if !is_plain_file_name(&name) {
+        return Err(format!("invalid session name: {:?}", name).into());
+    }
+
+    // The archive is untrusted: only a regular file next to the metadata is
+    // taken as the script, and it is always installed as `script.rhai`
+    let script = match &metadata.script {
+        Some(script) => {
+            let source = dir.join(script);
+            let regular = fs::symlink_metadata(&source).is_ok_and(|m| m.is_file());
+            if !is_plain_file_name(script) || !regular {
+                return Err(format!("invalid script in archive: {:?}", script).into());
+            }
+            Some(source)
+        }
+        None => None,
+    };
+
+    // Parse before installing so a corrupt archive leaves nothing behind
+    let content = fs::read_to_string(dir.join("session.json"))?;
+    format::parse(content.as_bytes())?;
+
+    let session_dir = Path::new(&config.session_dir);
+    let session_path = session_dir.join(format!("{}.json", name));
+    if session_path.exists() {
+        return Err(format!(
+            "session {} already exists; import it under another name",
+            session_path.display()
+        )
+        .into());
+    }
+
+    let imports_dir = Path::new(&config.state_dir).join("imports").join(&name);
+    fs::create_dir_all(&imports_dir)?;
+    fs::copy(dir.join("config.toml"), imports_dir.join("config.toml"))?;
+    fs::copy(dir.join("metadata.json"), imports_dir.join("metadata.json"))?;
+    if let Some(script) = script {
+        fs::copy(script, imports_dir.join("script.rhai"))?;
+    }
+
+    fs::create_dir_all(session_dir)?;
+    integrity::write_with_sidecars(
+        &session_path,
+        content.as_bytes(),
+        config.signing_key()?.as_deref(),
+    )?;
+    Ok((session_path, imports_dir))
+}
+
+/// Whether `name` names a file in the current directory, so joining it to
+/// a directory can't lead anywhere else
+fn is_plain_file_name(name: &str) -> bool {
+    !name.is_empty() && name != "." && name != ".." && !name.contains(['/', '\\', '\0'])
+}
+
+fn redact_home(text: &str) -> String {
+    match env::var("HOME") {
+        Ok(home) if !home.is_empty() => text.replace(&home, "~"),
+        _ => text.to_string(),
+    }
+}
+
+/// Fail before staging anything when `tar` can't run or lacks zstd support
+/// (GNU tar needs the `zstd` program for it)
+fn check_tar_zstd() -> Result<(), Box<dyn Error>> {
+    let status = Command::new("tar")
+        .args(["--zstd", "-cf", "/dev/null", "-T", "/dev/null"])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map_err(|e| format!("could not run tar: {}", e))?;
+    if !status.success() {
+        return Err(
+            "tar can't compress with zstd; install zstd, which session archives use".into(),
+        );
+    }
+    Ok(())
+}
+
+fn run_tar(args: &[&std::ffi::OsStr]) -> Result<(), Box<dyn Error>> {
+    let status = Command::new("tar").args(args).status()?;
+    if !status.success() {
+        return Err(format!("tar exited with {}", status).into());
+    }
+    Ok(())
+}
@@ -0,0 +1,133 @@
+use crate::ipc::SessionSnapshot;
+use crate::restore::select::WindowSelector;
+use std::error::Error;
+
+/// A change to the saved windows of a snapshot
+#[derive(Debug, Clone, PartialEq)]
+pub enum Edit {
+    /// Drop the windows, and any terminal they swallowed
+    Remove,
+    /// Restore the windows onto this regular workspace id, 1 or above
+    MoveTo(i32),
+    /// Launch the windows with this command when they are missing
+    Command(String),
+}
+
+/// Apply `edit` to every saved window `selector` matches, returning how
+/// many were changed. Nothing is changed when the edit is invalid.
+pub fn apply_edit(
+    snapshot: &mut SessionSnapshot,
+    selector: &WindowSelector,
+    edit: &Edit,
+) -> Result<usize, Box<dyn Error>> {
+    match edit {
+        Edit::MoveTo(0) => return Err("workspace 0 does not exist".into()),
+        // Their ids are assigned by Hyprland; only the name identifies one
+        Edit::MoveTo(id) if *id < 0 => {
+            return Err(format!("workspace {} is special and can't be picked by id", id).into())
+        }
+        Edit::Command(command) if command.trim().is_empty() => {
+            return Err("the launch command is empty".into())
+        }
+        _ => {}
+    }
+    let selected: Vec<usize> = (0..snapshot.clients.len())
+        .filter(|&i| selector.matches(&snapshot.clients[i]))
+        .collect();
+    if selected.is_empty() {
+        return Err("no saved window matches the selection".into());
+    }
+
+    match edit {
+        Edit::Remove => {
+            // A swallowed terminal only comes back together with its app
+            let swallowed: Vec<String> = selected
+                .iter()
+                .filter_map(|&i| snapshot.clients[i].swallowing.clone())
+                .collect();
+            let mut index = 0;
+            snapshot.clients.retain(|client| {
+                let keep = !selected.contains(&index) && !swallowed.contains(&client.address);
+                index += 1;
+                keep
+            });
+        }
+        Edit::MoveTo(id) => {
+            for &i in &selected {
+                let workspace = &mut snapshot.clients[i].workspace;
+                workspace.id = *id;
+                workspace.name = id.to_string();
+            }
+        }
+        Edit::Command(command) => {
+            for &i in &selected {
+                snapshot.clients[i].launch_command = Some(command.clone());
+            }
+        }
+    }
+    Ok(selected.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::HyprClient;
+
+    fn client(address: &str, class: &str, swallowing: Option<&str>) -> HyprClient {
+        HyprClient {
+            swallowing: swallowing.map(String::from),
+            ..HyprClient::fixture(address, class)
+        }
+    }
+
+    fn snapshot() -> SessionSnapshot {
+        let mut snapshot: SessionSnapshot =
+            serde_json::from_str(r#"{ "clients": [], "workspaces": [], "monitors": [] }"#).unwrap();
+        snapshot.clients = vec![
+            client("0x1", "kitty", None),
+            client("0x2", "mpv", Some("0x1")),
+            client("0x3", "obsidian", None),
+        ];
+        snapshot
+    }
+
+    fn classes(snapshot: &SessionSnapshot) -> Vec<&str> {
+        snapshot.clients.iter().map(|c| c.class.as_str()).collect()
+    }
+
+    #[test]
+    fn test_remove_takes_swallowed_terminal_along() {
+        let mut snapshot = snapshot();
+        let mpv = "class:mpv".parse().unwrap();
+
+        assert_eq!(apply_edit(&mut snapshot, &mpv, &Edit::Remove).unwrap(), 1);
+        assert_eq!(classes(&snapshot), vec!["obsidian"]);
+    }
+
+    #[test]
+    fn test_move_and_command_validate() {
+        let mut snapshot = snapshot();
+        let obsidian = "class:obsidian".parse().unwrap();
+
+        apply_edit(&mut snapshot, &obsidian, &Edit::MoveTo(4)).unwrap();
+        apply_edit(
+            &mut snapshot,
+            &obsidian,
+            &Edit::Command("obsidian --vault".into()),
+        )
+        .unwrap();
+        assert_eq!(snapshot.clients[2].workspace.name, "4");
+        assert_eq!(
+            snapshot.clients[2].launch_command.as_deref(),
+            Some("obsidian --vault")
+        );
+
+        assert!(apply_edit(&mut snapshot, &obsidian, &Edit::MoveTo(0)).is_err());
+        assert!(apply_edit(&mut snapshot, &obsidian, &Edit::MoveTo(-98)).is_err());
+        assert_eq!(snapshot.clients[2].workspace.id, 4);
+        assert!(apply_edit(&mut snapshot, &obsidian, &Edit::Command(" ".into())).is_err());
+        let missing = "class:firefox".parse().unwrap();
+        assert!(apply_edit(&mut snapshot, &missing, &Edit::Remove).is_err());
+        assert_eq!(classes(&snapshot).len(), 3);
+    }
+}
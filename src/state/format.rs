@@ -0,0 +1,87 @@
+//! On-disk snapshot format. Every snapshot records the format `version` it
+//! was written with; older files are migrated step by step when read.
+
+use crate::ipc::models::SNAPSHOT_VERSION;
+use crate::ipc::SessionSnapshot;
+use serde_json::Value;
+use std::error::Error;
+use std::fmt;
+
+/// Migrations by the version they upgrade from: entry `i` turns a version
+/// `i + 1` snapshot into a version `i + 2` one
+const MIGRATIONS: &[fn(&mut Value)] = &[v1_to_v2];
+
+/// Version 1 files predate the `version` field. Everything added to the
+/// format since has a serde default, so only the stamp changes.
+fn v1_to_v2(_snapshot: &mut Value) {}
+
+/// Why a snapshot couldn't be read. Neither means the file is damaged: a
+/// newer hyprdrover may have written it, or it was edited by hand.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FormatError {
+    /// Not JSON, or not shaped like a snapshot
+    Invalid(String),
+    /// Written in a format newer than this version understands
+    Newer(u32),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Invalid(e) => write!(f, "not a valid snapshot: {}", e),
+            Self::Newer(version) => write!(
+                f,
+                "snapshot format {} is newer than this hyprdrover understands ({}); upgrade hyprdrover",
+                version, SNAPSHOT_VERSION
+            ),
+        }
+    }
+}
+
+impl Error for FormatError {}
+
+/// Parse a snapshot of any known version, migrating it to the current one
+pub fn parse(content: &[u8]) -> Result<SessionSnapshot, FormatError> {
+    let invalid = |e: serde_json::Error| FormatError::Invalid(e.to_string());
+    let mut value: Value = serde_json::from_slice(content).map_err(invalid)?;
+    let version = match value.get("version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v >= 1)
+            .ok_or_else(|| FormatError::Invalid(format!("invalid snapshot version {}", v)))?,
+    };
+    if version > SNAPSHOT_VERSION {
+        return Err(FormatError::Newer(version));
+    }
+
+    for migrate in &MIGRATIONS[version as usize - 1..] {
+        migrate(&mut value);
+    }
+    let mut snapshot: SessionSnapshot = serde_json::from_value(value).map_err(invalid)?;
+    snapshot.version = SNAPSHOT_VERSION;
+    Ok(snapshot)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_migrates_and_rejects_newer() {
+        let legacy = br#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        assert_eq!(parse(legacy).unwrap().version, SNAPSHOT_VERSION);
+        assert_eq!(MIGRATIONS.len() as u32, SNAPSHOT_VERSION - 1);
+
+        let newer = format!(
+            r#"{{ "version": {}, "clients": [], "workspaces": [], "monitors": [] }}"#,
+            SNAPSHOT_VERSION + 1
+        );
+        assert_eq!(
+            parse(newer.as_bytes()).unwrap_err(),
+            FormatError::Newer(SNAPSHOT_VERSION + 1)
+        );
+        assert!(parse(br#"{ "version": 0, "clients": [] }"#).is_err());
+    }
+}
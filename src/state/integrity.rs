@@ -0,0 +1,224 @@
+//! Checksums and optional signatures stored next to each snapshot.
+//!
+//! `work.json` gets a `work.json.sha256` sidecar in `sha256sum` format and,
+//! when a signing key is configured, a `work.json.sig` holding an
+//! HMAC-SHA256 of the file. Snapshots without a checksum (written by older
+//! versions or by hand) are loaded unchecked, but once a key is configured
+//! every snapshot needs a signature.
+
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Directory inside the session directory that corrupt snapshots are moved to
+pub const QUARANTINE_DIR: &str = "quarantine";
+
+const BLOCK_SIZE: usize = 64;
+
+/// Why a snapshot was rejected
+#[derive(Debug, Clone, PartialEq)]
+pub enum Corruption {
+    ChecksumMismatch,
+    SignatureMismatch,
+    /// A key is configured but the snapshot was never signed
+    SignatureMissing,
+}
+
+impl Corruption {
+    /// Whether the content was changed after it was written, rather than
+    /// just lacking a signature
+    pub fn is_tampered(&self) -> bool {
+        !matches!(self, Self::SignatureMissing)
+    }
+}
+
+impl fmt::Display for Corruption {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::ChecksumMismatch => write!(f, "checksum mismatch"),
+            Self::SignatureMismatch => write!(f, "signature mismatch"),
+            Self::SignatureMissing => write!(f, "no signature, but a signing key is set"),
+        }
+    }
+}
+
+pub fn checksum_path(session: &Path) -> PathBuf {
+    sidecar(session, "sha256")
+}
+
+pub fn signature_path(session: &Path) -> PathBuf {
+    sidecar(session, "sig")
+}
+
+fn sidecar(session: &Path, extension: &str) -> PathBuf {
+    let mut name = session.as_os_str().to_os_string();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+pub fn sha256_hex(data: &[u8]) -> String {
+    hex(&Sha256::digest(data))
+}
+
+/// HMAC-SHA256 (RFC 2104) of `data` under `key`
+pub fn hmac_sha256_hex(key: &[u8], data: &[u8]) -> String {
+    let mut block = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        block[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        block[..key.len()].copy_from_slice(key);
+    }
+
+    let pad = |byte: u8| block.iter().map(|b| b ^ byte).collect::<Vec<u8>>();
+    let inner = Sha256::new()
+        .chain_update(pad(0x36))
+        .chain_update(data)
+        .finalize();
+    let outer = Sha256::new()
+        .chain_update(pad(0x5c))
+        .chain_update(inner)
+        .finalize();
+    hex(&outer)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Write `content` to `path` with its checksum, and its signature if `key` is set
+pub fn write_with_sidecars(
+    path: &Path,
+    content: &[u8],
+    key: Option<&[u8]>,
+) -> Result<(), Box<dyn Error>> {
+    fs::write(path, content)?;
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    fs::write(
+        checksum_path(path),
+        format!("{}  {}\n", sha256_hex(content), file_name),
+    )?;
+    match key {
+        Some(key) => fs::write(signature_path(path), hmac_sha256_hex(key, content) + "\n")?,
+        None => {
+            let _ = fs::remove_file(signature_path(path));
+        }
+    }
+    Ok(())
+}
+
+/// Check `content` (read from `path`) against the sidecars that exist.
+///
+/// A signature is only checked when a key is configured; it can't be
+/// verified otherwise. With a key, a missing signature is an error, so one
+/// can't be bypassed by deleting the `.sig`.
+pub fn verify(path: &Path, content: &[u8], key: Option<&[u8]>) -> Result<(), Corruption> {
+    if let Ok(recorded) = fs::read_to_string(checksum_path(path)) {
+        let expected = recorded.split_whitespace().next().unwrap_or_default();
+        if !expected.eq_ignore_ascii_case(&sha256_hex(content)) {
+            return Err(Corruption::ChecksumMismatch);
+        }
+    }
+    if let Some(key) = key {
+        let Ok(recorded) = fs::read_to_string(signature_path(path)) else {
+            return Err(Corruption::SignatureMissing);
+        };
+        if !recorded
+            .trim()
+            .eq_ignore_ascii_case(&hmac_sha256_hex(key, content))
+        {
+            return Err(Corruption::SignatureMismatch);
+        }
+    }
+    Ok(())
+}
+
+/// Move a snapshot and its sidecars into the session directory's quarantine,
+/// returning the snapshot's new path
+pub fn quarantine(path: &Path) -> Result<PathBuf, Box<dyn Error>> {
+    let dir = path
+        .parent()
+        .unwrap_or_else(|| Path::new("."))
+        .join(QUARANTINE_DIR);
+    fs::create_dir_all(&dir)?;
+    let file_name = path.file_name().ok_or("snapshot path has no file name")?;
+    let target = dir.join(file_name);
+    rename_with_sidecars(path, &target)?;
+    Ok(target)
+}
+
+/// Rename a snapshot along with whichever sidecars it has
+pub fn rename_with_sidecars(from: &Path, to: &Path) -> Result<(), Box<dyn Error>> {
+    fs::rename(from, to)?;
+    for (from, to) in [
+        (checksum_path(from), checksum_path(to)),
+        (signature_path(from), signature_path(to)),
+    ] {
+        if from.exists() {
+            fs::rename(from, to)?;
+        }
+    }
+    Ok(())
+}
+
+/// Delete a snapshot and its sidecars, ignoring files that don't exist
+pub fn remove_with_sidecars(path: &Path) {
+    for file in [
+        path.to_path_buf(),
+        checksum_path(path),
+        signature_path(path),
+    ] {
+        let _ = fs::remove_file(file);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn test_hmac_matches_rfc_4231() {
+        // Test case 2
+        assert_eq!(
+            hmac_sha256_hex(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+    }
+
+    #[test]
+    fn test_verify_and_quarantine() {
+        let dir = env::temp_dir().join(format!("hyprdrover-integrity-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("work.json");
+
+        write_with_sidecars(&path, b"{}", Some(b"key")).unwrap();
+        assert_eq!(verify(&path, b"{}", Some(b"key")), Ok(()));
+        assert_eq!(
+            verify(&path, b"{} ", None),
+            Err(Corruption::ChecksumMismatch)
+        );
+        assert_eq!(
+            verify(&path, b"{}", Some(b"other")),
+            Err(Corruption::SignatureMismatch)
+        );
+        write_with_sidecars(&path, b"{}", None).unwrap();
+        assert_eq!(
+            verify(&path, b"{}", Some(b"key")),
+            Err(Corruption::SignatureMissing)
+        );
+        write_with_sidecars(&path, b"{}", Some(b"key")).unwrap();
+
+        let moved = quarantine(&path).unwrap();
+        assert_eq!(moved, dir.join(QUARANTINE_DIR).join("work.json"));
+        assert!(!path.exists() && !checksum_path(&path).exists());
+        assert!(checksum_path(&moved).exists() && signature_path(&moved).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
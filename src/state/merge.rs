@@ -0,0 +1,263 @@
+use crate::ipc::{HyprClient, SessionSnapshot};
+use std::collections::HashSet;
+use std::error::Error;
+use std::str::FromStr;
+
+/// What to do when a merged snapshot uses a workspace already taken
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum WorkspaceConflict {
+    /// Move the incoming windows to the next free workspace ids
+    #[default]
+    Shift,
+    /// Put both sets of windows on the same workspace
+    Share,
+}
+
+/// What to do with an incoming window whose class is already in the result
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DuplicateClass {
+    #[default]
+    Keep,
+    Skip,
+}
+
+impl FromStr for WorkspaceConflict {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "shift" => Ok(Self::Shift),
+            "share" => Ok(Self::Share),
+            _ => Err(format!("expected `shift` or `share`, got {:?}", s)),
+        }
+    }
+}
+
+impl FromStr for DuplicateClass {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "keep" => Ok(Self::Keep),
+            "skip" => Ok(Self::Skip),
+            _ => Err(format!("expected `keep` or `skip`, got {:?}", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MergeOptions {
+    pub workspaces: WorkspaceConflict,
+    pub duplicates: DuplicateClass,
+}
+
+/// Fold `extra` into `base`. Monitors, input settings, the cursor and the
+/// note stay those of `base`; windows, workspaces, workspace rules, layers
+/// and plugins are combined.
+pub fn merge_snapshots(
+    base: &mut SessionSnapshot,
+    mut extra: SessionSnapshot,
+    options: MergeOptions,
+) -> Result<(), Box<dyn Error>> {
+    if options.duplicates == DuplicateClass::Skip {
+        let classes: HashSet<String> = base
+            .clients
+            .iter()
+            .map(|c| c.class.to_lowercase())
+            .collect();
+        extra
+            .clients
+            .retain(|c| !classes.contains(&c.class.to_lowercase()));
+        // An app whose swallowed terminal was dropped comes back on its own
+        let kept: HashSet<String> = extra.clients.iter().map(|c| c.address.clone()).collect();
+        for client in &mut extra.clients {
+            if client
+                .swallowing
+                .as_ref()
+                .is_some_and(|a| !kept.contains(a))
+            {
+                client.swallowing = None;
+            }
+        }
+    }
+
+    if options.workspaces == WorkspaceConflict::Shift {
+        shift_workspaces(base, &mut extra);
+    }
+
+    // Saved addresses key restore bookkeeping, so they must stay unique
+    let mut addresses: HashSet<String> = base.clients.iter().map(|c| c.address.clone()).collect();
+    for index in 0..extra.clients.len() {
+        let old = extra.clients[index].address.clone();
+        if !addresses.contains(&old) {
+            addresses.insert(old);
+            continue;
+        }
+        let new = (1..)
+            .map(|n| format!("{}-{}", old, n))
+            .find(|a| !addresses.contains(a))
+            .ok_or("ran out of window addresses")?;
+        rename_address(&mut extra.clients, &old, &new);
+        addresses.insert(new);
+    }
+
+    base.clients.extend(extra.clients);
+    for workspace in extra.workspaces {
+        if !base.workspaces.iter().any(|w| w.id == workspace.id) {
+            base.workspaces.push(workspace);
+        }
+    }
+    for rule in extra.workspace_rules {
+        if !base
+            .workspace_rules
+            .iter()
+            .any(|r| r.workspace_string == rule.workspace_string)
+        {
+            base.workspace_rules.push(rule);
+        }
+    }
+    for layer in extra.layers {
+        if !base.layers.contains(&layer) {
+            base.layers.push(layer);
+        }
+    }
+    for plugin in extra.plugins {
+        if !base.plugins.iter().any(|p| p.name == plugin.name) {
+            base.plugins.push(plugin);
+        }
+    }
+    Ok(())
+}
+
+/// Renumber the regular workspaces of `extra` that `base` also uses
+fn shift_workspaces(base: &SessionSnapshot, extra: &mut SessionSnapshot) {
+    let mut used: HashSet<i32> = base.clients.iter().map(|c| c.workspace.id).collect();
+    used.extend(base.workspaces.iter().map(|w| w.id));
+
+    let mut incoming: Vec<i32> = extra.clients.iter().map(|c| c.workspace.id).collect();
+    incoming.sort_unstable();
+    incoming.dedup();
+
+    // Special workspaces (negative ids) are named, not numbered; share them
+    let colliding: Vec<i32> = incoming
+        .iter()
+        .copied()
+        .filter(|&id| id > 0 && used.contains(&id))
+        .collect();
+    // New ids must not land on one of the incoming workspaces either
+    used.extend(incoming);
+    for id in colliding {
+        let new = (id + 1..).find(|n| !used.contains(n)).unwrap_or(id);
+        used.insert(new);
+        for client in extra.clients.iter_mut().filter(|c| c.workspace.id == id) {
+            client.workspace.id = new;
+            if client.workspace.name == id.to_string() {
+                client.workspace.name = new.to_string();
+            }
+        }
+        for workspace in extra.workspaces.iter_mut().filter(|w| w.id == id) {
+            workspace.id = new;
+            if workspace.name == id.to_string() {
+                workspace.name = new.to_string();
+            }
+        }
+        // A rule for the old id would now apply to the base's windows
+        extra
+            .workspace_rules
+            .retain(|r| r.workspace_string != id.to_string());
+    }
+}
+
+fn rename_address(clients: &mut [HyprClient], old: &str, new: &str) {
+    for client in clients {
+        if client.address == old {
+            client.address = new.to_string();
+        }
+        if client.swallowing.as_deref() == Some(old) {
+            client.swallowing = Some(new.to_string());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(address: &str, class: &str, workspace: i32) -> HyprClient {
+        HyprClient::fixture(address, class).with_workspace(workspace)
+    }
+
+    fn snapshot(clients: Vec<HyprClient>) -> SessionSnapshot {
+        let mut snapshot: SessionSnapshot =
+            serde_json::from_str(r#"{ "clients": [], "workspaces": [], "monitors": [] }"#).unwrap();
+        snapshot.clients = clients;
+        snapshot
+    }
+
+    fn placement(snapshot: &SessionSnapshot) -> Vec<(&str, &str, i32)> {
+        snapshot
+            .clients
+            .iter()
+            .map(|c| (c.address.as_str(), c.class.as_str(), c.workspace.id))
+            .collect()
+    }
+
+    #[test]
+    fn test_merge_shifts_colliding_workspaces() {
+        let mut base = snapshot(vec![client("0x1", "code", 1), client("0x2", "kitty", 2)]);
+        let extra = snapshot(vec![
+            client("0x1", "spotify", 1),
+            client("0x3", "pavucontrol", 9),
+        ]);
+
+        merge_snapshots(&mut base, extra, MergeOptions::default()).unwrap();
+
+        assert_eq!(
+            placement(&base),
+            vec![
+                ("0x1", "code", 1),
+                ("0x2", "kitty", 2),
+                ("0x1-1", "spotify", 3),
+                ("0x3", "pavucontrol", 9),
+            ]
+        );
+        assert_eq!(base.clients[2].workspace.name, "3");
+    }
+
+    #[test]
+    fn test_merge_can_share_workspaces_and_skip_duplicates() {
+        let mut base = snapshot(vec![client("0x1", "firefox", 1)]);
+        let extra = snapshot(vec![client("0x2", "Firefox", 1), client("0x3", "mpv", 1)]);
+        let options = MergeOptions {
+            workspaces: WorkspaceConflict::Share,
+            duplicates: DuplicateClass::Skip,
+        };
+
+        merge_snapshots(&mut base, extra, options).unwrap();
+
+        assert_eq!(
+            placement(&base),
+            vec![("0x1", "firefox", 1), ("0x3", "mpv", 1)]
+        );
+    }
+
+    #[test]
+    fn test_skipped_terminal_is_no_longer_swallowed() {
+        let mut base = snapshot(vec![client("0x1", "kitty", 1)]);
+        let mut mpv = client("0x3", "mpv", 2);
+        mpv.swallowing = Some("0x2".to_string());
+        let extra = snapshot(vec![client("0x2", "kitty", 2), mpv]);
+        let options = MergeOptions {
+            duplicates: DuplicateClass::Skip,
+            ..MergeOptions::default()
+        };
+
+        merge_snapshots(&mut base, extra, options).unwrap();
+
+        assert_eq!(
+            placement(&base),
+            vec![("0x1", "kitty", 1), ("0x3", "mpv", 2)]
+        );
+        assert_eq!(base.clients[1].swallowing, None);
+    }
+}
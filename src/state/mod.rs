@@ -1,5 +1,15 @@
+pub mod anonymize;
+pub mod archive;
+pub mod edit;
+pub mod format;
+pub mod integrity;
+pub mod merge;
 pub mod session;
+pub mod stats;
+pub mod summary;
 #[cfg(test)]
 mod tests;
+pub mod transient;
 
-pub use session::SessionManager;
+pub use anonymize::anonymize_snapshot;
+pub use session::{backup_path, NotedSession, SessionManager};
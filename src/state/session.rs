@@ -1,10 +1,24 @@
+use super::edit::{apply_edit, Edit};
+use super::format;
+use super::integrity;
+use super::merge::{merge_snapshots, MergeOptions};
+use super::transient::transient_reason;
 use crate::config::Config;
 use crate::ipc::{self, SessionSnapshot};
+use crate::restore::report::RestoreReport;
+use crate::restore::rules::RuleSet;
+use crate::restore::select::WindowSelector;
+use crate::restore::{RestoreOptions, RestorePlan};
+use crate::runtime::window_ids::WindowIds;
+use crate::runtime::RuntimeContext;
 use chrono::Local;
 use std::error::Error;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+/// A session file and the note saved with it
+pub type NotedSession = (PathBuf, Option<String>);
+
 pub struct SessionManager {
     config: Config,
 }
@@ -14,27 +28,66 @@ impl SessionManager {
         Self { config }
     }
 
-    /// Capture the current state, filtering out ignored windows
-    pub fn snapshot(&self, name: Option<&str>) -> Result<PathBuf, Box<dyn Error>> {
+    /// Capture the current state, filtering out ignored windows, and save it
+    pub fn snapshot(
+        &self,
+        name: Option<&str>,
+        note: Option<&str>,
+    ) -> Result<PathBuf, Box<dyn Error>> {
+        let mut state = self.capture(true)?;
+        state.note = note.map(String::from);
+        self.save(&state, name)
+    }
+
+    /// The current state without ignored and transient windows, saying
+    /// what was left out when `verbose`
+    pub fn capture(&self, verbose: bool) -> Result<SessionSnapshot, Box<dyn Error>> {
         // 1. Capture raw state from Hyprland
         let mut state = ipc::capture_state()?;
+        let live: Vec<String> = state.clients.iter().map(|c| c.address.clone()).collect();
 
-        // 2. Filter out ignored classes (like rofi, waybar)
+        // 2. Filter out ignored windows (like rofi, waybar) and transient
+        // ones (file choosers, splash screens) no `keep` rule protects
+        let rules = RuleSet::from_config(&self.config);
         let original_count = state.clients.len();
-        state
-            .clients
-            .retain(|client| !self.config.ignored_classes.contains(&client.class));
-        let filtered_count = original_count - state.clients.len();
+        let mut transient = 0;
+        state.clients.retain(|client| {
+            let decision = rules.evaluate(client);
+            if decision.ignore {
+                return false;
+            }
+            let reason =
+                transient_reason(client, &self.config.transient).filter(|_| !decision.keep);
+            if let Some(reason) = reason.filter(|_| verbose) {
+                println!(
+                    "Skipping {} window: {} {:?}",
+                    reason, client.class, client.title
+                );
+            }
+            transient += usize::from(reason.is_some());
+            reason.is_none()
+        });
+        let filtered_count = original_count - state.clients.len() - transient;
 
-        if filtered_count > 0 {
+        if verbose && filtered_count > 0 {
             println!("Filtered out {} ignored windows.", filtered_count);
         }
 
-        // 3. Save to disk
-        self.save_to_disk(&state, name)
+        // 3. Tag windows with their stable IDs, kept for the next capture
+        let mut ids = WindowIds::load(&RuntimeContext::for_config(&self.config));
+        ids.retain(live.iter().map(String::as_str));
+        for client in &mut state.clients {
+            client.window_id = Some(ids.id_for(&client.address));
+        }
+        if let Err(e) = ids.save() {
+            eprintln!("Warning: could not record window IDs: {}", e);
+        }
+        Ok(state)
     }
 
-    fn save_to_disk(
+    /// Write `snapshot` as the named session (keeping backups of the
+    /// previous one), or under a timestamped name
+    pub fn save(
         &self,
         snapshot: &SessionSnapshot,
         name: Option<&str>,
@@ -56,13 +109,30 @@ impl SessionManager {
         };
 
         let file_path = session_dir.join(&filename);
+        // Timestamped saves never collide, so only named sessions need backups
+        if name.is_some() {
+            rotate_backups(&file_path, self.config.backups)?;
+        }
 
         let json_string = serde_json::to_string_pretty(snapshot)?;
-        fs::write(&file_path, json_string)?;
+        integrity::write_with_sidecars(
+            &file_path,
+            json_string.as_bytes(),
+            self.config.signing_key()?.as_deref(),
+        )?;
 
         Ok(file_path)
     }
 
+    /// Backups of a named session that exist, newest first
+    pub fn list_backups(&self, session_path: &Path) -> Vec<PathBuf> {
+        (1..=self.config.backups)
+            .map(|n| backup_path(session_path, n))
+            .take_while(|path| path.exists())
+            .collect()
+    }
+
+    /// Saved sessions, most recently written first
     pub fn list_sessions(&self) -> Result<Vec<PathBuf>, Box<dyn Error>> {
         let session_dir = Path::new(&self.config.session_dir);
         if !session_dir.exists() {
@@ -74,24 +144,212 @@ impl SessionManager {
             let entry = entry?;
             let path = entry.path();
             if path.extension().and_then(|s| s.to_str()) == Some("json") {
-                sessions.push(path);
+                let modified = entry.metadata()?.modified()?;
+                sessions.push((modified, path));
             }
         }
-        // Sort by name (timestamp) descending
+        // Named sessions don't sort by time, so go by when they were written
         sessions.sort_by(|a, b| b.cmp(a));
-        Ok(sessions)
+        Ok(sessions.into_iter().map(|(_, path)| path).collect())
+    }
+
+    /// Read a snapshot, checking it against its checksum and signature.
+    /// Snapshots that fail either check are moved to the quarantine
+    /// directory; ones that are merely unsigned, or that this version can't
+    /// parse, such as those written by a newer hyprdrover, are left alone.
+    pub fn load(&self, session_path: &Path) -> Result<SessionSnapshot, Box<dyn Error>> {
+        self.read(session_path, true)
+    }
+
+    fn read(
+        &self,
+        session_path: &Path,
+        quarantine: bool,
+    ) -> Result<SessionSnapshot, Box<dyn Error>> {
+        let content = fs::read(session_path)?;
+        let key = self.config.signing_key()?;
+        let checked = integrity::verify(session_path, &content, key.as_deref());
+        checked.map_err(|corruption| -> Box<dyn Error> {
+            let location = if !quarantine || !corruption.is_tampered() {
+                "left in place".to_string()
+            } else {
+                match integrity::quarantine(session_path) {
+                    Ok(moved) => format!("moved to {}", moved.display()),
+                    Err(e) => format!("could not quarantine it: {}", e),
+                }
+            };
+            format!(
+                "session {} is corrupt ({}); {}",
+                session_path.display(),
+                corruption,
+                location
+            )
+            .into()
+        })?;
+        format::parse(&content)
+            .map_err(|e| format!("could not read session {}: {}", session_path.display(), e).into())
+    }
+
+    /// Sessions whose file name or note contains `query` (case-insensitive),
+    /// newest first, with their notes. Nothing is quarantined while
+    /// looking; corrupt sessions are skipped with a warning.
+    pub fn find_sessions(&self, query: &str) -> Result<Vec<NotedSession>, Box<dyn Error>> {
+        let query = query.to_lowercase();
+        let mut found = vec![];
+        for path in self.list_sessions()? {
+            let note = match self.read(&path, false) {
+                Ok(snapshot) => snapshot.note,
+                Err(e) => {
+                    eprintln!("Warning: {}", e);
+                    continue;
+                }
+            };
+            let name = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_lowercase())
+                .unwrap_or_default();
+            let note_matches = note
+                .as_ref()
+                .is_some_and(|n| n.to_lowercase().contains(&query));
+            if name.contains(&query) || note_matches {
+                found.push((path, note));
+            }
+        }
+        Ok(found)
     }
 
-    pub fn restore(&self, session_path: &Path) -> Result<(), Box<dyn Error>> {
+    pub fn restore(
+        &self,
+        session_path: &Path,
+        options: &RestoreOptions,
+    ) -> Result<RestoreReport, Box<dyn Error>> {
         // 1. Load snapshot
-        let content = fs::read_to_string(session_path)?;
-        let snapshot: SessionSnapshot = serde_json::from_str(&content)?;
+        let snapshot = self.load(session_path)?;
 
         println!("Restoring session from {}...", session_path.display());
 
         // 2. Delegate to restore module
-        crate::restore::restore_session(&snapshot)?;
+        let mut report = crate::restore::restore_session(&snapshot, &self.config, options)?;
+
+        // 3. Keep a record of the outcome for `report last`
+        report.session = session_path.display().to_string();
+        if let Err(e) = report.save(&self.reports_dir()) {
+            eprintln!("Warning: could not save restore report: {}", e);
+        }
+
+        Ok(report)
+    }
 
-        Ok(())
+    /// What restoring the session at `session_path` would do right now
+    pub fn plan(
+        &self,
+        session_path: &Path,
+        options: &RestoreOptions,
+    ) -> Result<RestorePlan, Box<dyn Error>> {
+        let snapshot = self.load(session_path)?;
+        crate::restore::restore_session_plan(&snapshot, &self.config, options)
+    }
+
+    /// Combine `inputs` (files, or names in the session directory) into one
+    /// snapshot written to `output`, returning its window count. Each input
+    /// is merged into the ones before it.
+    pub fn merge(
+        &self,
+        inputs: &[&str],
+        output: &Path,
+        options: MergeOptions,
+    ) -> Result<usize, Box<dyn Error>> {
+        let mut merged: Option<SessionSnapshot> = None;
+        for input in inputs {
+            let snapshot = self.load(&self.resolve(input))?;
+            match &mut merged {
+                Some(base) => merge_snapshots(base, snapshot, options)?,
+                None => merged = Some(snapshot),
+            }
+        }
+        let merged = merged.ok_or("nothing to merge")?;
+
+        if let Some(dir) = output.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        let json = serde_json::to_string_pretty(&merged)?;
+        integrity::write_with_sidecars(
+            output,
+            json.as_bytes(),
+            self.config.signing_key()?.as_deref(),
+        )?;
+        Ok(merged.clients.len())
+    }
+
+    /// Apply `edit` to the windows `selector` picks in a stored session,
+    /// keeping the previous version as a backup. Returns how many windows
+    /// were changed.
+    pub fn edit(
+        &self,
+        session: &str,
+        selector: &WindowSelector,
+        edit: &Edit,
+    ) -> Result<usize, Box<dyn Error>> {
+        let path = self.resolve(session);
+        let mut snapshot = self.load(&path)?;
+        let changed = apply_edit(&mut snapshot, selector, edit)?;
+
+        let json = serde_json::to_string_pretty(&snapshot)?;
+        let key = self.config.signing_key()?;
+        rotate_backups(&path, self.config.backups)?;
+        integrity::write_with_sidecars(&path, json.as_bytes(), key.as_deref())?;
+        Ok(changed)
+    }
+
+    /// Delete a session with its sidecars and backups, returning its path
+    pub fn delete(&self, session: &str) -> Result<PathBuf, Box<dyn Error>> {
+        let path = self.resolve(session);
+        if !path.exists() {
+            return Err(format!("session not found: {}", session).into());
+        }
+        for backup in self.list_backups(&path) {
+            integrity::remove_with_sidecars(&backup);
+        }
+        integrity::remove_with_sidecars(&path);
+        Ok(path)
+    }
+
+    /// `session` as a path if that file exists, otherwise as a name in the
+    /// session directory
+    pub fn resolve(&self, session: &str) -> PathBuf {
+        let path = PathBuf::from(session);
+        if path.exists() {
+            return path;
+        }
+        Path::new(&self.config.session_dir)
+            .join(session)
+            .with_extension("json")
+    }
+
+    pub fn reports_dir(&self) -> PathBuf {
+        Path::new(&self.config.state_dir).join("reports")
+    }
+}
+
+/// Path of the `n`th backup of a session; `work.json.1` is the most recent
+pub fn backup_path(session_path: &Path, n: usize) -> PathBuf {
+    let mut path = session_path.as_os_str().to_os_string();
+    path.push(format!(".{}", n));
+    PathBuf::from(path)
+}
+
+/// Shift `work.json` to `work.json.1`, `.1` to `.2` and so on, dropping the
+/// oldest beyond `keep`
+pub(crate) fn rotate_backups(session_path: &Path, keep: usize) -> Result<(), Box<dyn Error>> {
+    if keep == 0 || !session_path.exists() {
+        return Ok(());
+    }
+    integrity::remove_with_sidecars(&backup_path(session_path, keep));
+    for n in (1..keep).rev() {
+        let from = backup_path(session_path, n);
+        if from.exists() {
+            integrity::rename_with_sidecars(&from, &backup_path(session_path, n + 1))?;
+        }
     }
+    integrity::rename_with_sidecars(session_path, &backup_path(session_path, 1))
 }
@@ -0,0 +1,179 @@
+use crate::ipc::SessionSnapshot;
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// Window counts of one session
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct SessionStats {
+    pub windows: usize,
+    pub floating: usize,
+    pub tiled: usize,
+    pub xwayland: usize,
+    pub by_class: BTreeMap<String, usize>,
+    /// By workspace name
+    pub by_workspace: BTreeMap<String, usize>,
+}
+
+impl SessionStats {
+    pub fn of(snapshot: &SessionSnapshot) -> Self {
+        let mut stats = Self {
+            windows: snapshot.clients.len(),
+            ..Self::default()
+        };
+        for client in &snapshot.clients {
+            if client.floating {
+                stats.floating += 1;
+            } else {
+                stats.tiled += 1;
+            }
+            if client.xwayland {
+                stats.xwayland += 1;
+            }
+            *stats.by_class.entry(client.class.clone()).or_default() += 1;
+            *stats
+                .by_workspace
+                .entry(client.workspace.name.clone())
+                .or_default() += 1;
+        }
+        stats
+    }
+
+    pub fn print(&self) {
+        println!(
+            "Windows:    {} ({} tiled, {} floating, {} XWayland)",
+            self.windows, self.tiled, self.floating, self.xwayland
+        );
+        println!("Classes:");
+        for (class, count) in most_first(&self.by_class) {
+            println!("  {:>4}  {}", count, class);
+        }
+        println!("Workspaces:");
+        for (workspace, count) in &self.by_workspace {
+            println!("  {:>4}  {}", count, workspace);
+        }
+    }
+}
+
+/// Entries with the highest count first, ties by name
+fn most_first(counts: &BTreeMap<String, usize>) -> Vec<(&String, &usize)> {
+    let mut entries: Vec<_> = counts.iter().collect();
+    entries.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+    entries
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct CountChange {
+    pub before: usize,
+    pub after: usize,
+}
+
+impl CountChange {
+    fn delta(&self) -> i64 {
+        self.after as i64 - self.before as i64
+    }
+}
+
+/// How a session's counts differ from a baseline snapshot; only classes
+/// whose count changed are listed
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatsComparison {
+    pub windows: CountChange,
+    pub by_class: BTreeMap<String, CountChange>,
+}
+
+impl StatsComparison {
+    pub fn between(before: &SessionStats, after: &SessionStats) -> Self {
+        let count = |stats: &SessionStats, class: &str| stats.by_class.get(class).copied();
+        let by_class = before
+            .by_class
+            .keys()
+            .chain(after.by_class.keys())
+            .map(|class| {
+                let change = CountChange {
+                    before: count(before, class).unwrap_or(0),
+                    after: count(after, class).unwrap_or(0),
+                };
+                (class.clone(), change)
+            })
+            .filter(|(_, change)| change.delta() != 0)
+            .collect();
+        Self {
+            windows: CountChange {
+                before: before.windows,
+                after: after.windows,
+            },
+            by_class,
+        }
+    }
+
+    /// Biggest changes first, so runaway apps lead the list
+    pub fn print(&self, baseline: &str) {
+        println!(
+            "Compared with {}: {} -> {} windows ({:+})",
+            baseline,
+            self.windows.before,
+            self.windows.after,
+            self.windows.delta()
+        );
+        let mut changes: Vec<_> = self.by_class.iter().collect();
+        changes.sort_by(|a, b| {
+            b.1.delta()
+                .abs()
+                .cmp(&a.1.delta().abs())
+                .then_with(|| a.0.cmp(b.0))
+        });
+        for (class, change) in changes {
+            println!(
+                "  {:+4}  {} ({} -> {})",
+                change.delta(),
+                class,
+                change.before,
+                change.after
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::HyprClient;
+
+    fn snapshot(windows: &[(&str, i32, bool)]) -> SessionSnapshot {
+        let mut snapshot: SessionSnapshot =
+            serde_json::from_str(r#"{ "clients": [], "workspaces": [], "monitors": [] }"#).unwrap();
+        snapshot.clients = windows
+            .iter()
+            .enumerate()
+            .map(|(i, &(class, workspace, floating))| HyprClient {
+                xwayland: class == "steam",
+                ..HyprClient::fixture(&format!("0x{}", i), class)
+                    .with_workspace(workspace)
+                    .with_floating(floating)
+            })
+            .collect();
+        snapshot
+    }
+
+    #[test]
+    fn test_stats_and_comparison() {
+        let before = SessionStats::of(&snapshot(&[("kitty", 1, false), ("steam", 2, true)]));
+        let after = SessionStats::of(&snapshot(&[
+            ("kitty", 1, false),
+            ("kitty", 1, false),
+            ("kitty", 3, true),
+        ]));
+
+        assert_eq!((before.tiled, before.floating, before.xwayland), (1, 1, 1));
+        assert_eq!(after.by_class["kitty"], 3);
+        assert_eq!(after.by_workspace["1"], 2);
+
+        let comparison = StatsComparison::between(&before, &after);
+        assert_eq!(comparison.windows.delta(), 1);
+        assert_eq!(
+            comparison.by_class.keys().collect::<Vec<_>>(),
+            ["kitty", "steam"]
+        );
+        assert_eq!(comparison.by_class["steam"].after, 0);
+    }
+}
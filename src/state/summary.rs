@@ -0,0 +1,130 @@
+use crate::ipc::{HyprClient, SessionSnapshot};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+/// A readable Markdown overview of a snapshot: its monitors, then each
+/// workspace with the apps and titles on it
+pub fn to_markdown(name: &str, snapshot: &SessionSnapshot) -> String {
+    let mut md = format!("# Session `{}`\n\n", name);
+    if let Some(note) = &snapshot.note {
+        let _ = writeln!(md, "> {}\n", note.replace('\n', " "));
+    }
+    let _ = writeln!(
+        md,
+        "{} windows on {} workspaces across {} monitors.\n",
+        snapshot.clients.len(),
+        snapshot.workspaces.len(),
+        snapshot.monitors.len()
+    );
+
+    md.push_str("## Monitors\n\n");
+    md.push_str("| Monitor | Description | Mode | Position | Scale |\n");
+    md.push_str("|---|---|---|---|---|\n");
+    for monitor in &snapshot.monitors {
+        let _ = writeln!(
+            md,
+            "| {} | {} | {}x{}@{:.0}Hz | {},{} | {} |",
+            cell(&monitor.name),
+            cell(&monitor.description),
+            monitor.width,
+            monitor.height,
+            monitor.refresh_rate,
+            monitor.x,
+            monitor.y,
+            monitor.scale
+        );
+    }
+
+    let mut by_workspace: BTreeMap<i32, Vec<&HyprClient>> = BTreeMap::new();
+    for client in &snapshot.clients {
+        by_workspace
+            .entry(client.workspace.id)
+            .or_default()
+            .push(client);
+    }
+    md.push_str("\n## Workspaces\n");
+    for (id, clients) in by_workspace {
+        let name = &clients[0].workspace.name;
+        let title = if *name == id.to_string() {
+            format!("Workspace {}", id)
+        } else {
+            format!("Workspace {} ({})", id, name)
+        };
+        let monitor = snapshot
+            .workspaces
+            .iter()
+            .find(|ws| ws.id == id)
+            .map(|ws| format!(" on {}", ws.monitor))
+            .unwrap_or_default();
+        let _ = writeln!(md, "\n### {}{}\n", title, monitor);
+        md.push_str("| App | Title | Mode |\n");
+        md.push_str("|---|---|---|\n");
+        for client in clients {
+            let _ = writeln!(
+                md,
+                "| {} | {} | {} |",
+                cell(&client.class),
+                cell(&client.title),
+                mode(client)
+            );
+        }
+    }
+    md
+}
+
+fn mode(client: &HyprClient) -> String {
+    let mut mode = if client.floating {
+        format!(
+            "floating {}x{} at {},{}",
+            client.size[0], client.size[1], client.at[0], client.at[1]
+        )
+    } else {
+        "tiled".to_string()
+    };
+    if client.pinned {
+        mode.push_str(", pinned");
+    }
+    if client.fullscreen != 0 {
+        mode.push_str(", fullscreen");
+    }
+    mode
+}
+
+/// Text safe inside a table cell
+fn cell(text: &str) -> String {
+    text.replace('|', "\\|").replace(['\n', '\r'], " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_groups_windows_by_workspace() {
+        let mut snapshot: SessionSnapshot = serde_json::from_value(serde_json::json!({
+            "clients": [], "workspaces": [], "monitors": [], "note": "demo setup"
+        }))
+        .unwrap();
+        snapshot.clients = vec![
+            HyprClient::fixture("0x1", "kitty")
+                .with_title("htop | top")
+                .with_workspace(2),
+            HyprClient {
+                at: [40, 50],
+                size: [800, 600],
+                ..HyprClient::fixture("0x2", "firefox")
+                    .with_title("Docs")
+                    .with_floating(true)
+                    .with_pid(2)
+            },
+        ];
+
+        let md = to_markdown("lab", &snapshot);
+        assert!(md.starts_with("# Session `lab`\n\n> demo setup\n"));
+        let first = md.find("### Workspace 1").unwrap();
+        let second = md.find("### Workspace 2").unwrap();
+        assert!(first < second);
+        assert!(md.contains("| firefox | Docs | floating 800x600 at 40,50 |"));
+        assert!(md.contains("| kitty | htop \\| top | tiled |"));
+    }
+}
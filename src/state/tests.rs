@@ -1,9 +1,15 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
     use crate::config::Config;
-    use crate::state::SessionManager;
+    use crate::ipc::{HyprClient, SessionSnapshot};
+    use crate::state::anonymize_snapshot;
+    use crate::state::archive::{export_session, import_session, install_archive};
+    use crate::state::session::rotate_backups;
+    use crate::state::{backup_path, integrity, SessionManager};
     use std::fs;
     use std::path::Path;
+    use std::process::Command;
 
     #[test]
     fn test_session_manager_creation() {
@@ -12,6 +18,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::field_reassign_with_default)]
     fn test_list_sessions_empty() {
         // Use a temp dir for testing to avoid messing with real sessions
         let temp_dir = "test_sessions_empty";
@@ -32,4 +39,283 @@ mod tests {
             fs::remove_dir_all(temp_dir).unwrap();
         }
     }
+
+    #[test]
+    fn test_list_sessions_newest_first() {
+        let temp_dir =
+            std::env::temp_dir().join(format!("hyprdrover-newest-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&temp_dir);
+        fs::create_dir_all(&temp_dir).unwrap();
+
+        // A named session sorts ahead of timestamps by name, but is older
+        let empty = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        let named = temp_dir.join("work.json");
+        let stamped = temp_dir.join("2026-10-15_09-00-00.json");
+        fs::write(&named, empty).unwrap();
+        fs::write(&stamped, empty).unwrap();
+        let an_hour_ago = std::time::SystemTime::now() - std::time::Duration::from_secs(3600);
+        fs::File::options()
+            .write(true)
+            .open(&named)
+            .unwrap()
+            .set_modified(an_hour_ago)
+            .unwrap();
+
+        let manager = SessionManager::new(Config {
+            session_dir: temp_dir.to_string_lossy().into_owned(),
+            ..Config::default()
+        });
+        assert_eq!(manager.list_sessions().unwrap(), vec![stamped, named]);
+
+        fs::remove_dir_all(&temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_anonymize_snapshot_hashes_titles() {
+        let json = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        let mut snapshot: SessionSnapshot = serde_json::from_str(json).unwrap();
+        snapshot.clients = vec![HyprClient {
+            command_line: Some(vec!["mpv".into(), "--fs".into(), "secret-talk.mkv".into()]),
+            ..HyprClient::fixture("0x1", "kitty").with_title("secret project")
+        }];
+
+        anonymize_snapshot(&mut snapshot);
+
+        let client = &snapshot.clients[0];
+        assert_eq!(client.class, "kitty");
+        assert!(!client.title.contains("secret"));
+        assert_eq!(client.title, client.initial_title);
+
+        let argv = client.command_line.as_ref().unwrap();
+        assert_eq!(argv[..2], ["mpv", "--fs"]);
+        assert!(!argv[2].contains("secret"));
+    }
+
+    #[test]
+    fn test_find_sessions_searches_names_and_notes() {
+        let temp_dir = "test_sessions_find";
+        let _ = fs::remove_dir_all(temp_dir);
+        fs::create_dir_all(temp_dir).unwrap();
+
+        let empty = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        let noted = r#"{ "clients": [], "workspaces": [], "monitors": [],
+                         "note": "Before the big refactor demo" }"#;
+        fs::write(Path::new(temp_dir).join("work.json"), noted).unwrap();
+        fs::write(Path::new(temp_dir).join("gaming.json"), empty).unwrap();
+
+        let manager = SessionManager::new(Config {
+            session_dir: temp_dir.to_string(),
+            ..Config::default()
+        });
+
+        let found = manager.find_sessions("REFACTOR").unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].1.as_deref(), Some("Before the big refactor demo"));
+        assert_eq!(manager.find_sessions("gam").unwrap().len(), 1);
+        assert_eq!(manager.find_sessions("").unwrap().len(), 2);
+
+        fs::remove_dir_all(temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_corrupt_session_is_quarantined() {
+        let temp_dir = Path::new("test_sessions_corrupt");
+        let _ = fs::remove_dir_all(temp_dir);
+        fs::create_dir_all(temp_dir).unwrap();
+
+        let good = temp_dir.join("good.json");
+        let bad = temp_dir.join("bad.json");
+        let empty = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        integrity::write_with_sidecars(&good, empty.as_bytes(), None).unwrap();
+        integrity::write_with_sidecars(&bad, empty.as_bytes(), None).unwrap();
+        fs::write(&bad, r#"{ "clients": [], "workspaces": [] }"#).unwrap();
+
+        let manager = SessionManager::new(Config {
+            session_dir: temp_dir.to_string_lossy().into_owned(),
+            ..Config::default()
+        });
+
+        assert!(manager.load(&good).is_ok());
+        // Listing skips it without moving it
+        assert_eq!(manager.find_sessions("").unwrap().len(), 1);
+        assert!(bad.exists());
+        let err = manager.load(&bad).unwrap_err().to_string();
+        assert!(err.contains("checksum mismatch"), "{}", err);
+        assert!(!bad.exists());
+        assert!(temp_dir.join("quarantine").join("bad.json").exists());
+        assert_eq!(manager.list_sessions().unwrap(), vec![good]);
+
+        fs::remove_dir_all(temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_newer_session_is_not_quarantined() {
+        let temp_dir = Path::new("test_sessions_newer");
+        let _ = fs::remove_dir_all(temp_dir);
+        fs::create_dir_all(temp_dir).unwrap();
+
+        let newer = temp_dir.join("newer.json");
+        let content = format!(
+            r#"{{ "version": {}, "clients": [], "workspaces": [], "monitors": [] }}"#,
+            crate::ipc::models::SNAPSHOT_VERSION + 1
+        );
+        integrity::write_with_sidecars(&newer, content.as_bytes(), None).unwrap();
+
+        let manager = SessionManager::new(Config {
+            session_dir: temp_dir.to_string_lossy().into_owned(),
+            ..Config::default()
+        });
+        let err = manager.load(&newer).unwrap_err().to_string();
+        assert!(err.contains("newer"), "{}", err);
+        assert!(newer.exists());
+        assert!(!temp_dir.join("quarantine").exists());
+
+        fs::remove_dir_all(temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_backups_rotate_up_to_limit() {
+        let temp_dir = Path::new("test_sessions_backups");
+        let _ = fs::remove_dir_all(temp_dir);
+        fs::create_dir_all(temp_dir).unwrap();
+        let session = temp_dir.join("work.json");
+
+        for generation in 1..=4 {
+            rotate_backups(&session, 2).unwrap();
+            let content = format!(
+                r#"{{ "clients": [], "workspaces": [], "monitors": [], "note": "{}" }}"#,
+                generation
+            );
+            integrity::write_with_sidecars(&session, content.as_bytes(), None).unwrap();
+        }
+
+        let manager = SessionManager::new(Config {
+            session_dir: temp_dir.to_string_lossy().into_owned(),
+            backups: 2,
+            ..Config::default()
+        });
+        let note = |path: &Path| manager.load(path).unwrap().note.unwrap();
+        assert_eq!(note(&session), "4");
+        assert_eq!(note(&backup_path(&session, 1)), "3");
+        assert_eq!(note(&backup_path(&session, 2)), "2");
+        assert!(!backup_path(&session, 3).exists());
+        assert_eq!(manager.list_backups(&session).len(), 2);
+        // Backups don't show up as sessions of their own
+        assert_eq!(manager.list_sessions().unwrap(), vec![session]);
+
+        fs::remove_dir_all(temp_dir).unwrap();
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let root = Path::new("test_sessions_archive");
+        let _ = fs::remove_dir_all(root);
+        fs::create_dir_all(root.join("a")).unwrap();
+
+        let noted =
+            r#"{ "clients": [], "workspaces": [], "monitors": [], "note": "laptop setup" }"#;
+        fs::write(root.join("a").join("work.json"), noted).unwrap();
+
+        let source = Config {
+            session_dir: root.join("a").to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        let target = Config {
+            session_dir: root.join("b").to_string_lossy().into_owned(),
+            state_dir: root.join("state").to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+
+        if Command::new("zstd").arg("-V").output().is_err() {
+            eprintln!("skipping test_export_import_round_trip: zstd is not installed");
+            fs::remove_dir_all(root).unwrap();
+            return;
+        }
+        let archive = root.join("work.tar.zst");
+        export_session(&source, "work", Some(&archive), false).unwrap();
+
+        let (session, imports) = import_session(&target, &archive, None).unwrap();
+        assert_eq!(session, root.join("b").join("work.json"));
+        assert!(imports.join("config.toml").exists());
+
+        let manager = SessionManager::new(target.clone());
+        assert_eq!(
+            manager.load(&session).unwrap().note.as_deref(),
+            Some("laptop setup")
+        );
+        // A second import must not clobber the first
+        assert!(import_session(&target, &archive, None).is_err());
+        assert!(import_session(&target, &archive, Some("work-copy")).is_ok());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_staging_dirs_are_fresh_and_private() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let root = Path::new("test_sessions_staging");
+        let _ = fs::remove_dir_all(root);
+        let first = crate::paths::staging_dir("export", root).unwrap();
+        let second = crate::paths::staging_dir("export", root).unwrap();
+        assert_ne!(first, second);
+        let mode = fs::metadata(&first).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+
+        fs::remove_dir_all(&first).unwrap();
+        fs::remove_dir_all(&second).unwrap();
+        let _ = fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn test_import_rejects_paths_leaving_the_imports_dir() {
+        let root = Path::new("test_sessions_archive_paths");
+        let _ = fs::remove_dir_all(root);
+        let unpacked = root.join("unpacked");
+        fs::create_dir_all(&unpacked).unwrap();
+        let empty = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        fs::write(unpacked.join("session.json"), empty).unwrap();
+        fs::write(unpacked.join("config.toml"), "").unwrap();
+        fs::write(root.join("evil.rhai"), "").unwrap();
+        let metadata =
+            r#"{ "name": "work", "exported_at": "", "version": "", "script": "../evil.rhai" }"#;
+        fs::write(unpacked.join("metadata.json"), metadata).unwrap();
+
+        let config = Config {
+            session_dir: root.join("sessions").to_string_lossy().into_owned(),
+            state_dir: root.join("state").to_string_lossy().into_owned(),
+            ..Config::default()
+        };
+        assert!(install_archive(&config, &unpacked, None).is_err());
+        assert!(install_archive(&config, &unpacked, Some("..")).is_err());
+        assert!(!root.join("state").exists());
+        assert!(!root.join("sessions").exists());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_delete_removes_backups_and_sidecars() {
+        let temp_dir = Path::new("test_sessions_delete");
+        let _ = fs::remove_dir_all(temp_dir);
+        fs::create_dir_all(temp_dir).unwrap();
+        let session = temp_dir.join("work.json");
+        let empty = r#"{ "clients": [], "workspaces": [], "monitors": [] }"#;
+        for _ in 0..2 {
+            rotate_backups(&session, 2).unwrap();
+            integrity::write_with_sidecars(&session, empty.as_bytes(), None).unwrap();
+        }
+
+        let manager = SessionManager::new(Config {
+            session_dir: temp_dir.to_string_lossy().into_owned(),
+            backups: 2,
+            ..Config::default()
+        });
+        assert_eq!(manager.delete("work").unwrap(), session);
+
+        assert_eq!(fs::read_dir(temp_dir).unwrap().count(), 0);
+        assert!(manager.delete("work").is_err());
+
+        fs::remove_dir_all(temp_dir).unwrap();
+    }
 }
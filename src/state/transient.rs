@@ -0,0 +1,144 @@
+use crate::config::TransientConfig;
+use crate::ipc::HyprClient;
+
+/// Title prefixes of file choosers and similar short-lived dialogs
+const DIALOG_TITLES: &[&str] = &[
+    "open file",
+    "open files",
+    "open folder",
+    "save as",
+    "save file",
+    "select file",
+    "select folder",
+    "choose file",
+    "choose files",
+    "file upload",
+];
+
+/// Classes of the windows desktop portals open for other apps
+const DIALOG_CLASSES: &[&str] = &[
+    "xdg-desktop-portal-gtk",
+    "xdg-desktop-portal-gnome",
+    "xdg-desktop-portal-kde",
+    "org.freedesktop.impl.portal.desktop.kde",
+];
+
+/// Why `client` looks like a window that makes no sense to restore
+/// (a dialog, splash screen or popup), if it does
+pub fn transient_reason(client: &HyprClient, config: &TransientConfig) -> Option<&'static str> {
+    if !config.enabled {
+        return None;
+    }
+    if client.size[0] <= 0 || client.size[1] <= 0 {
+        return Some("zero-size");
+    }
+
+    let class = client.class.to_lowercase();
+    let title = client.title.to_lowercase();
+    let initial_title = client.initial_title.to_lowercase();
+    let titles = [title.as_str(), initial_title.as_str()];
+
+    if DIALOG_CLASSES.contains(&class.as_str())
+        || titles
+            .iter()
+            .any(|t| DIALOG_TITLES.iter().any(|prefix| t.starts_with(prefix)))
+    {
+        return Some("dialog");
+    }
+    // Only the current title, and only on floating windows: a main window
+    // may well have a title like "splash.rs" or have started as a splash
+    let splash_title = client.floating
+        && title
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|w| w == "splash");
+    if class.contains("splash") || splash_title {
+        return Some("splash screen");
+    }
+    if class.ends_with("popup") || titles.contains(&"popup") {
+        return Some("popup");
+    }
+
+    let configured = config
+        .classes
+        .iter()
+        .any(|c| c.eq_ignore_ascii_case(&client.class))
+        || config
+            .titles
+            .iter()
+            .any(|t| titles.iter().any(|title| title.contains(&t.to_lowercase())));
+    configured.then_some("configured")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn client(class: &str, title: &str, size: [i32; 2]) -> HyprClient {
+        HyprClient {
+            size,
+            ..HyprClient::fixture("0x1", class)
+                .with_title(title)
+                .with_floating(true)
+        }
+    }
+
+    #[test]
+    fn test_builtin_heuristics() {
+        let config = TransientConfig::default();
+        let reason = |class, title, size| transient_reason(&client(class, title, size), &config);
+
+        assert_eq!(reason("firefox", "", [0, 0]), Some("zero-size"));
+        assert_eq!(reason("firefox", "Save As", [800, 600]), Some("dialog"));
+        assert_eq!(
+            reason("gimp", "GIMP Startup Splash", [400, 300]),
+            Some("splash screen")
+        );
+        assert_eq!(reason("jetbrains-idea", "popup", [200, 100]), Some("popup"));
+        // Only prefixes count, so a page about saving is kept
+        assert_eq!(reason("firefox", "How to save as PDF", [800, 600]), None);
+        assert_eq!(
+            reason("firefox", "Splashdown - Wikipedia", [800, 600]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_main_window_mentioning_splash_is_kept() {
+        let config = TransientConfig::default();
+        let mut editor = client("code", "splash.rs - project", [1200, 900]);
+        editor.floating = false;
+        assert_eq!(transient_reason(&editor, &config), None);
+
+        // Started as a splash screen, and became the main window
+        let mut app = client("gimp", "GNU Image Manipulation Program", [1200, 900]);
+        app.initial_title = "GIMP Startup Splash".to_string();
+        assert_eq!(transient_reason(&app, &config), None);
+    }
+
+    #[test]
+    fn test_config_extends_and_disables_heuristics() {
+        let config = TransientConfig {
+            titles: vec!["Update available".to_string()],
+            classes: vec!["zoom_linux_float_video_window".to_string()],
+            ..TransientConfig::default()
+        };
+
+        assert_eq!(
+            transient_reason(&client("steam", "Update Available", [300, 200]), &config),
+            Some("configured")
+        );
+        assert_eq!(
+            transient_reason(
+                &client("zoom_linux_float_video_window", "", [300, 200]),
+                &config
+            ),
+            Some("configured")
+        );
+
+        let off = TransientConfig {
+            enabled: false,
+            ..config
+        };
+        assert_eq!(transient_reason(&client("kitty", "", [0, 0]), &off), None);
+    }
+}
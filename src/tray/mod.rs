@@ -0,0 +1,213 @@
+use crate::config::Config;
+use crate::restore::RestoreOptions;
+use crate::runtime::RuntimeContext;
+use crate::state::SessionManager;
+use ksni::blocking::TrayMethods;
+use ksni::menu::{CheckmarkItem, StandardItem, SubMenu};
+use ksni::{MenuItem, ToolTip, Tray};
+use std::error::Error;
+use std::fs;
+use std::path::PathBuf;
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+/// How often the tooltip's save freshness is recomputed
+const REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How many snapshots the "Restore" submenu offers
+const MENU_SESSIONS: usize = 10;
+
+/// Human-readable age of the newest snapshot, e.g. "Last saved 5 min ago"
+pub fn describe_freshness(age: Option<Duration>) -> String {
+    let Some(age) = age else {
+        return "No snapshots yet".to_string();
+    };
+
+    let secs = age.as_secs();
+    match secs {
+        0..60 => "Last saved just now".to_string(),
+        60..3600 => format!("Last saved {} min ago", secs / 60),
+        3600..86400 => format!("Last saved {} h ago", secs / 3600),
+        _ => format!("Last saved {} days ago", secs / 86400),
+    }
+}
+
+/// Show the tray icon until "Quit" is chosen or the tray host goes away
+pub fn run(config: &Config) -> Result<(), Box<dyn Error>> {
+    let handle = HyprdroverTray::new(config).spawn()?;
+
+    while !handle.is_closed() {
+        thread::sleep(REFRESH_INTERVAL);
+        handle.update(|tray| tray.refresh());
+    }
+    Ok(())
+}
+
+struct HyprdroverTray {
+    config: Config,
+    context: RuntimeContext,
+    sessions: Vec<PathBuf>,
+    freshness: String,
+}
+
+impl HyprdroverTray {
+    fn new(config: &Config) -> Self {
+        let mut tray = Self {
+            config: config.clone(),
+            context: RuntimeContext::for_config(config),
+            sessions: vec![],
+            freshness: String::new(),
+        };
+        tray.refresh();
+        tray
+    }
+
+    fn manager(&self) -> SessionManager {
+        SessionManager::new(self.config.clone())
+    }
+
+    fn refresh(&mut self) {
+        self.sessions = self.manager().list_sessions().unwrap_or_default();
+        let newest = self
+            .sessions
+            .iter()
+            .filter_map(|path| fs::metadata(path).and_then(|m| m.modified()).ok())
+            .max();
+        let age = newest.and_then(|time| SystemTime::now().duration_since(time).ok());
+        self.freshness = describe_freshness(age);
+    }
+
+    fn save(&mut self) {
+        if let Err(e) = self.manager().snapshot(None, None) {
+            eprintln!("Error saving session: {}", e);
+        }
+        self.refresh();
+    }
+
+    /// Restores run on their own thread so the menu stays responsive
+    fn restore(&self, path: PathBuf) {
+        let manager = self.manager();
+        thread::spawn(move || {
+            if let Err(e) = manager.restore(&path, &RestoreOptions::default()) {
+                eprintln!("Error restoring session: {}", e);
+            }
+        });
+    }
+}
+
+impl Tray for HyprdroverTray {
+    fn id(&self) -> String {
+        env!("CARGO_PKG_NAME").into()
+    }
+
+    fn title(&self) -> String {
+        "hyprdrover".into()
+    }
+
+    fn icon_name(&self) -> String {
+        "document-save".into()
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        let paused = if self.context.is_paused() {
+            " (tracking paused)"
+        } else {
+            ""
+        };
+        ToolTip {
+            title: "hyprdrover".into(),
+            description: format!("{}{}", self.freshness, paused),
+            ..Default::default()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.save();
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        let restore_items: Vec<MenuItem<Self>> = self
+            .sessions
+            .iter()
+            .take(MENU_SESSIONS)
+            .map(|path| {
+                let path = path.clone();
+                StandardItem {
+                    label: path
+                        .file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                    activate: Box::new(move |this: &mut Self| this.restore(path.clone())),
+                    ..Default::default()
+                }
+                .into()
+            })
+            .collect();
+
+        vec![
+            StandardItem {
+                label: self.freshness.clone(),
+                enabled: false,
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: "Save now".into(),
+                icon_name: "document-save".into(),
+                activate: Box::new(|this: &mut Self| this.save()),
+                ..Default::default()
+            }
+            .into(),
+            SubMenu {
+                label: "Restore…".into(),
+                enabled: !restore_items.is_empty(),
+                submenu: restore_items,
+                ..Default::default()
+            }
+            .into(),
+            CheckmarkItem {
+                label: "Pause tracking".into(),
+                checked: self.context.is_paused(),
+                activate: Box::new(|this: &mut Self| {
+                    let paused = !this.context.is_paused();
+                    if let Err(e) = this.context.set_paused(paused) {
+                        eprintln!("Error toggling pause: {}", e);
+                    }
+                }),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: "Quit".into(),
+                icon_name: "application-exit".into(),
+                activate: Box::new(|_| std::process::exit(0)),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_freshness() {
+        assert_eq!(describe_freshness(None), "No snapshots yet");
+        assert_eq!(
+            describe_freshness(Some(Duration::from_secs(5))),
+            "Last saved just now"
+        );
+        assert_eq!(
+            describe_freshness(Some(Duration::from_secs(300))),
+            "Last saved 5 min ago"
+        );
+        assert_eq!(
+            describe_freshness(Some(Duration::from_secs(2 * 86400))),
+            "Last saved 2 days ago"
+        );
+    }
+}
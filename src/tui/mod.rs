@@ -0,0 +1,294 @@
+use crate::config::Config;
+use crate::ipc::{self, events::IpcEventListener, queue::EventReceiver, SessionSnapshot};
+use crate::restore::RestoreOptions;
+use crate::runtime::{DaemonState, RuntimeContext};
+use crate::state::SessionManager;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::crossterm::execute;
+use ratatui::crossterm::terminal::{self, EnterAlternateScreen};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, List, ListItem, ListState, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+use std::error::Error;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// How often the dashboard wakes up to check keys and daemon health
+const TICK: Duration = Duration::from_millis(250);
+
+/// A workspace and the windows on it, as listed by the dashboard
+#[derive(Debug, PartialEq)]
+pub struct WorkspaceView {
+    pub id: i32,
+    pub name: String,
+    pub monitor: String,
+    pub windows: Vec<String>,
+}
+
+/// Group the windows of `snapshot` by workspace, ordered by workspace id
+pub fn workspace_views(snapshot: &SessionSnapshot) -> Vec<WorkspaceView> {
+    let mut views: Vec<WorkspaceView> = snapshot
+        .workspaces
+        .iter()
+        .map(|ws| WorkspaceView {
+            id: ws.id,
+            name: ws.name.clone(),
+            monitor: ws.monitor.clone(),
+            windows: vec![],
+        })
+        .collect();
+
+    for client in &snapshot.clients {
+        let label = if client.title.is_empty() {
+            client.class.clone()
+        } else {
+            format!("{} — {}", client.class, client.title)
+        };
+        match views.iter_mut().find(|v| v.id == client.workspace.id) {
+            Some(view) => view.windows.push(label),
+            None => views.push(WorkspaceView {
+                id: client.workspace.id,
+                name: client.workspace.name.clone(),
+                monitor: "?".to_string(),
+                windows: vec![label],
+            }),
+        }
+    }
+
+    views.sort_by_key(|v| v.id);
+    views
+}
+
+/// Run the dashboard until the user quits.
+///
+/// The view is rebuilt from fresh compositor state whenever Hyprland reports
+/// an event, so it follows window and workspace changes as they happen.
+pub fn run(config: &Config) -> Result<(), Box<dyn Error>> {
+    let events = IpcEventListener::new()
+        .ok()
+        .map(|listener| listener.spawn(&config.event_buffer));
+
+    let mut dashboard = Dashboard::new(config, events.is_some());
+    let mut terminal = ratatui::init();
+    let result = dashboard.run(&mut terminal, events.as_ref());
+    ratatui::restore();
+    Ok(result?)
+}
+
+struct Dashboard {
+    manager: SessionManager,
+    context: RuntimeContext,
+    daemon: DaemonState,
+    workspaces: Vec<WorkspaceView>,
+    sessions: Vec<PathBuf>,
+    selected: ListState,
+    events_connected: bool,
+    message: String,
+}
+
+impl Dashboard {
+    fn new(config: &Config, events_connected: bool) -> Self {
+        let context = RuntimeContext::for_config(config);
+        let mut dashboard = Self {
+            manager: SessionManager::new(config.clone()),
+            daemon: context.daemon_state(),
+            context,
+            workspaces: vec![],
+            sessions: vec![],
+            selected: ListState::default(),
+            events_connected,
+            message: String::new(),
+        };
+        dashboard.refresh();
+        dashboard.refresh_sessions();
+        dashboard
+    }
+
+    fn run(
+        &mut self,
+        terminal: &mut DefaultTerminal,
+        events: Option<&EventReceiver>,
+    ) -> io::Result<()> {
+        loop {
+            terminal.draw(|frame| self.draw(frame))?;
+
+            if event::poll(TICK)? {
+                if let Event::Key(key) = event::read()? {
+                    if key.kind == KeyEventKind::Press {
+                        match key.code {
+                            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+                            KeyCode::Up | KeyCode::Char('k') => self.selected.select_previous(),
+                            KeyCode::Down | KeyCode::Char('j') => self.selected.select_next(),
+                            KeyCode::Home | KeyCode::Char('g') => self.selected.select_first(),
+                            KeyCode::Char('s') => self.save(terminal)?,
+                            KeyCode::Char('r') | KeyCode::Enter => self.restore(terminal)?,
+                            _ => {}
+                        }
+                    }
+                }
+            }
+
+            if events.is_some_and(|events| !events.drain().is_empty()) {
+                self.refresh();
+            }
+            self.daemon = self.context.daemon_state();
+        }
+    }
+
+    fn refresh(&mut self) {
+        match ipc::capture_state() {
+            Ok(snapshot) => self.workspaces = workspace_views(&snapshot),
+            Err(e) => self.message = format!("Could not read compositor state: {}", e),
+        }
+    }
+
+    fn refresh_sessions(&mut self) {
+        self.sessions = self.manager.list_sessions().unwrap_or_default();
+        if self.selected.selected().is_none() && !self.sessions.is_empty() {
+            self.selected.select(Some(0));
+        }
+    }
+
+    fn save(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        self.message = match self.manager.snapshot(None, None) {
+            Ok(path) => format!("Saved {}", path.display()),
+            Err(e) => format!("Error saving session: {}", e),
+        };
+        self.refresh_sessions();
+        // Saving prints progress to stdout; repaint over it
+        terminal.clear()
+    }
+
+    /// Restore the selected snapshot outside the dashboard so its progress output is readable
+    fn restore(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let Some(path) = self
+            .selected
+            .selected()
+            .and_then(|i| self.sessions.get(i))
+            .cloned()
+        else {
+            return Ok(());
+        };
+
+        ratatui::restore();
+        self.message = match self.manager.restore(&path, &RestoreOptions::default()) {
+            Ok(_) => format!("Restored {}", path.display()),
+            Err(e) => format!("Error restoring session: {}", e),
+        };
+        println!("\nPress Enter to return to the dashboard");
+        io::stdin().read_line(&mut String::new())?;
+
+        terminal::enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen)?;
+        terminal.clear()?;
+        self.refresh();
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [header, body, footer] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [left, right] =
+            Layout::horizontal([Constraint::Percentage(65), Constraint::Percentage(35)])
+                .areas(body);
+
+        let daemon = match self.daemon {
+            DaemonState::Running(pid) => format!("daemon running (pid {})", pid),
+            DaemonState::Stale(pid) => format!("daemon not running (stale lock from pid {})", pid),
+            DaemonState::NotRunning => "daemon not running".to_string(),
+        };
+        let events = if self.events_connected {
+            "live"
+        } else {
+            "events unavailable"
+        };
+        frame.render_widget(
+            Paragraph::new(format!(
+                " hyprdrover · {} · {} · {}",
+                self.context.instance.as_deref().unwrap_or("no instance"),
+                daemon,
+                events
+            )),
+            header,
+        );
+
+        let mut lines = vec![];
+        for ws in &self.workspaces {
+            lines.push(ListItem::new(Line::styled(
+                format!("{} [{}] on {}", ws.id, ws.name, ws.monitor),
+                Style::default().add_modifier(Modifier::BOLD),
+            )));
+            lines.extend(ws.windows.iter().map(|w| ListItem::new(format!("  {}", w))));
+        }
+        frame.render_widget(
+            List::new(lines).block(Block::bordered().title(" Workspaces ")),
+            left,
+        );
+
+        let sessions: Vec<ListItem> = self
+            .sessions
+            .iter()
+            .map(|path| {
+                ListItem::new(
+                    path.file_stem()
+                        .map(|s| s.to_string_lossy().into_owned())
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
+        frame.render_stateful_widget(
+            List::new(sessions)
+                .block(Block::bordered().title(" Snapshots "))
+                .highlight_symbol("> ")
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED)),
+            right,
+            &mut self.selected,
+        );
+
+        let help = if self.message.is_empty() {
+            " s save · r/Enter restore selected · j/k move · g first · q quit".to_string()
+        } else {
+            format!(" {}", self.message)
+        };
+        frame.render_widget(Paragraph::new(help), footer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ipc::HyprClient;
+
+    #[test]
+    fn test_workspace_views_group_windows() {
+        let mut snapshot: SessionSnapshot = serde_json::from_value(serde_json::json!({
+            "clients": [],
+            "workspaces": [{
+                "id": 1, "name": "1", "monitor": "DP-1", "windows": 1,
+                "hasfullscreen": false, "lastwindow": "0x2", "lastwindowtitle": ""
+            }],
+            "monitors": []
+        }))
+        .unwrap();
+        snapshot.clients = vec![
+            HyprClient::fixture("0x1", "kitty")
+                .with_title("htop")
+                .with_workspace(3),
+            HyprClient::fixture("0x2", "firefox").with_pid(2),
+        ];
+
+        let views = workspace_views(&snapshot);
+        assert_eq!(views.len(), 2);
+        assert_eq!(views[0].monitor, "DP-1");
+        assert_eq!(views[0].windows, vec!["firefox"]);
+        assert_eq!(views[1].id, 3);
+        assert_eq!(views[1].windows, vec!["kitty — htop"]);
+    }
+}
@@ -0,0 +1,235 @@
+//! Integration tests that drive a real, throwaway Hyprland instance.
+//!
+//! Built with the `integration-tests` feature and ignored by default, so
+//! `--all-features` runs don't need Hyprland; run them with
+//! `cargo test --features integration-tests -- --ignored`. Hyprland is started
+//! nested inside the current Wayland session when `WAYLAND_DISPLAY` is set,
+//! and with the headless backend otherwise. Override the binaries with
+//! `HYPRDROVER_TEST_HYPRLAND` and `HYPRDROVER_TEST_TERMINAL` (default `foot`).
+//! A test fails, saying why, when `XDG_RUNTIME_DIR` is unset or a binary it
+//! needs is missing.
+#![cfg(feature = "integration-tests")]
+
+use serde_json::Value;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread::sleep;
+use std::time::{Duration, Instant};
+
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// A Hyprland compositor owned by a single test; killed on drop
+struct HeadlessHyprland {
+    process: Child,
+    signature: String,
+    home: PathBuf,
+}
+
+impl HeadlessHyprland {
+    /// Start a compositor, failing the test when this machine can't run one
+    fn start(name: &str) -> Self {
+        let runtime_dir = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| panic!("{}: XDG_RUNTIME_DIR is not set", name));
+        let binary = env::var("HYPRDROVER_TEST_HYPRLAND").unwrap_or_else(|_| "Hyprland".into());
+        let terminal = terminal();
+        if let Some(missing) = [binary.as_str(), "hyprctl", terminal.as_str()]
+            .into_iter()
+            .find(|program| !on_path(program))
+        {
+            panic!("{}: `{}` is not installed", name, missing);
+        }
+        let hypr_dir = runtime_dir.join("hypr");
+        let existing = list_instances(&hypr_dir);
+
+        let home = env::temp_dir().join(format!("hyprdrover-it-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+
+        let config_path = home.join("hyprland.conf");
+        fs::write(
+            &config_path,
+            "monitor = , 1920x1080@60, 0x0, 1\n\
+             misc {\n    disable_hyprland_logo = true\n    disable_splash_rendering = true\n}\n",
+        )
+        .unwrap();
+
+        let mut command = Command::new(binary);
+        command
+            .arg("--config")
+            .arg(&config_path)
+            .env_remove("HYPRLAND_INSTANCE_SIGNATURE")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null());
+        if env::var_os("WAYLAND_DISPLAY").is_none() {
+            command
+                .env("WLR_BACKENDS", "headless")
+                .env("WLR_LIBINPUT_NO_DEVICES", "1");
+        }
+        let process = command.spawn().expect("failed to start Hyprland");
+
+        let signature = wait_for(STARTUP_TIMEOUT, || {
+            list_instances(&hypr_dir)
+                .difference(&existing)
+                .find(|sig| hypr_dir.join(sig).join(".socket.sock").exists())
+                .cloned()
+        })
+        .expect("Hyprland did not create its sockets in time");
+
+        Self {
+            process,
+            signature,
+            home,
+        }
+    }
+
+    fn hyprctl(&self, args: &[&str]) -> String {
+        let output = Command::new("hyprctl")
+            .args(args)
+            .env("HYPRLAND_INSTANCE_SIGNATURE", &self.signature)
+            .output()
+            .expect("failed to run hyprctl");
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn clients(&self) -> Vec<Value> {
+        serde_json::from_str(&self.hyprctl(&["-j", "clients"])).unwrap_or_default()
+    }
+
+    fn hyprdrover(&self, args: &[&str]) -> String {
+        let output = Command::new(env!("CARGO_BIN_EXE_hyprdrover"))
+            .args(args)
+            .env("HYPRLAND_INSTANCE_SIGNATURE", &self.signature)
+            .env("HOME", &self.home)
+            .output()
+            .expect("failed to run hyprdrover");
+        assert!(output.status.success(), "hyprdrover {:?} failed", args);
+        String::from_utf8_lossy(&output.stdout).into_owned()
+    }
+
+    fn spawn_terminal(&self, workspace: i32) {
+        let terminal = terminal();
+        let before = self.clients().len();
+        self.hyprctl(&[
+            "dispatch",
+            "exec",
+            &format!("[workspace {} silent] {}", workspace, terminal),
+        ]);
+        wait_for(STARTUP_TIMEOUT, || {
+            (self.clients().len() > before).then_some(())
+        })
+        .expect("terminal window did not appear");
+    }
+
+    fn kill_all_windows(&self) {
+        for client in self.clients() {
+            if let Some(address) = client["address"].as_str() {
+                self.hyprctl(&["dispatch", "closewindow", &format!("address:{}", address)]);
+            }
+        }
+        wait_for(STARTUP_TIMEOUT, || self.clients().is_empty().then_some(()))
+            .expect("windows did not close");
+    }
+}
+
+impl Drop for HeadlessHyprland {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.home);
+    }
+}
+
+fn terminal() -> String {
+    env::var("HYPRDROVER_TEST_TERMINAL").unwrap_or_else(|_| "foot".into())
+}
+
+/// Whether `program` is a path to a file or found in `$PATH`
+fn on_path(program: &str) -> bool {
+    if program.contains('/') {
+        return Path::new(program).is_file();
+    }
+    env::var_os("PATH")
+        .is_some_and(|path| env::split_paths(&path).any(|dir| dir.join(program).is_file()))
+}
+
+fn list_instances(dir: &Path) -> HashSet<String> {
+    fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .filter_map(|e| e.ok())
+                .filter_map(|e| e.file_name().into_string().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn wait_for<T>(timeout: Duration, mut probe: impl FnMut() -> Option<T>) -> Option<T> {
+    let start = Instant::now();
+    while start.elapsed() < timeout {
+        if let Some(value) = probe() {
+            return Some(value);
+        }
+        sleep(POLL_INTERVAL);
+    }
+    None
+}
+
+fn workspaces_by_class(clients: &[Value]) -> Vec<(String, i64)> {
+    let mut result: Vec<(String, i64)> = clients
+        .iter()
+        .map(|c| {
+            (
+                c["class"].as_str().unwrap_or_default().to_string(),
+                c["workspace"]["id"].as_i64().unwrap_or_default(),
+            )
+        })
+        .collect();
+    result.sort();
+    result
+}
+
+#[test]
+#[ignore = "starts Hyprland; run with --ignored"]
+fn save_kill_restore_roundtrip() {
+    let hypr = HeadlessHyprland::start("roundtrip");
+    hypr.spawn_terminal(2);
+    hypr.spawn_terminal(3);
+    let expected = workspaces_by_class(&hypr.clients());
+
+    hypr.hyprdrover(&["--save", "roundtrip"]);
+    hypr.kill_all_windows();
+    hypr.hyprdrover(&["--load", "roundtrip"]);
+
+    let restored = wait_for(STARTUP_TIMEOUT, || {
+        let clients = hypr.clients();
+        (clients.len() == expected.len()).then_some(clients)
+    })
+    .expect("restore did not relaunch every window");
+
+    assert_eq!(workspaces_by_class(&restored), expected);
+}
+
+#[test]
+#[ignore = "starts Hyprland; run with --ignored"]
+fn restore_moves_existing_windows_back() {
+    let hypr = HeadlessHyprland::start("reposition");
+    hypr.spawn_terminal(4);
+    hypr.hyprdrover(&["--save", "reposition"]);
+
+    let address = hypr.clients()[0]["address"].as_str().unwrap().to_string();
+    hypr.hyprctl(&[
+        "dispatch",
+        "movetoworkspacesilent",
+        &format!("1,address:{}", address),
+    ]);
+    hypr.hyprdrover(&["--load", "reposition"]);
+
+    let clients = hypr.clients();
+    assert_eq!(clients.len(), 1, "restore must not spawn duplicates");
+    assert_eq!(clients[0]["workspace"]["id"].as_i64(), Some(4));
+}